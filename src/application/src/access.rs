@@ -0,0 +1,101 @@
+use domain::ToDoItem;
+use uuid::Uuid;
+
+/// Items a command or query may see and change.
+///
+/// Callers are restricted to the items they own within their tenant. Administrators see
+/// every owner's items, but never those of another tenant. Background jobs and requests
+/// served while authentication is disabled are unrestricted. Items outside the scope are
+/// reported as not found, so their existence is not revealed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AccessScope {
+    /// Tenant the items must belong to; `None` means every tenant.
+    pub tenant_id: Option<String>,
+    /// Owner the items must belong to; `None` means every owner.
+    pub owner_id: Option<Uuid>,
+    /// Whether items without an owner are accessible next to those of `owner_id`.
+    pub include_unowned: bool,
+}
+
+impl AccessScope {
+    pub fn unrestricted() -> Self {
+        Self::default()
+    }
+
+    pub fn owned_by(owner_id: Uuid) -> Self {
+        Self {
+            tenant_id: None,
+            owner_id: Some(owner_id),
+            include_unowned: false,
+        }
+    }
+
+    /// Also grants access to items that have no owner.
+    pub fn with_unowned(mut self, include_unowned: bool) -> Self {
+        self.include_unowned = include_unowned;
+        self
+    }
+
+    pub fn in_tenant(mut self, tenant_id: Option<String>) -> Self {
+        self.tenant_id = tenant_id;
+        self
+    }
+
+    pub fn permits(&self, item: &ToDoItem) -> bool {
+        let tenant_matches = self.tenant_id.is_none() || self.tenant_id == item.tenant_id;
+        let owner_matches = self.owner_id.is_none()
+            || self.owner_id == item.owner_id
+            || (self.include_unowned && item.owner_id.is_none());
+        tenant_matches && owner_matches
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn owner_scope_only_permits_items_of_that_owner() {
+        let owner = Uuid::new_v4();
+        let owned = ToDoItem::new("title".into(), "note".into()).with_owner(Some(owner));
+        let foreign = ToDoItem::new("title".into(), "note".into()).with_owner(Some(Uuid::new_v4()));
+        let anonymous = ToDoItem::new("title".into(), "note".into());
+
+        let scope = AccessScope::owned_by(owner);
+
+        assert!(scope.permits(&owned));
+        assert!(!scope.permits(&foreign));
+        assert!(!scope.permits(&anonymous));
+        assert!(AccessScope::unrestricted().permits(&foreign));
+        assert!(AccessScope::unrestricted().permits(&anonymous));
+    }
+
+    #[test]
+    fn owner_scope_permits_pre_existing_unowned_items_only_when_shared() {
+        let owner = Uuid::new_v4();
+        let unowned = ToDoItem::new("title".into(), "note".into());
+        let foreign = ToDoItem::new("title".into(), "note".into()).with_owner(Some(Uuid::new_v4()));
+
+        let shared = AccessScope::owned_by(owner).with_unowned(true);
+
+        assert!(shared.permits(&unowned));
+        assert!(!shared.permits(&foreign));
+        assert!(!AccessScope::owned_by(owner).permits(&unowned));
+    }
+
+    #[test]
+    fn tenant_scope_never_permits_items_of_another_tenant() {
+        let owner = Uuid::new_v4();
+        let item = ToDoItem::new("title".into(), "note".into())
+            .with_owner(Some(owner))
+            .with_tenant(Some("acme".into()));
+
+        let same_tenant = AccessScope::unrestricted().in_tenant(Some("acme".into()));
+        let other_tenant = AccessScope::owned_by(owner).in_tenant(Some("globex".into()));
+
+        assert!(same_tenant.permits(&item));
+        assert!(AccessScope::owned_by(owner).permits(&item));
+        assert!(!other_tenant.permits(&item));
+        assert!(!same_tenant.permits(&ToDoItem::new("title".into(), "note".into())));
+    }
+}
@@ -0,0 +1,77 @@
+use chrono::{DateTime, Utc};
+use uuid::Uuid;
+
+/// Credential issued to a service that calls the API without a user token.
+///
+/// Only the SHA-256 `key_hash` of the secret is stored; `prefix` repeats its first
+/// characters so operators can tell keys apart. Callers using the key act for `tenant_id`
+/// when multi-tenancy is enabled.
+#[derive(PartialEq, Debug, Clone)]
+pub struct ApiKey {
+    pub id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    pub key_hash: String,
+    pub scopes: Vec<String>,
+    pub tenant_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: Option<DateTime<Utc>>,
+    pub last_used_at: Option<DateTime<Utc>>,
+    pub revoked_at: Option<DateTime<Utc>>,
+}
+
+impl ApiKey {
+    pub fn new(
+        name: impl Into<String>,
+        prefix: impl Into<String>,
+        key_hash: impl Into<String>,
+        scopes: Vec<String>,
+        tenant_id: Option<String>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            name: name.into(),
+            prefix: prefix.into(),
+            key_hash: key_hash.into(),
+            scopes,
+            tenant_id,
+            created_at: Utc::now(),
+            expires_at,
+            last_used_at: None,
+            revoked_at: None,
+        }
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    pub fn is_revoked(&self) -> bool {
+        self.revoked_at.is_some()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn keys_without_expiry_never_expire() {
+        let now = Utc::now();
+        let permanent = ApiKey::new("batch", "tk_1234", "hash", Vec::new(), None, None);
+        let expiring = ApiKey::new(
+            "batch",
+            "tk_1234",
+            "hash",
+            Vec::new(),
+            None,
+            Some(now - Duration::seconds(1)),
+        );
+
+        assert!(!permanent.is_expired(now + Duration::days(3650)));
+        assert!(expiring.is_expired(now));
+        assert!(!expiring.is_revoked());
+    }
+}
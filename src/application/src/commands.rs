@@ -1,28 +1,51 @@
+use crate::{AccessScope, ApplicationError};
 use chrono::{DateTime, Utc};
+use domain::{ToDoItem, ToDoItemStatus};
 use uuid::Uuid;
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct CreateToDoItemCommand {
     pub title: String,
     pub note: String,
-    pub status: String,
+    pub status: ToDoItemStatus,
     pub due_at: Option<DateTime<Utc>>,
+    pub owner_id: Option<Uuid>,
+    pub tenant_id: Option<String>,
+    pub request_id: Option<String>,
 }
 
 impl CreateToDoItemCommand {
     pub fn new(
         title: impl Into<String>,
         note: impl Into<String>,
-        status: impl Into<String>,
+        status: ToDoItemStatus,
         due_at: Option<DateTime<Utc>>,
     ) -> Self {
         Self {
             title: title.into(),
             note: note.into(),
-            status: status.into(),
+            status,
             due_at,
+            owner_id: None,
+            tenant_id: None,
+            request_id: None,
         }
     }
+
+    pub fn with_owner(mut self, owner_id: Option<Uuid>) -> Self {
+        self.owner_id = owner_id;
+        self
+    }
+
+    pub fn with_tenant(mut self, tenant_id: Option<String>) -> Self {
+        self.tenant_id = tenant_id;
+        self
+    }
+
+    pub fn with_request_id(mut self, request_id: Option<String>) -> Self {
+        self.request_id = request_id;
+        self
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -30,9 +53,12 @@ pub struct UpdateToDoItemCommand {
     pub id: Uuid,
     pub title: String,
     pub note: String,
-    pub status: String,
+    pub status: ToDoItemStatus,
     pub due_at: Option<DateTime<Utc>>,
     pub version: i32,
+    pub updated_by: Option<Uuid>,
+    pub scope: AccessScope,
+    pub request_id: Option<String>,
 }
 
 impl UpdateToDoItemCommand {
@@ -40,7 +66,7 @@ impl UpdateToDoItemCommand {
         id: Uuid,
         title: impl Into<String>,
         note: impl Into<String>,
-        status: impl Into<String>,
+        status: ToDoItemStatus,
         due_at: Option<DateTime<Utc>>,
         version: i32,
     ) -> Self {
@@ -48,9 +74,106 @@ impl UpdateToDoItemCommand {
             id,
             title: title.into(),
             note: note.into(),
-            status: status.into(),
+            status,
             due_at,
             version,
+            updated_by: None,
+            scope: AccessScope::default(),
+            request_id: None,
+        }
+    }
+
+    pub fn with_updated_by(mut self, updated_by: Option<Uuid>) -> Self {
+        self.updated_by = updated_by;
+        self
+    }
+
+    pub fn within(mut self, scope: AccessScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    pub fn with_request_id(mut self, request_id: Option<String>) -> Self {
+        self.request_id = request_id;
+        self
+    }
+}
+
+/// Partial update following JSON Merge Patch semantics.
+///
+/// `None` leaves a field unchanged. `due_at` uses `Some(None)` to clear the due date.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct PatchToDoItemCommand {
+    pub id: Uuid,
+    pub title: Option<String>,
+    pub note: Option<String>,
+    pub status: Option<ToDoItemStatus>,
+    pub due_at: Option<Option<DateTime<Utc>>>,
+    pub version: i32,
+    pub updated_by: Option<Uuid>,
+    pub scope: AccessScope,
+    pub request_id: Option<String>,
+}
+
+impl PatchToDoItemCommand {
+    pub fn new(id: Uuid, version: i32) -> Self {
+        Self {
+            id,
+            version,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn with_note(mut self, note: impl Into<String>) -> Self {
+        self.note = Some(note.into());
+        self
+    }
+
+    pub fn with_status(mut self, status: ToDoItemStatus) -> Self {
+        self.status = Some(status);
+        self
+    }
+
+    pub fn with_due_at(mut self, due_at: Option<DateTime<Utc>>) -> Self {
+        self.due_at = Some(due_at);
+        self
+    }
+
+    pub fn with_updated_by(mut self, updated_by: Option<Uuid>) -> Self {
+        self.updated_by = updated_by;
+        self
+    }
+
+    pub fn within(mut self, scope: AccessScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    pub fn with_request_id(mut self, request_id: Option<String>) -> Self {
+        self.request_id = request_id;
+        self
+    }
+
+    /// Merges the patch into `item`, giving the full update it stands for.
+    pub fn apply_to(self, item: &ToDoItem) -> UpdateToDoItemCommand {
+        UpdateToDoItemCommand {
+            id: self.id,
+            title: self
+                .title
+                .or_else(|| item.title.clone())
+                .unwrap_or_default(),
+            note: self.note.or_else(|| item.note.clone()).unwrap_or_default(),
+            status: self.status.unwrap_or(item.status),
+            due_at: self.due_at.unwrap_or(item.due_at),
+            version: self.version,
+            updated_by: self.updated_by,
+            scope: self.scope,
+            request_id: self.request_id,
         }
     }
 }
@@ -59,10 +182,130 @@ impl UpdateToDoItemCommand {
 pub struct DeleteToDoItemCommand {
     pub id: Uuid,
     pub deleted_by: Option<Uuid>,
+    pub scope: AccessScope,
+    pub request_id: Option<String>,
 }
 
 impl DeleteToDoItemCommand {
     pub fn new(id: Uuid, deleted_by: Option<Uuid>) -> Self {
-        Self { id, deleted_by }
+        Self {
+            id,
+            deleted_by,
+            scope: AccessScope::default(),
+            request_id: None,
+        }
+    }
+
+    pub fn within(mut self, scope: AccessScope) -> Self {
+        self.scope = scope;
+        self
     }
+
+    pub fn with_request_id(mut self, request_id: Option<String>) -> Self {
+        self.request_id = request_id;
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestoreToDoItemCommand {
+    pub id: Uuid,
+    pub restored_by: Option<Uuid>,
+    pub scope: AccessScope,
+    pub request_id: Option<String>,
+}
+
+impl RestoreToDoItemCommand {
+    pub fn new(id: Uuid, restored_by: Option<Uuid>) -> Self {
+        Self {
+            id,
+            restored_by,
+            scope: AccessScope::default(),
+            request_id: None,
+        }
+    }
+
+    pub fn within(mut self, scope: AccessScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    pub fn with_request_id(mut self, request_id: Option<String>) -> Self {
+        self.request_id = request_id;
+        self
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PurgeDeletedToDoItemsCommand {
+    pub deleted_before: DateTime<Utc>,
+    pub batch_size: i64,
+}
+
+impl PurgeDeletedToDoItemsCommand {
+    pub fn new(deleted_before: DateTime<Utc>, batch_size: i64) -> Self {
+        Self {
+            deleted_before,
+            batch_size,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DispatchOutboxCommand {
+    pub batch_size: i64,
+}
+
+impl DispatchOutboxCommand {
+    pub fn new(batch_size: i64) -> Self {
+        Self { batch_size }
+    }
+}
+
+/// One operation of a batch request.
+///
+/// Update versions follow the `If-Match` rules of the single-item endpoint. A delete
+/// version is optional; when present it must match, and a missing item is reported as
+/// not found instead of being ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchOperation {
+    Create(CreateToDoItemCommand),
+    Update(UpdateToDoItemCommand),
+    Delete {
+        command: DeleteToDoItemCommand,
+        version: Option<i32>,
+    },
+}
+
+/// Applies many operations in one request.
+///
+/// Atomic batches run in a single unit of work and stop at the first failure. Otherwise
+/// all creates share one unit of work and every update or delete gets its own, so one
+/// failing operation does not affect the others.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchToDoItemsCommand {
+    pub operations: Vec<BatchOperation>,
+    pub atomic: bool,
+}
+
+impl BatchToDoItemsCommand {
+    pub fn new(operations: Vec<BatchOperation>, atomic: bool) -> Self {
+        Self { operations, atomic }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchOperationOutcome {
+    Created { id: Uuid },
+    Updated { id: Uuid, version: i32 },
+    Deleted { id: Uuid },
+}
+
+/// Result of one batch operation, in the order of the request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BatchOperationResult {
+    Applied(BatchOperationOutcome),
+    Failed(ApplicationError),
+    /// Rolled back or never attempted because another operation of an atomic batch failed.
+    NotApplied,
 }
@@ -1,3 +1,4 @@
+use domain::ToDoItemStatus;
 use thiserror::Error;
 use uuid::Uuid;
 
@@ -17,6 +18,16 @@ pub enum ApplicationError {
         actual_version: i32,
     },
 
+    #[error("todo item with id {id} cannot move from status {from} to {to}")]
+    InvalidStatusTransition {
+        id: Uuid,
+        from: ToDoItemStatus,
+        to: ToDoItemStatus,
+    },
+
+    #[error("todo item with id {id} is not deleted")]
+    NotDeleted { id: Uuid },
+
     #[error("{message}")]
     Internal { message: String },
 }
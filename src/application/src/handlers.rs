@@ -1,8 +1,19 @@
-use crate::commands::{CreateToDoItemCommand, DeleteToDoItemCommand, UpdateToDoItemCommand};
-use crate::queries::{GetAllToDoItemsQuery, GetDeletedToDoItemForAuditQuery, GetToDoItemQuery};
-use crate::repositories::{ToDoItemCommandRepository, ToDoItemQueryRepository};
-use crate::ApplicationResult;
+use crate::commands::{
+    BatchOperation, BatchOperationOutcome, BatchOperationResult, BatchToDoItemsCommand,
+    CreateToDoItemCommand, DeleteToDoItemCommand, DispatchOutboxCommand, PatchToDoItemCommand,
+    PurgeDeletedToDoItemsCommand, RestoreToDoItemCommand, UpdateToDoItemCommand,
+};
+use crate::outbox::{EventPublisher, OutboxDispatch, OutboxRetryPolicy};
+use crate::queries::{
+    GetAllToDoItemsQuery, GetDeletedToDoItemForAuditQuery, GetDeletedToDoItemsForAuditQuery,
+    GetToDoItemDiffQuery, GetToDoItemHistoryQuery, GetToDoItemQuery, GetToDoItemRevisionQuery,
+};
+use crate::repositories::{ToDoItemQueryRepository, UnitOfWork, UnitOfWorkFactory};
+use crate::revisions::{ChangeType, ToDoItemDiff, ToDoItemRevision};
+use crate::{ApplicationError, ApplicationResult, PaginatedResult};
+use chrono::Utc;
 use domain::ToDoItem;
+use std::collections::HashSet;
 use std::sync::Arc;
 use uuid::Uuid;
 
@@ -18,7 +29,7 @@ impl GetToDoItemQueryHandler {
     }
 
     pub async fn execute(&self, query: GetToDoItemQuery) -> ApplicationResult<ToDoItem> {
-        self.repository.get_by_id(query.id).await
+        self.repository.get_by_id(query.id, query.scope).await
     }
 }
 
@@ -42,66 +53,517 @@ impl GetAllToDoItemsQueryHandler {
 }
 
 pub struct CreateToDoItemCommandHandler {
-    repository: Arc<dyn ToDoItemCommandRepository + Send + Sync>,
+    unit_of_work: Arc<dyn UnitOfWorkFactory + Send + Sync>,
 }
 
 impl CreateToDoItemCommandHandler {
     pub fn new(
-        repository: Arc<dyn ToDoItemCommandRepository + Send + Sync>,
+        unit_of_work: Arc<dyn UnitOfWorkFactory + Send + Sync>,
     ) -> CreateToDoItemCommandHandler {
-        CreateToDoItemCommandHandler { repository }
+        CreateToDoItemCommandHandler { unit_of_work }
     }
 
     pub async fn execute(&self, command: CreateToDoItemCommand) -> ApplicationResult<Uuid> {
-        self.repository
-            .create(ToDoItem::new_with_lifecycle(
-                command.title,
-                command.note,
-                command.status,
-                command.due_at,
-            ))
-            .await
+        let item = ToDoItem::new_with_lifecycle(
+            command.title,
+            command.note,
+            command.status,
+            command.due_at,
+        )
+        .with_owner(command.owner_id)
+        .with_tenant(command.tenant_id);
+        let event = item.created_event();
+        let revision = ToDoItemRevision::new(
+            &item,
+            ChangeType::Created,
+            command.owner_id,
+            command.request_id,
+        );
+
+        let unit_of_work = self.unit_of_work.begin().await?;
+        let id = unit_of_work.to_do_items().create(item).await?;
+        unit_of_work.outbox().append(vec![event]).await?;
+        unit_of_work.revisions().append(vec![revision]).await?;
+        unit_of_work.commit().await?;
+
+        Ok(id)
     }
 }
 
 pub struct UpdateToDoItemCommandHandler {
-    repository: Arc<dyn ToDoItemCommandRepository + Send + Sync>,
+    unit_of_work: Arc<dyn UnitOfWorkFactory + Send + Sync>,
 }
 
 impl UpdateToDoItemCommandHandler {
     pub fn new(
-        repository: Arc<dyn ToDoItemCommandRepository + Send + Sync>,
+        unit_of_work: Arc<dyn UnitOfWorkFactory + Send + Sync>,
     ) -> UpdateToDoItemCommandHandler {
-        UpdateToDoItemCommandHandler { repository }
+        UpdateToDoItemCommandHandler { unit_of_work }
     }
 
     pub async fn execute(&self, command: UpdateToDoItemCommand) -> ApplicationResult<Uuid> {
-        self.repository
-            .update(ToDoItem::new_versioned(
-                command.id,
+        let unit_of_work = self.unit_of_work.begin().await?;
+        let item = unit_of_work
+            .to_do_items()
+            .get_for_update(command.id, command.scope.clone())
+            .await?;
+        let id = update_in(unit_of_work.as_ref(), item, command).await?;
+        unit_of_work.commit().await?;
+
+        Ok(id)
+    }
+}
+
+/// Applies `command` to `item`, loaded for update within `unit_of_work`.
+async fn update_in(
+    unit_of_work: &dyn UnitOfWork,
+    mut item: ToDoItem,
+    command: UpdateToDoItemCommand,
+) -> ApplicationResult<Uuid> {
+    if item.version != command.version {
+        return Err(ApplicationError::Conflict {
+            id: item.id,
+            expected_version: command.version,
+            actual_version: item.version,
+        });
+    }
+
+    let status_changed = item.change_status(command.status).map_err(|err| {
+        ApplicationError::InvalidStatusTransition {
+            id: item.id,
+            from: err.from,
+            to: err.to,
+        }
+    })?;
+    let details_changed =
+        item.change_details(Some(command.title), Some(command.note), command.due_at);
+    item.updated_by = command.updated_by;
+    let events = status_changed.into_iter().chain(details_changed).collect();
+
+    let updated = unit_of_work.to_do_items().update(item).await?;
+    unit_of_work.outbox().append(events).await?;
+    unit_of_work
+        .revisions()
+        .append(vec![ToDoItemRevision::new(
+            &updated,
+            ChangeType::Updated,
+            command.updated_by,
+            command.request_id,
+        )])
+        .await?;
+
+    Ok(updated.id)
+}
+
+pub struct PatchToDoItemCommandHandler {
+    unit_of_work: Arc<dyn UnitOfWorkFactory + Send + Sync>,
+}
+
+impl PatchToDoItemCommandHandler {
+    pub fn new(
+        unit_of_work: Arc<dyn UnitOfWorkFactory + Send + Sync>,
+    ) -> PatchToDoItemCommandHandler {
+        PatchToDoItemCommandHandler { unit_of_work }
+    }
+
+    pub async fn execute(&self, command: PatchToDoItemCommand) -> ApplicationResult<Uuid> {
+        let unit_of_work = self.unit_of_work.begin().await?;
+        let item = unit_of_work
+            .to_do_items()
+            .get_for_update(command.id, command.scope.clone())
+            .await?;
+        let command = command.apply_to(&item);
+        let id = update_in(unit_of_work.as_ref(), item, command).await?;
+        unit_of_work.commit().await?;
+
+        Ok(id)
+    }
+}
+
+pub struct DeleteToDoItemCommandHandler {
+    unit_of_work: Arc<dyn UnitOfWorkFactory + Send + Sync>,
+}
+
+impl DeleteToDoItemCommandHandler {
+    pub fn new(
+        unit_of_work: Arc<dyn UnitOfWorkFactory + Send + Sync>,
+    ) -> DeleteToDoItemCommandHandler {
+        DeleteToDoItemCommandHandler { unit_of_work }
+    }
+
+    pub async fn execute(&self, command: DeleteToDoItemCommand) -> ApplicationResult<()> {
+        let unit_of_work = self.unit_of_work.begin().await?;
+        delete_in(unit_of_work.as_ref(), command, None).await?;
+        unit_of_work.commit().await
+    }
+}
+
+/// Soft-deletes an item, checking `version` when given.
+///
+/// Without a version, deleting a missing or already deleted item stays a no-op without
+/// an event. With a version, the precondition cannot hold for a missing item, so it is
+/// reported as not found.
+async fn delete_in(
+    unit_of_work: &dyn UnitOfWork,
+    command: DeleteToDoItemCommand,
+    version: Option<i32>,
+) -> ApplicationResult<()> {
+    let mut item = match unit_of_work
+        .to_do_items()
+        .get_for_update(command.id, command.scope.clone())
+        .await
+    {
+        Ok(item) => item,
+        Err(ApplicationError::NotFound { .. }) if version.is_none() => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    if let Some(expected_version) = version.filter(|expected| *expected != item.version) {
+        return Err(ApplicationError::Conflict {
+            id: item.id,
+            expected_version,
+            actual_version: item.version,
+        });
+    }
+    let events = item
+        .mark_deleted_once(command.deleted_by)
+        .into_iter()
+        .collect();
+
+    let deleted = unit_of_work
+        .to_do_items()
+        .delete(command.id, command.deleted_by, command.scope)
+        .await?;
+    let revision = ToDoItemRevision::new(
+        &deleted,
+        ChangeType::Deleted,
+        command.deleted_by,
+        command.request_id,
+    );
+    unit_of_work.outbox().append(events).await?;
+    unit_of_work.revisions().append(vec![revision]).await
+}
+
+/// Handles create, update and delete operations of a batch request.
+///
+/// Operations run in request order. An atomic batch shares one unit of work and writes
+/// consecutive creates with a single multi-row insert; otherwise every operation runs in
+/// its own unit of work and gets its own result. The handler fails as a whole only when an
+/// atomic batch cannot be started or committed.
+pub struct BatchToDoItemsCommandHandler {
+    unit_of_work: Arc<dyn UnitOfWorkFactory + Send + Sync>,
+}
+
+impl BatchToDoItemsCommandHandler {
+    pub fn new(
+        unit_of_work: Arc<dyn UnitOfWorkFactory + Send + Sync>,
+    ) -> BatchToDoItemsCommandHandler {
+        BatchToDoItemsCommandHandler { unit_of_work }
+    }
+
+    pub async fn execute(
+        &self,
+        command: BatchToDoItemsCommand,
+    ) -> ApplicationResult<Vec<BatchOperationResult>> {
+        let mut results = vec![BatchOperationResult::NotApplied; command.operations.len()];
+        if command.atomic {
+            return self.execute_atomic(command.operations, results).await;
+        }
+
+        for (index, operation) in command.operations.into_iter().enumerate() {
+            results[index] = match self.apply(operation).await {
+                Ok(outcome) => BatchOperationResult::Applied(outcome),
+                Err(err) => BatchOperationResult::Failed(err),
+            };
+        }
+
+        Ok(results)
+    }
+
+    async fn execute_atomic(
+        &self,
+        operations: Vec<BatchOperation>,
+        mut results: Vec<BatchOperationResult>,
+    ) -> ApplicationResult<Vec<BatchOperationResult>> {
+        let unit_of_work = self.unit_of_work.begin().await?;
+        let mut applied = Vec::with_capacity(results.len());
+        let mut operations = operations.into_iter().enumerate().peekable();
+        while let Some((index, operation)) = operations.next() {
+            let BatchOperation::Create(create) = operation else {
+                match apply_in(unit_of_work.as_ref(), operation).await {
+                    Ok(outcome) => applied.push((index, outcome)),
+                    Err(err) => {
+                        results[index] = BatchOperationResult::Failed(err);
+                        return Ok(results);
+                    }
+                }
+                continue;
+            };
+
+            let mut indexes = vec![index];
+            let mut creates = vec![create];
+            while let Some((index, BatchOperation::Create(create))) =
+                operations.next_if(|(_, operation)| matches!(operation, BatchOperation::Create(_)))
+            {
+                indexes.push(index);
+                creates.push(create);
+            }
+            match create_all_in(unit_of_work.as_ref(), creates).await {
+                Ok(ids) => applied.extend(
+                    indexes
+                        .into_iter()
+                        .zip(ids)
+                        .map(|(index, id)| (index, BatchOperationOutcome::Created { id })),
+                ),
+                Err(err) => {
+                    for index in indexes {
+                        results[index] = BatchOperationResult::Failed(err.clone());
+                    }
+                    return Ok(results);
+                }
+            }
+        }
+        unit_of_work.commit().await?;
+
+        for (index, outcome) in applied {
+            results[index] = BatchOperationResult::Applied(outcome);
+        }
+        Ok(results)
+    }
+
+    async fn apply(&self, operation: BatchOperation) -> ApplicationResult<BatchOperationOutcome> {
+        let unit_of_work = self.unit_of_work.begin().await?;
+        let outcome = apply_in(unit_of_work.as_ref(), operation).await?;
+        unit_of_work.commit().await?;
+
+        Ok(outcome)
+    }
+}
+
+async fn create_all_in(
+    unit_of_work: &dyn UnitOfWork,
+    commands: Vec<CreateToDoItemCommand>,
+) -> ApplicationResult<Vec<Uuid>> {
+    let (items, revisions): (Vec<_>, Vec<_>) = commands
+        .into_iter()
+        .map(|command| {
+            let item = ToDoItem::new_with_lifecycle(
                 command.title,
                 command.note,
                 command.status,
                 command.due_at,
-                command.version,
-            ))
+            )
+            .with_owner(command.owner_id)
+            .with_tenant(command.tenant_id);
+            let revision = ToDoItemRevision::new(
+                &item,
+                ChangeType::Created,
+                command.owner_id,
+                command.request_id,
+            );
+            (item, revision)
+        })
+        .unzip();
+    let events = items.iter().map(ToDoItem::created_event).collect();
+
+    let ids = unit_of_work.to_do_items().create_many(items).await?;
+    unit_of_work.outbox().append(events).await?;
+    unit_of_work.revisions().append(revisions).await?;
+
+    Ok(ids)
+}
+
+async fn apply_in(
+    unit_of_work: &dyn UnitOfWork,
+    operation: BatchOperation,
+) -> ApplicationResult<BatchOperationOutcome> {
+    match operation {
+        BatchOperation::Create(command) => {
+            let ids = create_all_in(unit_of_work, vec![command]).await?;
+            Ok(BatchOperationOutcome::Created { id: ids[0] })
+        }
+        BatchOperation::Update(command) => {
+            let version = command.version + 1;
+            let item = unit_of_work
+                .to_do_items()
+                .get_for_update(command.id, command.scope.clone())
+                .await?;
+            let id = update_in(unit_of_work, item, command).await?;
+            Ok(BatchOperationOutcome::Updated { id, version })
+        }
+        BatchOperation::Delete { command, version } => {
+            let id = command.id;
+            delete_in(unit_of_work, command, version).await?;
+            Ok(BatchOperationOutcome::Deleted { id })
+        }
+    }
+}
+
+pub struct RestoreToDoItemCommandHandler {
+    unit_of_work: Arc<dyn UnitOfWorkFactory + Send + Sync>,
+}
+
+impl RestoreToDoItemCommandHandler {
+    pub fn new(
+        unit_of_work: Arc<dyn UnitOfWorkFactory + Send + Sync>,
+    ) -> RestoreToDoItemCommandHandler {
+        RestoreToDoItemCommandHandler { unit_of_work }
+    }
+
+    /// Returns the restored item with its bumped version.
+    pub async fn execute(&self, command: RestoreToDoItemCommand) -> ApplicationResult<ToDoItem> {
+        let unit_of_work = self.unit_of_work.begin().await?;
+        let mut item = match unit_of_work
+            .to_do_items()
+            .get_deleted_for_update(command.id, command.scope.clone())
             .await
+        {
+            Ok(item) => item,
+            Err(ApplicationError::NotFound { id }) => {
+                unit_of_work
+                    .to_do_items()
+                    .get_for_update(id, command.scope)
+                    .await?;
+                return Err(ApplicationError::NotDeleted { id });
+            }
+            Err(err) => return Err(err),
+        };
+        let event = item
+            .restore(command.restored_by)
+            .ok_or(ApplicationError::NotDeleted { id: item.id })?;
+
+        unit_of_work.to_do_items().restore(item.clone()).await?;
+        item.version += 1;
+        unit_of_work.outbox().append(vec![event]).await?;
+        unit_of_work
+            .revisions()
+            .append(vec![ToDoItemRevision::new(
+                &item,
+                ChangeType::Restored,
+                command.restored_by,
+                command.request_id,
+            )])
+            .await?;
+        unit_of_work.commit().await?;
+
+        Ok(item)
     }
 }
 
-pub struct DeleteToDoItemCommandHandler {
-    repository: Arc<dyn ToDoItemCommandRepository + Send + Sync>,
+/// Physically removes items whose soft delete is older than the command's cutoff.
+///
+/// Every batch runs in its own unit of work so that a large purge never holds row locks
+/// for longer than one batch. The outbox messages of purged items go in the same unit of
+/// work, since their payloads still carry the item's content.
+pub struct PurgeDeletedToDoItemsCommandHandler {
+    unit_of_work: Arc<dyn UnitOfWorkFactory + Send + Sync>,
 }
 
-impl DeleteToDoItemCommandHandler {
+impl PurgeDeletedToDoItemsCommandHandler {
     pub fn new(
-        repository: Arc<dyn ToDoItemCommandRepository + Send + Sync>,
-    ) -> DeleteToDoItemCommandHandler {
-        DeleteToDoItemCommandHandler { repository }
+        unit_of_work: Arc<dyn UnitOfWorkFactory + Send + Sync>,
+    ) -> PurgeDeletedToDoItemsCommandHandler {
+        PurgeDeletedToDoItemsCommandHandler { unit_of_work }
     }
 
-    pub async fn execute(&self, command: DeleteToDoItemCommand) -> ApplicationResult<()> {
-        self.repository.delete(command.id, command.deleted_by).await
+    /// Returns the total number of purged items.
+    pub async fn execute(&self, command: PurgeDeletedToDoItemsCommand) -> ApplicationResult<usize> {
+        let batch_size = command.batch_size.max(1);
+        let mut purged = 0;
+
+        loop {
+            let unit_of_work = self.unit_of_work.begin().await?;
+            let ids = unit_of_work
+                .to_do_items()
+                .get_purgeable_ids(command.deleted_before, batch_size)
+                .await?;
+            let count = ids.len();
+            if count == 0 {
+                return Ok(purged);
+            }
+
+            unit_of_work.to_do_items().purge(ids.clone()).await?;
+            unit_of_work.outbox().purge_aggregates(ids).await?;
+            unit_of_work.commit().await?;
+            purged += count;
+
+            if (count as i64) < batch_size {
+                return Ok(purged);
+            }
+        }
+    }
+}
+
+/// Publishes one batch of pending outbox messages.
+///
+/// Messages are published in order. A message the publisher rejects is scheduled for a
+/// retry with the handler's [`OutboxRetryPolicy`], or marked failed once it runs out of
+/// attempts, and the later messages of its item are held back so they are not delivered
+/// ahead of it. Messages of other items are still published.
+pub struct DispatchOutboxCommandHandler {
+    unit_of_work: Arc<dyn UnitOfWorkFactory + Send + Sync>,
+    publisher: Arc<dyn EventPublisher + Send + Sync>,
+    retry_policy: OutboxRetryPolicy,
+}
+
+impl DispatchOutboxCommandHandler {
+    pub fn new(
+        unit_of_work: Arc<dyn UnitOfWorkFactory + Send + Sync>,
+        publisher: Arc<dyn EventPublisher + Send + Sync>,
+    ) -> DispatchOutboxCommandHandler {
+        DispatchOutboxCommandHandler {
+            unit_of_work,
+            publisher,
+            retry_policy: OutboxRetryPolicy::default(),
+        }
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: OutboxRetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub async fn execute(
+        &self,
+        command: DispatchOutboxCommand,
+    ) -> ApplicationResult<OutboxDispatch> {
+        let unit_of_work = self.unit_of_work.begin().await?;
+        let messages = unit_of_work
+            .outbox()
+            .fetch_pending(command.batch_size)
+            .await?;
+
+        let mut dispatch = OutboxDispatch {
+            fetched: messages.len(),
+            ..OutboxDispatch::default()
+        };
+        let mut held_back = HashSet::new();
+        for message in messages {
+            if held_back.contains(&message.aggregate_id) {
+                continue;
+            }
+
+            match self.publisher.publish(&message).await {
+                Ok(()) => {
+                    unit_of_work.outbox().mark_published(message.id).await?;
+                    dispatch.published += 1;
+                }
+                Err(err) => {
+                    held_back.insert(message.aggregate_id);
+                    let retry_at = self.retry_policy.retry_at(message.attempts + 1, Utc::now());
+                    match retry_at {
+                        Some(_) => dispatch.retried += 1,
+                        None => dispatch.failed += 1,
+                    }
+                    unit_of_work
+                        .outbox()
+                        .mark_failed(message.id, err.to_string(), retry_at)
+                        .await?;
+                }
+            }
+        }
+        unit_of_work.commit().await?;
+
+        Ok(dispatch)
     }
 }
 
@@ -120,16 +582,119 @@ impl GetDeletedToDoItemForAuditQueryHandler {
         &self,
         query: GetDeletedToDoItemForAuditQuery,
     ) -> ApplicationResult<ToDoItem> {
-        self.repository.get_deleted_by_id_for_audit(query.id).await
+        self.repository
+            .get_deleted_by_id_for_audit(query.id, query.scope)
+            .await
+    }
+}
+
+pub struct GetDeletedToDoItemsForAuditQueryHandler {
+    repository: Arc<dyn ToDoItemQueryRepository + Send + Sync>,
+}
+
+impl GetDeletedToDoItemsForAuditQueryHandler {
+    pub fn new(
+        repository: Arc<dyn ToDoItemQueryRepository + Send + Sync>,
+    ) -> GetDeletedToDoItemsForAuditQueryHandler {
+        GetDeletedToDoItemsForAuditQueryHandler { repository }
+    }
+
+    pub async fn execute(
+        &self,
+        query: GetDeletedToDoItemsForAuditQuery,
+    ) -> ApplicationResult<PaginatedResult<ToDoItem>> {
+        self.repository.get_deleted_for_audit(query).await
+    }
+}
+
+pub struct GetToDoItemHistoryQueryHandler {
+    repository: Arc<dyn ToDoItemQueryRepository + Send + Sync>,
+}
+
+impl GetToDoItemHistoryQueryHandler {
+    pub fn new(
+        repository: Arc<dyn ToDoItemQueryRepository + Send + Sync>,
+    ) -> GetToDoItemHistoryQueryHandler {
+        GetToDoItemHistoryQueryHandler { repository }
+    }
+
+    /// Reports items without any recorded revision as not found.
+    pub async fn execute(
+        &self,
+        query: GetToDoItemHistoryQuery,
+    ) -> ApplicationResult<PaginatedResult<ToDoItemRevision>> {
+        let history = self
+            .repository
+            .get_history(query.id, query.page, query.page_size, query.scope)
+            .await?;
+        if history.total_items == Some(0) {
+            return Err(ApplicationError::NotFound { id: query.id });
+        }
+
+        Ok(history)
+    }
+}
+
+pub struct GetToDoItemRevisionQueryHandler {
+    repository: Arc<dyn ToDoItemQueryRepository + Send + Sync>,
+}
+
+impl GetToDoItemRevisionQueryHandler {
+    pub fn new(
+        repository: Arc<dyn ToDoItemQueryRepository + Send + Sync>,
+    ) -> GetToDoItemRevisionQueryHandler {
+        GetToDoItemRevisionQueryHandler { repository }
+    }
+
+    pub async fn execute(
+        &self,
+        query: GetToDoItemRevisionQuery,
+    ) -> ApplicationResult<ToDoItemRevision> {
+        self.repository
+            .get_revision(query.id, query.version, query.scope)
+            .await
+    }
+}
+
+pub struct GetToDoItemDiffQueryHandler {
+    repository: Arc<dyn ToDoItemQueryRepository + Send + Sync>,
+}
+
+impl GetToDoItemDiffQueryHandler {
+    pub fn new(
+        repository: Arc<dyn ToDoItemQueryRepository + Send + Sync>,
+    ) -> GetToDoItemDiffQueryHandler {
+        GetToDoItemDiffQueryHandler { repository }
+    }
+
+    /// Reports the item as not found unless both revisions exist.
+    pub async fn execute(&self, query: GetToDoItemDiffQuery) -> ApplicationResult<ToDoItemDiff> {
+        let revisions = self
+            .repository
+            .get_revisions(query.id, query.from, query.to, query.scope)
+            .await?;
+        let complete = revisions.first().map(|revision| revision.snapshot.version)
+            == Some(query.from)
+            && revisions.last().map(|revision| revision.snapshot.version) == Some(query.to);
+        if !complete {
+            return Err(ApplicationError::NotFound { id: query.id });
+        }
+
+        ToDoItemDiff::between(revisions).ok_or(ApplicationError::NotFound { id: query.id })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{ApplicationError, PaginatedResult};
+    use crate::in_memory::InMemoryUnitOfWorkFactory;
+    use crate::outbox::OutboxMessage;
+    use crate::repositories::ToDoItemCommandRepository;
+    use crate::AccessScope;
+    use crate::PaginatedResult;
     use async_trait::async_trait;
-    use chrono::Utc;
+    use chrono::{DateTime, Duration, Utc};
+    use domain::ToDoItemStatus;
     use std::sync::{Arc, Mutex};
 
     struct QueryOnlyRepository {
@@ -154,49 +719,201 @@ mod tests {
             Ok(PaginatedResult::new(items, query.page, query.page_size, 1))
         }
 
-        async fn get_by_id(&self, id: Uuid) -> ApplicationResult<ToDoItem> {
+        async fn get_by_id(&self, id: Uuid, scope: AccessScope) -> ApplicationResult<ToDoItem> {
             self.items
                 .lock()
                 .expect("items lock")
                 .iter()
-                .find(|item| item.id == id)
+                .find(|item| item.id == id && scope.permits(item))
                 .cloned()
                 .ok_or(ApplicationError::NotFound { id })
         }
 
-        async fn get_deleted_by_id_for_audit(&self, id: Uuid) -> ApplicationResult<ToDoItem> {
-            self.get_by_id(id).await
+        async fn get_deleted_by_id_for_audit(
+            &self,
+            id: Uuid,
+            scope: AccessScope,
+        ) -> ApplicationResult<ToDoItem> {
+            self.get_by_id(id, scope).await
+        }
+
+        async fn get_deleted_for_audit(
+            &self,
+            query: GetDeletedToDoItemsForAuditQuery,
+        ) -> ApplicationResult<PaginatedResult<ToDoItem>> {
+            let items = self.items.lock().expect("items lock").clone();
+            Ok(PaginatedResult::new(items, query.page, query.page_size, 1))
+        }
+
+        async fn get_history(
+            &self,
+            _id: Uuid,
+            page: u32,
+            page_size: u32,
+            _scope: AccessScope,
+        ) -> ApplicationResult<PaginatedResult<ToDoItemRevision>> {
+            Ok(PaginatedResult::new(Vec::new(), page, page_size, 0))
+        }
+
+        async fn get_revision(
+            &self,
+            id: Uuid,
+            _version: i32,
+            _scope: AccessScope,
+        ) -> ApplicationResult<ToDoItemRevision> {
+            Err(ApplicationError::NotFound { id })
+        }
+
+        async fn get_revisions(
+            &self,
+            _id: Uuid,
+            _from: i32,
+            _to: i32,
+            _scope: AccessScope,
+        ) -> ApplicationResult<Vec<ToDoItemRevision>> {
+            Ok(Vec::new())
         }
     }
 
     struct CommandOnlyRepository {
         created: Arc<Mutex<Vec<ToDoItem>>>,
+        updated: Arc<Mutex<Vec<ToDoItem>>>,
         deleted: Arc<Mutex<Vec<Uuid>>>,
+        restored: Arc<Mutex<Vec<ToDoItem>>>,
+        rejected_title: Option<&'static str>,
     }
 
     impl CommandOnlyRepository {
         fn new() -> Self {
             Self {
                 created: Arc::new(Mutex::new(Vec::new())),
+                updated: Arc::new(Mutex::new(Vec::new())),
                 deleted: Arc::new(Mutex::new(Vec::new())),
+                restored: Arc::new(Mutex::new(Vec::new())),
+                rejected_title: None,
+            }
+        }
+
+        /// Fails creates of items titled `title`, like a database constraint would.
+        fn rejecting(mut self, title: &'static str) -> Self {
+            self.rejected_title = Some(title);
+            self
+        }
+
+        fn check(&self, entity: &ToDoItem) -> ApplicationResult<()> {
+            if self.rejected_title.is_some() && entity.title.as_deref() == self.rejected_title {
+                return Err(ApplicationError::internal("title violates a constraint"));
             }
+            Ok(())
+        }
+
+        fn with_item(item: ToDoItem) -> Self {
+            let repository = Self::new();
+            repository.created.lock().expect("created lock").push(item);
+            repository
         }
     }
 
     #[async_trait]
     impl ToDoItemCommandRepository for CommandOnlyRepository {
+        async fn get_for_update(
+            &self,
+            id: Uuid,
+            scope: AccessScope,
+        ) -> ApplicationResult<ToDoItem> {
+            self.created
+                .lock()
+                .expect("created lock")
+                .iter()
+                .find(|item| item.id == id && item.is_active() && scope.permits(item))
+                .cloned()
+                .ok_or(ApplicationError::NotFound { id })
+        }
+
+        async fn get_deleted_for_update(
+            &self,
+            id: Uuid,
+            scope: AccessScope,
+        ) -> ApplicationResult<ToDoItem> {
+            self.created
+                .lock()
+                .expect("created lock")
+                .iter()
+                .find(|item| item.id == id && item.is_deleted() && scope.permits(item))
+                .cloned()
+                .ok_or(ApplicationError::NotFound { id })
+        }
+
         async fn create(&self, entity: ToDoItem) -> ApplicationResult<Uuid> {
+            self.check(&entity)?;
             let id = entity.id;
             self.created.lock().expect("created lock").push(entity);
             Ok(id)
         }
 
-        async fn update(&self, entity: ToDoItem) -> ApplicationResult<Uuid> {
-            Ok(entity.id)
+        async fn create_many(&self, entities: Vec<ToDoItem>) -> ApplicationResult<Vec<Uuid>> {
+            for entity in &entities {
+                self.check(entity)?;
+            }
+            let ids = entities.iter().map(|entity| entity.id).collect();
+            self.created.lock().expect("created lock").extend(entities);
+            Ok(ids)
         }
 
-        async fn delete(&self, id: Uuid, _deleted_by: Option<Uuid>) -> ApplicationResult<()> {
+        async fn update(&self, entity: ToDoItem) -> ApplicationResult<ToDoItem> {
+            self.updated
+                .lock()
+                .expect("updated lock")
+                .push(entity.clone());
+            Ok(entity)
+        }
+
+        async fn delete(
+            &self,
+            id: Uuid,
+            deleted_by: Option<Uuid>,
+            scope: AccessScope,
+        ) -> ApplicationResult<ToDoItem> {
+            let mut deleted = self.get_for_update(id, scope).await?;
+            deleted.deleted_at = Some(Utc::now());
+            deleted.deleted_by = deleted_by;
+            deleted.version += 1;
             self.deleted.lock().expect("deleted lock").push(id);
+            Ok(deleted)
+        }
+
+        async fn restore(&self, entity: ToDoItem) -> ApplicationResult<Uuid> {
+            let id = entity.id;
+            self.restored.lock().expect("restored lock").push(entity);
+            Ok(id)
+        }
+
+        async fn get_purgeable_ids(
+            &self,
+            deleted_before: DateTime<Utc>,
+            limit: i64,
+        ) -> ApplicationResult<Vec<Uuid>> {
+            let mut deleted = self
+                .created
+                .lock()
+                .expect("created lock")
+                .iter()
+                .filter(|item| item.deleted_at.is_some_and(|at| at < deleted_before))
+                .cloned()
+                .collect::<Vec<_>>();
+            deleted.sort_by_key(|item| item.deleted_at);
+            Ok(deleted
+                .into_iter()
+                .take(limit as usize)
+                .map(|item| item.id)
+                .collect())
+        }
+
+        async fn purge(&self, ids: Vec<Uuid>) -> ApplicationResult<()> {
+            self.created
+                .lock()
+                .expect("created lock")
+                .retain(|item| !ids.contains(&item.id));
             Ok(())
         }
     }
@@ -206,7 +923,7 @@ mod tests {
         let item = ToDoItem::new_with_lifecycle(
             "title".to_string(),
             "note".to_string(),
-            "pending",
+            ToDoItemStatus::Pending,
             Some(Utc::now()),
         );
         let id = item.id;
@@ -225,11 +942,17 @@ mod tests {
     #[tokio::test]
     async fn command_handlers_work_with_command_repository_only() {
         let repository = Arc::new(CommandOnlyRepository::new());
-        let create_handler = CreateToDoItemCommandHandler::new(repository.clone());
-        let delete_handler = DeleteToDoItemCommandHandler::new(repository.clone());
+        let unit_of_work = Arc::new(InMemoryUnitOfWorkFactory::new(repository.clone()));
+        let create_handler = CreateToDoItemCommandHandler::new(unit_of_work.clone());
+        let delete_handler = DeleteToDoItemCommandHandler::new(unit_of_work);
 
         let created_id = create_handler
-            .execute(CreateToDoItemCommand::new("title", "note", "pending", None))
+            .execute(CreateToDoItemCommand::new(
+                "title",
+                "note",
+                ToDoItemStatus::Pending,
+                None,
+            ))
             .await
             .expect("create result");
         delete_handler
@@ -243,4 +966,867 @@ mod tests {
             [created_id]
         );
     }
+
+    #[tokio::test]
+    async fn update_handler_applies_allowed_status_transition() {
+        let item = ToDoItem::new("title".to_string(), "note".to_string());
+        let id = item.id;
+        let repository = Arc::new(CommandOnlyRepository::with_item(item));
+        let handler = UpdateToDoItemCommandHandler::new(Arc::new(InMemoryUnitOfWorkFactory::new(
+            repository.clone(),
+        )));
+
+        handler
+            .execute(UpdateToDoItemCommand::new(
+                id,
+                "title",
+                "note",
+                ToDoItemStatus::InProgress,
+                None,
+                1,
+            ))
+            .await
+            .expect("pending -> in_progress should be allowed");
+
+        let updated = repository.updated.lock().expect("updated lock");
+        assert_eq!(updated.len(), 1);
+        assert_eq!(updated[0].status, ToDoItemStatus::InProgress);
+    }
+
+    #[tokio::test]
+    async fn update_handler_rejects_illegal_status_transition() {
+        let item = ToDoItem::new("title".to_string(), "note".to_string());
+        let id = item.id;
+        let repository = Arc::new(CommandOnlyRepository::with_item(item));
+        let handler = UpdateToDoItemCommandHandler::new(Arc::new(InMemoryUnitOfWorkFactory::new(
+            repository.clone(),
+        )));
+
+        let result = handler
+            .execute(UpdateToDoItemCommand::new(
+                id,
+                "title",
+                "note",
+                ToDoItemStatus::Done,
+                None,
+                1,
+            ))
+            .await;
+
+        assert_eq!(
+            result,
+            Err(ApplicationError::InvalidStatusTransition {
+                id,
+                from: ToDoItemStatus::Pending,
+                to: ToDoItemStatus::Done,
+            })
+        );
+        assert!(repository.updated.lock().expect("updated lock").is_empty());
+    }
+
+    #[tokio::test]
+    async fn update_handler_reports_stale_version_before_transition_errors() {
+        let item = ToDoItem::new("title".to_string(), "note".to_string());
+        let id = item.id;
+        let repository = Arc::new(CommandOnlyRepository::with_item(item));
+        let handler =
+            UpdateToDoItemCommandHandler::new(Arc::new(InMemoryUnitOfWorkFactory::new(repository)));
+
+        let result = handler
+            .execute(UpdateToDoItemCommand::new(
+                id,
+                "title",
+                "note",
+                ToDoItemStatus::Done,
+                None,
+                7,
+            ))
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(ApplicationError::Conflict {
+                expected_version: 7,
+                actual_version: 1,
+                ..
+            })
+        ));
+    }
+
+    struct RecordingPublisher {
+        published: Mutex<Vec<OutboxMessage>>,
+        fail_on: Option<String>,
+    }
+
+    impl RecordingPublisher {
+        fn new(fail_on: Option<&str>) -> Self {
+            Self {
+                published: Mutex::new(Vec::new()),
+                fail_on: fail_on.map(str::to_string),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl EventPublisher for RecordingPublisher {
+        async fn publish(&self, message: &OutboxMessage) -> ApplicationResult<()> {
+            if self.fail_on.as_deref() == Some(message.event_type.as_str()) {
+                return Err(ApplicationError::internal("broker unavailable"));
+            }
+            self.published
+                .lock()
+                .expect("published lock")
+                .push(message.clone());
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn command_handlers_append_domain_events_to_outbox() {
+        let repository = Arc::new(CommandOnlyRepository::new());
+        let unit_of_work = Arc::new(InMemoryUnitOfWorkFactory::new(repository.clone()));
+        let create_handler = CreateToDoItemCommandHandler::new(unit_of_work.clone());
+        let update_handler = UpdateToDoItemCommandHandler::new(unit_of_work.clone());
+        let delete_handler = DeleteToDoItemCommandHandler::new(unit_of_work.clone());
+
+        let id = create_handler
+            .execute(CreateToDoItemCommand::new(
+                "title",
+                "note",
+                ToDoItemStatus::Pending,
+                None,
+            ))
+            .await
+            .expect("create result");
+        update_handler
+            .execute(UpdateToDoItemCommand::new(
+                id,
+                "renamed",
+                "note",
+                ToDoItemStatus::InProgress,
+                None,
+                1,
+            ))
+            .await
+            .expect("update result");
+        delete_handler
+            .execute(DeleteToDoItemCommand::new(id, None))
+            .await
+            .expect("delete result");
+
+        let event_types: Vec<_> = unit_of_work
+            .pending_outbox_messages()
+            .into_iter()
+            .map(|message| message.event_type)
+            .collect();
+        assert_eq!(
+            event_types,
+            [
+                "to_do_item.created",
+                "to_do_item.status_changed",
+                "to_do_item.updated",
+                "to_do_item.deleted",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn command_handlers_record_a_numbered_revision_per_change() {
+        let actor = Some(Uuid::new_v4());
+        let mut deleted = ToDoItem::new("deleted".to_string(), "note".to_string());
+        deleted.mark_deleted_once(actor);
+        let deleted_id = deleted.id;
+        let unit_of_work = Arc::new(InMemoryUnitOfWorkFactory::new(Arc::new(
+            CommandOnlyRepository::with_item(deleted),
+        )));
+        let request_id = Some("req-1".to_string());
+
+        let id = CreateToDoItemCommandHandler::new(unit_of_work.clone())
+            .execute(
+                CreateToDoItemCommand::new("title", "note", ToDoItemStatus::Pending, None)
+                    .with_owner(actor)
+                    .with_request_id(request_id.clone()),
+            )
+            .await
+            .expect("create result");
+        PatchToDoItemCommandHandler::new(unit_of_work.clone())
+            .execute(
+                PatchToDoItemCommand::new(id, 1)
+                    .with_title("renamed")
+                    .with_updated_by(actor),
+            )
+            .await
+            .expect("patch result");
+        DeleteToDoItemCommandHandler::new(unit_of_work.clone())
+            .execute(DeleteToDoItemCommand::new(id, actor))
+            .await
+            .expect("delete result");
+        RestoreToDoItemCommandHandler::new(unit_of_work.clone())
+            .execute(RestoreToDoItemCommand::new(deleted_id, actor))
+            .await
+            .expect("restore result");
+
+        let revisions = unit_of_work.recorded_revisions();
+        let summary: Vec<_> = revisions
+            .iter()
+            .map(|revision| (revision.item_id, revision.revision, revision.change_type))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (id, 1, ChangeType::Created),
+                (id, 2, ChangeType::Updated),
+                (id, 3, ChangeType::Deleted),
+                (deleted_id, 1, ChangeType::Restored),
+            ]
+        );
+        assert_eq!(revisions[0].request_id, request_id);
+        assert_eq!(revisions[1].snapshot.title.as_deref(), Some("renamed"));
+        assert_eq!(revisions[1].snapshot.version, 2);
+        assert!(revisions[2].snapshot.is_deleted());
+        assert_eq!(revisions[3].snapshot.version, 2);
+        assert!(revisions.iter().all(|revision| revision.actor == actor));
+    }
+
+    #[tokio::test]
+    async fn failed_command_does_not_append_events() {
+        let item = ToDoItem::new("title".to_string(), "note".to_string());
+        let id = item.id;
+        let unit_of_work = Arc::new(InMemoryUnitOfWorkFactory::new(Arc::new(
+            CommandOnlyRepository::with_item(item),
+        )));
+        let handler = UpdateToDoItemCommandHandler::new(unit_of_work.clone());
+
+        let result = handler
+            .execute(UpdateToDoItemCommand::new(
+                id,
+                "renamed",
+                "note",
+                ToDoItemStatus::Done,
+                None,
+                1,
+            ))
+            .await;
+
+        assert!(result.is_err());
+        assert!(unit_of_work.pending_outbox_messages().is_empty());
+    }
+
+    #[tokio::test]
+    async fn dispatch_handler_publishes_pending_messages_once() {
+        let repository = Arc::new(CommandOnlyRepository::new());
+        let unit_of_work = Arc::new(InMemoryUnitOfWorkFactory::new(repository));
+        let publisher = Arc::new(RecordingPublisher::new(None));
+        let create_handler = CreateToDoItemCommandHandler::new(unit_of_work.clone());
+        let dispatch_handler =
+            DispatchOutboxCommandHandler::new(unit_of_work.clone(), publisher.clone());
+
+        create_handler
+            .execute(CreateToDoItemCommand::new(
+                "title",
+                "note",
+                ToDoItemStatus::Pending,
+                None,
+            ))
+            .await
+            .expect("create result");
+
+        let first = dispatch_handler
+            .execute(DispatchOutboxCommand::new(10))
+            .await
+            .expect("first dispatch");
+        let second = dispatch_handler
+            .execute(DispatchOutboxCommand::new(10))
+            .await
+            .expect("second dispatch");
+
+        assert_eq!(first.published, 1);
+        assert_eq!(second, OutboxDispatch::default());
+        assert_eq!(publisher.published.lock().expect("published lock").len(), 1);
+        assert!(unit_of_work.pending_outbox_messages().is_empty());
+    }
+
+    #[tokio::test]
+    async fn dispatch_handler_holds_back_later_messages_of_a_failed_item() {
+        let repository = Arc::new(CommandOnlyRepository::new());
+        let unit_of_work = Arc::new(InMemoryUnitOfWorkFactory::new(repository));
+        let publisher = Arc::new(RecordingPublisher::new(Some("to_do_item.status_changed")));
+        let create_handler = CreateToDoItemCommandHandler::new(unit_of_work.clone());
+        let update_handler = UpdateToDoItemCommandHandler::new(unit_of_work.clone());
+        let dispatch_handler =
+            DispatchOutboxCommandHandler::new(unit_of_work.clone(), publisher.clone());
+
+        let id = create_handler
+            .execute(CreateToDoItemCommand::new(
+                "title",
+                "note",
+                ToDoItemStatus::Pending,
+                None,
+            ))
+            .await
+            .expect("create result");
+        update_handler
+            .execute(UpdateToDoItemCommand::new(
+                id,
+                "renamed",
+                "note",
+                ToDoItemStatus::InProgress,
+                None,
+                1,
+            ))
+            .await
+            .expect("update result");
+
+        let dispatch = dispatch_handler
+            .execute(DispatchOutboxCommand::new(10))
+            .await
+            .expect("dispatch result");
+
+        assert_eq!(dispatch.published, 1);
+        assert_eq!(dispatch.retried, 1);
+        assert_eq!(publisher.published.lock().expect("published lock").len(), 1);
+        let pending = unit_of_work.pending_outbox_messages();
+        assert_eq!(pending.len(), 2);
+        assert_eq!(pending[0].event_type, "to_do_item.status_changed");
+        assert_eq!(pending[0].attempts, 1);
+    }
+
+    #[tokio::test]
+    async fn dispatch_handler_keeps_publishing_past_a_message_that_keeps_failing() {
+        let repository = Arc::new(CommandOnlyRepository::new());
+        let unit_of_work = Arc::new(InMemoryUnitOfWorkFactory::new(repository));
+        let publisher = Arc::new(RecordingPublisher::new(Some("to_do_item.status_changed")));
+        let create_handler = CreateToDoItemCommandHandler::new(unit_of_work.clone());
+        let update_handler = UpdateToDoItemCommandHandler::new(unit_of_work.clone());
+        let dispatch_handler =
+            DispatchOutboxCommandHandler::new(unit_of_work.clone(), publisher.clone())
+                .with_retry_policy(OutboxRetryPolicy::new(
+                    2,
+                    std::time::Duration::ZERO,
+                    std::time::Duration::ZERO,
+                ));
+
+        let poisoned = create_handler
+            .execute(CreateToDoItemCommand::new(
+                "poisoned",
+                "note",
+                ToDoItemStatus::Pending,
+                None,
+            ))
+            .await
+            .expect("create result");
+        update_handler
+            .execute(UpdateToDoItemCommand::new(
+                poisoned,
+                "renamed",
+                "note",
+                ToDoItemStatus::InProgress,
+                None,
+                1,
+            ))
+            .await
+            .expect("update result");
+        create_handler
+            .execute(CreateToDoItemCommand::new(
+                "later",
+                "note",
+                ToDoItemStatus::Pending,
+                None,
+            ))
+            .await
+            .expect("create result");
+
+        let mut dispatches = Vec::new();
+        for _ in 0..4 {
+            let dispatch = dispatch_handler
+                .execute(DispatchOutboxCommand::new(10))
+                .await
+                .expect("dispatch result");
+            dispatches.push((dispatch.published, dispatch.retried, dispatch.failed));
+        }
+
+        assert_eq!(dispatches, vec![(2, 1, 0), (0, 0, 1), (1, 0, 0), (0, 0, 0)]);
+        let published = publisher.published.lock().expect("published lock");
+        assert_eq!(
+            published
+                .iter()
+                .map(|message| message.event_type.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "to_do_item.created",
+                "to_do_item.created",
+                "to_do_item.updated"
+            ]
+        );
+        assert!(unit_of_work.pending_outbox_messages().is_empty());
+        let failed = unit_of_work.failed_outbox_messages();
+        assert_eq!(failed.len(), 1);
+        assert_eq!(failed[0].event_type, "to_do_item.status_changed");
+        assert_eq!(failed[0].attempts, 2);
+    }
+
+    #[tokio::test]
+    async fn patch_handler_changes_only_provided_fields() {
+        let item = ToDoItem::new_with_lifecycle(
+            "title".to_string(),
+            "note".to_string(),
+            ToDoItemStatus::Pending,
+            Some(Utc::now()),
+        );
+        let id = item.id;
+        let repository = Arc::new(CommandOnlyRepository::with_item(item));
+        let handler = PatchToDoItemCommandHandler::new(Arc::new(InMemoryUnitOfWorkFactory::new(
+            repository.clone(),
+        )));
+
+        handler
+            .execute(
+                PatchToDoItemCommand::new(id, 1)
+                    .with_status(ToDoItemStatus::InProgress)
+                    .with_due_at(None),
+            )
+            .await
+            .expect("patch result");
+
+        let updated = repository.updated.lock().expect("updated lock");
+        assert_eq!(updated.len(), 1);
+        assert_eq!(updated[0].title.as_deref(), Some("title"));
+        assert_eq!(updated[0].note.as_deref(), Some("note"));
+        assert_eq!(updated[0].status, ToDoItemStatus::InProgress);
+        assert_eq!(updated[0].due_at, None);
+    }
+
+    #[tokio::test]
+    async fn patch_handler_without_changes_keeps_outbox_empty() {
+        let item = ToDoItem::new("title".to_string(), "note".to_string());
+        let id = item.id;
+        let unit_of_work = Arc::new(InMemoryUnitOfWorkFactory::new(Arc::new(
+            CommandOnlyRepository::with_item(item),
+        )));
+        let handler = PatchToDoItemCommandHandler::new(unit_of_work.clone());
+
+        handler
+            .execute(PatchToDoItemCommand::new(id, 1).with_title("title"))
+            .await
+            .expect("patch result");
+
+        assert!(unit_of_work.pending_outbox_messages().is_empty());
+    }
+
+    #[tokio::test]
+    async fn patch_handler_rejects_stale_version() {
+        let item = ToDoItem::new("title".to_string(), "note".to_string());
+        let id = item.id;
+        let handler = PatchToDoItemCommandHandler::new(Arc::new(InMemoryUnitOfWorkFactory::new(
+            Arc::new(CommandOnlyRepository::with_item(item)),
+        )));
+
+        let result = handler
+            .execute(PatchToDoItemCommand::new(id, 3).with_note("changed"))
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(ApplicationError::Conflict {
+                expected_version: 3,
+                actual_version: 1,
+                ..
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn restore_handler_restores_deleted_item_and_bumps_version() {
+        let mut item = ToDoItem::new("title".to_string(), "note".to_string());
+        item.mark_deleted_once(Some(Uuid::new_v4()));
+        let id = item.id;
+        let actor = Some(Uuid::new_v4());
+        let repository = Arc::new(CommandOnlyRepository::with_item(item));
+        let unit_of_work = Arc::new(InMemoryUnitOfWorkFactory::new(repository.clone()));
+        let handler = RestoreToDoItemCommandHandler::new(unit_of_work.clone());
+
+        let restored = handler
+            .execute(RestoreToDoItemCommand::new(id, actor))
+            .await
+            .expect("restore result");
+
+        assert_eq!(restored.version, 2);
+        assert!(restored.is_active());
+        assert_eq!(restored.restored_by, actor);
+        let persisted = repository.restored.lock().expect("restored lock");
+        assert_eq!(persisted.len(), 1);
+        assert_eq!(persisted[0].version, 1);
+        assert_eq!(
+            unit_of_work.pending_outbox_messages()[0].event_type,
+            "to_do_item.restored"
+        );
+    }
+
+    #[tokio::test]
+    async fn restore_handler_rejects_active_item() {
+        let item = ToDoItem::new("title".to_string(), "note".to_string());
+        let id = item.id;
+        let handler = RestoreToDoItemCommandHandler::new(Arc::new(InMemoryUnitOfWorkFactory::new(
+            Arc::new(CommandOnlyRepository::with_item(item)),
+        )));
+
+        let result = handler.execute(RestoreToDoItemCommand::new(id, None)).await;
+
+        assert_eq!(result, Err(ApplicationError::NotDeleted { id }));
+    }
+
+    #[tokio::test]
+    async fn restore_handler_reports_missing_item_as_not_found() {
+        let id = Uuid::new_v4();
+        let handler = RestoreToDoItemCommandHandler::new(Arc::new(InMemoryUnitOfWorkFactory::new(
+            Arc::new(CommandOnlyRepository::new()),
+        )));
+
+        let result = handler.execute(RestoreToDoItemCommand::new(id, None)).await;
+
+        assert_eq!(result, Err(ApplicationError::NotFound { id }));
+    }
+
+    #[tokio::test]
+    async fn scoped_commands_treat_items_of_other_owners_as_missing() {
+        let owner = Uuid::new_v4();
+        let intruder = AccessScope::owned_by(Uuid::new_v4());
+        let item = ToDoItem::new("title".to_string(), "note".to_string()).with_owner(Some(owner));
+        let id = item.id;
+        let repository = Arc::new(CommandOnlyRepository::with_item(item));
+        let unit_of_work = Arc::new(InMemoryUnitOfWorkFactory::new(repository.clone()));
+
+        let patched = PatchToDoItemCommandHandler::new(unit_of_work.clone())
+            .execute(
+                PatchToDoItemCommand::new(id, 1)
+                    .with_note("changed")
+                    .within(intruder.clone()),
+            )
+            .await;
+        DeleteToDoItemCommandHandler::new(unit_of_work.clone())
+            .execute(DeleteToDoItemCommand::new(id, None).within(intruder))
+            .await
+            .expect("delete result");
+        PatchToDoItemCommandHandler::new(unit_of_work)
+            .execute(
+                PatchToDoItemCommand::new(id, 1)
+                    .with_note("changed")
+                    .with_updated_by(Some(owner))
+                    .within(AccessScope::owned_by(owner)),
+            )
+            .await
+            .expect("owner patch result");
+
+        assert_eq!(patched, Err(ApplicationError::NotFound { id }));
+        assert!(repository.deleted.lock().expect("deleted lock").is_empty());
+        let updated = repository.updated.lock().expect("updated lock");
+        assert_eq!(updated.len(), 1);
+        assert_eq!(updated[0].updated_by, Some(owner));
+    }
+
+    #[tokio::test]
+    async fn create_handler_records_the_owner() {
+        let owner = Some(Uuid::new_v4());
+        let repository = Arc::new(CommandOnlyRepository::new());
+        let handler = CreateToDoItemCommandHandler::new(Arc::new(InMemoryUnitOfWorkFactory::new(
+            repository.clone(),
+        )));
+
+        handler
+            .execute(
+                CreateToDoItemCommand::new("title", "note", ToDoItemStatus::Pending, None)
+                    .with_owner(owner),
+            )
+            .await
+            .expect("create result");
+
+        assert_eq!(
+            repository.created.lock().expect("created lock")[0].owner_id,
+            owner
+        );
+    }
+
+    #[tokio::test]
+    async fn purge_handler_removes_only_expired_deletions_in_batches() {
+        let repository = Arc::new(CommandOnlyRepository::new());
+        let cutoff = Utc::now() - Duration::days(30);
+        let mut expected_survivors = Vec::new();
+        for deleted_days_ago in [Some(45), Some(31), Some(2), None] {
+            let mut item = ToDoItem::new("title".to_string(), "note".to_string());
+            if let Some(days) = deleted_days_ago {
+                item.mark_deleted_once(None);
+                item.deleted_at = Some(Utc::now() - Duration::days(days));
+            }
+            if item.deleted_at.is_none_or(|at| at >= cutoff) {
+                expected_survivors.push(item.id);
+            }
+            repository.created.lock().expect("created lock").push(item);
+        }
+        let handler = PurgeDeletedToDoItemsCommandHandler::new(Arc::new(
+            InMemoryUnitOfWorkFactory::new(repository.clone()),
+        ));
+
+        let purged = handler
+            .execute(PurgeDeletedToDoItemsCommand::new(cutoff, 1))
+            .await
+            .expect("purge result");
+
+        assert_eq!(purged, 2);
+        let survivors = repository
+            .created
+            .lock()
+            .expect("created lock")
+            .iter()
+            .map(|item| item.id)
+            .collect::<Vec<_>>();
+        assert_eq!(survivors, expected_survivors);
+    }
+
+    #[tokio::test]
+    async fn purge_handler_removes_outbox_messages_of_purged_items() {
+        let repository = Arc::new(CommandOnlyRepository::new());
+        let unit_of_work = Arc::new(InMemoryUnitOfWorkFactory::new(repository.clone()));
+        let create_handler = CreateToDoItemCommandHandler::new(unit_of_work.clone());
+        let delete_handler = DeleteToDoItemCommandHandler::new(unit_of_work.clone());
+        let purge_handler = PurgeDeletedToDoItemsCommandHandler::new(unit_of_work.clone());
+
+        let mut ids = Vec::new();
+        for title in ["purged", "kept"] {
+            ids.push(
+                create_handler
+                    .execute(CreateToDoItemCommand::new(
+                        title,
+                        "secret note",
+                        ToDoItemStatus::Pending,
+                        None,
+                    ))
+                    .await
+                    .expect("create result"),
+            );
+        }
+        delete_handler
+            .execute(DeleteToDoItemCommand::new(ids[0], None))
+            .await
+            .expect("delete result");
+        repository
+            .created
+            .lock()
+            .expect("created lock")
+            .iter_mut()
+            .find(|item| item.id == ids[0])
+            .expect("deleted item")
+            .deleted_at = Some(Utc::now() - Duration::days(45));
+
+        let purged = purge_handler
+            .execute(PurgeDeletedToDoItemsCommand::new(
+                Utc::now() - Duration::days(30),
+                10,
+            ))
+            .await
+            .expect("purge result");
+
+        assert_eq!(purged, 1);
+        let remaining = unit_of_work.pending_outbox_messages();
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].aggregate_id, ids[1]);
+    }
+
+    fn batch_handler(
+        repository: Arc<CommandOnlyRepository>,
+    ) -> (Arc<InMemoryUnitOfWorkFactory>, BatchToDoItemsCommandHandler) {
+        let unit_of_work = Arc::new(InMemoryUnitOfWorkFactory::new(repository));
+        (
+            unit_of_work.clone(),
+            BatchToDoItemsCommandHandler::new(unit_of_work),
+        )
+    }
+
+    #[tokio::test]
+    async fn atomic_batch_rolls_back_everything_after_a_failure() {
+        let item = ToDoItem::new("title".to_string(), "note".to_string());
+        let id = item.id;
+        let repository = Arc::new(CommandOnlyRepository::with_item(item));
+        let (unit_of_work, handler) = batch_handler(repository.clone());
+
+        let results = handler
+            .execute(BatchToDoItemsCommand::new(
+                vec![
+                    BatchOperation::Create(CreateToDoItemCommand::new(
+                        "new",
+                        "note",
+                        ToDoItemStatus::Pending,
+                        None,
+                    )),
+                    BatchOperation::Update(UpdateToDoItemCommand::new(
+                        id,
+                        "changed",
+                        "note",
+                        ToDoItemStatus::Pending,
+                        None,
+                        5,
+                    )),
+                    BatchOperation::Delete {
+                        command: DeleteToDoItemCommand::new(id, None),
+                        version: None,
+                    },
+                ],
+                true,
+            ))
+            .await
+            .expect("batch result");
+
+        assert!(matches!(results[0], BatchOperationResult::NotApplied));
+        assert!(matches!(
+            results[1],
+            BatchOperationResult::Failed(ApplicationError::Conflict { .. })
+        ));
+        assert!(matches!(results[2], BatchOperationResult::NotApplied));
+        assert_eq!(repository.created.lock().expect("created lock").len(), 1);
+        assert!(repository.updated.lock().expect("updated lock").is_empty());
+        assert!(repository.deleted.lock().expect("deleted lock").is_empty());
+        assert!(unit_of_work.pending_outbox_messages().is_empty());
+    }
+
+    #[tokio::test]
+    async fn non_atomic_batch_reports_each_operation_in_request_order() {
+        let item = ToDoItem::new("title".to_string(), "note".to_string());
+        let id = item.id;
+        let missing = Uuid::new_v4();
+        let repository = Arc::new(CommandOnlyRepository::with_item(item));
+        let (_, handler) = batch_handler(repository.clone());
+
+        let results = handler
+            .execute(BatchToDoItemsCommand::new(
+                vec![
+                    BatchOperation::Update(UpdateToDoItemCommand::new(
+                        missing,
+                        "changed",
+                        "note",
+                        ToDoItemStatus::Pending,
+                        None,
+                        1,
+                    )),
+                    BatchOperation::Create(CreateToDoItemCommand::new(
+                        "first",
+                        "note",
+                        ToDoItemStatus::Pending,
+                        None,
+                    )),
+                    BatchOperation::Update(UpdateToDoItemCommand::new(
+                        id,
+                        "changed",
+                        "note",
+                        ToDoItemStatus::InProgress,
+                        None,
+                        1,
+                    )),
+                    BatchOperation::Create(CreateToDoItemCommand::new(
+                        "second",
+                        "note",
+                        ToDoItemStatus::Pending,
+                        None,
+                    )),
+                ],
+                false,
+            ))
+            .await
+            .expect("batch result");
+
+        assert_eq!(
+            results[0],
+            BatchOperationResult::Failed(ApplicationError::NotFound { id: missing })
+        );
+        assert!(matches!(
+            results[1],
+            BatchOperationResult::Applied(BatchOperationOutcome::Created { .. })
+        ));
+        assert_eq!(
+            results[2],
+            BatchOperationResult::Applied(BatchOperationOutcome::Updated { id, version: 2 })
+        );
+        assert!(matches!(
+            results[3],
+            BatchOperationResult::Applied(BatchOperationOutcome::Created { .. })
+        ));
+        let created = repository.created.lock().expect("created lock");
+        assert_eq!(created.len(), 3);
+        assert_eq!(created[1].title.as_deref(), Some("first"));
+        assert_eq!(created[2].title.as_deref(), Some("second"));
+        assert_eq!(repository.updated.lock().expect("updated lock").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn non_atomic_batch_isolates_an_invalid_create_from_other_operations() {
+        let item = ToDoItem::new("title".to_string(), "note".to_string());
+        let id = item.id;
+        let repository = Arc::new(CommandOnlyRepository::with_item(item).rejecting("invalid"));
+        let (unit_of_work, handler) = batch_handler(repository.clone());
+        let create = |title| {
+            BatchOperation::Create(CreateToDoItemCommand::new(
+                title,
+                "note",
+                ToDoItemStatus::Pending,
+                None,
+            ))
+        };
+
+        let results = handler
+            .execute(BatchToDoItemsCommand::new(
+                vec![
+                    create("first"),
+                    BatchOperation::Update(UpdateToDoItemCommand::new(
+                        id,
+                        "changed",
+                        "note",
+                        ToDoItemStatus::InProgress,
+                        None,
+                        1,
+                    )),
+                    create("invalid"),
+                    create("last"),
+                ],
+                false,
+            ))
+            .await
+            .expect("batch result");
+
+        assert!(matches!(
+            results[0],
+            BatchOperationResult::Applied(BatchOperationOutcome::Created { .. })
+        ));
+        assert_eq!(
+            results[1],
+            BatchOperationResult::Applied(BatchOperationOutcome::Updated { id, version: 2 })
+        );
+        assert!(matches!(
+            results[2],
+            BatchOperationResult::Failed(ApplicationError::Internal { .. })
+        ));
+        assert!(matches!(
+            results[3],
+            BatchOperationResult::Applied(BatchOperationOutcome::Created { .. })
+        ));
+        let titles = repository
+            .created
+            .lock()
+            .expect("created lock")
+            .iter()
+            .map(|item| item.title.clone().unwrap_or_default())
+            .collect::<Vec<_>>();
+        assert_eq!(titles, vec!["title", "first", "last"]);
+        assert_eq!(repository.updated.lock().expect("updated lock").len(), 1);
+        assert_eq!(
+            unit_of_work
+                .pending_outbox_messages()
+                .iter()
+                .map(|message| message.event_type.as_str())
+                .collect::<Vec<_>>(),
+            vec![
+                "to_do_item.created",
+                "to_do_item.status_changed",
+                "to_do_item.updated",
+                "to_do_item.created"
+            ]
+        );
+    }
 }
@@ -0,0 +1,42 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+/// Response captured for an idempotency key so that retries can be answered without
+/// running the request again.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct IdempotentResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+/// Request claimed under an idempotency key.
+///
+/// `fingerprint` identifies the original request so a key reused for a different payload
+/// can be told apart from a retry. `response` stays empty while the request is in flight.
+#[derive(PartialEq, Debug, Clone)]
+pub struct IdempotencyRecord {
+    pub key: String,
+    pub fingerprint: String,
+    pub response: Option<IdempotentResponse>,
+    pub expires_at: DateTime<Utc>,
+}
+
+impl IdempotencyRecord {
+    pub fn new(
+        key: impl Into<String>,
+        fingerprint: impl Into<String>,
+        expires_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            key: key.into(),
+            fingerprint: fingerprint.into(),
+            response: None,
+            expires_at,
+        }
+    }
+
+    pub fn is_expired(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at <= now
+    }
+}
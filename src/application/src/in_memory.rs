@@ -0,0 +1,750 @@
+use crate::idempotency::{IdempotencyRecord, IdempotentResponse};
+use crate::outbox::OutboxMessage;
+use crate::repositories::{
+    ApiKeyRepository, IdempotencyRepository, OutboxRepository, RevisionRepository,
+    ToDoItemCommandRepository, UnitOfWork, UnitOfWorkFactory,
+};
+use crate::{AccessScope, ApiKey, ApplicationError, ApplicationResult, ToDoItemRevision};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::{ToDoItem, ToDoItemEvent};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use uuid::Uuid;
+
+/// Unit of work factory that stages writes in memory and replays them against the
+/// wrapped command repository on commit.
+///
+/// Intended for handler tests and other non-persistent setups: rolling back simply
+/// discards the staged writes, while a failure during commit leaves earlier writes applied.
+/// Outbox messages and revisions are kept by the factory itself and shared by all of its
+/// units of work.
+pub struct InMemoryUnitOfWorkFactory {
+    repository: Arc<dyn ToDoItemCommandRepository + Send + Sync>,
+    outbox: Arc<Mutex<Vec<InMemoryOutboxEntry>>>,
+    revisions: Arc<Mutex<Vec<ToDoItemRevision>>>,
+}
+
+impl InMemoryUnitOfWorkFactory {
+    pub fn new(repository: Arc<dyn ToDoItemCommandRepository + Send + Sync>) -> Self {
+        Self {
+            repository,
+            outbox: Arc::new(Mutex::new(Vec::new())),
+            revisions: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Returns committed revisions in the order they were recorded.
+    pub fn recorded_revisions(&self) -> Vec<ToDoItemRevision> {
+        self.revisions.lock().expect("revisions lock").clone()
+    }
+
+    /// Returns committed outbox messages that still await publishing.
+    pub fn pending_outbox_messages(&self) -> Vec<OutboxMessage> {
+        self.outbox_messages(|entry| !entry.published && !entry.failed)
+    }
+
+    /// Returns outbox messages that ran out of publish attempts.
+    pub fn failed_outbox_messages(&self) -> Vec<OutboxMessage> {
+        self.outbox_messages(|entry| entry.failed)
+    }
+
+    fn outbox_messages(&self, filter: impl Fn(&InMemoryOutboxEntry) -> bool) -> Vec<OutboxMessage> {
+        self.outbox
+            .lock()
+            .expect("outbox lock")
+            .iter()
+            .filter(|entry| filter(entry))
+            .map(|entry| entry.message.clone())
+            .collect()
+    }
+}
+
+#[async_trait]
+impl UnitOfWorkFactory for InMemoryUnitOfWorkFactory {
+    async fn begin(&self) -> ApplicationResult<Box<dyn UnitOfWork>> {
+        Ok(Box::new(InMemoryUnitOfWork {
+            repository: self.repository.clone(),
+            outbox: self.outbox.clone(),
+            revisions: self.revisions.clone(),
+            staged: Mutex::new(Vec::new()),
+            staged_outbox: Mutex::new(Vec::new()),
+            staged_revisions: Mutex::new(Vec::new()),
+        }))
+    }
+}
+
+struct InMemoryOutboxEntry {
+    message: OutboxMessage,
+    published: bool,
+    failed: bool,
+    retry_at: Option<DateTime<Utc>>,
+}
+
+impl InMemoryOutboxEntry {
+    fn waiting_for_retry(&self, now: DateTime<Utc>) -> bool {
+        !self.published && !self.failed && self.retry_at.is_some_and(|retry_at| retry_at > now)
+    }
+}
+
+enum StagedOutboxWrite {
+    Append(OutboxMessage),
+    Published(Uuid),
+    Failed {
+        id: Uuid,
+        retry_at: Option<DateTime<Utc>>,
+    },
+    PurgeAggregates(Vec<Uuid>),
+}
+
+enum StagedWrite {
+    Create(ToDoItem),
+    Update(ToDoItem),
+    Delete {
+        id: Uuid,
+        deleted_by: Option<Uuid>,
+        scope: AccessScope,
+    },
+    Restore(ToDoItem),
+    Purge(Uuid),
+}
+
+impl StagedWrite {
+    fn id(&self) -> Uuid {
+        match self {
+            StagedWrite::Create(item) | StagedWrite::Update(item) | StagedWrite::Restore(item) => {
+                item.id
+            }
+            StagedWrite::Delete { id, .. } | StagedWrite::Purge(id) => *id,
+        }
+    }
+}
+
+struct InMemoryUnitOfWork {
+    repository: Arc<dyn ToDoItemCommandRepository + Send + Sync>,
+    outbox: Arc<Mutex<Vec<InMemoryOutboxEntry>>>,
+    revisions: Arc<Mutex<Vec<ToDoItemRevision>>>,
+    staged: Mutex<Vec<StagedWrite>>,
+    staged_outbox: Mutex<Vec<StagedOutboxWrite>>,
+    staged_revisions: Mutex<Vec<ToDoItemRevision>>,
+}
+
+impl InMemoryUnitOfWork {
+    fn stage(&self, write: StagedWrite) {
+        self.staged.lock().expect("staged writes lock").push(write);
+    }
+
+    fn take_staged(&self) -> Vec<StagedWrite> {
+        std::mem::take(&mut *self.staged.lock().expect("staged writes lock"))
+    }
+
+    fn stage_outbox(&self, write: StagedOutboxWrite) {
+        self.staged_outbox
+            .lock()
+            .expect("staged outbox lock")
+            .push(write);
+    }
+
+    fn take_staged_outbox(&self) -> Vec<StagedOutboxWrite> {
+        std::mem::take(&mut *self.staged_outbox.lock().expect("staged outbox lock"))
+    }
+
+    fn take_staged_revisions(&self) -> Vec<ToDoItemRevision> {
+        std::mem::take(&mut *self.staged_revisions.lock().expect("staged revisions lock"))
+    }
+}
+
+#[async_trait]
+impl ToDoItemCommandRepository for InMemoryUnitOfWork {
+    async fn get_for_update(&self, id: Uuid, scope: AccessScope) -> ApplicationResult<ToDoItem> {
+        let latest = self
+            .staged
+            .lock()
+            .expect("staged writes lock")
+            .iter()
+            .rev()
+            .find(|write| write.id() == id)
+            .map(|write| match write {
+                StagedWrite::Create(item)
+                | StagedWrite::Update(item)
+                | StagedWrite::Restore(item)
+                    if !scope.permits(item) =>
+                {
+                    Err(ApplicationError::NotFound { id })
+                }
+                StagedWrite::Create(item) => Ok(item.clone()),
+                StagedWrite::Update(item) | StagedWrite::Restore(item) => {
+                    let mut item = item.clone();
+                    item.version += 1;
+                    Ok(item)
+                }
+                StagedWrite::Delete { .. } | StagedWrite::Purge(_) => {
+                    Err(ApplicationError::NotFound { id })
+                }
+            });
+
+        match latest {
+            Some(result) => result,
+            None => self.repository.get_for_update(id, scope).await,
+        }
+    }
+
+    async fn get_deleted_for_update(
+        &self,
+        id: Uuid,
+        scope: AccessScope,
+    ) -> ApplicationResult<ToDoItem> {
+        let staged_active = self
+            .staged
+            .lock()
+            .expect("staged writes lock")
+            .iter()
+            .rev()
+            .find(|write| write.id() == id)
+            .is_some_and(|write| !matches!(write, StagedWrite::Delete { .. }));
+
+        if staged_active {
+            return Err(ApplicationError::NotFound { id });
+        }
+        self.repository.get_deleted_for_update(id, scope).await
+    }
+
+    async fn create(&self, entity: ToDoItem) -> ApplicationResult<Uuid> {
+        let id = entity.id;
+        self.stage(StagedWrite::Create(entity));
+        Ok(id)
+    }
+
+    async fn create_many(&self, entities: Vec<ToDoItem>) -> ApplicationResult<Vec<Uuid>> {
+        let ids = entities.iter().map(|entity| entity.id).collect();
+        for entity in entities {
+            self.stage(StagedWrite::Create(entity));
+        }
+        Ok(ids)
+    }
+
+    async fn update(&self, entity: ToDoItem) -> ApplicationResult<ToDoItem> {
+        let mut updated = entity.clone();
+        updated.version += 1;
+        self.stage(StagedWrite::Update(entity));
+        Ok(updated)
+    }
+
+    async fn delete(
+        &self,
+        id: Uuid,
+        deleted_by: Option<Uuid>,
+        scope: AccessScope,
+    ) -> ApplicationResult<ToDoItem> {
+        let mut deleted = self.get_for_update(id, scope.clone()).await?;
+        deleted.deleted_at = Some(Utc::now());
+        deleted.deleted_by = deleted_by;
+        deleted.version += 1;
+        self.stage(StagedWrite::Delete {
+            id,
+            deleted_by,
+            scope,
+        });
+        Ok(deleted)
+    }
+
+    async fn restore(&self, entity: ToDoItem) -> ApplicationResult<Uuid> {
+        let id = entity.id;
+        self.stage(StagedWrite::Restore(entity));
+        Ok(id)
+    }
+
+    async fn get_purgeable_ids(
+        &self,
+        deleted_before: DateTime<Utc>,
+        limit: i64,
+    ) -> ApplicationResult<Vec<Uuid>> {
+        self.repository
+            .get_purgeable_ids(deleted_before, limit)
+            .await
+    }
+
+    async fn purge(&self, ids: Vec<Uuid>) -> ApplicationResult<()> {
+        for id in ids {
+            self.stage(StagedWrite::Purge(id));
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl OutboxRepository for InMemoryUnitOfWork {
+    async fn append(&self, events: Vec<ToDoItemEvent>) -> ApplicationResult<()> {
+        for event in events {
+            self.stage_outbox(StagedOutboxWrite::Append(OutboxMessage::new(event)));
+        }
+        Ok(())
+    }
+
+    async fn fetch_pending(&self, limit: i64) -> ApplicationResult<Vec<OutboxMessage>> {
+        let limit = usize::try_from(limit).unwrap_or_default();
+        let now = Utc::now();
+        let outbox = self.outbox.lock().expect("outbox lock");
+        Ok(outbox
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| {
+                !entry.published && !entry.failed && entry.retry_at.is_none_or(|at| at <= now)
+            })
+            .filter(|(position, entry)| {
+                !outbox[..*position].iter().any(|earlier| {
+                    earlier.message.aggregate_id == entry.message.aggregate_id
+                        && earlier.waiting_for_retry(now)
+                })
+            })
+            .take(limit)
+            .map(|(_, entry)| entry.message.clone())
+            .collect())
+    }
+
+    async fn mark_published(&self, id: Uuid) -> ApplicationResult<()> {
+        self.stage_outbox(StagedOutboxWrite::Published(id));
+        Ok(())
+    }
+
+    async fn mark_failed(
+        &self,
+        id: Uuid,
+        _error: String,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> ApplicationResult<()> {
+        self.stage_outbox(StagedOutboxWrite::Failed { id, retry_at });
+        Ok(())
+    }
+
+    async fn purge_aggregates(&self, aggregate_ids: Vec<Uuid>) -> ApplicationResult<()> {
+        self.stage_outbox(StagedOutboxWrite::PurgeAggregates(aggregate_ids));
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl RevisionRepository for InMemoryUnitOfWork {
+    async fn append(&self, revisions: Vec<ToDoItemRevision>) -> ApplicationResult<()> {
+        self.staged_revisions
+            .lock()
+            .expect("staged revisions lock")
+            .extend(revisions);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for InMemoryUnitOfWork {
+    fn to_do_items(&self) -> &dyn ToDoItemCommandRepository {
+        self
+    }
+
+    fn outbox(&self) -> &dyn OutboxRepository {
+        self
+    }
+
+    fn revisions(&self) -> &dyn RevisionRepository {
+        self
+    }
+
+    async fn commit(self: Box<Self>) -> ApplicationResult<()> {
+        let mut purged = Vec::new();
+        for write in self.take_staged() {
+            match write {
+                StagedWrite::Create(item) => {
+                    self.repository.create(item).await?;
+                }
+                StagedWrite::Update(item) => {
+                    self.repository.update(item).await?;
+                }
+                StagedWrite::Delete {
+                    id,
+                    deleted_by,
+                    scope,
+                } => {
+                    self.repository.delete(id, deleted_by, scope).await?;
+                }
+                StagedWrite::Restore(item) => {
+                    self.repository.restore(item).await?;
+                }
+                StagedWrite::Purge(id) => {
+                    self.repository.purge(vec![id]).await?;
+                    purged.push(id);
+                }
+            }
+        }
+
+        let mut outbox = self.outbox.lock().expect("outbox lock");
+        for write in self.take_staged_outbox() {
+            match write {
+                StagedOutboxWrite::Append(message) => outbox.push(InMemoryOutboxEntry {
+                    message,
+                    published: false,
+                    failed: false,
+                    retry_at: None,
+                }),
+                StagedOutboxWrite::Published(id) => {
+                    if let Some(entry) = outbox.iter_mut().find(|entry| entry.message.id == id) {
+                        entry.published = true;
+                    }
+                }
+                StagedOutboxWrite::Failed { id, retry_at } => {
+                    if let Some(entry) = outbox.iter_mut().find(|entry| entry.message.id == id) {
+                        entry.message.attempts += 1;
+                        entry.failed = retry_at.is_none();
+                        entry.retry_at = retry_at;
+                    }
+                }
+                StagedOutboxWrite::PurgeAggregates(aggregate_ids) => {
+                    outbox.retain(|entry| !aggregate_ids.contains(&entry.message.aggregate_id));
+                }
+            }
+        }
+        drop(outbox);
+
+        let mut revisions = self.revisions.lock().expect("revisions lock");
+        revisions.retain(|revision| !purged.contains(&revision.item_id));
+        for mut revision in self.take_staged_revisions() {
+            revision.revision = revisions
+                .iter()
+                .filter(|recorded| recorded.item_id == revision.item_id)
+                .map(|recorded| recorded.revision)
+                .max()
+                .unwrap_or_default()
+                + 1;
+            revisions.push(revision);
+        }
+
+        Ok(())
+    }
+
+    async fn rollback(self: Box<Self>) -> ApplicationResult<()> {
+        self.take_staged();
+        self.take_staged_outbox();
+        self.take_staged_revisions();
+        Ok(())
+    }
+}
+
+/// Idempotency store kept in process memory, for tests and single-instance setups.
+#[derive(Default)]
+pub struct InMemoryIdempotencyRepository {
+    records: Mutex<HashMap<String, IdempotencyRecord>>,
+}
+
+impl InMemoryIdempotencyRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl IdempotencyRepository for InMemoryIdempotencyRepository {
+    async fn reserve(
+        &self,
+        record: IdempotencyRecord,
+    ) -> ApplicationResult<Option<IdempotencyRecord>> {
+        let mut records = self.records.lock().expect("idempotency lock");
+        if let Some(existing) = records
+            .get(&record.key)
+            .filter(|existing| !existing.is_expired(Utc::now()))
+        {
+            return Ok(Some(existing.clone()));
+        }
+
+        records.insert(record.key.clone(), record);
+        Ok(None)
+    }
+
+    async fn complete(&self, key: &str, response: IdempotentResponse) -> ApplicationResult<()> {
+        if let Some(record) = self.records.lock().expect("idempotency lock").get_mut(key) {
+            record.response = Some(response);
+        }
+        Ok(())
+    }
+
+    async fn release(&self, key: &str) -> ApplicationResult<()> {
+        self.records.lock().expect("idempotency lock").remove(key);
+        Ok(())
+    }
+
+    async fn purge_expired(&self, now: DateTime<Utc>) -> ApplicationResult<usize> {
+        let mut records = self.records.lock().expect("idempotency lock");
+        let before = records.len();
+        records.retain(|_, record| !record.is_expired(now));
+        Ok(before - records.len())
+    }
+}
+
+/// API key store kept in process memory, for tests.
+#[derive(Default)]
+pub struct InMemoryApiKeyRepository {
+    keys: Mutex<Vec<ApiKey>>,
+}
+
+impl InMemoryApiKeyRepository {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for InMemoryApiKeyRepository {
+    async fn create(&self, key: ApiKey) -> ApplicationResult<()> {
+        self.keys.lock().expect("api keys lock").push(key);
+        Ok(())
+    }
+
+    async fn find_by_hash(&self, key_hash: &str) -> ApplicationResult<Option<ApiKey>> {
+        Ok(self
+            .keys
+            .lock()
+            .expect("api keys lock")
+            .iter()
+            .find(|key| key.key_hash == key_hash)
+            .cloned())
+    }
+
+    async fn list(&self) -> ApplicationResult<Vec<ApiKey>> {
+        let mut keys = self.keys.lock().expect("api keys lock").clone();
+        keys.sort_by_key(|key| std::cmp::Reverse(key.created_at));
+        Ok(keys)
+    }
+
+    async fn revoke(&self, id: Uuid, now: DateTime<Utc>) -> ApplicationResult<bool> {
+        let mut keys = self.keys.lock().expect("api keys lock");
+        match keys
+            .iter_mut()
+            .find(|key| key.id == id && !key.is_revoked())
+        {
+            Some(key) => {
+                key.revoked_at = Some(now);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    async fn record_use(&self, id: Uuid, used_at: DateTime<Utc>) -> ApplicationResult<()> {
+        if let Some(key) = self
+            .keys
+            .lock()
+            .expect("api keys lock")
+            .iter_mut()
+            .find(|key| key.id == id)
+        {
+            key.last_used_at = Some(used_at);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct RecordingRepository {
+        items: Mutex<Vec<ToDoItem>>,
+    }
+
+    #[async_trait]
+    impl ToDoItemCommandRepository for RecordingRepository {
+        async fn get_for_update(
+            &self,
+            id: Uuid,
+            scope: AccessScope,
+        ) -> ApplicationResult<ToDoItem> {
+            self.items
+                .lock()
+                .expect("items lock")
+                .iter()
+                .find(|item| item.id == id && scope.permits(item))
+                .cloned()
+                .ok_or(ApplicationError::NotFound { id })
+        }
+
+        async fn get_deleted_for_update(
+            &self,
+            id: Uuid,
+            _scope: AccessScope,
+        ) -> ApplicationResult<ToDoItem> {
+            Err(ApplicationError::NotFound { id })
+        }
+
+        async fn create(&self, entity: ToDoItem) -> ApplicationResult<Uuid> {
+            let id = entity.id;
+            self.items.lock().expect("items lock").push(entity);
+            Ok(id)
+        }
+
+        async fn create_many(&self, entities: Vec<ToDoItem>) -> ApplicationResult<Vec<Uuid>> {
+            let ids = entities.iter().map(|entity| entity.id).collect();
+            self.items.lock().expect("items lock").extend(entities);
+            Ok(ids)
+        }
+
+        async fn update(&self, entity: ToDoItem) -> ApplicationResult<ToDoItem> {
+            Ok(entity)
+        }
+
+        async fn delete(
+            &self,
+            id: Uuid,
+            deleted_by: Option<Uuid>,
+            _scope: AccessScope,
+        ) -> ApplicationResult<ToDoItem> {
+            let mut items = self.items.lock().expect("items lock");
+            let position = items
+                .iter()
+                .position(|item| item.id == id)
+                .ok_or(ApplicationError::NotFound { id })?;
+            let mut deleted = items.remove(position);
+            deleted.deleted_at = Some(Utc::now());
+            deleted.deleted_by = deleted_by;
+            deleted.version += 1;
+            Ok(deleted)
+        }
+
+        async fn restore(&self, entity: ToDoItem) -> ApplicationResult<Uuid> {
+            Ok(entity.id)
+        }
+
+        async fn get_purgeable_ids(
+            &self,
+            _deleted_before: DateTime<Utc>,
+            _limit: i64,
+        ) -> ApplicationResult<Vec<Uuid>> {
+            Ok(Vec::new())
+        }
+
+        async fn purge(&self, _ids: Vec<Uuid>) -> ApplicationResult<()> {
+            Ok(())
+        }
+    }
+
+    fn factory() -> (Arc<RecordingRepository>, InMemoryUnitOfWorkFactory) {
+        let repository = Arc::new(RecordingRepository {
+            items: Mutex::new(Vec::new()),
+        });
+        let factory = InMemoryUnitOfWorkFactory::new(repository.clone());
+        (repository, factory)
+    }
+
+    #[tokio::test]
+    async fn staged_writes_are_applied_on_commit() {
+        let (repository, factory) = factory();
+        let unit_of_work = factory.begin().await.expect("begin");
+
+        let first = unit_of_work
+            .to_do_items()
+            .create(ToDoItem::new("first".into(), "note".into()))
+            .await
+            .expect("create first");
+        unit_of_work
+            .to_do_items()
+            .create(ToDoItem::new("second".into(), "note".into()))
+            .await
+            .expect("create second");
+
+        assert!(repository.items.lock().expect("items lock").is_empty());
+        assert!(unit_of_work
+            .to_do_items()
+            .get_for_update(first, AccessScope::default())
+            .await
+            .is_ok());
+
+        unit_of_work.commit().await.expect("commit");
+
+        assert_eq!(repository.items.lock().expect("items lock").len(), 2);
+    }
+
+    #[tokio::test]
+    async fn rollback_discards_staged_writes() {
+        let (repository, factory) = factory();
+        let unit_of_work = factory.begin().await.expect("begin");
+
+        unit_of_work
+            .to_do_items()
+            .create(ToDoItem::new("title".into(), "note".into()))
+            .await
+            .expect("create");
+        unit_of_work.rollback().await.expect("rollback");
+
+        assert!(repository.items.lock().expect("items lock").is_empty());
+    }
+
+    #[tokio::test]
+    async fn dropping_uncommitted_unit_of_work_discards_staged_writes() {
+        let (repository, factory) = factory();
+
+        {
+            let unit_of_work = factory.begin().await.expect("begin");
+            unit_of_work
+                .to_do_items()
+                .create(ToDoItem::new("title".into(), "note".into()))
+                .await
+                .expect("create");
+        }
+
+        assert!(repository.items.lock().expect("items lock").is_empty());
+    }
+
+    #[tokio::test]
+    async fn staged_delete_hides_item_from_get_for_update() {
+        let (repository, factory) = factory();
+        let item = ToDoItem::new("title".into(), "note".into());
+        let id = item.id;
+        repository.items.lock().expect("items lock").push(item);
+        let unit_of_work = factory.begin().await.expect("begin");
+
+        unit_of_work
+            .to_do_items()
+            .delete(id, None, AccessScope::default())
+            .await
+            .expect("delete");
+
+        assert_eq!(
+            unit_of_work
+                .to_do_items()
+                .get_for_update(id, AccessScope::default())
+                .await,
+            Err(ApplicationError::NotFound { id })
+        );
+    }
+
+    #[tokio::test]
+    async fn outbox_messages_become_pending_only_after_commit() {
+        let (_, factory) = factory();
+        let item = ToDoItem::new("title".into(), "note".into());
+        let unit_of_work = factory.begin().await.expect("begin");
+
+        unit_of_work
+            .outbox()
+            .append(vec![item.created_event()])
+            .await
+            .expect("append");
+        assert!(factory.pending_outbox_messages().is_empty());
+
+        unit_of_work.commit().await.expect("commit");
+
+        let pending = factory.pending_outbox_messages();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0].aggregate_id, item.id);
+        assert_eq!(pending[0].event_type, "to_do_item.created");
+    }
+
+    #[tokio::test]
+    async fn idempotency_reserve_returns_live_record_and_replaces_expired_one() {
+        let repository = InMemoryIdempotencyRepository::new();
+        let expired =
+            IdempotencyRecord::new("key", "old", Utc::now() - chrono::Duration::seconds(1));
+        assert_eq!(repository.reserve(expired).await, Ok(None));
+
+        let live = IdempotencyRecord::new("key", "new", Utc::now() + chrono::Duration::hours(1));
+        assert_eq!(repository.reserve(live.clone()).await, Ok(None));
+
+        let retry = IdempotencyRecord::new("key", "retry", Utc::now() + chrono::Duration::hours(1));
+        assert_eq!(repository.reserve(retry).await, Ok(Some(live)));
+    }
+}
@@ -1,22 +1,55 @@
+mod access;
+mod api_keys;
 mod commands;
 mod errors;
 mod handlers;
+mod idempotency;
+mod in_memory;
 mod mappers;
+mod outbox;
 mod queries;
 mod repositories;
+mod revisions;
 mod services;
 mod settings;
 
-pub use crate::commands::{CreateToDoItemCommand, DeleteToDoItemCommand, UpdateToDoItemCommand};
+pub use crate::access::AccessScope;
+pub use crate::api_keys::ApiKey;
+pub use crate::commands::{
+    BatchOperation, BatchOperationOutcome, BatchOperationResult, BatchToDoItemsCommand,
+    CreateToDoItemCommand, DeleteToDoItemCommand, DispatchOutboxCommand, PatchToDoItemCommand,
+    PurgeDeletedToDoItemsCommand, RestoreToDoItemCommand, UpdateToDoItemCommand,
+};
 pub use crate::handlers::{
-    CreateToDoItemCommandHandler, DeleteToDoItemCommandHandler, GetAllToDoItemsQueryHandler,
-    GetDeletedToDoItemForAuditQueryHandler, GetToDoItemQueryHandler, UpdateToDoItemCommandHandler,
+    BatchToDoItemsCommandHandler, CreateToDoItemCommandHandler, DeleteToDoItemCommandHandler,
+    DispatchOutboxCommandHandler, GetAllToDoItemsQueryHandler,
+    GetDeletedToDoItemForAuditQueryHandler, GetDeletedToDoItemsForAuditQueryHandler,
+    GetToDoItemDiffQueryHandler, GetToDoItemHistoryQueryHandler, GetToDoItemQueryHandler,
+    GetToDoItemRevisionQueryHandler, PatchToDoItemCommandHandler,
+    PurgeDeletedToDoItemsCommandHandler, RestoreToDoItemCommandHandler,
+    UpdateToDoItemCommandHandler,
+};
+pub use crate::idempotency::{IdempotencyRecord, IdempotentResponse};
+pub use crate::in_memory::{
+    InMemoryApiKeyRepository, InMemoryIdempotencyRepository, InMemoryUnitOfWorkFactory,
 };
+pub use crate::mappers::ToDoItemMapper;
+pub use crate::outbox::{EventPublisher, OutboxDispatch, OutboxMessage, OutboxRetryPolicy};
 pub use crate::queries::{
-    GetAllToDoItemsQuery, GetDeletedToDoItemForAuditQuery, GetToDoItemQuery, PaginatedResult,
-    SortDirection, ToDoItemSort, ToDoItemSortField,
+    DeletedToDoItemFilter, GetAllToDoItemsQuery, GetDeletedToDoItemForAuditQuery,
+    GetDeletedToDoItemsForAuditQuery, GetToDoItemDiffQuery, GetToDoItemHistoryQuery,
+    GetToDoItemQuery, GetToDoItemRevisionQuery, KeysetPage, NullsOrder, PaginatedResult,
+    SearchMode, SortDirection, ToDoItemCursor, ToDoItemFilter, ToDoItemSort, ToDoItemSortField,
+    ToDoItemSortKey, ToDoItemSortValue,
 };
-pub use crate::repositories::{ToDoItemCommandRepository, ToDoItemQueryRepository};
+pub use crate::repositories::{
+    ApiKeyRepository, IdempotencyRepository, OutboxRepository, RevisionRepository,
+    ToDoItemCommandRepository, ToDoItemQueryRepository, UnitOfWork, UnitOfWorkFactory,
+};
+pub use crate::revisions::{ChangeType, FieldChange, ToDoItemDiff, ToDoItemRevision};
 pub use crate::services::{ToDoItemService, ToDoItemServiceBoxed};
-pub use crate::settings::{Audit, Settings};
+pub use crate::settings::{
+    ApiKeys, Audit, AuditCredential, Authentication, Authorization, AuthorizationRule, HttpCache,
+    Idempotency, Outbox, Retention, Settings, Tenancy,
+};
 pub use errors::{ApplicationError, ApplicationResult};
@@ -1,4 +1,4 @@
-#![allow(dead_code)]
+use crate::{ApplicationError, ApplicationResult};
 use chrono::{DateTime, Utc};
 use domain::ToDoItem;
 use std::time::SystemTime;
@@ -7,12 +7,17 @@ use tokio_postgres::Row;
 pub struct ToDoItemMapper {}
 
 impl ToDoItemMapper {
-    pub fn from(row: Row) -> ToDoItem {
-        ToDoItem {
+    pub fn from(row: Row) -> ApplicationResult<ToDoItem> {
+        let status = row
+            .get::<_, String>("status")
+            .parse()
+            .map_err(|err| ApplicationError::internal(format!("invalid stored status: {err}")))?;
+
+        Ok(ToDoItem {
             id: row.get("id"),
             title: row.get("title"),
             note: row.get("note"),
-            status: row.get("status"),
+            status,
             created_at: DateTime::<Utc>::from(row.get::<_, SystemTime>("created_at")),
             updated_at: DateTime::<Utc>::from(row.get::<_, SystemTime>("updated_at")),
             due_at: row
@@ -23,10 +28,19 @@ impl ToDoItemMapper {
                 .get::<_, Option<SystemTime>>("deleted_at")
                 .map(DateTime::<Utc>::from),
             deleted_by: row.get("deleted_by"),
-        }
+            restored_at: row
+                .get::<_, Option<SystemTime>>("restored_at")
+                .map(DateTime::<Utc>::from),
+            restored_by: row.get("restored_by"),
+            owner_id: row.get("owner_id"),
+            updated_by: row.get("updated_by"),
+            tenant_id: row.get("tenant_id"),
+        })
     }
 
-    pub fn from_vec(rows: Vec<Row>) -> Vec<ToDoItem> {
-        rows.into_iter().map(Self::from).collect::<Vec<ToDoItem>>()
+    pub fn from_vec(rows: Vec<Row>) -> ApplicationResult<Vec<ToDoItem>> {
+        rows.into_iter()
+            .map(Self::from)
+            .collect::<ApplicationResult<Vec<ToDoItem>>>()
     }
 }
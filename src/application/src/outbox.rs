@@ -0,0 +1,119 @@
+use crate::ApplicationResult;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use domain::ToDoItemEvent;
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Domain event stored in the transactional outbox until it has been published.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct OutboxMessage {
+    pub id: Uuid,
+    pub aggregate_id: Uuid,
+    pub event_type: String,
+    pub occurred_at: DateTime<Utc>,
+    pub attempts: i32,
+    pub payload: ToDoItemEvent,
+}
+
+impl OutboxMessage {
+    pub fn new(event: ToDoItemEvent) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            aggregate_id: event.aggregate_id(),
+            event_type: event.event_type().to_string(),
+            occurred_at: event.occurred_at(),
+            attempts: 0,
+            payload: event,
+        }
+    }
+}
+
+/// Delivers outbox messages to downstream consumers.
+///
+/// Delivery is at least once: a message may be handed to the publisher again when
+/// marking it as published fails, so consumers should deduplicate on `id`.
+#[async_trait]
+pub trait EventPublisher: Send + Sync {
+    async fn publish(&self, message: &OutboxMessage) -> ApplicationResult<()>;
+}
+
+/// Exponential backoff for messages the publisher rejected.
+///
+/// Failed attempt `n` is retried after `base_delay * 2^(n - 1)`, capped at `max_delay`.
+/// Once `max_attempts` attempts have failed the message is marked failed and no longer
+/// dispatched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OutboxRetryPolicy {
+    pub max_attempts: i32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl OutboxRetryPolicy {
+    pub fn new(max_attempts: i32, base_delay: Duration, max_delay: Duration) -> Self {
+        Self {
+            max_attempts,
+            base_delay,
+            max_delay,
+        }
+    }
+
+    /// When to retry a message that has now failed `attempts` times, or `None` to give up.
+    pub fn retry_at(&self, attempts: i32, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        if attempts >= self.max_attempts {
+            return None;
+        }
+
+        let exponent = attempts.saturating_sub(1).clamp(0, 31) as u32;
+        let delay = self
+            .base_delay
+            .saturating_mul(2_u32.saturating_pow(exponent))
+            .min(self.max_delay);
+        Some(now + chrono::Duration::from_std(delay).unwrap_or(chrono::Duration::MAX))
+    }
+}
+
+impl Default for OutboxRetryPolicy {
+    fn default() -> Self {
+        Self::new(10, Duration::from_secs(1), Duration::from_secs(300))
+    }
+}
+
+/// Outcome of one dispatch batch.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OutboxDispatch {
+    /// Messages fetched for the batch, including the ones held back behind a failure.
+    pub fetched: usize,
+    pub published: usize,
+    /// Failed messages scheduled for another attempt.
+    pub retried: usize,
+    /// Failed messages that reached the attempt limit.
+    pub failed: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn retry_policy_backs_off_exponentially_up_to_the_cap() {
+        let policy = OutboxRetryPolicy::new(5, Duration::from_secs(1), Duration::from_secs(5));
+        let now = Utc::now();
+
+        assert_eq!(
+            policy.retry_at(1, now),
+            Some(now + chrono::Duration::seconds(1))
+        );
+        assert_eq!(
+            policy.retry_at(3, now),
+            Some(now + chrono::Duration::seconds(4))
+        );
+        assert_eq!(
+            policy.retry_at(4, now),
+            Some(now + chrono::Duration::seconds(5))
+        );
+        assert_eq!(policy.retry_at(5, now), None);
+    }
+}
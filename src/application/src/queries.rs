@@ -1,38 +1,258 @@
+use crate::AccessScope;
+use chrono::{DateTime, Utc};
+use domain::{ToDoItem, ToDoItemStatus};
+use std::cmp::Ordering;
+use std::collections::HashMap;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SortDirection {
     Asc,
     Desc,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ToDoItemSortField {
     Id,
     Title,
+    Status,
+    CreatedAt,
+    UpdatedAt,
+    DueAt,
+    /// Only offered when listing deleted items, which always have it.
+    DeletedAt,
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct ToDoItemSort {
+impl ToDoItemSortField {
+    pub fn is_nullable(&self) -> bool {
+        matches!(self, Self::Title | Self::DueAt)
+    }
+}
+
+/// Placement of `NULL` values; only meaningful for nullable fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NullsOrder {
+    First,
+    Last,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ToDoItemSortKey {
     pub field: ToDoItemSortField,
     pub direction: SortDirection,
+    pub nulls: NullsOrder,
+}
+
+impl ToDoItemSortKey {
+    /// Creates a key with the PostgreSQL default placement of `NULL` values:
+    /// last when ascending, first when descending.
+    pub fn new(field: ToDoItemSortField, direction: SortDirection) -> Self {
+        let nulls = match direction {
+            SortDirection::Asc => NullsOrder::Last,
+            SortDirection::Desc => NullsOrder::First,
+        };
+
+        Self {
+            field,
+            direction,
+            nulls,
+        }
+    }
+
+    pub fn with_nulls(mut self, nulls: NullsOrder) -> Self {
+        self.nulls = nulls;
+        self
+    }
+
+    fn compare(&self, left: &ToDoItemSortValue, right: &ToDoItemSortValue) -> Ordering {
+        let ordering = match (left, right) {
+            (ToDoItemSortValue::Id(left), ToDoItemSortValue::Id(right)) => left.cmp(right),
+            (ToDoItemSortValue::Status(left), ToDoItemSortValue::Status(right)) => {
+                left.lifecycle_rank().cmp(&right.lifecycle_rank())
+            }
+            (ToDoItemSortValue::Text(left), ToDoItemSortValue::Text(right)) => {
+                return self.compare_nullable(left.as_ref(), right.as_ref());
+            }
+            (ToDoItemSortValue::Timestamp(left), ToDoItemSortValue::Timestamp(right)) => {
+                return self.compare_nullable(left.as_ref(), right.as_ref());
+            }
+            _ => Ordering::Equal,
+        };
+
+        self.directed(ordering)
+    }
+
+    fn compare_nullable<T: Ord>(&self, left: Option<&T>, right: Option<&T>) -> Ordering {
+        let null_ordering = match self.nulls {
+            NullsOrder::First => Ordering::Less,
+            NullsOrder::Last => Ordering::Greater,
+        };
+
+        match (left, right) {
+            (Some(left), Some(right)) => self.directed(left.cmp(right)),
+            (None, None) => Ordering::Equal,
+            (None, Some(_)) => null_ordering,
+            (Some(_), None) => null_ordering.reverse(),
+        }
+    }
+
+    fn directed(&self, ordering: Ordering) -> Ordering {
+        match self.direction {
+            SortDirection::Asc => ordering,
+            SortDirection::Desc => ordering.reverse(),
+        }
+    }
+}
+
+/// Ordered list of sort keys, applied lexicographically.
+///
+/// Unless `id` is one of the keys, it is appended as a final tiebreaker in the direction
+/// of the last key, so the order is always total and stable across pages.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToDoItemSort {
+    pub keys: Vec<ToDoItemSortKey>,
+}
+
+impl ToDoItemSort {
+    pub fn new(keys: Vec<ToDoItemSortKey>) -> Self {
+        Self { keys }
+    }
+
+    /// Sort keys including the implicit `id` tiebreaker.
+    pub fn effective_keys(&self) -> Vec<ToDoItemSortKey> {
+        let mut keys = self.keys.clone();
+        if !keys.iter().any(|key| key.field == ToDoItemSortField::Id) {
+            let direction = keys
+                .last()
+                .map(|key| key.direction)
+                .unwrap_or(SortDirection::Asc);
+            keys.push(ToDoItemSortKey::new(ToDoItemSortField::Id, direction));
+        }
+        keys
+    }
+
+    /// Orders two items the same way the database does, except that text is compared
+    /// byte-wise instead of using the database collation.
+    pub fn compare(&self, left: &ToDoItem, right: &ToDoItem) -> Ordering {
+        self.effective_keys()
+            .iter()
+            .map(|key| {
+                key.compare(
+                    &ToDoItemSortValue::of(left, key.field),
+                    &ToDoItemSortValue::of(right, key.field),
+                )
+            })
+            .find(|ordering| ordering.is_ne())
+            .unwrap_or(Ordering::Equal)
+    }
 }
 
 impl Default for ToDoItemSort {
     fn default() -> Self {
+        Self::new(vec![ToDoItemSortKey::new(
+            ToDoItemSortField::Id,
+            SortDirection::Asc,
+        )])
+    }
+}
+
+/// Value of a single sort key taken from an item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ToDoItemSortValue {
+    Id(Uuid),
+    Text(Option<String>),
+    Status(ToDoItemStatus),
+    Timestamp(Option<DateTime<Utc>>),
+}
+
+impl ToDoItemSortValue {
+    pub fn of(item: &ToDoItem, field: ToDoItemSortField) -> Self {
+        match field {
+            ToDoItemSortField::Id => Self::Id(item.id),
+            ToDoItemSortField::Title => Self::Text(item.title.clone()),
+            ToDoItemSortField::Status => Self::Status(item.status),
+            ToDoItemSortField::CreatedAt => Self::Timestamp(Some(item.created_at)),
+            ToDoItemSortField::UpdatedAt => Self::Timestamp(Some(item.updated_at)),
+            ToDoItemSortField::DueAt => Self::Timestamp(item.due_at),
+            ToDoItemSortField::DeletedAt => Self::Timestamp(item.deleted_at),
+        }
+    }
+}
+
+/// Structured list filters, combined with each other and with the text search using AND.
+///
+/// `*_after` and `updated_since` bounds are inclusive, `*_before` bounds are exclusive.
+/// `overdue` keeps items that are past their due date and not done yet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ToDoItemFilter {
+    pub statuses: Vec<ToDoItemStatus>,
+    pub due_after: Option<DateTime<Utc>>,
+    pub due_before: Option<DateTime<Utc>>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+    pub updated_since: Option<DateTime<Utc>>,
+    pub overdue: bool,
+}
+
+/// Position of the last item of a keyset page.
+///
+/// Holds one value per effective sort key, including the `id` tiebreaker, so the next
+/// page can resume right after the item even when several items share the same values.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ToDoItemCursor {
+    pub sort: ToDoItemSort,
+    pub values: Vec<ToDoItemSortValue>,
+}
+
+impl ToDoItemCursor {
+    pub fn after(item: &ToDoItem, sort: &ToDoItemSort) -> Self {
+        let values = sort
+            .effective_keys()
+            .iter()
+            .map(|key| ToDoItemSortValue::of(item, key.field))
+            .collect();
+
         Self {
-            field: ToDoItemSortField::Id,
-            direction: SortDirection::Asc,
+            sort: sort.clone(),
+            values,
         }
     }
 }
 
+/// Keyset pagination request: the page starts right after `after`, or at the beginning
+/// when no cursor is given. Counting all matches is skipped unless `include_total` is set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct KeysetPage {
+    pub after: Option<ToDoItemCursor>,
+    pub include_total: bool,
+}
+
+/// How the `search` text is turned into a full-text query.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SearchMode {
+    /// All words must match, in any order.
+    #[default]
+    Plain,
+    /// The words must match as a phrase, in the given order.
+    Phrase,
+    /// Web search engine syntax: quoted phrases, `or` and `-` for exclusion.
+    Websearch,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GetAllToDoItemsQuery {
     pub page: u32,
     pub page_size: u32,
     pub search: Option<String>,
+    pub search_mode: SearchMode,
+    /// Orders matches by search relevance before `sort`; ignored without `search` and not
+    /// supported together with keyset pagination.
+    pub rank_by_relevance: bool,
     pub sort: ToDoItemSort,
+    pub filter: ToDoItemFilter,
+    /// Switches the query from offset to keyset pagination; `page` is ignored when set.
+    pub keyset: Option<KeysetPage>,
+    pub scope: AccessScope,
 }
 
 impl Default for GetAllToDoItemsQuery {
@@ -41,7 +261,12 @@ impl Default for GetAllToDoItemsQuery {
             page: 1,
             page_size: 20,
             search: None,
+            search_mode: SearchMode::default(),
+            rank_by_relevance: false,
             sort: ToDoItemSort::default(),
+            filter: ToDoItemFilter::default(),
+            keyset: None,
+            scope: AccessScope::default(),
         }
     }
 }
@@ -52,10 +277,40 @@ impl GetAllToDoItemsQuery {
             page,
             page_size,
             search,
+            search_mode: SearchMode::default(),
+            rank_by_relevance: false,
             sort,
+            filter: ToDoItemFilter::default(),
+            keyset: None,
+            scope: AccessScope::default(),
         }
     }
 
+    pub fn with_search_mode(mut self, search_mode: SearchMode) -> Self {
+        self.search_mode = search_mode;
+        self
+    }
+
+    pub fn with_relevance_ranking(mut self) -> Self {
+        self.rank_by_relevance = true;
+        self
+    }
+
+    pub fn with_filter(mut self, filter: ToDoItemFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn with_keyset(mut self, keyset: KeysetPage) -> Self {
+        self.keyset = Some(keyset);
+        self
+    }
+
+    pub fn within(mut self, scope: AccessScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
     pub fn offset(&self) -> i64 {
         ((self.page - 1) * self.page_size) as i64
     }
@@ -65,51 +320,332 @@ impl GetAllToDoItemsQuery {
     }
 }
 
+/// One page of results.
+///
+/// Offset pages always carry `page` and the totals. Keyset pages carry `next_cursor` while
+/// more items follow, and totals only when they were requested.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct PaginatedResult<T> {
     pub items: Vec<T>,
-    pub page: u32,
+    pub page: Option<u32>,
     pub page_size: u32,
-    pub total_items: i64,
-    pub total_pages: u32,
+    pub total_items: Option<i64>,
+    pub total_pages: Option<u32>,
+    pub next_cursor: Option<ToDoItemCursor>,
+    /// Search snippets with the matched words highlighted, keyed by item id.
+    /// Empty unless the query searched text.
+    pub highlights: HashMap<Uuid, String>,
 }
 
 impl<T> PaginatedResult<T> {
     pub fn new(items: Vec<T>, page: u32, page_size: u32, total_items: i64) -> Self {
-        let total_pages = if total_items == 0 {
-            0
-        } else {
-            ((total_items + page_size as i64 - 1) / page_size as i64) as u32
-        };
+        Self {
+            items,
+            page: Some(page),
+            page_size,
+            total_items: Some(total_items),
+            total_pages: Some(total_pages(total_items, page_size)),
+            next_cursor: None,
+            highlights: HashMap::new(),
+        }
+    }
 
+    pub fn keyset(
+        items: Vec<T>,
+        page_size: u32,
+        total_items: Option<i64>,
+        next_cursor: Option<ToDoItemCursor>,
+    ) -> Self {
         Self {
             items,
-            page,
+            page: None,
             page_size,
             total_items,
-            total_pages,
+            total_pages: total_items.map(|total_items| total_pages(total_items, page_size)),
+            next_cursor,
+            highlights: HashMap::new(),
         }
     }
+
+    pub fn with_highlights(mut self, highlights: HashMap<Uuid, String>) -> Self {
+        self.highlights = highlights;
+        self
+    }
+}
+
+fn total_pages(total_items: i64, page_size: u32) -> u32 {
+    if total_items == 0 {
+        0
+    } else {
+        ((total_items + page_size as i64 - 1) / page_size as i64) as u32
+    }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GetToDoItemQuery {
     pub id: Uuid,
+    pub scope: AccessScope,
 }
 
 impl GetToDoItemQuery {
     pub fn new(id: Uuid) -> Self {
-        Self { id }
+        Self {
+            id,
+            scope: AccessScope::default(),
+        }
+    }
+
+    pub fn within(mut self, scope: AccessScope) -> Self {
+        self.scope = scope;
+        self
     }
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct GetDeletedToDoItemForAuditQuery {
     pub id: Uuid,
+    pub scope: AccessScope,
 }
 
 impl GetDeletedToDoItemForAuditQuery {
     pub fn new(id: Uuid) -> Self {
-        Self { id }
+        Self {
+            id,
+            scope: AccessScope::default(),
+        }
+    }
+
+    pub fn within(mut self, scope: AccessScope) -> Self {
+        self.scope = scope;
+        self
+    }
+}
+
+/// Filters of the audit listing of deleted items, combined with the text search using AND.
+///
+/// `deleted_after` is inclusive and `deleted_before` exclusive.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DeletedToDoItemFilter {
+    pub deleted_by: Option<Uuid>,
+    pub deleted_after: Option<DateTime<Utc>>,
+    pub deleted_before: Option<DateTime<Utc>>,
+}
+
+/// Lists soft-deleted items for auditing, most recently deleted first by default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetDeletedToDoItemsForAuditQuery {
+    pub page: u32,
+    pub page_size: u32,
+    pub search: Option<String>,
+    pub search_mode: SearchMode,
+    pub sort: ToDoItemSort,
+    pub filter: DeletedToDoItemFilter,
+    pub scope: AccessScope,
+}
+
+impl Default for GetDeletedToDoItemsForAuditQuery {
+    fn default() -> Self {
+        Self::new(1, 20, None, Self::default_sort())
+    }
+}
+
+impl GetDeletedToDoItemsForAuditQuery {
+    pub fn new(page: u32, page_size: u32, search: Option<String>, sort: ToDoItemSort) -> Self {
+        Self {
+            page,
+            page_size,
+            search,
+            search_mode: SearchMode::default(),
+            sort,
+            filter: DeletedToDoItemFilter::default(),
+            scope: AccessScope::default(),
+        }
+    }
+
+    pub fn default_sort() -> ToDoItemSort {
+        ToDoItemSort::new(vec![ToDoItemSortKey::new(
+            ToDoItemSortField::DeletedAt,
+            SortDirection::Desc,
+        )])
+    }
+
+    pub fn with_search_mode(mut self, search_mode: SearchMode) -> Self {
+        self.search_mode = search_mode;
+        self
+    }
+
+    pub fn with_filter(mut self, filter: DeletedToDoItemFilter) -> Self {
+        self.filter = filter;
+        self
+    }
+
+    pub fn within(mut self, scope: AccessScope) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    pub fn offset(&self) -> i64 {
+        ((self.page - 1) * self.page_size) as i64
+    }
+
+    pub fn limit(&self) -> i64 {
+        self.page_size as i64
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetToDoItemHistoryQuery {
+    pub id: Uuid,
+    pub page: u32,
+    pub page_size: u32,
+    pub scope: AccessScope,
+}
+
+impl GetToDoItemHistoryQuery {
+    pub fn new(id: Uuid, page: u32, page_size: u32) -> Self {
+        Self {
+            id,
+            page,
+            page_size,
+            scope: AccessScope::default(),
+        }
+    }
+
+    pub fn within(mut self, scope: AccessScope) -> Self {
+        self.scope = scope;
+        self
+    }
+}
+
+/// Looks up the revision whose change left an item at `version`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetToDoItemRevisionQuery {
+    pub id: Uuid,
+    pub version: i32,
+    pub scope: AccessScope,
+}
+
+impl GetToDoItemRevisionQuery {
+    pub fn new(id: Uuid, version: i32) -> Self {
+        Self {
+            id,
+            version,
+            scope: AccessScope::default(),
+        }
+    }
+
+    pub fn within(mut self, scope: AccessScope) -> Self {
+        self.scope = scope;
+        self
+    }
+}
+
+/// Compares the revision that left an item at version `from` with the one that left it at
+/// the later version `to`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GetToDoItemDiffQuery {
+    pub id: Uuid,
+    pub from: i32,
+    pub to: i32,
+    pub scope: AccessScope,
+}
+
+impl GetToDoItemDiffQuery {
+    pub fn new(id: Uuid, from: i32, to: i32) -> Self {
+        Self {
+            id,
+            from,
+            to,
+            scope: AccessScope::default(),
+        }
+    }
+
+    pub fn within(mut self, scope: AccessScope) -> Self {
+        self.scope = scope;
+        self
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn item(title: Option<&str>, status: ToDoItemStatus, due_in_days: Option<i64>) -> ToDoItem {
+        let mut item = ToDoItem::new(String::new(), String::new());
+        item.title = title.map(str::to_string);
+        item.status = status;
+        item.due_at = due_in_days.map(|days| item.created_at + Duration::days(days));
+        item
+    }
+
+    fn titles(items: &[ToDoItem]) -> Vec<Option<&str>> {
+        items.iter().map(|item| item.title.as_deref()).collect()
+    }
+
+    #[test]
+    fn effective_keys_append_id_tiebreaker_in_last_key_direction() {
+        let sort = ToDoItemSort::new(vec![ToDoItemSortKey::new(
+            ToDoItemSortField::DueAt,
+            SortDirection::Desc,
+        )]);
+
+        let keys = sort.effective_keys();
+
+        assert_eq!(keys.len(), 2);
+        assert_eq!(keys[0].nulls, NullsOrder::First);
+        assert_eq!(
+            keys[1],
+            ToDoItemSortKey::new(ToDoItemSortField::Id, SortDirection::Desc)
+        );
+        assert_eq!(ToDoItemSort::default().effective_keys().len(), 1);
+    }
+
+    #[test]
+    fn compare_orders_by_status_lifecycle_then_due_date_with_nulls_first() {
+        let sort = ToDoItemSort::new(vec![
+            ToDoItemSortKey::new(ToDoItemSortField::Status, SortDirection::Asc),
+            ToDoItemSortKey::new(ToDoItemSortField::DueAt, SortDirection::Asc)
+                .with_nulls(NullsOrder::First),
+        ]);
+        let mut items = vec![
+            item(Some("done"), ToDoItemStatus::Done, Some(1)),
+            item(Some("pending-late"), ToDoItemStatus::Pending, Some(5)),
+            item(Some("in-progress"), ToDoItemStatus::InProgress, None),
+            item(Some("pending-undated"), ToDoItemStatus::Pending, None),
+            item(Some("pending-soon"), ToDoItemStatus::Pending, Some(1)),
+        ];
+
+        items.sort_by(|left, right| sort.compare(left, right));
+
+        assert_eq!(
+            titles(&items),
+            vec![
+                Some("pending-undated"),
+                Some("pending-soon"),
+                Some("pending-late"),
+                Some("in-progress"),
+                Some("done"),
+            ]
+        );
+    }
+
+    #[test]
+    fn cursor_holds_one_value_per_effective_key() {
+        let item = item(Some("title"), ToDoItemStatus::InProgress, None);
+        let sort = ToDoItemSort::new(vec![ToDoItemSortKey::new(
+            ToDoItemSortField::Status,
+            SortDirection::Asc,
+        )]);
+
+        let cursor = ToDoItemCursor::after(&item, &sort);
+
+        assert_eq!(
+            cursor.values,
+            vec![
+                ToDoItemSortValue::Status(ToDoItemStatus::InProgress),
+                ToDoItemSortValue::Id(item.id),
+            ]
+        );
     }
 }
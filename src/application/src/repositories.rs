@@ -1,22 +1,166 @@
 use async_trait::async_trait;
-use domain::ToDoItem;
+use chrono::{DateTime, Utc};
+use domain::{ToDoItem, ToDoItemEvent};
 use uuid::Uuid;
 
-use crate::{ApplicationResult, GetAllToDoItemsQuery, PaginatedResult};
+use crate::{
+    AccessScope, ApiKey, ApplicationResult, GetAllToDoItemsQuery, GetDeletedToDoItemsForAuditQuery,
+    IdempotencyRecord, IdempotentResponse, OutboxMessage, PaginatedResult, ToDoItemRevision,
+};
 
+/// Read access to to-do items. Lookups only return items within the given [`AccessScope`]
+/// and report every other item as not found.
 #[async_trait]
 pub trait ToDoItemQueryRepository: Send + Sync {
     async fn get_all(
         &self,
         query: GetAllToDoItemsQuery,
     ) -> ApplicationResult<PaginatedResult<ToDoItem>>;
-    async fn get_by_id(&self, id: Uuid) -> ApplicationResult<ToDoItem>;
-    async fn get_deleted_by_id_for_audit(&self, id: Uuid) -> ApplicationResult<ToDoItem>;
+    async fn get_by_id(&self, id: Uuid, scope: AccessScope) -> ApplicationResult<ToDoItem>;
+    async fn get_deleted_by_id_for_audit(
+        &self,
+        id: Uuid,
+        scope: AccessScope,
+    ) -> ApplicationResult<ToDoItem>;
+    /// Returns a page of soft-deleted items, based on their `deleted_at` and `deleted_by`.
+    async fn get_deleted_for_audit(
+        &self,
+        query: GetDeletedToDoItemsForAuditQuery,
+    ) -> ApplicationResult<PaginatedResult<ToDoItem>>;
+    /// Returns a page of the revisions of an item, oldest first. Deleted items keep their
+    /// history, so it is also returned for them.
+    async fn get_history(
+        &self,
+        id: Uuid,
+        page: u32,
+        page_size: u32,
+        scope: AccessScope,
+    ) -> ApplicationResult<PaginatedResult<ToDoItemRevision>>;
+    /// Returns the revision whose change left the item at `version`.
+    async fn get_revision(
+        &self,
+        id: Uuid,
+        version: i32,
+        scope: AccessScope,
+    ) -> ApplicationResult<ToDoItemRevision>;
+    /// Returns the revisions that left the item at versions `from` to `to`, both included,
+    /// oldest first.
+    async fn get_revisions(
+        &self,
+        id: Uuid,
+        from: i32,
+        to: i32,
+        scope: AccessScope,
+    ) -> ApplicationResult<Vec<ToDoItemRevision>>;
 }
 
 #[async_trait]
 pub trait ToDoItemCommandRepository: Send + Sync {
+    async fn get_for_update(&self, id: Uuid, scope: AccessScope) -> ApplicationResult<ToDoItem>;
+    async fn get_deleted_for_update(
+        &self,
+        id: Uuid,
+        scope: AccessScope,
+    ) -> ApplicationResult<ToDoItem>;
     async fn create(&self, entity: ToDoItem) -> ApplicationResult<Uuid>;
-    async fn update(&self, entity: ToDoItem) -> ApplicationResult<Uuid>;
-    async fn delete(&self, id: Uuid, deleted_by: Option<Uuid>) -> ApplicationResult<()>;
+    /// Inserts several items at once, using multi-row statements where the store supports them.
+    async fn create_many(&self, entities: Vec<ToDoItem>) -> ApplicationResult<Vec<Uuid>>;
+    /// Writes the changes of `entity` and returns the item as stored, with its new version
+    /// and `updated_at`.
+    async fn update(&self, entity: ToDoItem) -> ApplicationResult<ToDoItem>;
+    /// Soft-deletes the item, advancing its version like every other change, and returns
+    /// the item as stored.
+    async fn delete(
+        &self,
+        id: Uuid,
+        deleted_by: Option<Uuid>,
+        scope: AccessScope,
+    ) -> ApplicationResult<ToDoItem>;
+    async fn restore(&self, entity: ToDoItem) -> ApplicationResult<Uuid>;
+    /// Returns ids of items soft-deleted before `deleted_before`, oldest deletions first.
+    async fn get_purgeable_ids(
+        &self,
+        deleted_before: DateTime<Utc>,
+        limit: i64,
+    ) -> ApplicationResult<Vec<Uuid>>;
+    /// Physically removes soft-deleted items; active items are never purged.
+    async fn purge(&self, ids: Vec<Uuid>) -> ApplicationResult<()>;
+}
+
+/// Pending domain events, written in the same transaction as the aggregate changes.
+///
+/// `fetch_pending` returns due messages oldest first: unpublished, not failed and past
+/// their retry time. It holds back messages of an aggregate whose older message is waiting
+/// for a retry, so the events of one item stay in order, and skips rows that another
+/// dispatcher is currently working on.
+#[async_trait]
+pub trait OutboxRepository: Send + Sync {
+    async fn append(&self, events: Vec<ToDoItemEvent>) -> ApplicationResult<()>;
+    async fn fetch_pending(&self, limit: i64) -> ApplicationResult<Vec<OutboxMessage>>;
+    async fn mark_published(&self, id: Uuid) -> ApplicationResult<()>;
+    /// Records a failed attempt. The message is retried from `retry_at`, or marked failed
+    /// for good when there is none.
+    async fn mark_failed(
+        &self,
+        id: Uuid,
+        error: String,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> ApplicationResult<()>;
+    /// Deletes every message of the given items, so purged items leave no payload behind.
+    async fn purge_aggregates(&self, aggregate_ids: Vec<Uuid>) -> ApplicationResult<()>;
+}
+
+/// Append-only change history of to-do items, written in the same transaction as the
+/// changes it records.
+#[async_trait]
+pub trait RevisionRepository: Send + Sync {
+    /// Stores the revisions, numbering each after the latest stored revision of its item.
+    async fn append(&self, revisions: Vec<ToDoItemRevision>) -> ApplicationResult<()>;
+}
+
+/// Stored outcomes of requests sent with an idempotency key.
+///
+/// Records past `expires_at` are treated as absent and may be replaced at any time.
+#[async_trait]
+pub trait IdempotencyRepository: Send + Sync {
+    /// Claims the key of `record` for a new request. Returns the live record instead when
+    /// the key is already taken.
+    async fn reserve(
+        &self,
+        record: IdempotencyRecord,
+    ) -> ApplicationResult<Option<IdempotencyRecord>>;
+    async fn complete(&self, key: &str, response: IdempotentResponse) -> ApplicationResult<()>;
+    /// Forgets a claimed key so the request can be retried, e.g. after a server error.
+    async fn release(&self, key: &str) -> ApplicationResult<()>;
+    async fn purge_expired(&self, now: DateTime<Utc>) -> ApplicationResult<usize>;
+}
+
+/// Issued API keys, looked up by the hash of their secret.
+#[async_trait]
+pub trait ApiKeyRepository: Send + Sync {
+    async fn create(&self, key: ApiKey) -> ApplicationResult<()>;
+    async fn find_by_hash(&self, key_hash: &str) -> ApplicationResult<Option<ApiKey>>;
+    /// Returns every key, revoked ones included, newest first.
+    async fn list(&self) -> ApplicationResult<Vec<ApiKey>>;
+    /// Revokes a key. Returns `false` when no active key has the given id.
+    async fn revoke(&self, id: Uuid, now: DateTime<Utc>) -> ApplicationResult<bool>;
+    async fn record_use(&self, id: Uuid, used_at: DateTime<Utc>) -> ApplicationResult<()>;
+}
+
+/// Transaction scope spanning every write performed by a single command.
+///
+/// Repositories obtained from a unit of work share its transaction. Changes become
+/// visible only after `commit`; dropping an uncommitted unit of work rolls it back.
+#[async_trait]
+pub trait UnitOfWork: Send + Sync {
+    fn to_do_items(&self) -> &dyn ToDoItemCommandRepository;
+    fn outbox(&self) -> &dyn OutboxRepository;
+    fn revisions(&self) -> &dyn RevisionRepository;
+    async fn commit(self: Box<Self>) -> ApplicationResult<()>;
+    async fn rollback(self: Box<Self>) -> ApplicationResult<()>;
+}
+
+#[async_trait]
+pub trait UnitOfWorkFactory: Send + Sync {
+    async fn begin(&self) -> ApplicationResult<Box<dyn UnitOfWork>>;
 }
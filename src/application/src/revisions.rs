@@ -0,0 +1,234 @@
+use chrono::{DateTime, Utc};
+use domain::ToDoItem;
+use serde_json::Value;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+use uuid::Uuid;
+
+/// Kind of change a [`ToDoItemRevision`] records.
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum ChangeType {
+    Created,
+    Updated,
+    Deleted,
+    Restored,
+}
+
+impl ChangeType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeType::Created => "created",
+            ChangeType::Updated => "updated",
+            ChangeType::Deleted => "deleted",
+            ChangeType::Restored => "restored",
+        }
+    }
+}
+
+impl Display for ChangeType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ChangeType {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "created" => Ok(ChangeType::Created),
+            "updated" => Ok(ChangeType::Updated),
+            "deleted" => Ok(ChangeType::Deleted),
+            "restored" => Ok(ChangeType::Restored),
+            other => Err(format!("unknown change type {other:?}")),
+        }
+    }
+}
+
+/// Immutable snapshot of a to-do item taken after one of its changes.
+///
+/// `revision` numbers the recorded changes of an item from 1 and is assigned when the
+/// revision is stored. Every change also advances the `version` of the snapshot, which
+/// identifies the revision to clients; items changed before history was recorded start
+/// their revisions at a later version.
+#[derive(PartialEq, Debug, Clone)]
+pub struct ToDoItemRevision {
+    pub item_id: Uuid,
+    pub revision: i32,
+    pub change_type: ChangeType,
+    pub snapshot: ToDoItem,
+    pub actor: Option<Uuid>,
+    pub request_id: Option<String>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+impl ToDoItemRevision {
+    pub fn new(
+        snapshot: &ToDoItem,
+        change_type: ChangeType,
+        actor: Option<Uuid>,
+        request_id: Option<String>,
+    ) -> Self {
+        Self {
+            item_id: snapshot.id,
+            revision: 0,
+            change_type,
+            snapshot: snapshot.clone(),
+            actor,
+            request_id,
+            recorded_at: Utc::now(),
+        }
+    }
+}
+
+/// Snapshot fields compared by [`ToDoItemDiff`], in the order changes are reported. The
+/// id, `version` and timestamps maintained on every write are left out.
+const DIFFED_FIELDS: [&str; 10] = [
+    "title",
+    "note",
+    "status",
+    "due_at",
+    "owner_id",
+    "updated_by",
+    "deleted_at",
+    "deleted_by",
+    "restored_at",
+    "restored_by",
+];
+
+/// Change of one snapshot field between two revisions, attributed to the last revision
+/// in between that touched the field.
+#[derive(PartialEq, Debug, Clone)]
+pub struct FieldChange {
+    pub field: &'static str,
+    pub old_value: Value,
+    pub new_value: Value,
+    pub revision: i32,
+    pub changed_by: Option<Uuid>,
+    pub changed_at: DateTime<Utc>,
+}
+
+/// Field-level difference between two revisions of the same item.
+#[derive(PartialEq, Debug, Clone)]
+pub struct ToDoItemDiff {
+    pub from: ToDoItemRevision,
+    pub to: ToDoItemRevision,
+    pub changes: Vec<FieldChange>,
+}
+
+impl ToDoItemDiff {
+    /// Compares the first and last of `revisions`, which must be consecutive and ordered.
+    /// Fields that changed and were changed back in between are not reported.
+    pub fn between(mut revisions: Vec<ToDoItemRevision>) -> Option<Self> {
+        let from = revisions.first()?.clone();
+        let to = revisions.pop()?;
+        let snapshots = revisions
+            .iter()
+            .chain([&to])
+            .map(|revision| (revision, snapshot_fields(&revision.snapshot)))
+            .collect::<Vec<_>>();
+
+        let changes = DIFFED_FIELDS
+            .iter()
+            .enumerate()
+            .filter_map(|(index, field)| {
+                let old_value = &snapshots[0].1[index];
+                let new_value = &snapshots[snapshots.len() - 1].1[index];
+                if old_value == new_value {
+                    return None;
+                }
+                let last_change = snapshots
+                    .windows(2)
+                    .rev()
+                    .find(|pair| pair[0].1[index] != pair[1].1[index])
+                    .map(|pair| pair[1].0)?;
+
+                Some(FieldChange {
+                    field,
+                    old_value: old_value.clone(),
+                    new_value: new_value.clone(),
+                    revision: last_change.revision,
+                    changed_by: last_change.actor,
+                    changed_at: last_change.recorded_at,
+                })
+            })
+            .collect();
+
+        Some(Self { from, to, changes })
+    }
+}
+
+fn snapshot_fields(item: &ToDoItem) -> Vec<Value> {
+    let mut snapshot = serde_json::to_value(item).unwrap_or_default();
+    DIFFED_FIELDS
+        .iter()
+        .map(|field| snapshot[*field].take())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::ToDoItemStatus;
+    use serde_json::json;
+
+    fn revision(number: i32, snapshot: &ToDoItem, actor: Option<Uuid>) -> ToDoItemRevision {
+        ToDoItemRevision {
+            revision: number,
+            ..ToDoItemRevision::new(snapshot, ChangeType::Updated, actor, None)
+        }
+    }
+
+    #[test]
+    fn diff_reports_net_changes_with_the_revision_that_made_them() {
+        let (editor, reviewer) = (Some(Uuid::new_v4()), Some(Uuid::new_v4()));
+        let mut item = ToDoItem::new("title".to_string(), "note".to_string());
+        let first = revision(1, &item, None);
+        item.title = Some("renamed".to_string());
+        item.note = Some("draft".to_string());
+        let second = revision(2, &item, editor);
+        item.status = ToDoItemStatus::InProgress;
+        item.note = Some("note".to_string());
+        let third = revision(3, &item, reviewer);
+
+        let diff = ToDoItemDiff::between(vec![first, second, third]).unwrap();
+
+        let changes = diff
+            .changes
+            .iter()
+            .map(|change| {
+                (
+                    change.field,
+                    change.old_value.clone(),
+                    change.new_value.clone(),
+                    change.revision,
+                    change.changed_by,
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            [
+                ("title", json!("title"), json!("renamed"), 2, editor),
+                (
+                    "status",
+                    json!("pending"),
+                    json!("in_progress"),
+                    3,
+                    reviewer
+                ),
+            ]
+        );
+        assert_eq!((diff.from.revision, diff.to.revision), (1, 3));
+    }
+
+    #[test]
+    fn diff_of_a_single_revision_is_empty() {
+        let item = ToDoItem::new("title".to_string(), "note".to_string());
+
+        let diff = ToDoItemDiff::between(vec![revision(1, &item, None)]).unwrap();
+
+        assert!(diff.changes.is_empty());
+        assert!(ToDoItemDiff::between(Vec::new()).is_none());
+    }
+}
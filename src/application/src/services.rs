@@ -1,8 +1,11 @@
 use crate::handlers::{
-    CreateToDoItemCommandHandler, DeleteToDoItemCommandHandler, GetAllToDoItemsQueryHandler,
-    GetDeletedToDoItemForAuditQueryHandler, GetToDoItemQueryHandler, UpdateToDoItemCommandHandler,
+    BatchToDoItemsCommandHandler, CreateToDoItemCommandHandler, DeleteToDoItemCommandHandler,
+    GetAllToDoItemsQueryHandler, GetDeletedToDoItemForAuditQueryHandler,
+    GetDeletedToDoItemsForAuditQueryHandler, GetToDoItemDiffQueryHandler,
+    GetToDoItemHistoryQueryHandler, GetToDoItemQueryHandler, GetToDoItemRevisionQueryHandler,
+    PatchToDoItemCommandHandler, RestoreToDoItemCommandHandler, UpdateToDoItemCommandHandler,
 };
-use crate::repositories::{ToDoItemCommandRepository, ToDoItemQueryRepository};
+use crate::repositories::{ToDoItemQueryRepository, UnitOfWorkFactory};
 use std::sync::Arc;
 
 /// Service container that manages command and query handlers with explicit CQRS boundaries.
@@ -12,14 +15,21 @@ pub struct ToDoItemService {
     get_all_query_handler: Arc<GetAllToDoItemsQueryHandler>,
     create_command_handler: Arc<CreateToDoItemCommandHandler>,
     update_command_handler: Arc<UpdateToDoItemCommandHandler>,
+    patch_command_handler: Arc<PatchToDoItemCommandHandler>,
     delete_command_handler: Arc<DeleteToDoItemCommandHandler>,
+    restore_command_handler: Arc<RestoreToDoItemCommandHandler>,
+    batch_command_handler: Arc<BatchToDoItemsCommandHandler>,
     get_deleted_for_audit_query_handler: Arc<GetDeletedToDoItemForAuditQueryHandler>,
+    get_all_deleted_for_audit_query_handler: Arc<GetDeletedToDoItemsForAuditQueryHandler>,
+    get_history_query_handler: Arc<GetToDoItemHistoryQueryHandler>,
+    get_revision_query_handler: Arc<GetToDoItemRevisionQueryHandler>,
+    get_diff_query_handler: Arc<GetToDoItemDiffQueryHandler>,
 }
 
 impl ToDoItemService {
     pub fn new(
         query_repository: Arc<dyn ToDoItemQueryRepository + Send + Sync>,
-        command_repository: Arc<dyn ToDoItemCommandRepository + Send + Sync>,
+        unit_of_work: Arc<dyn UnitOfWorkFactory + Send + Sync>,
     ) -> Self {
         Self {
             get_query_handler: Arc::new(GetToDoItemQueryHandler::new(query_repository.clone())),
@@ -27,15 +37,32 @@ impl ToDoItemService {
                 query_repository.clone(),
             )),
             create_command_handler: Arc::new(CreateToDoItemCommandHandler::new(
-                command_repository.clone(),
+                unit_of_work.clone(),
             )),
             update_command_handler: Arc::new(UpdateToDoItemCommandHandler::new(
-                command_repository.clone(),
+                unit_of_work.clone(),
             )),
-            delete_command_handler: Arc::new(DeleteToDoItemCommandHandler::new(command_repository)),
+            patch_command_handler: Arc::new(PatchToDoItemCommandHandler::new(unit_of_work.clone())),
+            delete_command_handler: Arc::new(DeleteToDoItemCommandHandler::new(
+                unit_of_work.clone(),
+            )),
+            restore_command_handler: Arc::new(RestoreToDoItemCommandHandler::new(
+                unit_of_work.clone(),
+            )),
+            batch_command_handler: Arc::new(BatchToDoItemsCommandHandler::new(unit_of_work)),
             get_deleted_for_audit_query_handler: Arc::new(
-                GetDeletedToDoItemForAuditQueryHandler::new(query_repository),
+                GetDeletedToDoItemForAuditQueryHandler::new(query_repository.clone()),
+            ),
+            get_all_deleted_for_audit_query_handler: Arc::new(
+                GetDeletedToDoItemsForAuditQueryHandler::new(query_repository.clone()),
             ),
+            get_history_query_handler: Arc::new(GetToDoItemHistoryQueryHandler::new(
+                query_repository.clone(),
+            )),
+            get_revision_query_handler: Arc::new(GetToDoItemRevisionQueryHandler::new(
+                query_repository.clone(),
+            )),
+            get_diff_query_handler: Arc::new(GetToDoItemDiffQueryHandler::new(query_repository)),
         }
     }
 
@@ -55,30 +82,60 @@ impl ToDoItemService {
         self.update_command_handler.clone()
     }
 
+    pub fn patch_command_handler(&self) -> Arc<PatchToDoItemCommandHandler> {
+        self.patch_command_handler.clone()
+    }
+
     pub fn delete_command_handler(&self) -> Arc<DeleteToDoItemCommandHandler> {
         self.delete_command_handler.clone()
     }
 
+    pub fn restore_command_handler(&self) -> Arc<RestoreToDoItemCommandHandler> {
+        self.restore_command_handler.clone()
+    }
+
+    pub fn batch_command_handler(&self) -> Arc<BatchToDoItemsCommandHandler> {
+        self.batch_command_handler.clone()
+    }
+
     pub fn get_deleted_for_audit_query_handler(
         &self,
     ) -> Arc<GetDeletedToDoItemForAuditQueryHandler> {
         self.get_deleted_for_audit_query_handler.clone()
     }
+
+    pub fn get_all_deleted_for_audit_query_handler(
+        &self,
+    ) -> Arc<GetDeletedToDoItemsForAuditQueryHandler> {
+        self.get_all_deleted_for_audit_query_handler.clone()
+    }
+
+    pub fn get_history_query_handler(&self) -> Arc<GetToDoItemHistoryQueryHandler> {
+        self.get_history_query_handler.clone()
+    }
+
+    pub fn get_revision_query_handler(&self) -> Arc<GetToDoItemRevisionQueryHandler> {
+        self.get_revision_query_handler.clone()
+    }
+
+    pub fn get_diff_query_handler(&self) -> Arc<GetToDoItemDiffQueryHandler> {
+        self.get_diff_query_handler.clone()
+    }
 }
 
 pub struct ToDoItemServiceBoxed {
     query_repository: Arc<dyn ToDoItemQueryRepository + Send + Sync>,
-    command_repository: Arc<dyn ToDoItemCommandRepository + Send + Sync>,
+    unit_of_work: Arc<dyn UnitOfWorkFactory + Send + Sync>,
 }
 
 impl ToDoItemServiceBoxed {
     pub fn new(
         query_repository: Arc<dyn ToDoItemQueryRepository + Send + Sync>,
-        command_repository: Arc<dyn ToDoItemCommandRepository + Send + Sync>,
+        unit_of_work: Arc<dyn UnitOfWorkFactory + Send + Sync>,
     ) -> Self {
         Self {
             query_repository,
-            command_repository,
+            unit_of_work,
         }
     }
 
@@ -93,23 +150,31 @@ impl ToDoItemServiceBoxed {
     }
 
     pub fn create_create_command_handler(&self) -> Box<CreateToDoItemCommandHandler> {
-        Box::new(CreateToDoItemCommandHandler::new(
-            self.command_repository.clone(),
-        ))
+        Box::new(CreateToDoItemCommandHandler::new(self.unit_of_work.clone()))
     }
 
     pub fn create_update_command_handler(&self) -> Box<UpdateToDoItemCommandHandler> {
-        Box::new(UpdateToDoItemCommandHandler::new(
-            self.command_repository.clone(),
-        ))
+        Box::new(UpdateToDoItemCommandHandler::new(self.unit_of_work.clone()))
+    }
+
+    pub fn create_patch_command_handler(&self) -> Box<PatchToDoItemCommandHandler> {
+        Box::new(PatchToDoItemCommandHandler::new(self.unit_of_work.clone()))
     }
 
     pub fn create_delete_command_handler(&self) -> Box<DeleteToDoItemCommandHandler> {
-        Box::new(DeleteToDoItemCommandHandler::new(
-            self.command_repository.clone(),
+        Box::new(DeleteToDoItemCommandHandler::new(self.unit_of_work.clone()))
+    }
+
+    pub fn create_restore_command_handler(&self) -> Box<RestoreToDoItemCommandHandler> {
+        Box::new(RestoreToDoItemCommandHandler::new(
+            self.unit_of_work.clone(),
         ))
     }
 
+    pub fn create_batch_command_handler(&self) -> Box<BatchToDoItemsCommandHandler> {
+        Box::new(BatchToDoItemsCommandHandler::new(self.unit_of_work.clone()))
+    }
+
     pub fn create_get_deleted_for_audit_query_handler(
         &self,
     ) -> Box<GetDeletedToDoItemForAuditQueryHandler> {
@@ -117,17 +182,46 @@ impl ToDoItemServiceBoxed {
             self.query_repository.clone(),
         ))
     }
+
+    pub fn create_get_all_deleted_for_audit_query_handler(
+        &self,
+    ) -> Box<GetDeletedToDoItemsForAuditQueryHandler> {
+        Box::new(GetDeletedToDoItemsForAuditQueryHandler::new(
+            self.query_repository.clone(),
+        ))
+    }
+
+    pub fn create_get_history_query_handler(&self) -> Box<GetToDoItemHistoryQueryHandler> {
+        Box::new(GetToDoItemHistoryQueryHandler::new(
+            self.query_repository.clone(),
+        ))
+    }
+
+    pub fn create_get_revision_query_handler(&self) -> Box<GetToDoItemRevisionQueryHandler> {
+        Box::new(GetToDoItemRevisionQueryHandler::new(
+            self.query_repository.clone(),
+        ))
+    }
+
+    pub fn create_get_diff_query_handler(&self) -> Box<GetToDoItemDiffQueryHandler> {
+        Box::new(GetToDoItemDiffQueryHandler::new(
+            self.query_repository.clone(),
+        ))
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::repositories::ToDoItemCommandRepository;
     use crate::{
-        ApplicationError, ApplicationResult, CreateToDoItemCommand, GetAllToDoItemsQuery,
-        GetToDoItemQuery, PaginatedResult, UpdateToDoItemCommand,
+        AccessScope, ApplicationError, ApplicationResult, CreateToDoItemCommand,
+        GetAllToDoItemsQuery, GetDeletedToDoItemsForAuditQuery, GetToDoItemQuery,
+        InMemoryUnitOfWorkFactory, PaginatedResult, ToDoItemRevision, UpdateToDoItemCommand,
     };
     use async_trait::async_trait;
-    use domain::ToDoItem;
+    use chrono::{DateTime, Utc};
+    use domain::{ToDoItem, ToDoItemStatus};
     use std::sync::{Arc, Mutex};
     use tokio::task;
     use uuid::Uuid;
@@ -159,7 +253,8 @@ mod tests {
             query: GetAllToDoItemsQuery,
         ) -> ApplicationResult<PaginatedResult<ToDoItem>> {
             *self.query_call_count.lock().expect("query count lock") += 1;
-            let items = self.items.lock().expect("items lock").clone();
+            let mut items = self.items.lock().expect("items lock").clone();
+            items.sort_by(|left, right| query.sort.compare(left, right));
             let total_items = items.len() as i64;
             let paged_items = items
                 .into_iter()
@@ -175,18 +270,22 @@ mod tests {
             ))
         }
 
-        async fn get_by_id(&self, id: Uuid) -> ApplicationResult<ToDoItem> {
+        async fn get_by_id(&self, id: Uuid, scope: AccessScope) -> ApplicationResult<ToDoItem> {
             *self.query_call_count.lock().expect("query count lock") += 1;
             self.items
                 .lock()
                 .expect("items lock")
                 .iter()
-                .find(|item| item.id == id)
+                .find(|item| item.id == id && scope.permits(item))
                 .cloned()
                 .ok_or(ApplicationError::NotFound { id })
         }
 
-        async fn get_deleted_by_id_for_audit(&self, id: Uuid) -> ApplicationResult<ToDoItem> {
+        async fn get_deleted_by_id_for_audit(
+            &self,
+            id: Uuid,
+            _scope: AccessScope,
+        ) -> ApplicationResult<ToDoItem> {
             *self.query_call_count.lock().expect("query count lock") += 1;
             self.items
                 .lock()
@@ -196,10 +295,92 @@ mod tests {
                 .cloned()
                 .ok_or(ApplicationError::NotFound { id })
         }
+
+        async fn get_deleted_for_audit(
+            &self,
+            query: GetDeletedToDoItemsForAuditQuery,
+        ) -> ApplicationResult<PaginatedResult<ToDoItem>> {
+            *self.query_call_count.lock().expect("query count lock") += 1;
+            let mut items = self
+                .items
+                .lock()
+                .expect("items lock")
+                .iter()
+                .filter(|item| item.deleted_at.is_some())
+                .cloned()
+                .collect::<Vec<_>>();
+            items.sort_by(|left, right| query.sort.compare(left, right));
+            let total_items = items.len() as i64;
+            let paged_items = items
+                .into_iter()
+                .skip(query.offset() as usize)
+                .take(query.limit() as usize)
+                .collect();
+
+            Ok(PaginatedResult::new(
+                paged_items,
+                query.page,
+                query.page_size,
+                total_items,
+            ))
+        }
+
+        async fn get_history(
+            &self,
+            _id: Uuid,
+            page: u32,
+            page_size: u32,
+            _scope: AccessScope,
+        ) -> ApplicationResult<PaginatedResult<ToDoItemRevision>> {
+            Ok(PaginatedResult::new(Vec::new(), page, page_size, 0))
+        }
+
+        async fn get_revision(
+            &self,
+            id: Uuid,
+            _revision: i32,
+            _scope: AccessScope,
+        ) -> ApplicationResult<ToDoItemRevision> {
+            Err(ApplicationError::NotFound { id })
+        }
+
+        async fn get_revisions(
+            &self,
+            _id: Uuid,
+            _from: i32,
+            _to: i32,
+            _scope: AccessScope,
+        ) -> ApplicationResult<Vec<ToDoItemRevision>> {
+            Ok(Vec::new())
+        }
     }
 
     #[async_trait]
     impl ToDoItemCommandRepository for SharedRepositoryState {
+        async fn get_for_update(
+            &self,
+            id: Uuid,
+            scope: AccessScope,
+        ) -> ApplicationResult<ToDoItem> {
+            *self.command_call_count.lock().expect("command count lock") += 1;
+            self.items
+                .lock()
+                .expect("items lock")
+                .iter()
+                .find(|item| item.id == id && scope.permits(item))
+                .cloned()
+                .ok_or(ApplicationError::NotFound { id })
+        }
+
+        async fn get_deleted_for_update(
+            &self,
+            id: Uuid,
+            _scope: AccessScope,
+        ) -> ApplicationResult<ToDoItem> {
+            *self.command_call_count.lock().expect("command count lock") += 1;
+            Err(ApplicationError::NotFound { id })
+        }
+
         async fn create(&self, entity: ToDoItem) -> ApplicationResult<Uuid> {
             *self.command_call_count.lock().expect("command count lock") += 1;
             let id = entity.id;
@@ -207,7 +388,14 @@ mod tests {
             Ok(id)
         }
 
-        async fn update(&self, entity: ToDoItem) -> ApplicationResult<Uuid> {
+        async fn create_many(&self, entities: Vec<ToDoItem>) -> ApplicationResult<Vec<Uuid>> {
+            *self.command_call_count.lock().expect("command count lock") += 1;
+            let ids = entities.iter().map(|entity| entity.id).collect();
+            self.items.lock().expect("items lock").extend(entities);
+            Ok(ids)
+        }
+
+        async fn update(&self, entity: ToDoItem) -> ApplicationResult<ToDoItem> {
             *self.command_call_count.lock().expect("command count lock") += 1;
             let id = entity.id;
             let mut items = self.items.lock().expect("items lock");
@@ -230,15 +418,44 @@ mod tests {
             existing.due_at = entity.due_at;
             existing.version += 1;
 
-            Ok(id)
+            Ok(existing.clone())
         }
 
-        async fn delete(&self, id: Uuid, _deleted_by: Option<Uuid>) -> ApplicationResult<()> {
+        async fn delete(
+            &self,
+            id: Uuid,
+            deleted_by: Option<Uuid>,
+            _scope: AccessScope,
+        ) -> ApplicationResult<ToDoItem> {
+            *self.command_call_count.lock().expect("command count lock") += 1;
+            let mut items = self.items.lock().expect("items lock");
+            let position = items
+                .iter()
+                .position(|item| item.id == id)
+                .ok_or(ApplicationError::NotFound { id })?;
+            let mut deleted = items.remove(position);
+            deleted.deleted_at = Some(Utc::now());
+            deleted.deleted_by = deleted_by;
+            deleted.version += 1;
+            Ok(deleted)
+        }
+
+        async fn restore(&self, entity: ToDoItem) -> ApplicationResult<Uuid> {
+            *self.command_call_count.lock().expect("command count lock") += 1;
+            Ok(entity.id)
+        }
+
+        async fn get_purgeable_ids(
+            &self,
+            _deleted_before: DateTime<Utc>,
+            _limit: i64,
+        ) -> ApplicationResult<Vec<Uuid>> {
+            *self.command_call_count.lock().expect("command count lock") += 1;
+            Ok(Vec::new())
+        }
+
+        async fn purge(&self, _ids: Vec<Uuid>) -> ApplicationResult<()> {
             *self.command_call_count.lock().expect("command count lock") += 1;
-            self.items
-                .lock()
-                .expect("items lock")
-                .retain(|item| item.id != id);
             Ok(())
         }
     }
@@ -246,7 +463,10 @@ mod tests {
     #[tokio::test]
     async fn service_clones_share_query_handlers() {
         let repository = Arc::new(SharedRepositoryState::new());
-        let service = ToDoItemService::new(repository.clone(), repository);
+        let service = ToDoItemService::new(
+            repository.clone(),
+            Arc::new(InMemoryUnitOfWorkFactory::new(repository)),
+        );
 
         let cloned = service.clone();
 
@@ -262,7 +482,10 @@ mod tests {
         let item = ToDoItem::new("title".to_string(), "note".to_string());
         let id = item.id;
         repository.add_item(item);
-        let service = ToDoItemService::new(repository.clone(), repository.clone());
+        let service = ToDoItemService::new(
+            repository.clone(),
+            Arc::new(InMemoryUnitOfWorkFactory::new(repository.clone())),
+        );
 
         let _ = service
             .get_query_handler()
@@ -291,14 +514,49 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn audit_listing_returns_deleted_items_most_recently_deleted_first() {
+        let repository = Arc::new(SharedRepositoryState::new());
+        let now = Utc::now();
+        let mut earlier = ToDoItem::new("earlier".to_string(), "note".to_string());
+        earlier.deleted_at = Some(now - chrono::Duration::hours(1));
+        let mut later = ToDoItem::new("later".to_string(), "note".to_string());
+        later.deleted_at = Some(now);
+        repository.add_item(earlier.clone());
+        repository.add_item(ToDoItem::new("active".to_string(), "note".to_string()));
+        repository.add_item(later.clone());
+        let service = ToDoItemService::new(
+            repository.clone(),
+            Arc::new(InMemoryUnitOfWorkFactory::new(repository)),
+        );
+
+        let page = service
+            .get_all_deleted_for_audit_query_handler()
+            .execute(GetDeletedToDoItemsForAuditQuery::default())
+            .await
+            .expect("audit list result");
+
+        let ids = page.items.iter().map(|item| item.id).collect::<Vec<_>>();
+        assert_eq!(ids, [later.id, earlier.id]);
+        assert_eq!(page.total_items, Some(2));
+    }
+
     #[tokio::test]
     async fn command_handlers_only_increment_command_side_state() {
         let repository = Arc::new(SharedRepositoryState::new());
-        let service = ToDoItemService::new(repository.clone(), repository.clone());
+        let service = ToDoItemService::new(
+            repository.clone(),
+            Arc::new(InMemoryUnitOfWorkFactory::new(repository.clone())),
+        );
 
         let created_id = service
             .create_command_handler()
-            .execute(CreateToDoItemCommand::new("Title", "Note", "pending", None))
+            .execute(CreateToDoItemCommand::new(
+                "Title",
+                "Note",
+                ToDoItemStatus::Pending,
+                None,
+            ))
             .await
             .expect("create result");
         service
@@ -319,7 +577,8 @@ mod tests {
                 .command_call_count
                 .lock()
                 .expect("command count lock"),
-            2
+            // create, get_for_update before the delete and for the staged delete, delete
+            4
         );
     }
 
@@ -329,7 +588,10 @@ mod tests {
         let existing = ToDoItem::new("Title".to_string(), "Note".to_string());
         let id = existing.id;
         repository.add_item(existing);
-        let service = ToDoItemService::new(repository.clone(), repository);
+        let service = ToDoItemService::new(
+            repository.clone(),
+            Arc::new(InMemoryUnitOfWorkFactory::new(repository)),
+        );
 
         let result = service
             .update_command_handler()
@@ -337,7 +599,7 @@ mod tests {
                 id,
                 "Updated",
                 "Updated note",
-                "pending",
+                ToDoItemStatus::Pending,
                 None,
                 99,
             ))
@@ -355,7 +617,10 @@ mod tests {
     #[tokio::test]
     async fn service_is_send_sync_for_query_execution() {
         let repository = Arc::new(SharedRepositoryState::new());
-        let service = Arc::new(ToDoItemService::new(repository.clone(), repository));
+        let service = Arc::new(ToDoItemService::new(
+            repository.clone(),
+            Arc::new(InMemoryUnitOfWorkFactory::new(repository)),
+        ));
 
         let handle = task::spawn(async move {
             let handler = service.get_all_query_handler();
@@ -4,19 +4,32 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::entity;
+use crate::events::{
+    ToDoItemCreated, ToDoItemDeleted, ToDoItemEvent, ToDoItemRestored, ToDoItemStatusChanged,
+    ToDoItemUpdated,
+};
+use crate::status::{InvalidStatusTransition, ToDoItemStatus};
 
 #[derive(Queryable, Serialize, Deserialize, PartialEq, Debug, Clone)]
 pub struct ToDoItem {
     pub id: Uuid,
     pub title: Option<String>,
     pub note: Option<String>,
-    pub status: String,
+    pub status: ToDoItemStatus,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
     pub due_at: Option<DateTime<Utc>>,
     pub version: i32,
     pub deleted_at: Option<DateTime<Utc>>,
     pub deleted_by: Option<Uuid>,
+    pub restored_at: Option<DateTime<Utc>>,
+    pub restored_by: Option<Uuid>,
+    /// Caller that created the item; `None` for items created without authentication.
+    pub owner_id: Option<Uuid>,
+    /// Caller of the last update.
+    pub updated_by: Option<Uuid>,
+    /// Organization the item belongs to; `None` while multi-tenancy is disabled.
+    pub tenant_id: Option<String>,
 }
 
 impl ToDoItem {
@@ -27,20 +40,25 @@ impl ToDoItem {
             id: Uuid::new_v4(),
             title: Some(title),
             note: Some(note),
-            status: "pending".into(),
+            status: ToDoItemStatus::Pending,
             created_at: now,
             updated_at: now,
             due_at: None,
             version: 1,
             deleted_at: None,
             deleted_by: None,
+            restored_at: None,
+            restored_by: None,
+            owner_id: None,
+            updated_by: None,
+            tenant_id: None,
         }
     }
 
     pub fn new_with_lifecycle(
         title: String,
         note: String,
-        status: impl Into<String>,
+        status: ToDoItemStatus,
         due_at: Option<DateTime<Utc>>,
     ) -> Self {
         let now = Utc::now();
@@ -49,13 +67,18 @@ impl ToDoItem {
             id: Uuid::new_v4(),
             title: Some(title),
             note: Some(note),
-            status: status.into(),
+            status,
             created_at: now,
             updated_at: now,
             due_at,
             version: 1,
             deleted_at: None,
             deleted_by: None,
+            restored_at: None,
+            restored_by: None,
+            owner_id: None,
+            updated_by: None,
+            tenant_id: None,
         }
     }
 
@@ -63,7 +86,7 @@ impl ToDoItem {
         id: Uuid,
         title: String,
         note: String,
-        status: impl Into<String>,
+        status: ToDoItemStatus,
         due_at: Option<DateTime<Utc>>,
         version: i32,
     ) -> Self {
@@ -73,16 +96,31 @@ impl ToDoItem {
             id,
             title: Some(title),
             note: Some(note),
-            status: status.into(),
+            status,
             created_at: now,
             updated_at: now,
             due_at,
             version,
             deleted_at: None,
             deleted_by: None,
+            restored_at: None,
+            restored_by: None,
+            owner_id: None,
+            updated_by: None,
+            tenant_id: None,
         }
     }
 
+    pub fn with_owner(mut self, owner_id: Option<Uuid>) -> Self {
+        self.owner_id = owner_id;
+        self
+    }
+
+    pub fn with_tenant(mut self, tenant_id: Option<String>) -> Self {
+        self.tenant_id = tenant_id;
+        self
+    }
+
     pub fn is_deleted(&self) -> bool {
         self.deleted_at.is_some()
     }
@@ -91,11 +129,93 @@ impl ToDoItem {
         !self.is_deleted()
     }
 
-    pub fn mark_deleted_once(&mut self, deleted_by: Option<Uuid>) {
-        if self.deleted_at.is_none() {
-            self.deleted_at = Some(Utc::now());
-            self.deleted_by = deleted_by;
+    pub fn created_event(&self) -> ToDoItemEvent {
+        ToDoItemEvent::Created(ToDoItemCreated {
+            id: self.id,
+            title: self.title.clone(),
+            note: self.note.clone(),
+            status: self.status,
+            due_at: self.due_at,
+            occurred_at: self.created_at,
+        })
+    }
+
+    /// Moves the item to `next`, returning a `StatusChanged` event when the status actually changes.
+    pub fn change_status(
+        &mut self,
+        next: ToDoItemStatus,
+    ) -> Result<Option<ToDoItemEvent>, InvalidStatusTransition> {
+        let previous = self.status;
+        self.status = previous.transition_to(next)?;
+
+        if previous == self.status {
+            return Ok(None);
         }
+
+        Ok(Some(ToDoItemEvent::StatusChanged(ToDoItemStatusChanged {
+            id: self.id,
+            from: previous,
+            to: self.status,
+            occurred_at: Utc::now(),
+        })))
+    }
+
+    /// Replaces title, note and due date, returning an `Updated` event when any of them changes.
+    pub fn change_details(
+        &mut self,
+        title: Option<String>,
+        note: Option<String>,
+        due_at: Option<DateTime<Utc>>,
+    ) -> Option<ToDoItemEvent> {
+        if self.title == title && self.note == note && self.due_at == due_at {
+            return None;
+        }
+
+        self.title = title;
+        self.note = note;
+        self.due_at = due_at;
+
+        Some(ToDoItemEvent::Updated(ToDoItemUpdated {
+            id: self.id,
+            title: self.title.clone(),
+            note: self.note.clone(),
+            due_at: self.due_at,
+            occurred_at: Utc::now(),
+        }))
+    }
+
+    pub fn mark_deleted_once(&mut self, deleted_by: Option<Uuid>) -> Option<ToDoItemEvent> {
+        if self.deleted_at.is_some() {
+            return None;
+        }
+
+        let now = Utc::now();
+        self.deleted_at = Some(now);
+        self.deleted_by = deleted_by;
+
+        Some(ToDoItemEvent::Deleted(ToDoItemDeleted {
+            id: self.id,
+            deleted_by,
+            occurred_at: now,
+        }))
+    }
+
+    /// Clears the deletion metadata of a deleted item, returning `None` when it is not deleted.
+    pub fn restore(&mut self, restored_by: Option<Uuid>) -> Option<ToDoItemEvent> {
+        self.deleted_at?;
+
+        let now = Utc::now();
+        self.deleted_at = None;
+        self.deleted_by = None;
+        self.restored_at = Some(now);
+        self.restored_by = restored_by;
+        self.updated_at = now;
+
+        Some(ToDoItemEvent::Restored(ToDoItemRestored {
+            id: self.id,
+            restored_by,
+            occurred_at: now,
+        }))
     }
 }
 
@@ -104,6 +224,7 @@ impl entity::Entity<ToDoItem> for ToDoItem {}
 #[cfg(test)]
 mod tests {
     use super::ToDoItem;
+    use crate::{ToDoItemEvent, ToDoItemStatus};
     use uuid::Uuid;
 
     #[test]
@@ -131,4 +252,102 @@ mod tests {
         assert!(item.deleted_at.is_some());
         assert_eq!(item.deleted_by, None);
     }
+
+    #[test]
+    fn change_status_applies_allowed_transitions() {
+        let mut item = ToDoItem::new("title".into(), "note".into());
+
+        item.change_status(ToDoItemStatus::InProgress)
+            .expect("pending -> in_progress should be allowed");
+
+        assert_eq!(item.status, ToDoItemStatus::InProgress);
+    }
+
+    #[test]
+    fn change_status_keeps_status_on_illegal_transition() {
+        let mut item = ToDoItem::new("title".into(), "note".into());
+
+        let result = item.change_status(ToDoItemStatus::Done);
+
+        assert!(result.is_err());
+        assert_eq!(item.status, ToDoItemStatus::Pending);
+    }
+
+    #[test]
+    fn change_status_raises_event_only_when_status_changes() {
+        let mut item = ToDoItem::new("title".into(), "note".into());
+
+        let unchanged = item
+            .change_status(ToDoItemStatus::Pending)
+            .expect("keeping the status should be allowed");
+        let changed = item
+            .change_status(ToDoItemStatus::InProgress)
+            .expect("pending -> in_progress should be allowed");
+
+        assert_eq!(unchanged, None);
+        match changed {
+            Some(ToDoItemEvent::StatusChanged(event)) => {
+                assert_eq!(event.id, item.id);
+                assert_eq!(event.from, ToDoItemStatus::Pending);
+                assert_eq!(event.to, ToDoItemStatus::InProgress);
+            }
+            other => panic!("expected status changed event, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn change_details_raises_event_only_when_details_change() {
+        let mut item = ToDoItem::new("title".into(), "note".into());
+
+        assert_eq!(
+            item.change_details(Some("title".into()), Some("note".into()), None),
+            None
+        );
+
+        let event = item.change_details(Some("renamed".into()), Some("note".into()), None);
+
+        assert!(matches!(event, Some(ToDoItemEvent::Updated(_))));
+        assert_eq!(item.title.as_deref(), Some("renamed"));
+    }
+
+    #[test]
+    fn mark_deleted_once_raises_event_only_on_first_call() {
+        let mut item = ToDoItem::new("title".into(), "note".into());
+        let actor = Some(Uuid::new_v4());
+
+        let first = item.mark_deleted_once(actor);
+        let second = item.mark_deleted_once(actor);
+
+        match first {
+            Some(ToDoItemEvent::Deleted(event)) => {
+                assert_eq!(event.deleted_by, actor);
+                assert_eq!(Some(event.occurred_at), item.deleted_at);
+            }
+            other => panic!("expected deleted event, got {other:?}"),
+        }
+        assert_eq!(second, None);
+    }
+
+    #[test]
+    fn restore_clears_deletion_metadata_and_records_actor() {
+        let mut item = ToDoItem::new("title".into(), "note".into());
+        let actor = Some(Uuid::new_v4());
+        item.mark_deleted_once(Some(Uuid::new_v4()));
+
+        let event = item.restore(actor);
+
+        assert!(matches!(event, Some(ToDoItemEvent::Restored(_))));
+        assert!(item.is_active());
+        assert_eq!(item.deleted_by, None);
+        assert_eq!(item.restored_by, actor);
+        assert!(item.restored_at.is_some());
+    }
+
+    #[test]
+    fn restore_of_active_item_is_rejected() {
+        let mut item = ToDoItem::new("title".into(), "note".into());
+
+        assert_eq!(item.restore(None), None);
+        assert_eq!(item.restored_at, None);
+    }
 }
@@ -0,0 +1,91 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::status::ToDoItemStatus;
+
+/// Event raised by the `ToDoItem` aggregate.
+///
+/// Completion is reported as a `StatusChanged` event whose `to` status is `done`.
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ToDoItemEvent {
+    Created(ToDoItemCreated),
+    Updated(ToDoItemUpdated),
+    StatusChanged(ToDoItemStatusChanged),
+    Deleted(ToDoItemDeleted),
+    Restored(ToDoItemRestored),
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct ToDoItemCreated {
+    pub id: Uuid,
+    pub title: Option<String>,
+    pub note: Option<String>,
+    pub status: ToDoItemStatus,
+    pub due_at: Option<DateTime<Utc>>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct ToDoItemUpdated {
+    pub id: Uuid,
+    pub title: Option<String>,
+    pub note: Option<String>,
+    pub due_at: Option<DateTime<Utc>>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct ToDoItemStatusChanged {
+    pub id: Uuid,
+    pub from: ToDoItemStatus,
+    pub to: ToDoItemStatus,
+    pub occurred_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct ToDoItemDeleted {
+    pub id: Uuid,
+    pub deleted_by: Option<Uuid>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, PartialEq, Debug, Clone)]
+pub struct ToDoItemRestored {
+    pub id: Uuid,
+    pub restored_by: Option<Uuid>,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl ToDoItemEvent {
+    pub fn aggregate_id(&self) -> Uuid {
+        match self {
+            ToDoItemEvent::Created(event) => event.id,
+            ToDoItemEvent::Updated(event) => event.id,
+            ToDoItemEvent::StatusChanged(event) => event.id,
+            ToDoItemEvent::Deleted(event) => event.id,
+            ToDoItemEvent::Restored(event) => event.id,
+        }
+    }
+
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            ToDoItemEvent::Created(_) => "to_do_item.created",
+            ToDoItemEvent::Updated(_) => "to_do_item.updated",
+            ToDoItemEvent::StatusChanged(_) => "to_do_item.status_changed",
+            ToDoItemEvent::Deleted(_) => "to_do_item.deleted",
+            ToDoItemEvent::Restored(_) => "to_do_item.restored",
+        }
+    }
+
+    pub fn occurred_at(&self) -> DateTime<Utc> {
+        match self {
+            ToDoItemEvent::Created(event) => event.occurred_at,
+            ToDoItemEvent::Updated(event) => event.occurred_at,
+            ToDoItemEvent::StatusChanged(event) => event.occurred_at,
+            ToDoItemEvent::Deleted(event) => event.occurred_at,
+            ToDoItemEvent::Restored(event) => event.occurred_at,
+        }
+    }
+}
@@ -1,7 +1,14 @@
 mod entities;
 mod entity;
+mod events;
 mod schema;
+mod status;
 
 pub use entities::ToDoItem;
 pub use entity::Entity;
-pub use schema::to_do_items;
+pub use events::{
+    ToDoItemCreated, ToDoItemDeleted, ToDoItemEvent, ToDoItemRestored, ToDoItemStatusChanged,
+    ToDoItemUpdated,
+};
+pub use schema::{api_keys, idempotency_keys, outbox, to_do_item_revisions, to_do_items};
+pub use status::{InvalidStatusTransition, ParseToDoItemStatusError, ToDoItemStatus};
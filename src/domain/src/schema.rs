@@ -16,5 +16,78 @@ table! {
         version -> Int4,
         deleted_at -> Nullable<Timestamptz>,
         deleted_by -> Nullable<Uuid>,
+        restored_at -> Nullable<Timestamptz>,
+        restored_by -> Nullable<Uuid>,
+        owner_id -> Nullable<Uuid>,
+        updated_by -> Nullable<Uuid>,
+        #[max_length = 64]
+        tenant_id -> Nullable<Varchar>,
+    }
+}
+
+table! {
+    outbox (id) {
+        id -> Uuid,
+        aggregate_id -> Uuid,
+        #[max_length = 64]
+        event_type -> Varchar,
+        payload -> Jsonb,
+        occurred_at -> Timestamptz,
+        published_at -> Nullable<Timestamptz>,
+        attempts -> Int4,
+        last_error -> Nullable<Text>,
+        next_attempt_at -> Nullable<Timestamptz>,
+        failed_at -> Nullable<Timestamptz>,
+    }
+}
+
+table! {
+    idempotency_keys (key) {
+        #[max_length = 357]
+        key -> Varchar,
+        #[max_length = 64]
+        fingerprint -> Varchar,
+        response_status -> Nullable<Int4>,
+        response_headers -> Nullable<Jsonb>,
+        response_body -> Nullable<Bytea>,
+        created_at -> Timestamptz,
+        expires_at -> Timestamptz,
+    }
+}
+
+table! {
+    api_keys (id) {
+        id -> Uuid,
+        #[max_length = 100]
+        name -> Varchar,
+        #[max_length = 16]
+        prefix -> Varchar,
+        #[max_length = 64]
+        key_hash -> Varchar,
+        scopes -> Array<Text>,
+        created_at -> Timestamptz,
+        expires_at -> Nullable<Timestamptz>,
+        last_used_at -> Nullable<Timestamptz>,
+        revoked_at -> Nullable<Timestamptz>,
+        #[max_length = 64]
+        tenant_id -> Nullable<Varchar>,
+    }
+}
+
+table! {
+    to_do_item_revisions (item_id, revision) {
+        item_id -> Uuid,
+        revision -> Int4,
+        version -> Int4,
+        #[max_length = 16]
+        change_type -> Varchar,
+        snapshot -> Jsonb,
+        actor -> Nullable<Uuid>,
+        #[max_length = 200]
+        request_id -> Nullable<Varchar>,
+        owner_id -> Nullable<Uuid>,
+        #[max_length = 64]
+        tenant_id -> Nullable<Varchar>,
+        recorded_at -> Timestamptz,
     }
 }
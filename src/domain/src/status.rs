@@ -0,0 +1,185 @@
+use diesel::deserialize::{self, FromSql, FromSqlRow};
+use diesel::expression::AsExpression;
+use diesel::pg::{Pg, PgValue};
+use diesel::serialize::{self, IsNull, Output, ToSql};
+use diesel::sql_types::Varchar;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+use std::io::Write;
+use std::str::FromStr;
+use thiserror::Error;
+
+/// Lifecycle status of a to-do item.
+///
+/// Allowed transitions:
+///
+/// - `pending -> in_progress`
+/// - `in_progress -> done`
+/// - `in_progress -> pending` (work paused)
+/// - `done -> pending` (item reopened)
+///
+/// Keeping the current status is always allowed.
+#[derive(
+    Debug,
+    Default,
+    Clone,
+    Copy,
+    PartialEq,
+    Eq,
+    Hash,
+    Serialize,
+    Deserialize,
+    AsExpression,
+    FromSqlRow,
+)]
+#[serde(rename_all = "snake_case")]
+#[diesel(sql_type = Varchar)]
+pub enum ToDoItemStatus {
+    #[default]
+    Pending,
+    InProgress,
+    Done,
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+#[error("unknown to-do item status: {value}")]
+pub struct ParseToDoItemStatusError {
+    pub value: String,
+}
+
+#[derive(Error, Debug, Clone, Copy, PartialEq, Eq)]
+#[error("status transition from {from} to {to} is not allowed")]
+pub struct InvalidStatusTransition {
+    pub from: ToDoItemStatus,
+    pub to: ToDoItemStatus,
+}
+
+impl ToDoItemStatus {
+    pub const ALL: [ToDoItemStatus; 3] = [Self::Pending, Self::InProgress, Self::Done];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::InProgress => "in_progress",
+            Self::Done => "done",
+        }
+    }
+
+    /// Position of the status in the item lifecycle, used to sort items by status.
+    pub fn lifecycle_rank(&self) -> i32 {
+        match self {
+            Self::Pending => 0,
+            Self::InProgress => 1,
+            Self::Done => 2,
+        }
+    }
+
+    pub fn can_transition_to(&self, next: ToDoItemStatus) -> bool {
+        matches!(
+            (self, next),
+            (Self::Pending, Self::Pending)
+                | (Self::InProgress, Self::InProgress)
+                | (Self::Done, Self::Done)
+                | (Self::Pending, Self::InProgress)
+                | (Self::InProgress, Self::Done)
+                | (Self::InProgress, Self::Pending)
+                | (Self::Done, Self::Pending)
+        )
+    }
+
+    pub fn transition_to(&self, next: ToDoItemStatus) -> Result<Self, InvalidStatusTransition> {
+        if self.can_transition_to(next) {
+            Ok(next)
+        } else {
+            Err(InvalidStatusTransition {
+                from: *self,
+                to: next,
+            })
+        }
+    }
+}
+
+impl Display for ToDoItemStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for ToDoItemStatus {
+    type Err = ParseToDoItemStatusError;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        match value {
+            "pending" => Ok(Self::Pending),
+            "in_progress" => Ok(Self::InProgress),
+            "done" => Ok(Self::Done),
+            _ => Err(ParseToDoItemStatusError {
+                value: value.to_string(),
+            }),
+        }
+    }
+}
+
+impl ToSql<Varchar, Pg> for ToDoItemStatus {
+    fn to_sql<'b>(&'b self, out: &mut Output<'b, '_, Pg>) -> serialize::Result {
+        out.write_all(self.as_str().as_bytes())?;
+        Ok(IsNull::No)
+    }
+}
+
+impl FromSql<Varchar, Pg> for ToDoItemStatus {
+    fn from_sql(bytes: PgValue<'_>) -> deserialize::Result<Self> {
+        let value = <String as FromSql<Varchar, Pg>>::from_sql(bytes)?;
+        Ok(value.parse()?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_round_trips_through_string_representation() {
+        for status in ToDoItemStatus::ALL {
+            assert_eq!(status.as_str().parse::<ToDoItemStatus>(), Ok(status));
+        }
+        assert!("garbage".parse::<ToDoItemStatus>().is_err());
+    }
+
+    #[test]
+    fn forward_transitions_are_allowed() {
+        assert_eq!(
+            ToDoItemStatus::Pending.transition_to(ToDoItemStatus::InProgress),
+            Ok(ToDoItemStatus::InProgress)
+        );
+        assert_eq!(
+            ToDoItemStatus::InProgress.transition_to(ToDoItemStatus::Done),
+            Ok(ToDoItemStatus::Done)
+        );
+    }
+
+    #[test]
+    fn reopen_and_pause_transitions_are_allowed() {
+        assert!(ToDoItemStatus::Done.can_transition_to(ToDoItemStatus::Pending));
+        assert!(ToDoItemStatus::InProgress.can_transition_to(ToDoItemStatus::Pending));
+    }
+
+    #[test]
+    fn skipping_or_reviving_work_is_rejected() {
+        assert_eq!(
+            ToDoItemStatus::Pending.transition_to(ToDoItemStatus::Done),
+            Err(InvalidStatusTransition {
+                from: ToDoItemStatus::Pending,
+                to: ToDoItemStatus::Done,
+            })
+        );
+        assert!(!ToDoItemStatus::Done.can_transition_to(ToDoItemStatus::InProgress));
+    }
+
+    #[test]
+    fn keeping_the_current_status_is_allowed() {
+        for status in ToDoItemStatus::ALL {
+            assert!(status.can_transition_to(status));
+        }
+    }
+}
@@ -0,0 +1,179 @@
+//! Compares the throughput of the Diesel and `tokio-postgres` repositories.
+//!
+//! Needs the database configured in `config.app.toml` (or `MICROSERVICE__DATABASE__*`):
+//!
+//! ```sh
+//! cargo bench -p infrastructure --bench repository_throughput
+//! ```
+//!
+//! `BENCH_CONCURRENCY` (default 64) sets the number of concurrent callers and
+//! `BENCH_SECONDS` (default 5) the duration of each workload. The items it creates are
+//! removed again at the end.
+
+use actix_web::web::Data;
+use application::{
+    AccessScope, GetAllToDoItemsQuery, Settings, ToDoItemCommandRepository,
+    ToDoItemQueryRepository, ToDoItemSort,
+};
+use domain::ToDoItem;
+use infrastructure::{PostgresToDoItemRepository, TokioPostgresToDoItemRepository};
+use std::env;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+const SEEDED_ITEMS: usize = 1000;
+
+trait Repository: ToDoItemQueryRepository + ToDoItemCommandRepository {}
+
+impl<T: ToDoItemQueryRepository + ToDoItemCommandRepository> Repository for T {}
+
+#[derive(Clone, Copy)]
+enum Workload {
+    GetById,
+    GetAll,
+    Create,
+}
+
+impl Workload {
+    const ALL: [Workload; 3] = [Workload::GetById, Workload::GetAll, Workload::Create];
+
+    fn name(self) -> &'static str {
+        match self {
+            Workload::GetById => "get_by_id",
+            Workload::GetAll => "get_all",
+            Workload::Create => "create",
+        }
+    }
+}
+
+struct Run {
+    tenant: String,
+    seeded: Vec<Uuid>,
+    concurrency: usize,
+    duration: Duration,
+}
+
+#[tokio::main]
+async fn main() -> anyhow::Result<()> {
+    let settings = Settings::with_path("../../").load()?;
+    let pool = Data::new(infrastructure::configure(&settings).await?);
+    let async_pool = infrastructure::configure_async_pool(&settings)?;
+
+    let repositories: [(&str, Arc<dyn Repository + Send + Sync>); 2] = [
+        ("diesel", Arc::new(PostgresToDoItemRepository::new(&pool))),
+        (
+            "tokio_postgres",
+            Arc::new(TokioPostgresToDoItemRepository::new(&async_pool)),
+        ),
+    ];
+
+    let tenant = format!("bench-{}", Uuid::new_v4());
+    let seeded = (0..SEEDED_ITEMS)
+        .map(|index| {
+            ToDoItem::new(format!("bench item {index}"), "throughput benchmark".into())
+                .with_tenant(Some(tenant.clone()))
+        })
+        .collect::<Vec<_>>();
+    let seeded = repositories[0].1.create_many(seeded).await?;
+    let run = Arc::new(Run {
+        tenant,
+        seeded,
+        concurrency: env_or("BENCH_CONCURRENCY", 64),
+        duration: Duration::from_secs(env_or("BENCH_SECONDS", 5) as u64),
+    });
+
+    println!(
+        "{} concurrent callers, {:?} per workload",
+        run.concurrency, run.duration
+    );
+    println!(
+        "{:<16} {:<10} {:>12} {:>14}",
+        "driver", "workload", "ops/s", "mean latency"
+    );
+    for workload in Workload::ALL {
+        for (name, repository) in &repositories {
+            let (operations, elapsed) = measure(repository.clone(), run.clone(), workload).await?;
+            let throughput = operations as f64 / elapsed.as_secs_f64();
+            let latency = elapsed.mul_f64(run.concurrency as f64) / operations.max(1) as u32;
+            println!(
+                "{:<16} {:<10} {:>12.0} {:>14?}",
+                name,
+                workload.name(),
+                throughput,
+                latency
+            );
+        }
+    }
+
+    async_pool
+        .get()
+        .await?
+        .execute(
+            "DELETE FROM to_do_items WHERE tenant_id = $1",
+            &[&run.tenant],
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Runs `workload` from `run.concurrency` tasks until `run.duration` has passed and
+/// returns the number of completed operations.
+async fn measure(
+    repository: Arc<dyn Repository + Send + Sync>,
+    run: Arc<Run>,
+    workload: Workload,
+) -> anyhow::Result<(usize, Duration)> {
+    let operations = Arc::new(AtomicUsize::new(0));
+    let started = Instant::now();
+    let deadline = started + run.duration;
+
+    let tasks = (0..run.concurrency)
+        .map(|caller| {
+            let repository = repository.clone();
+            let run = run.clone();
+            let operations = operations.clone();
+            tokio::spawn(async move {
+                let scope = AccessScope::unrestricted().in_tenant(Some(run.tenant.clone()));
+                let mut iteration = caller;
+                while Instant::now() < deadline {
+                    match workload {
+                        Workload::GetById => {
+                            let id = run.seeded[iteration % run.seeded.len()];
+                            repository.get_by_id(id, scope.clone()).await?;
+                        }
+                        Workload::GetAll => {
+                            let query =
+                                GetAllToDoItemsQuery::new(1, 20, None, ToDoItemSort::default())
+                                    .within(scope.clone());
+                            repository.get_all(query).await?;
+                        }
+                        Workload::Create => {
+                            let item = ToDoItem::new("created".into(), "benchmark".into())
+                                .with_tenant(Some(run.tenant.clone()));
+                            repository.create(item).await?;
+                        }
+                    }
+                    operations.fetch_add(1, Ordering::Relaxed);
+                    iteration += run.concurrency;
+                }
+                Ok::<_, application::ApplicationError>(())
+            })
+        })
+        .collect::<Vec<_>>();
+
+    for task in tasks {
+        task.await??;
+    }
+
+    Ok((operations.load(Ordering::Relaxed), started.elapsed()))
+}
+
+fn env_or(name: &str, default: usize) -> usize {
+    env::var(name)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(default)
+}
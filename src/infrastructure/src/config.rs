@@ -1,23 +1,297 @@
-use anyhow::{anyhow, Context};
+use anyhow::{anyhow, bail, Context};
 use application::Settings;
-use diesel::r2d2::{ConnectionManager, Pool};
+use deadpool_postgres::{Hook, HookError, Manager, ManagerConfig, RecyclingMethod, Runtime};
+use diesel::connection::SimpleConnection;
+use diesel::r2d2::{ConnectionManager, CustomizeConnection, Pool};
 use diesel::{prelude::*, r2d2};
 use diesel_migrations::*;
+use std::time::Duration;
 
 pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("./src/migrations");
 
 type DbPool = r2d2::Pool<r2d2::ConnectionManager<PgConnection>>;
 
 pub async fn configure(settings: &Settings) -> anyhow::Result<DbPool> {
-    let database_url = settings.database.database_url.as_str();
-    let mut connection =
-        PgConnection::establish(database_url).context("failed to establish database connection")?;
+    let options = PoolOptions::primary(settings)?;
+    let mut connection = PgConnection::establish(&options.database_url)
+        .context("failed to establish database connection")?;
     connection
         .run_pending_migrations(MIGRATIONS)
         .map_err(|err| anyhow!("failed to run database migrations: {err}"))?;
 
-    let manager = ConnectionManager::<PgConnection>::new(database_url);
-    Pool::builder()
-        .build(manager)
-        .context("failed to create database connection pool")
+    build_pool(options).context("failed to create database connection pool")
+}
+
+/// Builds the read-only pool of the `diesel` driver, if `database.read_pool` is enabled.
+pub fn configure_read_pool(settings: &Settings) -> anyhow::Result<Option<DbPool>> {
+    PoolOptions::read(settings)?
+        .map(|options| {
+            build_pool(options).context("failed to create read-only database connection pool")
+        })
+        .transpose()
+}
+
+/// Builds the pool of the `tokio_postgres` database driver. Connections are opened on
+/// first use; the schema is migrated by `configure`.
+pub fn configure_async_pool(settings: &Settings) -> anyhow::Result<deadpool_postgres::Pool> {
+    build_async_pool(PoolOptions::primary(settings)?)
+        .context("failed to create async database connection pool")
+}
+
+/// Builds the read-only pool of the `tokio_postgres` driver, if `database.read_pool` is
+/// enabled.
+pub fn configure_async_read_pool(
+    settings: &Settings,
+) -> anyhow::Result<Option<deadpool_postgres::Pool>> {
+    PoolOptions::read(settings)?
+        .map(|options| {
+            build_async_pool(options)
+                .context("failed to create read-only async database connection pool")
+        })
+        .transpose()
+}
+
+fn build_pool(options: PoolOptions) -> anyhow::Result<DbPool> {
+    let manager = ConnectionManager::<PgConnection>::new(options.database_url);
+    Ok(Pool::builder()
+        .max_size(options.max_size)
+        .min_idle(Some(options.min_idle))
+        .connection_timeout(options.connection_timeout)
+        .idle_timeout(options.idle_timeout)
+        .max_lifetime(options.max_lifetime)
+        .connection_customizer(Box::new(options.session))
+        .build(manager)?)
+}
+
+fn build_async_pool(options: PoolOptions) -> anyhow::Result<deadpool_postgres::Pool> {
+    let mut config = options
+        .database_url
+        .parse::<tokio_postgres::Config>()
+        .context("invalid database url")?;
+    let session_options = options.session.options();
+    if !session_options.is_empty() {
+        let session_options = match config.get_options() {
+            Some(existing) => format!("{existing} {session_options}"),
+            None => session_options,
+        };
+        config.options(session_options);
+    }
+
+    let manager = Manager::from_config(
+        config,
+        tokio_postgres::NoTls,
+        ManagerConfig {
+            recycling_method: RecyclingMethod::Fast,
+        },
+    );
+    let (idle_timeout, max_lifetime) = (options.idle_timeout, options.max_lifetime);
+    Ok(deadpool_postgres::Pool::builder(manager)
+        .max_size(options.max_size as usize)
+        .runtime(Runtime::Tokio1)
+        .wait_timeout(Some(options.connection_timeout))
+        .create_timeout(Some(options.connection_timeout))
+        .pre_recycle(Hook::sync_fn(move |_, metrics| {
+            if max_lifetime.is_some_and(|lifetime| metrics.age() >= lifetime) {
+                return Err(HookError::message("connection exceeded its max lifetime"));
+            }
+            if idle_timeout.is_some_and(|timeout| metrics.last_used() >= timeout) {
+                return Err(HookError::message("connection exceeded its idle timeout"));
+            }
+            Ok(())
+        }))
+        .build()?)
+}
+
+/// Connection target and limits of one pool, taken from `database` and validated, since
+/// r2d2 panics on sizes it cannot honour.
+struct PoolOptions {
+    database_url: String,
+    max_size: u32,
+    min_idle: u32,
+    connection_timeout: Duration,
+    idle_timeout: Option<Duration>,
+    max_lifetime: Option<Duration>,
+    session: SessionSettings,
+}
+
+impl PoolOptions {
+    fn primary(settings: &Settings) -> anyhow::Result<Self> {
+        let database = &settings.database;
+        Self::new(
+            "database.pool",
+            database.database_url.clone(),
+            database.pool.max_size,
+            database.pool.min_idle,
+            settings,
+            false,
+        )
+    }
+
+    fn read(settings: &Settings) -> anyhow::Result<Option<Self>> {
+        let database = &settings.database;
+        if !database.read_pool.enabled {
+            return Ok(None);
+        }
+
+        let database_url = if database.read_pool.database_url.trim().is_empty() {
+            database.database_url.clone()
+        } else {
+            database.read_pool.database_url.clone()
+        };
+        Self::new(
+            "database.read_pool",
+            database_url,
+            database.read_pool.max_size,
+            database.read_pool.min_idle,
+            settings,
+            true,
+        )
+        .map(Some)
+    }
+
+    fn new(
+        section: &str,
+        database_url: String,
+        max_size: u32,
+        min_idle: u32,
+        settings: &Settings,
+        read_only: bool,
+    ) -> anyhow::Result<Self> {
+        let pool = &settings.database.pool;
+        if max_size == 0 {
+            bail!("{section}.max_size must be greater than 0");
+        }
+        if min_idle > max_size {
+            bail!("{section}.min_idle must not exceed {section}.max_size");
+        }
+        if pool.connection_timeout_secs == 0 {
+            bail!("database.pool.connection_timeout_secs must be greater than 0");
+        }
+
+        Ok(Self {
+            database_url,
+            max_size,
+            min_idle,
+            connection_timeout: Duration::from_secs(pool.connection_timeout_secs),
+            idle_timeout: non_zero_secs(pool.idle_timeout_secs),
+            max_lifetime: non_zero_secs(pool.max_lifetime_secs),
+            session: SessionSettings {
+                statement_timeout_ms: settings.database.statement_timeout_ms,
+                lock_timeout_ms: settings.database.lock_timeout_ms,
+                read_only,
+            },
+        })
+    }
+}
+
+fn non_zero_secs(secs: u64) -> Option<Duration> {
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// Session parameters set on every pooled connection. Zero timeouts are left to the
+/// server default.
+#[derive(Debug, Clone)]
+struct SessionSettings {
+    statement_timeout_ms: u64,
+    lock_timeout_ms: u64,
+    read_only: bool,
+}
+
+impl SessionSettings {
+    fn parameters(&self) -> Vec<(&'static str, String)> {
+        let mut parameters = Vec::new();
+        if self.statement_timeout_ms > 0 {
+            parameters.push(("statement_timeout", self.statement_timeout_ms.to_string()));
+        }
+        if self.lock_timeout_ms > 0 {
+            parameters.push(("lock_timeout", self.lock_timeout_ms.to_string()));
+        }
+        if self.read_only {
+            parameters.push(("default_transaction_read_only", "on".into()));
+        }
+        parameters
+    }
+
+    /// `SET` statements run by the `diesel` pool once a connection is opened.
+    fn statements(&self) -> String {
+        self.parameters()
+            .iter()
+            .map(|(name, value)| format!("SET {name} = {value};"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Startup `options` of the `tokio_postgres` pool, applied by the server on connect.
+    fn options(&self) -> String {
+        self.parameters()
+            .iter()
+            .map(|(name, value)| format!("-c {name}={value}"))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+impl CustomizeConnection<PgConnection, r2d2::Error> for SessionSettings {
+    fn on_acquire(&self, connection: &mut PgConnection) -> Result<(), r2d2::Error> {
+        let statements = self.statements();
+        if statements.is_empty() {
+            return Ok(());
+        }
+        connection
+            .batch_execute(&statements)
+            .map_err(r2d2::Error::QueryError)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn session(read_only: bool) -> SessionSettings {
+        SessionSettings {
+            statement_timeout_ms: 30000,
+            lock_timeout_ms: 5000,
+            read_only,
+        }
+    }
+
+    #[test]
+    fn session_settings_render_statements_and_startup_options() {
+        assert_eq!(
+            session(false).statements(),
+            "SET statement_timeout = 30000; SET lock_timeout = 5000;"
+        );
+        assert_eq!(
+            session(true).options(),
+            "-c statement_timeout=30000 -c lock_timeout=5000 -c default_transaction_read_only=on"
+        );
+    }
+
+    #[test]
+    fn session_settings_skip_zero_timeouts() {
+        let session = SessionSettings {
+            statement_timeout_ms: 0,
+            lock_timeout_ms: 0,
+            read_only: false,
+        };
+        assert!(session.statements().is_empty());
+        assert!(session.options().is_empty());
+    }
+
+    #[test]
+    fn read_pool_is_only_built_when_enabled() {
+        assert!(PoolOptions::read(&Settings::default()).unwrap().is_none());
+    }
+
+    #[test]
+    fn pool_options_reject_min_idle_above_max_size() {
+        let settings = Settings::default();
+        let error = PoolOptions::new("database.pool", String::new(), 2, 5, &settings, false)
+            .err()
+            .unwrap();
+        assert_eq!(
+            error.to_string(),
+            "database.pool.min_idle must not exceed database.pool.max_size"
+        );
+    }
 }
@@ -0,0 +1,130 @@
+use crate::{AsyncDbPool, DbPool, Error};
+use metrics::{counter, gauge, histogram};
+use std::future::Future;
+use std::time::{Duration, Instant};
+
+/// Connection pool whose occupancy is exported as the `db_pool_*` gauges.
+#[derive(Clone)]
+pub enum MonitoredPool {
+    Diesel(DbPool),
+    TokioPostgres(AsyncDbPool),
+}
+
+impl MonitoredPool {
+    /// Sets the size, idle and in-use gauges of the pool, labelled with `name`.
+    pub fn record(&self, name: &'static str) {
+        let (size, idle) = match self {
+            MonitoredPool::Diesel(pool) => {
+                let state = pool.state();
+                (state.connections as usize, state.idle_connections as usize)
+            }
+            MonitoredPool::TokioPostgres(pool) => {
+                let status = pool.status();
+                (status.size, status.available)
+            }
+        };
+
+        gauge!("db_pool_size", "pool" => name).set(size as f64);
+        gauge!("db_pool_idle_connections", "pool" => name).set(idle as f64);
+        gauge!("db_pool_in_use_connections", "pool" => name).set(size.saturating_sub(idle) as f64);
+    }
+}
+
+/// Records how long `repository` waited for a pooled connection.
+pub(crate) fn record_connection_wait(repository: &'static str, waited: Duration) {
+    histogram!("db_connection_wait_seconds", "repository" => repository)
+        .record(waited.as_secs_f64());
+}
+
+/// Records the latency of one repository method and counts its failure, if any, under
+/// the `infrastructure::Error` variant.
+pub(crate) fn record_query<T>(
+    repository: &'static str,
+    operation: &'static str,
+    elapsed: Duration,
+    result: &Result<T, Error>,
+) {
+    histogram!(
+        "db_query_duration_seconds",
+        "repository" => repository,
+        "operation" => operation
+    )
+    .record(elapsed.as_secs_f64());
+    if let Err(err) = result {
+        counter!(
+            "db_query_errors_total",
+            "repository" => repository,
+            "operation" => operation,
+            "error" => err.kind()
+        )
+        .increment(1);
+    }
+}
+
+/// Awaits `query` and records it like [`record_query`].
+pub(crate) async fn observe<T>(
+    repository: &'static str,
+    operation: &'static str,
+    query: impl Future<Output = Result<T, Error>>,
+) -> Result<T, Error> {
+    let started = Instant::now();
+    let result = query.await;
+    record_query(repository, operation, started.elapsed(), &result);
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use metrics_exporter_prometheus::PrometheusBuilder;
+    use uuid::Uuid;
+
+    #[test]
+    fn record_query_labels_latency_and_errors_by_operation_and_variant() {
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+
+        metrics::with_local_recorder(&recorder, || {
+            record_query::<()>("to_do_items", "get_all", Duration::from_millis(3), &Ok(()));
+            record_query::<()>(
+                "to_do_items",
+                "update",
+                Duration::from_millis(5),
+                &Err(Error::VersionConflict {
+                    id: Uuid::new_v4(),
+                    expected_version: 1,
+                    actual_version: 2,
+                }),
+            );
+        });
+        let rendered = handle.render();
+
+        assert!(rendered.contains(
+            r#"db_query_duration_seconds_count{repository="to_do_items",operation="get_all"} 1"#
+        ));
+        assert!(rendered.contains(
+            r#"db_query_errors_total{repository="to_do_items",operation="update",error="version_conflict"} 1"#
+        ));
+        assert!(!rendered.contains(r#"operation="get_all",error="#));
+    }
+
+    #[test]
+    fn record_query_keeps_shared_operation_names_apart_per_repository() {
+        let recorder = PrometheusBuilder::new().build_recorder();
+        let handle = recorder.handle();
+
+        metrics::with_local_recorder(&recorder, || {
+            record_query::<()>("to_do_items", "update", Duration::from_millis(2), &Ok(()));
+            record_query::<()>("unit_of_work", "update", Duration::from_millis(4), &Ok(()));
+            record_query::<()>("unit_of_work", "update", Duration::from_millis(6), &Ok(()));
+        });
+        let rendered = handle.render();
+
+        assert!(rendered.contains(
+            r#"db_query_duration_seconds_count{repository="to_do_items",operation="update"} 1"#
+        ));
+        assert!(rendered.contains(
+            r#"db_query_duration_seconds_count{repository="unit_of_work",operation="update"} 2"#
+        ));
+    }
+}
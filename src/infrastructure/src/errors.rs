@@ -18,6 +18,24 @@ pub enum Error {
     InternalError(String),
 }
 
+impl Error {
+    /// Snake-case name of the variant, used as the `error` label of `db_query_errors_total`.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::ItemNotFound { .. } => "item_not_found",
+            Error::VersionConflict { .. } => "version_conflict",
+            Error::InternalError(_) => "internal_error",
+        }
+    }
+}
+
+/// Lets Diesel transactions use this error type.
+impl From<diesel::result::Error> for Error {
+    fn from(err: diesel::result::Error) -> Self {
+        Error::InternalError(format!("database operation failed: {err}"))
+    }
+}
+
 impl From<Error> for ApplicationError {
     fn from(value: Error) -> Self {
         match value {
@@ -0,0 +1,103 @@
+use application::{ApplicationError, ApplicationResult, EventPublisher, OutboxMessage};
+use async_trait::async_trait;
+use std::path::PathBuf;
+use tokio::fs::OpenOptions;
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+
+/// Writes every outbox message to the application log.
+pub struct LogEventPublisher;
+
+#[async_trait]
+impl EventPublisher for LogEventPublisher {
+    async fn publish(&self, message: &OutboxMessage) -> ApplicationResult<()> {
+        let payload = serde_json::to_string(&message.payload).map_err(|err| {
+            ApplicationError::internal(format!("failed to serialize outbox message: {err}"))
+        })?;
+
+        tracing::info!(
+            message_id = %message.id,
+            aggregate_id = %message.aggregate_id,
+            event_type = %message.event_type,
+            payload = %payload,
+            "domain event published"
+        );
+        Ok(())
+    }
+}
+
+/// Appends every outbox message as one JSON line to a local file.
+pub struct NdjsonFileEventPublisher {
+    path: PathBuf,
+    write_lock: Mutex<()>,
+}
+
+impl NdjsonFileEventPublisher {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            write_lock: Mutex::new(()),
+        }
+    }
+}
+
+#[async_trait]
+impl EventPublisher for NdjsonFileEventPublisher {
+    async fn publish(&self, message: &OutboxMessage) -> ApplicationResult<()> {
+        let mut line = serde_json::to_vec(message).map_err(|err| {
+            ApplicationError::internal(format!("failed to serialize outbox message: {err}"))
+        })?;
+        line.push(b'\n');
+
+        let _guard = self.write_lock.lock().await;
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .map_err(|err| {
+                ApplicationError::internal(format!(
+                    "failed to open event file {}: {err}",
+                    self.path.display()
+                ))
+            })?;
+        file.write_all(&line).await.map_err(|err| {
+            ApplicationError::internal(format!(
+                "failed to write event file {}: {err}",
+                self.path.display()
+            ))
+        })?;
+        file.flush().await.map_err(|err| {
+            ApplicationError::internal(format!(
+                "failed to flush event file {}: {err}",
+                self.path.display()
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::ToDoItem;
+    use uuid::Uuid;
+
+    #[tokio::test]
+    async fn ndjson_publisher_appends_one_line_per_message() {
+        let path = std::env::temp_dir().join(format!("outbox-{}.ndjson", Uuid::new_v4()));
+        let publisher = NdjsonFileEventPublisher::new(&path);
+        let first = OutboxMessage::new(ToDoItem::new("a".into(), "b".into()).created_event());
+        let second = OutboxMessage::new(ToDoItem::new("c".into(), "d".into()).created_event());
+
+        publisher.publish(&first).await.expect("publish first");
+        publisher.publish(&second).await.expect("publish second");
+
+        let content = std::fs::read_to_string(&path).expect("read event file");
+        let _ = std::fs::remove_file(&path);
+        let lines: Vec<OutboxMessage> = content
+            .lines()
+            .map(|line| serde_json::from_str(line).expect("valid json line"))
+            .collect();
+        assert_eq!(lines, [first, second]);
+    }
+}
@@ -1,10 +1,29 @@
 mod config;
+mod db_metrics;
 mod errors;
+mod event_publishers;
+mod postgres_api_keys;
+mod postgres_idempotency;
+mod postgres_outbox;
 mod postgres_repositories;
+mod postgres_revisions;
+mod postgres_unit_of_work;
+mod tokio_postgres_outbox;
+mod tokio_postgres_repositories;
+mod tokio_postgres_revisions;
+mod tokio_postgres_unit_of_work;
 
 use diesel::{r2d2, PgConnection};
 pub type DbPool = r2d2::Pool<r2d2::ConnectionManager<PgConnection>>;
+pub type AsyncDbPool = deadpool_postgres::Pool;
 
-pub use config::configure;
+pub use config::{configure, configure_async_pool, configure_async_read_pool, configure_read_pool};
+pub use db_metrics::MonitoredPool;
 pub use errors::Error;
+pub use event_publishers::{LogEventPublisher, NdjsonFileEventPublisher};
+pub use postgres_api_keys::PostgresApiKeyRepository;
+pub use postgres_idempotency::PostgresIdempotencyRepository;
 pub use postgres_repositories::PostgresToDoItemRepository;
+pub use postgres_unit_of_work::PostgresUnitOfWorkFactory;
+pub use tokio_postgres_repositories::TokioPostgresToDoItemRepository;
+pub use tokio_postgres_unit_of_work::TokioPostgresUnitOfWorkFactory;
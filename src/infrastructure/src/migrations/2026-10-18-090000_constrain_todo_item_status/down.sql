@@ -0,0 +1,2 @@
+ALTER TABLE to_do_items
+DROP CONSTRAINT IF EXISTS "CK_ToDoItems_Status";
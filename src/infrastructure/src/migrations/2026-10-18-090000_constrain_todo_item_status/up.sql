@@ -0,0 +1,6 @@
+UPDATE to_do_items
+SET status = LOWER(TRIM(status))
+WHERE status <> LOWER(TRIM(status));
+
+ALTER TABLE to_do_items
+ADD CONSTRAINT "CK_ToDoItems_Status" CHECK (status IN ('pending', 'in_progress', 'done'));
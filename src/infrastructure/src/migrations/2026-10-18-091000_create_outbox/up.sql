@@ -0,0 +1,17 @@
+CREATE TABLE IF NOT EXISTS outbox (
+    "id" uuid NOT NULL,
+    "aggregate_id" uuid NOT NULL,
+    "event_type" varchar(64) NOT NULL,
+    "payload" jsonb NOT NULL,
+    "occurred_at" timestamptz NOT NULL,
+    "published_at" timestamptz NULL,
+    "attempts" integer NOT NULL DEFAULT 0,
+    "last_error" text NULL,
+    "next_attempt_at" timestamptz NULL,
+    "failed_at" timestamptz NULL,
+    CONSTRAINT "PK_Outbox" PRIMARY KEY ("id")
+);
+
+CREATE INDEX IF NOT EXISTS "IX_Outbox_Pending"
+ON outbox (occurred_at)
+WHERE published_at IS NULL AND failed_at IS NULL;
@@ -0,0 +1,3 @@
+ALTER TABLE to_do_items
+DROP COLUMN IF EXISTS restored_by,
+DROP COLUMN IF EXISTS restored_at;
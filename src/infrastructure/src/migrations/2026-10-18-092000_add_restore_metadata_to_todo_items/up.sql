@@ -0,0 +1,3 @@
+ALTER TABLE to_do_items
+ADD COLUMN IF NOT EXISTS restored_at TIMESTAMPTZ NULL,
+ADD COLUMN IF NOT EXISTS restored_by UUID NULL;
@@ -0,0 +1 @@
+DROP INDEX IF EXISTS "IX_ToDoItems_DeletedAt";
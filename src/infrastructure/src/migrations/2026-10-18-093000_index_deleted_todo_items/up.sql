@@ -0,0 +1,3 @@
+CREATE INDEX IF NOT EXISTS "IX_ToDoItems_DeletedAt"
+ON to_do_items (deleted_at)
+WHERE deleted_at IS NOT NULL;
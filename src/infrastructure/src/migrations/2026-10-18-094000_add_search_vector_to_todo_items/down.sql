@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS "IX_ToDoItems_SearchVector";
+
+ALTER TABLE to_do_items DROP COLUMN search_vector;
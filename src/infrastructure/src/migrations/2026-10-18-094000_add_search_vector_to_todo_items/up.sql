@@ -0,0 +1,8 @@
+-- Full-text search document: title matches rank above note matches.
+ALTER TABLE to_do_items
+    ADD COLUMN search_vector tsvector GENERATED ALWAYS AS (
+        setweight(to_tsvector('english', coalesce(title, '')), 'A') ||
+        setweight(to_tsvector('english', coalesce(note, '')), 'B')
+    ) STORED;
+
+CREATE INDEX "IX_ToDoItems_SearchVector" ON to_do_items USING GIN (search_vector);
@@ -0,0 +1,14 @@
+-- Keys are stored as <tenant>:<principal>:<key>, up to 64 + 1 + 36 + 1 + 255 characters.
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    "key" varchar(357) NOT NULL,
+    "fingerprint" varchar(64) NOT NULL,
+    "response_status" integer NULL,
+    "response_headers" jsonb NULL,
+    "response_body" bytea NULL,
+    "created_at" timestamptz NOT NULL,
+    "expires_at" timestamptz NOT NULL,
+    CONSTRAINT "PK_IdempotencyKeys" PRIMARY KEY ("key")
+);
+
+CREATE INDEX IF NOT EXISTS "IX_IdempotencyKeys_ExpiresAt"
+ON idempotency_keys (expires_at);
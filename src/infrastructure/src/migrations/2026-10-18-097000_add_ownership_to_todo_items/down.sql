@@ -0,0 +1,5 @@
+DROP INDEX IF EXISTS "IX_ToDoItems_OwnerId";
+
+ALTER TABLE to_do_items
+DROP COLUMN IF EXISTS updated_by,
+DROP COLUMN IF EXISTS owner_id;
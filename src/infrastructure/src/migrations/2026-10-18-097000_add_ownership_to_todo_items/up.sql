@@ -0,0 +1,7 @@
+ALTER TABLE to_do_items
+ADD COLUMN IF NOT EXISTS owner_id UUID NULL,
+ADD COLUMN IF NOT EXISTS updated_by UUID NULL;
+
+CREATE INDEX IF NOT EXISTS "IX_ToDoItems_OwnerId"
+ON to_do_items (owner_id)
+WHERE deleted_at IS NULL;
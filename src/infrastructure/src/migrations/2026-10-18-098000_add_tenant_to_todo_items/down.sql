@@ -0,0 +1,4 @@
+DROP INDEX IF EXISTS "IX_ToDoItems_TenantId_OwnerId";
+
+ALTER TABLE to_do_items
+DROP COLUMN IF EXISTS tenant_id;
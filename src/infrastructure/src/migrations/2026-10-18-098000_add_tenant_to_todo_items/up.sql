@@ -0,0 +1,6 @@
+ALTER TABLE to_do_items
+ADD COLUMN IF NOT EXISTS tenant_id varchar(64) NULL;
+
+CREATE INDEX IF NOT EXISTS "IX_ToDoItems_TenantId_OwnerId"
+ON to_do_items (tenant_id, owner_id)
+WHERE deleted_at IS NULL;
@@ -0,0 +1,3 @@
+DROP POLICY IF EXISTS "PL_ToDoItems_TenantIsolation" ON to_do_items;
+
+ALTER TABLE to_do_items DISABLE ROW LEVEL SECURITY;
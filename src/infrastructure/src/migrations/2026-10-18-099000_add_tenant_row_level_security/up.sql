@@ -0,0 +1,15 @@
+-- Rows are limited to the tenant bound to the transaction through app.tenant_id.
+-- The retention purge, which works across tenants, binds app.all_tenants = 'on' instead; a
+-- session that binds neither sees no rows. Table owners and superusers bypass these policies, so
+-- they only apply to a dedicated application role.
+ALTER TABLE to_do_items ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "PL_ToDoItems_TenantIsolation" ON to_do_items
+USING (
+    tenant_id = NULLIF(current_setting('app.tenant_id', true), '')
+    OR current_setting('app.all_tenants', true) = 'on'
+)
+WITH CHECK (
+    tenant_id = NULLIF(current_setting('app.tenant_id', true), '')
+    OR current_setting('app.all_tenants', true) = 'on'
+);
@@ -0,0 +1,13 @@
+CREATE TABLE IF NOT EXISTS api_keys (
+    "id" uuid NOT NULL,
+    "name" varchar(100) NOT NULL,
+    "prefix" varchar(16) NOT NULL,
+    "key_hash" varchar(64) NOT NULL,
+    "scopes" text[] NOT NULL DEFAULT '{}',
+    "created_at" timestamptz NOT NULL,
+    "expires_at" timestamptz NULL,
+    "last_used_at" timestamptz NULL,
+    "revoked_at" timestamptz NULL,
+    CONSTRAINT "PK_ApiKeys" PRIMARY KEY ("id"),
+    CONSTRAINT "UQ_ApiKeys_KeyHash" UNIQUE ("key_hash")
+);
@@ -0,0 +1 @@
+DROP TABLE IF EXISTS to_do_item_revisions;
@@ -0,0 +1,34 @@
+-- One row per change of a to-do item, keyed for clients by the item version the change
+-- produced. Rows are never updated; purging an item removes its history with it.
+CREATE TABLE IF NOT EXISTS to_do_item_revisions (
+    "item_id" uuid NOT NULL,
+    "revision" integer NOT NULL,
+    "version" integer NOT NULL,
+    "change_type" varchar(16) NOT NULL,
+    "snapshot" jsonb NOT NULL,
+    "actor" uuid NULL,
+    "request_id" varchar(200) NULL,
+    "owner_id" uuid NULL,
+    "tenant_id" varchar(64) NULL,
+    "recorded_at" timestamptz NOT NULL,
+    CONSTRAINT "PK_ToDoItemRevisions" PRIMARY KEY ("item_id", "revision"),
+    CONSTRAINT "UQ_ToDoItemRevisions_Version" UNIQUE ("item_id", "version"),
+    CONSTRAINT "FK_ToDoItemRevisions_ToDoItems" FOREIGN KEY ("item_id")
+        REFERENCES to_do_items ("id") ON DELETE CASCADE,
+    CONSTRAINT "CK_ToDoItemRevisions_ChangeType"
+        CHECK ("change_type" IN ('created', 'updated', 'deleted', 'restored'))
+);
+
+-- Same tenant isolation as "PL_ToDoItems_TenantIsolation": a transaction sees the rows of
+-- the tenant it binds, or every row with app.all_tenants = 'on', and nothing otherwise.
+ALTER TABLE to_do_item_revisions ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY "PL_ToDoItemRevisions_TenantIsolation" ON to_do_item_revisions
+USING (
+    tenant_id = NULLIF(current_setting('app.tenant_id', true), '')
+    OR current_setting('app.all_tenants', true) = 'on'
+)
+WITH CHECK (
+    tenant_id = NULLIF(current_setting('app.tenant_id', true), '')
+    OR current_setting('app.all_tenants', true) = 'on'
+);
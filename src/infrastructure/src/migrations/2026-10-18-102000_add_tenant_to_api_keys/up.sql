@@ -0,0 +1,2 @@
+ALTER TABLE api_keys
+ADD COLUMN IF NOT EXISTS tenant_id varchar(64) NULL;
@@ -0,0 +1,161 @@
+use crate::db_metrics::{record_connection_wait, record_query};
+use crate::postgres_repositories::map_diesel_error;
+use crate::DbPool;
+use actix_web::web::Data;
+use application::{ApiKey, ApiKeyRepository, ApplicationError, ApplicationResult};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use diesel::{
+    ExpressionMethods, Insertable, OptionalExtension, PgConnection, QueryDsl, Queryable,
+    RunQueryDsl,
+};
+use domain::api_keys::dsl::{
+    api_keys, created_at, id as key_id, key_hash, last_used_at, revoked_at,
+};
+use std::time::Instant;
+use tokio::task;
+use uuid::Uuid;
+
+const REPOSITORY: &str = "api_keys";
+
+pub struct PostgresApiKeyRepository {
+    pool: Data<DbPool>,
+}
+
+#[derive(Queryable, Insertable)]
+#[diesel(table_name = domain::api_keys)]
+struct DbApiKey {
+    id: Uuid,
+    name: String,
+    prefix: String,
+    key_hash: String,
+    scopes: Vec<String>,
+    created_at: DateTime<Utc>,
+    expires_at: Option<DateTime<Utc>>,
+    last_used_at: Option<DateTime<Utc>>,
+    revoked_at: Option<DateTime<Utc>>,
+    tenant_id: Option<String>,
+}
+
+impl From<DbApiKey> for ApiKey {
+    fn from(row: DbApiKey) -> Self {
+        ApiKey {
+            id: row.id,
+            name: row.name,
+            prefix: row.prefix,
+            key_hash: row.key_hash,
+            scopes: row.scopes,
+            tenant_id: row.tenant_id,
+            created_at: row.created_at,
+            expires_at: row.expires_at,
+            last_used_at: row.last_used_at,
+            revoked_at: row.revoked_at,
+        }
+    }
+}
+
+impl From<ApiKey> for DbApiKey {
+    fn from(key: ApiKey) -> Self {
+        DbApiKey {
+            id: key.id,
+            name: key.name,
+            prefix: key.prefix,
+            key_hash: key.key_hash,
+            scopes: key.scopes,
+            created_at: key.created_at,
+            expires_at: key.expires_at,
+            last_used_at: key.last_used_at,
+            revoked_at: key.revoked_at,
+            tenant_id: key.tenant_id,
+        }
+    }
+}
+
+impl PostgresApiKeyRepository {
+    pub fn new(pool: &Data<DbPool>) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    async fn run_db<T, F>(&self, operation: &'static str, query: F) -> ApplicationResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut PgConnection) -> std::result::Result<T, crate::Error> + Send + 'static,
+    {
+        let pool = self.pool.clone();
+
+        task::spawn_blocking(move || {
+            let waiting = Instant::now();
+            let connection = pool.get();
+            record_connection_wait(REPOSITORY, waiting.elapsed());
+            let mut connection = connection.map_err(|err| {
+                ApplicationError::internal(format!("failed to acquire database connection: {err}"))
+            })?;
+            let started = Instant::now();
+            let result = query(&mut connection);
+            record_query(REPOSITORY, operation, started.elapsed(), &result);
+            result.map_err(ApplicationError::from)
+        })
+        .await
+        .map_err(|err| ApplicationError::internal(format!("database task join failure: {err}")))?
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for PostgresApiKeyRepository {
+    async fn create(&self, key: ApiKey) -> ApplicationResult<()> {
+        self.run_db("create", move |connection| {
+            diesel::insert_into(api_keys)
+                .values(DbApiKey::from(key))
+                .execute(connection)
+                .map_err(map_diesel_error)?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn find_by_hash(&self, hash: &str) -> ApplicationResult<Option<ApiKey>> {
+        let hash = hash.to_string();
+        self.run_db("find_by_hash", move |connection| {
+            api_keys
+                .filter(key_hash.eq(hash))
+                .first::<DbApiKey>(connection)
+                .optional()
+                .map(|row| row.map(ApiKey::from))
+                .map_err(map_diesel_error)
+        })
+        .await
+    }
+
+    async fn list(&self) -> ApplicationResult<Vec<ApiKey>> {
+        self.run_db("list", move |connection| {
+            api_keys
+                .order(created_at.desc())
+                .load::<DbApiKey>(connection)
+                .map(|rows| rows.into_iter().map(ApiKey::from).collect())
+                .map_err(map_diesel_error)
+        })
+        .await
+    }
+
+    async fn revoke(&self, id: Uuid, now: DateTime<Utc>) -> ApplicationResult<bool> {
+        self.run_db("revoke", move |connection| {
+            diesel::update(api_keys.filter(key_id.eq(id)).filter(revoked_at.is_null()))
+                .set(revoked_at.eq(Some(now)))
+                .execute(connection)
+                .map(|updated| updated > 0)
+                .map_err(map_diesel_error)
+        })
+        .await
+    }
+
+    async fn record_use(&self, id: Uuid, used_at: DateTime<Utc>) -> ApplicationResult<()> {
+        self.run_db("record_use", move |connection| {
+            diesel::update(api_keys.filter(key_id.eq(id)))
+                .set(last_used_at.eq(Some(used_at)))
+                .execute(connection)
+                .map_err(map_diesel_error)?;
+            Ok(())
+        })
+        .await
+    }
+}
@@ -0,0 +1,214 @@
+use crate::db_metrics::{record_connection_wait, record_query};
+use crate::errors::Error::InternalError;
+use crate::postgres_repositories::map_diesel_error;
+use crate::DbPool;
+use actix_web::web::Data;
+use application::{
+    ApplicationError, ApplicationResult, IdempotencyRecord, IdempotencyRepository,
+    IdempotentResponse,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use diesel::upsert::excluded;
+use diesel::{
+    ExpressionMethods, Insertable, OptionalExtension, PgConnection, QueryDsl, QueryResult,
+    Queryable, RunQueryDsl,
+};
+use domain::idempotency_keys::dsl::{
+    created_at, expires_at, fingerprint, idempotency_keys, key as idempotency_key, response_body,
+    response_headers, response_status,
+};
+use std::time::Instant;
+use tokio::task;
+
+const REPOSITORY: &str = "idempotency";
+
+pub struct PostgresIdempotencyRepository {
+    pool: Data<DbPool>,
+}
+
+#[derive(Queryable)]
+struct DbIdempotencyKey {
+    key: String,
+    fingerprint: String,
+    response_status: Option<i32>,
+    response_headers: Option<serde_json::Value>,
+    response_body: Option<Vec<u8>>,
+    _created_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[diesel(table_name = domain::idempotency_keys)]
+struct NewDbIdempotencyKey {
+    key: String,
+    fingerprint: String,
+    created_at: DateTime<Utc>,
+    expires_at: DateTime<Utc>,
+}
+
+impl TryFrom<DbIdempotencyKey> for IdempotencyRecord {
+    type Error = crate::Error;
+
+    fn try_from(row: DbIdempotencyKey) -> Result<Self, Self::Error> {
+        let response = match row.response_status {
+            None => None,
+            Some(status) => Some(IdempotentResponse {
+                status: u16::try_from(status).map_err(|_| {
+                    InternalError(format!(
+                        "idempotency key {} has an invalid status {status}",
+                        row.key
+                    ))
+                })?,
+                headers: row
+                    .response_headers
+                    .map(serde_json::from_value)
+                    .transpose()
+                    .map_err(|err| {
+                        InternalError(format!(
+                            "idempotency key {} has invalid headers: {err}",
+                            row.key
+                        ))
+                    })?
+                    .unwrap_or_default(),
+                body: row.response_body.unwrap_or_default(),
+            }),
+        };
+
+        Ok(IdempotencyRecord {
+            key: row.key,
+            fingerprint: row.fingerprint,
+            response,
+            expires_at: row.expires_at,
+        })
+    }
+}
+
+impl PostgresIdempotencyRepository {
+    pub fn new(pool: &Data<DbPool>) -> Self {
+        Self { pool: pool.clone() }
+    }
+
+    async fn run_db<T, F>(&self, operation: &'static str, query: F) -> ApplicationResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut PgConnection) -> std::result::Result<T, crate::Error> + Send + 'static,
+    {
+        let pool = self.pool.clone();
+
+        task::spawn_blocking(move || {
+            let waiting = Instant::now();
+            let connection = pool.get();
+            record_connection_wait(REPOSITORY, waiting.elapsed());
+            let mut connection = connection.map_err(|err| {
+                ApplicationError::internal(format!("failed to acquire database connection: {err}"))
+            })?;
+            let started = Instant::now();
+            let result = query(&mut connection);
+            record_query(REPOSITORY, operation, started.elapsed(), &result);
+            result.map_err(ApplicationError::from)
+        })
+        .await
+        .map_err(|err| ApplicationError::internal(format!("database task join failure: {err}")))?
+    }
+}
+
+#[async_trait]
+impl IdempotencyRepository for PostgresIdempotencyRepository {
+    async fn reserve(
+        &self,
+        record: IdempotencyRecord,
+    ) -> ApplicationResult<Option<IdempotencyRecord>> {
+        self.run_db("reserve", move |connection| reserve_key(connection, record))
+            .await
+    }
+
+    async fn complete(&self, key: &str, response: IdempotentResponse) -> ApplicationResult<()> {
+        let key = key.to_string();
+        self.run_db("complete", move |connection| {
+            let headers = serde_json::to_value(&response.headers).map_err(|err| {
+                InternalError(format!("failed to serialize response headers: {err}"))
+            })?;
+            diesel::update(idempotency_keys.filter(idempotency_key.eq(key)))
+                .set((
+                    response_status.eq(Some(i32::from(response.status))),
+                    response_headers.eq(Some(headers)),
+                    response_body.eq(Some(response.body)),
+                ))
+                .execute(connection)
+                .map_err(map_diesel_error)?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn release(&self, key: &str) -> ApplicationResult<()> {
+        let key = key.to_string();
+        self.run_db("release", move |connection| {
+            diesel::delete(idempotency_keys.filter(idempotency_key.eq(key)))
+                .execute(connection)
+                .map_err(map_diesel_error)?;
+            Ok(())
+        })
+        .await
+    }
+
+    async fn purge_expired(&self, now: DateTime<Utc>) -> ApplicationResult<usize> {
+        self.run_db("purge_expired", move |connection| {
+            diesel::delete(idempotency_keys.filter(expires_at.le(now)))
+                .execute(connection)
+                .map_err(map_diesel_error)
+        })
+        .await
+    }
+}
+
+/// Inserts the key, or takes over an expired row in the same statement. When neither
+/// happens the live row belongs to another request and is returned.
+fn reserve_key(
+    connection: &mut PgConnection,
+    record: IdempotencyRecord,
+) -> std::result::Result<Option<IdempotencyRecord>, crate::Error> {
+    let record_key = record.key.clone();
+    if claim_key(connection, record).map_err(map_diesel_error)? {
+        return Ok(None);
+    }
+
+    idempotency_keys
+        .filter(idempotency_key.eq(record_key))
+        .first::<DbIdempotencyKey>(connection)
+        .optional()
+        .map_err(map_diesel_error)?
+        .map(IdempotencyRecord::try_from)
+        .transpose()
+}
+
+fn claim_key(connection: &mut PgConnection, record: IdempotencyRecord) -> QueryResult<bool> {
+    // `QueryDsl` also provides `filter`, but only `FilterDsl` covers `ON CONFLICT ... WHERE`.
+    use diesel::query_dsl::methods::FilterDsl;
+
+    let now = Utc::now();
+    let claimed = diesel::insert_into(idempotency_keys)
+        .values(NewDbIdempotencyKey {
+            key: record.key,
+            fingerprint: record.fingerprint,
+            created_at: now,
+            expires_at: record.expires_at,
+        })
+        .on_conflict(idempotency_key)
+        .do_update()
+        .set((
+            fingerprint.eq(excluded(fingerprint)),
+            response_status.eq(None::<i32>),
+            response_headers.eq(None::<serde_json::Value>),
+            response_body.eq(None::<Vec<u8>>),
+            created_at.eq(excluded(created_at)),
+            expires_at.eq(excluded(expires_at)),
+        ))
+        .filter(expires_at.le(now))
+        .returning(idempotency_key)
+        .get_result::<String>(connection)
+        .optional()?;
+
+    Ok(claimed.is_some())
+}
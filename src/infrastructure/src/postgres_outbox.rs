@@ -0,0 +1,210 @@
+use crate::errors::Error::InternalError;
+use crate::postgres_repositories::map_diesel_error;
+use application::OutboxMessage;
+use chrono::{DateTime, Utc};
+use diesel::dsl::{now, sql};
+use diesel::sql_types::Bool;
+use diesel::{
+    BoolExpressionMethods, ExpressionMethods, Insertable, PgConnection, QueryDsl, Queryable,
+    RunQueryDsl,
+};
+use domain::outbox::dsl::{
+    aggregate_id as outbox_aggregate_id, attempts as outbox_attempts,
+    failed_at as outbox_failed_at, id as outbox_id, last_error as outbox_last_error,
+    next_attempt_at as outbox_next_attempt_at, occurred_at, outbox,
+    published_at as outbox_published_at,
+};
+use domain::ToDoItemEvent;
+use uuid::Uuid;
+
+#[derive(Queryable)]
+pub(crate) struct DbOutboxMessage {
+    pub(crate) id: Uuid,
+    pub(crate) aggregate_id: Uuid,
+    pub(crate) event_type: String,
+    pub(crate) payload: serde_json::Value,
+    pub(crate) occurred_at: DateTime<Utc>,
+    pub(crate) _published_at: Option<DateTime<Utc>>,
+    pub(crate) attempts: i32,
+    pub(crate) _last_error: Option<String>,
+    pub(crate) _next_attempt_at: Option<DateTime<Utc>>,
+    pub(crate) _failed_at: Option<DateTime<Utc>>,
+}
+
+/// Keeps a message back while an earlier message of the same item waits for its next
+/// attempt, so consumers still see the events of one item in order.
+pub(crate) const NO_EARLIER_MESSAGE_AWAITING_RETRY: &str = "NOT EXISTS (\
+    SELECT 1 FROM outbox AS earlier \
+    WHERE earlier.aggregate_id = outbox.aggregate_id \
+    AND earlier.published_at IS NULL AND earlier.failed_at IS NULL \
+    AND earlier.next_attempt_at > now() \
+    AND (earlier.occurred_at, earlier.id) < (outbox.occurred_at, outbox.id))";
+
+#[derive(Insertable)]
+#[diesel(table_name = domain::outbox)]
+pub(crate) struct NewDbOutboxMessage {
+    pub(crate) id: Uuid,
+    pub(crate) aggregate_id: Uuid,
+    pub(crate) event_type: String,
+    pub(crate) payload: serde_json::Value,
+    pub(crate) occurred_at: DateTime<Utc>,
+}
+
+impl TryFrom<DbOutboxMessage> for OutboxMessage {
+    type Error = crate::Error;
+
+    fn try_from(message: DbOutboxMessage) -> Result<Self, Self::Error> {
+        let payload = serde_json::from_value(message.payload).map_err(|err| {
+            InternalError(format!(
+                "outbox message {} has an invalid payload: {err}",
+                message.id
+            ))
+        })?;
+
+        Ok(OutboxMessage {
+            id: message.id,
+            aggregate_id: message.aggregate_id,
+            event_type: message.event_type,
+            occurred_at: message.occurred_at,
+            attempts: message.attempts,
+            payload,
+        })
+    }
+}
+
+impl TryFrom<ToDoItemEvent> for NewDbOutboxMessage {
+    type Error = crate::Error;
+
+    fn try_from(event: ToDoItemEvent) -> Result<Self, Self::Error> {
+        let message = OutboxMessage::new(event);
+        let payload = serde_json::to_value(&message.payload)
+            .map_err(|err| InternalError(format!("failed to serialize outbox message: {err}")))?;
+
+        Ok(Self {
+            id: message.id,
+            aggregate_id: message.aggregate_id,
+            event_type: message.event_type,
+            payload,
+            occurred_at: message.occurred_at,
+        })
+    }
+}
+
+pub(crate) fn append_messages(
+    connection: &mut PgConnection,
+    events: Vec<ToDoItemEvent>,
+) -> std::result::Result<(), crate::Error> {
+    if events.is_empty() {
+        return Ok(());
+    }
+
+    let messages = events
+        .into_iter()
+        .map(NewDbOutboxMessage::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+    diesel::insert_into(outbox)
+        .values(&messages)
+        .execute(connection)
+        .map_err(map_diesel_error)?;
+    Ok(())
+}
+
+pub(crate) fn fetch_pending_messages(
+    connection: &mut PgConnection,
+    limit: i64,
+) -> std::result::Result<Vec<OutboxMessage>, crate::Error> {
+    outbox
+        .filter(outbox_published_at.is_null())
+        .filter(outbox_failed_at.is_null())
+        .filter(
+            outbox_next_attempt_at
+                .is_null()
+                .or(outbox_next_attempt_at.le(now)),
+        )
+        .filter(sql::<Bool>(NO_EARLIER_MESSAGE_AWAITING_RETRY))
+        .order((occurred_at.asc(), outbox_id.asc()))
+        .limit(limit)
+        .for_update()
+        .skip_locked()
+        .load::<DbOutboxMessage>(connection)
+        .map_err(map_diesel_error)?
+        .into_iter()
+        .map(OutboxMessage::try_from)
+        .collect()
+}
+
+pub(crate) fn mark_message_published(
+    connection: &mut PgConnection,
+    message_id: Uuid,
+) -> std::result::Result<(), crate::Error> {
+    diesel::update(outbox.filter(outbox_id.eq(message_id)))
+        .set((
+            outbox_published_at.eq(Some(Utc::now())),
+            outbox_attempts.eq(outbox_attempts + 1),
+            outbox_last_error.eq(None::<String>),
+        ))
+        .execute(connection)
+        .map_err(map_diesel_error)?;
+    Ok(())
+}
+
+/// Records a failed publish attempt. Without `retry_at` the message is given up on and
+/// moved to the failed state.
+pub(crate) fn mark_message_failed(
+    connection: &mut PgConnection,
+    message_id: Uuid,
+    error: String,
+    retry_at: Option<DateTime<Utc>>,
+) -> std::result::Result<(), crate::Error> {
+    let failed_at = retry_at.is_none().then(Utc::now);
+    diesel::update(outbox.filter(outbox_id.eq(message_id)))
+        .set((
+            outbox_attempts.eq(outbox_attempts + 1),
+            outbox_last_error.eq(Some(error)),
+            outbox_next_attempt_at.eq(retry_at),
+            outbox_failed_at.eq(failed_at),
+        ))
+        .execute(connection)
+        .map_err(map_diesel_error)?;
+    Ok(())
+}
+
+pub(crate) fn delete_messages_of(
+    connection: &mut PgConnection,
+    aggregate_ids: &[Uuid],
+) -> std::result::Result<(), crate::Error> {
+    diesel::delete(outbox.filter(outbox_aggregate_id.eq_any(aggregate_ids)))
+        .execute(connection)
+        .map_err(map_diesel_error)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::ToDoItem;
+
+    #[test]
+    fn outbox_row_round_trips_event_payload() {
+        let event = ToDoItem::new("title".into(), "note".into()).created_event();
+
+        let row = NewDbOutboxMessage::try_from(event.clone()).expect("serialize event");
+        let message = OutboxMessage::try_from(DbOutboxMessage {
+            id: row.id,
+            aggregate_id: row.aggregate_id,
+            event_type: row.event_type,
+            payload: row.payload,
+            occurred_at: row.occurred_at,
+            _published_at: None,
+            attempts: 0,
+            _last_error: None,
+            _next_attempt_at: None,
+            _failed_at: None,
+        })
+        .expect("deserialize event");
+
+        assert_eq!(message.event_type, "to_do_item.created");
+        assert_eq!(message.aggregate_id, event.aggregate_id());
+        assert_eq!(message.payload, event);
+    }
+}
@@ -1,29 +1,46 @@
-use crate::errors::Error::{InternalError, ItemNotFound, VersionConflict};
+use crate::db_metrics::{record_connection_wait, record_query};
+use crate::errors::Error::{ItemNotFound, VersionConflict};
+use crate::postgres_revisions::{find_revision, load_history, load_revision_range};
 use crate::DbPool;
 use actix_web::web::Data;
 use application::{
-    ApplicationError, ApplicationResult, GetAllToDoItemsQuery, PaginatedResult, SortDirection,
-    ToDoItemCommandRepository, ToDoItemQueryRepository, ToDoItemSortField,
+    AccessScope, ApplicationError, ApplicationResult, DeletedToDoItemFilter, GetAllToDoItemsQuery,
+    GetDeletedToDoItemsForAuditQuery, KeysetPage, NullsOrder, PaginatedResult, SearchMode,
+    SortDirection, ToDoItemCommandRepository, ToDoItemCursor, ToDoItemFilter,
+    ToDoItemQueryRepository, ToDoItemRevision, ToDoItemSort, ToDoItemSortField, ToDoItemSortKey,
+    ToDoItemSortValue,
 };
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
-use diesel::dsl::count_star;
+use diesel::dsl::{count_star, sql};
+use diesel::expression::{BoxableExpression, SqlLiteral};
 use diesel::pg::Pg;
 use diesel::prelude::BoolExpressionMethods;
+use diesel::sql_types::{Bool, Float, Integer, Nullable, Text};
 use diesel::ExpressionMethods;
-use diesel::PgTextExpressionMethods;
-use diesel::{Insertable, OptionalExtension, PgConnection, QueryDsl, Queryable, RunQueryDsl};
+use diesel::{
+    Connection, Insertable, OptionalExtension, PgConnection, QueryDsl, QueryResult, Queryable,
+    RunQueryDsl,
+};
+use diesel::{NullableExpressionMethods, PgSortExpressionMethods};
 use domain::to_do_items::dsl::{
-    deleted_at as item_deleted_at, deleted_by as item_deleted_by, due_at as item_due_at,
-    id as item_id, note as item_note, status as item_status, title as item_title, to_do_items,
-    updated_at as item_updated_at, version as item_version,
+    created_at as item_created_at, deleted_at as item_deleted_at, deleted_by as item_deleted_by,
+    due_at as item_due_at, id as item_id, note as item_note, owner_id as item_owner_id,
+    restored_at as item_restored_at, restored_by as item_restored_by, status as item_status,
+    tenant_id as item_tenant_id, title as item_title, to_do_items, updated_at as item_updated_at,
+    updated_by as item_updated_by, version as item_version,
 };
-use domain::ToDoItem;
+use domain::{ToDoItem, ToDoItemStatus};
+use std::collections::HashMap;
+use std::time::Instant;
 use tokio::task;
 use uuid::Uuid;
 
+const REPOSITORY: &str = "to_do_items";
+
 pub struct PostgresToDoItemRepository {
     pool: Data<DbPool>,
+    row_level_security: bool,
 }
 
 #[derive(Queryable)]
@@ -31,13 +48,18 @@ struct DbToDoItem {
     id: Uuid,
     title: Option<String>,
     note: Option<String>,
-    status: String,
+    status: ToDoItemStatus,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
     due_at: Option<DateTime<Utc>>,
     version: i32,
     deleted_at: Option<DateTime<Utc>>,
     deleted_by: Option<Uuid>,
+    restored_at: Option<DateTime<Utc>>,
+    restored_by: Option<Uuid>,
+    owner_id: Option<Uuid>,
+    updated_by: Option<Uuid>,
+    tenant_id: Option<String>,
 }
 
 #[derive(Insertable)]
@@ -46,13 +68,18 @@ struct NewDbToDoItem {
     id: Uuid,
     title: Option<String>,
     note: Option<String>,
-    status: String,
+    status: ToDoItemStatus,
     created_at: DateTime<Utc>,
     updated_at: DateTime<Utc>,
     due_at: Option<DateTime<Utc>>,
     version: i32,
     deleted_at: Option<DateTime<Utc>>,
     deleted_by: Option<Uuid>,
+    restored_at: Option<DateTime<Utc>>,
+    restored_by: Option<Uuid>,
+    owner_id: Option<Uuid>,
+    updated_by: Option<Uuid>,
+    tenant_id: Option<String>,
 }
 
 impl From<DbToDoItem> for ToDoItem {
@@ -68,6 +95,11 @@ impl From<DbToDoItem> for ToDoItem {
             version: item.version,
             deleted_at: item.deleted_at,
             deleted_by: item.deleted_by,
+            restored_at: item.restored_at,
+            restored_by: item.restored_by,
+            owner_id: item.owner_id,
+            updated_by: item.updated_by,
+            tenant_id: item.tenant_id,
         }
     }
 }
@@ -78,23 +110,93 @@ impl From<&ToDoItem> for NewDbToDoItem {
             id: item.id,
             title: item.title.clone(),
             note: item.note.clone(),
-            status: item.status.clone(),
+            status: item.status,
             created_at: item.created_at,
             updated_at: item.updated_at,
             due_at: item.due_at,
             version: item.version,
             deleted_at: item.deleted_at,
             deleted_by: item.deleted_by,
+            restored_at: item.restored_at,
+            restored_by: item.restored_by,
+            owner_id: item.owner_id,
+            updated_by: item.updated_by,
+            tenant_id: item.tenant_id.clone(),
         }
     }
 }
 
 impl PostgresToDoItemRepository {
     pub fn new(pool: &Data<DbPool>) -> Self {
-        Self { pool: pool.clone() }
+        Self {
+            pool: pool.clone(),
+            row_level_security: false,
+        }
     }
 
-    async fn run_db<T, F>(&self, operation: F) -> ApplicationResult<T>
+    /// Binds every operation to its tenant before running it, for the row-level security
+    /// policies on `to_do_items`. Operations run in a transaction either way, which the
+    /// binding does not outlive on a pooled connection.
+    pub fn with_row_level_security(mut self, enabled: bool) -> Self {
+        self.row_level_security = enabled;
+        self
+    }
+
+    async fn run_in_tenant<T, F>(
+        &self,
+        operation: &'static str,
+        tenant_id: Option<String>,
+        query: F,
+    ) -> ApplicationResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut PgConnection) -> std::result::Result<T, crate::Error> + Send + 'static,
+    {
+        self.run_bound(
+            operation,
+            move |connection| bind_tenant(connection, tenant_id.as_deref()),
+            query,
+        )
+        .await
+    }
+
+    /// Like [`Self::run_in_tenant`] for the rows of every tenant, see [`bind_all_tenants`].
+    async fn run_across_tenants<T, F>(
+        &self,
+        operation: &'static str,
+        query: F,
+    ) -> ApplicationResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut PgConnection) -> std::result::Result<T, crate::Error> + Send + 'static,
+    {
+        self.run_bound(operation, bind_all_tenants, query).await
+    }
+
+    async fn run_bound<T, B, F>(
+        &self,
+        operation: &'static str,
+        bind: B,
+        query: F,
+    ) -> ApplicationResult<T>
+    where
+        T: Send + 'static,
+        B: FnOnce(&mut PgConnection) -> std::result::Result<(), crate::Error> + Send + 'static,
+        F: FnOnce(&mut PgConnection) -> std::result::Result<T, crate::Error> + Send + 'static,
+    {
+        let row_level_security = self.row_level_security;
+        self.run_db(operation, move |connection| {
+            connection.transaction(|connection| {
+                if row_level_security {
+                    bind(connection)?;
+                }
+                query(connection)
+            })
+        })
+        .await
+    }
+
+    async fn run_db<T, F>(&self, operation: &'static str, query: F) -> ApplicationResult<T>
     where
         T: Send + 'static,
         F: FnOnce(&mut PgConnection) -> std::result::Result<T, crate::Error> + Send + 'static,
@@ -102,10 +204,16 @@ impl PostgresToDoItemRepository {
         let pool = self.pool.clone();
 
         task::spawn_blocking(move || {
-            let mut connection = pool.get().map_err(|err| {
+            let waiting = Instant::now();
+            let connection = pool.get();
+            record_connection_wait(REPOSITORY, waiting.elapsed());
+            let mut connection = connection.map_err(|err| {
                 ApplicationError::internal(format!("failed to acquire database connection: {err}"))
             })?;
-            operation(&mut connection).map_err(ApplicationError::from)
+            let started = Instant::now();
+            let result = query(&mut connection);
+            record_query(REPOSITORY, operation, started.elapsed(), &result);
+            result.map_err(ApplicationError::from)
         })
         .await
         .map_err(|err| ApplicationError::internal(format!("database task join failure: {err}")))?
@@ -118,170 +226,953 @@ impl ToDoItemQueryRepository for PostgresToDoItemRepository {
         &self,
         query: GetAllToDoItemsQuery,
     ) -> ApplicationResult<PaginatedResult<ToDoItem>> {
-        self.run_db(move |connection| {
-            let total_items = build_filtered_query(query.search.as_deref())
-                .select(count_star())
-                .first::<i64>(connection)
-                .map_err(map_diesel_error)?;
+        self.run_in_tenant(
+            "get_all",
+            query.scope.tenant_id.clone(),
+            move |connection| {
+                let result = match &query.keyset {
+                    Some(keyset) => load_keyset_page(connection, &query, keyset)?,
+                    None => load_offset_page(connection, &query)?,
+                };
 
-            let items = apply_sort(build_filtered_query(query.search.as_deref()), &query)
-                .offset(query.offset())
-                .limit(query.limit())
-                .load::<DbToDoItem>(connection)
-                .map_err(map_diesel_error)?
-                .into_iter()
-                .map(ToDoItem::from)
-                .collect();
-
-            Ok(PaginatedResult::new(
-                items,
-                query.page,
-                query.page_size,
-                total_items,
-            ))
+                match query.search.as_deref() {
+                    Some(search) => {
+                        let highlights =
+                            load_highlights(connection, &result.items, search, query.search_mode)?;
+                        Ok(result.with_highlights(highlights))
+                    }
+                    None => Ok(result),
+                }
+            },
+        )
+        .await
+    }
+
+    async fn get_by_id(
+        &self,
+        todo_item_id: Uuid,
+        scope: AccessScope,
+    ) -> ApplicationResult<ToDoItem> {
+        self.run_in_tenant("get_by_id", scope.tenant_id.clone(), move |connection| {
+            find_active_by_id(connection, todo_item_id, &scope)
         })
         .await
     }
 
-    async fn get_by_id(&self, todo_item_id: Uuid) -> ApplicationResult<ToDoItem> {
-        self.run_db(move |connection| {
-            to_do_items
-                .filter(item_id.eq(&todo_item_id).and(item_deleted_at.is_null()))
-                .first::<DbToDoItem>(connection)
-                .optional()
-                .map_err(map_diesel_error)?
-                .map(ToDoItem::from)
-                .ok_or(ItemNotFound { id: todo_item_id })
+    async fn get_deleted_by_id_for_audit(
+        &self,
+        todo_item_id: Uuid,
+        scope: AccessScope,
+    ) -> ApplicationResult<ToDoItem> {
+        self.run_in_tenant(
+            "get_deleted_by_id_for_audit",
+            scope.tenant_id.clone(),
+            move |connection| {
+                to_do_items
+                    .filter(item_id.eq(&todo_item_id).and(item_deleted_at.is_not_null()))
+                    .filter(scope_filter(&scope))
+                    .first::<DbToDoItem>(connection)
+                    .optional()
+                    .map_err(map_diesel_error)?
+                    .map(ToDoItem::from)
+                    .ok_or(ItemNotFound { id: todo_item_id })
+            },
+        )
+        .await
+    }
+
+    async fn get_deleted_for_audit(
+        &self,
+        query: GetDeletedToDoItemsForAuditQuery,
+    ) -> ApplicationResult<PaginatedResult<ToDoItem>> {
+        self.run_in_tenant(
+            "get_deleted_for_audit",
+            query.scope.tenant_id.clone(),
+            move |connection| {
+                let total_items = build_deleted_query(&query)
+                    .select(count_star())
+                    .first::<i64>(connection)
+                    .map_err(map_diesel_error)?;
+
+                let items = apply_sort_keys(build_deleted_query(&query), &query.sort)
+                    .offset(query.offset())
+                    .limit(query.limit())
+                    .load::<DbToDoItem>(connection)
+                    .map_err(map_diesel_error)?
+                    .into_iter()
+                    .map(ToDoItem::from)
+                    .collect();
+
+                Ok(PaginatedResult::new(
+                    items,
+                    query.page,
+                    query.page_size,
+                    total_items,
+                ))
+            },
+        )
+        .await
+    }
+
+    async fn get_history(
+        &self,
+        todo_item_id: Uuid,
+        page: u32,
+        page_size: u32,
+        scope: AccessScope,
+    ) -> ApplicationResult<PaginatedResult<ToDoItemRevision>> {
+        self.run_in_tenant("get_history", scope.tenant_id.clone(), move |connection| {
+            load_history(connection, todo_item_id, page, page_size, &scope)
         })
         .await
     }
 
-    async fn get_deleted_by_id_for_audit(&self, todo_item_id: Uuid) -> ApplicationResult<ToDoItem> {
-        self.run_db(move |connection| {
-            to_do_items
-                .filter(item_id.eq(&todo_item_id).and(item_deleted_at.is_not_null()))
-                .first::<DbToDoItem>(connection)
-                .optional()
-                .map_err(map_diesel_error)?
-                .map(ToDoItem::from)
-                .ok_or(ItemNotFound { id: todo_item_id })
+    async fn get_revision(
+        &self,
+        todo_item_id: Uuid,
+        version: i32,
+        scope: AccessScope,
+    ) -> ApplicationResult<ToDoItemRevision> {
+        self.run_in_tenant("get_revision", scope.tenant_id.clone(), move |connection| {
+            find_revision(connection, todo_item_id, version, &scope)
         })
         .await
     }
+
+    async fn get_revisions(
+        &self,
+        todo_item_id: Uuid,
+        from: i32,
+        to: i32,
+        scope: AccessScope,
+    ) -> ApplicationResult<Vec<ToDoItemRevision>> {
+        self.run_in_tenant(
+            "get_revisions",
+            scope.tenant_id.clone(),
+            move |connection| load_revision_range(connection, todo_item_id, from, to, &scope),
+        )
+        .await
+    }
 }
 
 #[async_trait]
 impl ToDoItemCommandRepository for PostgresToDoItemRepository {
+    async fn get_for_update(
+        &self,
+        todo_item_id: Uuid,
+        scope: AccessScope,
+    ) -> ApplicationResult<ToDoItem> {
+        self.run_in_tenant(
+            "get_for_update",
+            scope.tenant_id.clone(),
+            move |connection| lock_active_by_id(connection, todo_item_id, &scope),
+        )
+        .await
+    }
+
+    async fn get_deleted_for_update(
+        &self,
+        todo_item_id: Uuid,
+        scope: AccessScope,
+    ) -> ApplicationResult<ToDoItem> {
+        self.run_in_tenant(
+            "get_deleted_for_update",
+            scope.tenant_id.clone(),
+            move |connection| lock_deleted_by_id(connection, todo_item_id, &scope),
+        )
+        .await
+    }
+
     async fn create(&self, entity: ToDoItem) -> ApplicationResult<Uuid> {
-        self.run_db(move |connection| {
-            let new_entity = NewDbToDoItem::from(&entity);
-            diesel::insert_into(to_do_items)
-                .values(&new_entity)
-                .execute(connection)
-                .map_err(map_diesel_error)?;
-            Ok(entity.id)
+        self.run_in_tenant("create", entity.tenant_id.clone(), move |connection| {
+            insert_item(connection, &entity)
         })
         .await
     }
 
-    async fn update(&self, entity: ToDoItem) -> ApplicationResult<Uuid> {
-        self.run_db(move |connection| {
-            let next_updated_at = Utc::now();
-            let affected_rows = diesel::update(
-                to_do_items.filter(
-                    item_id
-                        .eq(entity.id)
-                        .and(item_version.eq(entity.version))
-                        .and(item_deleted_at.is_null()),
-                ),
-            )
-            .set((
-                item_title.eq(entity.title.clone()),
-                item_note.eq(entity.note.clone()),
-                item_status.eq(entity.status.clone()),
-                item_due_at.eq(entity.due_at),
-                item_updated_at.eq(next_updated_at),
-                item_version.eq(entity.version + 1),
-            ))
-            .execute(connection)
-            .map_err(map_diesel_error)?;
+    async fn create_many(&self, entities: Vec<ToDoItem>) -> ApplicationResult<Vec<Uuid>> {
+        let tenant_id = entities.first().and_then(|entity| entity.tenant_id.clone());
+        self.run_in_tenant("create_many", tenant_id, move |connection| {
+            connection
+                .transaction(|connection| insert_rows(connection, &entities))
+                .map_err(map_diesel_error)?;
+            Ok(entities.iter().map(|entity| entity.id).collect())
+        })
+        .await
+    }
 
-            if affected_rows == 1 {
-                return Ok(entity.id);
-            }
+    async fn update(&self, entity: ToDoItem) -> ApplicationResult<ToDoItem> {
+        self.run_in_tenant("update", entity.tenant_id.clone(), move |connection| {
+            update_item(connection, &entity)
+        })
+        .await
+    }
 
-            let actual_version = to_do_items
-                .filter(item_id.eq(entity.id).and(item_deleted_at.is_null()))
-                .select(item_version)
-                .first::<i32>(connection)
-                .optional()
-                .map_err(map_diesel_error)?;
+    async fn delete(
+        &self,
+        todo_item_id: Uuid,
+        deleted_by: Option<Uuid>,
+        scope: AccessScope,
+    ) -> ApplicationResult<ToDoItem> {
+        self.run_in_tenant("delete", scope.tenant_id.clone(), move |connection| {
+            soft_delete_item(connection, todo_item_id, deleted_by, &scope)
+        })
+        .await
+    }
 
-            match actual_version {
-                Some(actual_version) => Err(VersionConflict {
-                    id: entity.id,
-                    expected_version: entity.version,
-                    actual_version,
-                }),
-                None => Err(ItemNotFound { id: entity.id }),
-            }
+    async fn restore(&self, entity: ToDoItem) -> ApplicationResult<Uuid> {
+        self.run_in_tenant("restore", entity.tenant_id.clone(), move |connection| {
+            restore_item(connection, &entity)
         })
         .await
     }
 
-    async fn delete(&self, todo_item_id: Uuid, deleted_by: Option<Uuid>) -> ApplicationResult<()> {
-        self.run_db(move |connection| {
-            let deleted_at = Utc::now();
-            diesel::update(
-                to_do_items.filter(item_id.eq(&todo_item_id).and(item_deleted_at.is_null())),
-            )
-            .set((
-                item_deleted_at.eq(Some(deleted_at)),
-                item_deleted_by.eq(deleted_by),
-            ))
-            .execute(connection)
-            .map_err(map_diesel_error)?;
-            Ok(())
+    async fn get_purgeable_ids(
+        &self,
+        deleted_before: DateTime<Utc>,
+        limit: i64,
+    ) -> ApplicationResult<Vec<Uuid>> {
+        self.run_across_tenants("get_purgeable_ids", move |connection| {
+            lock_purgeable_ids(connection, deleted_before, limit)
         })
         .await
     }
+
+    async fn purge(&self, ids: Vec<Uuid>) -> ApplicationResult<()> {
+        self.run_across_tenants("purge", move |connection| purge_items(connection, &ids))
+            .await
+    }
+}
+
+pub(crate) fn insert_item(
+    connection: &mut PgConnection,
+    entity: &ToDoItem,
+) -> std::result::Result<Uuid, crate::Error> {
+    let new_entity = NewDbToDoItem::from(entity);
+    diesel::insert_into(to_do_items)
+        .values(&new_entity)
+        .execute(connection)
+        .map_err(map_diesel_error)?;
+    Ok(entity.id)
+}
+
+/// Rows per multi-row `INSERT`, which keeps each statement well below the PostgreSQL
+/// limit of 65535 bind parameters.
+pub(crate) const INSERT_CHUNK_SIZE: usize = 1000;
+
+pub(crate) fn insert_items(
+    connection: &mut PgConnection,
+    entities: &[ToDoItem],
+) -> std::result::Result<Vec<Uuid>, crate::Error> {
+    insert_rows(connection, entities).map_err(map_diesel_error)?;
+    Ok(entities.iter().map(|entity| entity.id).collect())
+}
+
+fn insert_rows(connection: &mut PgConnection, entities: &[ToDoItem]) -> QueryResult<()> {
+    for chunk in entities.chunks(INSERT_CHUNK_SIZE) {
+        let new_entities = chunk.iter().map(NewDbToDoItem::from).collect::<Vec<_>>();
+        diesel::insert_into(to_do_items)
+            .values(&new_entities)
+            .execute(connection)?;
+    }
+    Ok(())
+}
+
+/// Writes the changes of `entity` if its version is still current and returns the row as
+/// stored, with the new version and `updated_at`.
+pub(crate) fn update_item(
+    connection: &mut PgConnection,
+    entity: &ToDoItem,
+) -> std::result::Result<ToDoItem, crate::Error> {
+    let next_updated_at = Utc::now();
+    let updated = diesel::update(
+        to_do_items
+            .filter(
+                item_id
+                    .eq(entity.id)
+                    .and(item_version.eq(entity.version))
+                    .and(item_deleted_at.is_null()),
+            )
+            .filter(tenant_filter(entity.tenant_id.as_deref())),
+    )
+    .set((
+        item_title.eq(entity.title.clone()),
+        item_note.eq(entity.note.clone()),
+        item_status.eq(entity.status),
+        item_due_at.eq(entity.due_at),
+        item_updated_at.eq(next_updated_at),
+        item_updated_by.eq(entity.updated_by),
+        item_version.eq(entity.version + 1),
+    ))
+    .get_result::<DbToDoItem>(connection)
+    .optional()
+    .map_err(map_diesel_error)?;
+
+    if let Some(updated) = updated {
+        return Ok(ToDoItem::from(updated));
+    }
+
+    let actual_version = to_do_items
+        .filter(item_id.eq(entity.id).and(item_deleted_at.is_null()))
+        .filter(tenant_filter(entity.tenant_id.as_deref()))
+        .select(item_version)
+        .first::<i32>(connection)
+        .optional()
+        .map_err(map_diesel_error)?;
+
+    match actual_version {
+        Some(actual_version) => Err(VersionConflict {
+            id: entity.id,
+            expected_version: entity.version,
+            actual_version,
+        }),
+        None => Err(ItemNotFound { id: entity.id }),
+    }
+}
+
+pub(crate) fn soft_delete_item(
+    connection: &mut PgConnection,
+    todo_item_id: Uuid,
+    deleted_by: Option<Uuid>,
+    scope: &AccessScope,
+) -> std::result::Result<ToDoItem, crate::Error> {
+    let deleted_at = Utc::now();
+    diesel::update(
+        to_do_items
+            .filter(item_id.eq(&todo_item_id).and(item_deleted_at.is_null()))
+            .filter(scope_filter(scope)),
+    )
+    .set((
+        item_deleted_at.eq(Some(deleted_at)),
+        item_deleted_by.eq(deleted_by),
+        item_version.eq(item_version + 1),
+    ))
+    .get_result::<DbToDoItem>(connection)
+    .optional()
+    .map_err(map_diesel_error)?
+    .map(ToDoItem::from)
+    .ok_or(ItemNotFound { id: todo_item_id })
+}
+
+/// Clears the deletion metadata of a soft-deleted item and records who restored it.
+pub(crate) fn restore_item(
+    connection: &mut PgConnection,
+    entity: &ToDoItem,
+) -> std::result::Result<Uuid, crate::Error> {
+    let affected_rows = diesel::update(
+        to_do_items
+            .filter(
+                item_id
+                    .eq(entity.id)
+                    .and(item_version.eq(entity.version))
+                    .and(item_deleted_at.is_not_null()),
+            )
+            .filter(tenant_filter(entity.tenant_id.as_deref())),
+    )
+    .set((
+        item_deleted_at.eq(None::<DateTime<Utc>>),
+        item_deleted_by.eq(None::<Uuid>),
+        item_restored_at.eq(entity.restored_at),
+        item_restored_by.eq(entity.restored_by),
+        item_updated_at.eq(entity.updated_at),
+        item_version.eq(entity.version + 1),
+    ))
+    .execute(connection)
+    .map_err(map_diesel_error)?;
+
+    if affected_rows == 1 {
+        return Ok(entity.id);
+    }
+
+    let actual_version = to_do_items
+        .filter(item_id.eq(entity.id).and(item_deleted_at.is_not_null()))
+        .filter(tenant_filter(entity.tenant_id.as_deref()))
+        .select(item_version)
+        .first::<i32>(connection)
+        .optional()
+        .map_err(map_diesel_error)?;
+
+    match actual_version {
+        Some(actual_version) => Err(VersionConflict {
+            id: entity.id,
+            expected_version: entity.version,
+            actual_version,
+        }),
+        None => Err(ItemNotFound { id: entity.id }),
+    }
+}
+
+/// Locks up to `limit` items soft-deleted before `deleted_before`, skipping rows that a
+/// concurrent purge already holds.
+pub(crate) fn lock_purgeable_ids(
+    connection: &mut PgConnection,
+    deleted_before: DateTime<Utc>,
+    limit: i64,
+) -> std::result::Result<Vec<Uuid>, crate::Error> {
+    to_do_items
+        .filter(item_deleted_at.lt(deleted_before))
+        .order((item_deleted_at.asc(), item_id.asc()))
+        .limit(limit)
+        .select(item_id)
+        .for_update()
+        .skip_locked()
+        .load::<Uuid>(connection)
+        .map_err(map_diesel_error)
+}
+
+pub(crate) fn purge_items(
+    connection: &mut PgConnection,
+    ids: &[Uuid],
+) -> std::result::Result<(), crate::Error> {
+    diesel::delete(to_do_items.filter(item_id.eq_any(ids).and(item_deleted_at.is_not_null())))
+        .execute(connection)
+        .map_err(map_diesel_error)?;
+    Ok(())
+}
+
+fn find_active_by_id(
+    connection: &mut PgConnection,
+    todo_item_id: Uuid,
+    scope: &AccessScope,
+) -> std::result::Result<ToDoItem, crate::Error> {
+    to_do_items
+        .filter(item_id.eq(&todo_item_id).and(item_deleted_at.is_null()))
+        .filter(scope_filter(scope))
+        .first::<DbToDoItem>(connection)
+        .optional()
+        .map_err(map_diesel_error)?
+        .map(ToDoItem::from)
+        .ok_or(ItemNotFound { id: todo_item_id })
+}
+
+/// Loads an active item and locks its row until the surrounding transaction ends, so the
+/// version check in a command handler cannot race with a concurrent writer.
+pub(crate) fn lock_active_by_id(
+    connection: &mut PgConnection,
+    todo_item_id: Uuid,
+    scope: &AccessScope,
+) -> std::result::Result<ToDoItem, crate::Error> {
+    to_do_items
+        .filter(item_id.eq(&todo_item_id).and(item_deleted_at.is_null()))
+        .filter(scope_filter(scope))
+        .for_update()
+        .first::<DbToDoItem>(connection)
+        .optional()
+        .map_err(map_diesel_error)?
+        .map(ToDoItem::from)
+        .ok_or(ItemNotFound { id: todo_item_id })
+}
+
+/// Loads a soft-deleted item and locks its row until the surrounding transaction ends.
+pub(crate) fn lock_deleted_by_id(
+    connection: &mut PgConnection,
+    todo_item_id: Uuid,
+    scope: &AccessScope,
+) -> std::result::Result<ToDoItem, crate::Error> {
+    to_do_items
+        .filter(item_id.eq(&todo_item_id).and(item_deleted_at.is_not_null()))
+        .filter(scope_filter(scope))
+        .for_update()
+        .first::<DbToDoItem>(connection)
+        .optional()
+        .map_err(map_diesel_error)?
+        .map(ToDoItem::from)
+        .ok_or(ItemNotFound { id: todo_item_id })
+}
+
+/// Restricts a statement to the rows visible within `scope`.
+fn scope_filter(scope: &AccessScope) -> SortCondition {
+    let tenant = tenant_filter(scope.tenant_id.as_deref());
+    match scope.owner_id {
+        Some(owner) if scope.include_unowned => {
+            Box::new(tenant.and(item_owner_id.eq(owner).or(item_owner_id.is_null())))
+        }
+        Some(owner) => Box::new(tenant.and(item_owner_id.eq(owner))),
+        None => tenant,
+    }
+}
+
+/// Restricts a statement to the rows of `tenant_id`; `None` leaves it unrestricted.
+fn tenant_filter(tenant_id: Option<&str>) -> SortCondition {
+    match tenant_id {
+        Some(tenant_id) => Box::new(item_tenant_id.eq(tenant_id.to_string())),
+        None => Box::new(item_id.is_not_null().nullable()),
+    }
 }
 
-fn build_filtered_query<'a>(search: Option<&str>) -> domain::to_do_items::BoxedQuery<'a, Pg> {
+/// Sets `app.tenant_id` and `app.all_tenants` for the current transaction only.
+const BIND_TENANT_SQL: &str =
+    "SELECT set_config('app.tenant_id', $1, true), set_config('app.all_tenants', $2, true)";
+
+/// Binds the current transaction to `tenant_id` for the row-level security policies on
+/// `to_do_items`. Without a tenant, as in a session that binds nothing, no rows are visible.
+pub(crate) fn bind_tenant(
+    connection: &mut PgConnection,
+    tenant_id: Option<&str>,
+) -> std::result::Result<(), crate::Error> {
+    set_tenant_binding(connection, tenant_id.unwrap_or_default(), false)
+}
+
+/// Opens the rows of every tenant to the current transaction. Only for purging deleted
+/// items, which is not done on behalf of a tenant.
+pub(crate) fn bind_all_tenants(
+    connection: &mut PgConnection,
+) -> std::result::Result<(), crate::Error> {
+    set_tenant_binding(connection, "", true)
+}
+
+fn set_tenant_binding(
+    connection: &mut PgConnection,
+    tenant_id: &str,
+    all_tenants: bool,
+) -> std::result::Result<(), crate::Error> {
+    diesel::sql_query(BIND_TENANT_SQL)
+        .bind::<Text, _>(tenant_id)
+        .bind::<Text, _>(if all_tenants { "on" } else { "off" })
+        .execute(connection)
+        .map_err(map_diesel_error)?;
+    Ok(())
+}
+
+fn build_filtered_query<'a>(
+    params: &GetAllToDoItemsQuery,
+) -> domain::to_do_items::BoxedQuery<'a, Pg> {
     let mut query = to_do_items
         .filter(item_deleted_at.is_null())
+        .filter(scope_filter(&params.scope))
         .into_boxed::<Pg>();
 
-    if let Some(search) = search {
-        let pattern = format!("%{}%", search.trim());
+    if let Some(search) = params.search.clone() {
+        query = query.filter(search_condition(search, params.search_mode));
+    }
+
+    apply_filter(query, &params.filter)
+}
+
+fn build_deleted_query<'a>(
+    params: &GetDeletedToDoItemsForAuditQuery,
+) -> domain::to_do_items::BoxedQuery<'a, Pg> {
+    let mut query = to_do_items
+        .filter(item_deleted_at.is_not_null())
+        .filter(scope_filter(&params.scope))
+        .into_boxed::<Pg>();
+
+    if let Some(search) = params.search.clone() {
+        query = query.filter(search_condition(search, params.search_mode));
+    }
+
+    apply_deleted_filter(query, &params.filter)
+}
+
+/// Matches rows whose `search_vector` matches `search`.
+fn search_condition(
+    search: String,
+    mode: SearchMode,
+) -> Box<dyn BoxableExpression<domain::to_do_items::table, Pg, SqlType = Bool>> {
+    Box::new(
+        sql::<Bool>(&format!("search_vector @@ {}", ts_query_prefix(mode)))
+            .bind::<Text, _>(search)
+            .sql(")"),
+    )
+}
+
+fn apply_filter<'a>(
+    mut query: domain::to_do_items::BoxedQuery<'a, Pg>,
+    filter: &ToDoItemFilter,
+) -> domain::to_do_items::BoxedQuery<'a, Pg> {
+    if !filter.statuses.is_empty() {
+        query = query.filter(item_status.eq_any(filter.statuses.clone()));
+    }
+    if let Some(due_after) = filter.due_after {
+        query = query.filter(item_due_at.ge(due_after));
+    }
+    if let Some(due_before) = filter.due_before {
+        query = query.filter(item_due_at.lt(due_before));
+    }
+    if let Some(created_after) = filter.created_after {
+        query = query.filter(item_created_at.ge(created_after));
+    }
+    if let Some(created_before) = filter.created_before {
+        query = query.filter(item_created_at.lt(created_before));
+    }
+    if let Some(updated_since) = filter.updated_since {
+        query = query.filter(item_updated_at.ge(updated_since));
+    }
+    if filter.overdue {
         query = query.filter(
-            item_title
-                .ilike(pattern.clone())
-                .or(item_note.ilike(pattern)),
+            item_due_at
+                .lt(Utc::now())
+                .and(item_status.ne(ToDoItemStatus::Done)),
         );
     }
 
     query
 }
 
-fn apply_sort<'a>(
+fn apply_deleted_filter<'a>(
+    mut query: domain::to_do_items::BoxedQuery<'a, Pg>,
+    filter: &DeletedToDoItemFilter,
+) -> domain::to_do_items::BoxedQuery<'a, Pg> {
+    if let Some(deleted_by) = filter.deleted_by {
+        query = query.filter(item_deleted_by.eq(deleted_by));
+    }
+    if let Some(deleted_after) = filter.deleted_after {
+        query = query.filter(item_deleted_at.ge(deleted_after));
+    }
+    if let Some(deleted_before) = filter.deleted_before {
+        query = query.filter(item_deleted_at.lt(deleted_before));
+    }
+
+    query
+}
+
+/// Opens the full-text query call for the search text that is bound right after it.
+///
+/// Uses the same `english` configuration as the generated `search_vector` column, so
+/// both sides are stemmed and stripped of stop words the same way.
+pub(crate) fn ts_query_prefix(mode: SearchMode) -> &'static str {
+    match mode {
+        SearchMode::Plain => "plainto_tsquery('english', ",
+        SearchMode::Phrase => "phraseto_tsquery('english', ",
+        SearchMode::Websearch => "websearch_to_tsquery('english', ",
+    }
+}
+
+fn load_offset_page(
+    connection: &mut PgConnection,
+    query: &GetAllToDoItemsQuery,
+) -> std::result::Result<PaginatedResult<ToDoItem>, crate::Error> {
+    let total_items = build_filtered_query(query)
+        .select(count_star())
+        .first::<i64>(connection)
+        .map_err(map_diesel_error)?;
+
+    let items = apply_sort(build_filtered_query(query), query)
+        .offset(query.offset())
+        .limit(query.limit())
+        .load::<DbToDoItem>(connection)
+        .map_err(map_diesel_error)?
+        .into_iter()
+        .map(ToDoItem::from)
+        .collect();
+
+    Ok(PaginatedResult::new(
+        items,
+        query.page,
+        query.page_size,
+        total_items,
+    ))
+}
+
+/// Builds `ts_headline` snippets for the items of the current page only, so highlighting
+/// never runs over the whole result set.
+fn load_highlights(
+    connection: &mut PgConnection,
+    items: &[ToDoItem],
+    search: &str,
+    mode: SearchMode,
+) -> std::result::Result<HashMap<Uuid, String>, crate::Error> {
+    if items.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let headline = sql::<Text>(&format!(
+        "ts_headline('english', concat_ws(' ', title, note), {}",
+        ts_query_prefix(mode)
+    ))
+    .bind::<Text, _>(search.to_string())
+    .sql("), 'StartSel=<mark>, StopSel=</mark>, MaxWords=20, MinWords=5')");
+
+    to_do_items
+        .filter(item_id.eq_any(items.iter().map(|item| item.id).collect::<Vec<_>>()))
+        .select((item_id, headline))
+        .load::<(Uuid, String)>(connection)
+        .map(|rows| rows.into_iter().collect())
+        .map_err(map_diesel_error)
+}
+
+/// Loads one row more than requested to find out whether a next page exists, so the
+/// cursor is only returned when there is something left to read.
+fn load_keyset_page(
+    connection: &mut PgConnection,
+    query: &GetAllToDoItemsQuery,
+    keyset: &KeysetPage,
+) -> std::result::Result<PaginatedResult<ToDoItem>, crate::Error> {
+    let total_items = if keyset.include_total {
+        Some(
+            build_filtered_query(query)
+                .select(count_star())
+                .first::<i64>(connection)
+                .map_err(map_diesel_error)?,
+        )
+    } else {
+        None
+    };
+
+    let mut items_query = build_filtered_query(query);
+    if let Some(cursor) = &keyset.after {
+        items_query = apply_cursor(items_query, cursor);
+    }
+    let mut items = apply_sort(items_query, query)
+        .limit(query.limit() + 1)
+        .load::<DbToDoItem>(connection)
+        .map_err(map_diesel_error)?
+        .into_iter()
+        .map(ToDoItem::from)
+        .collect::<Vec<_>>();
+
+    let next_cursor = if items.len() > query.page_size as usize {
+        items.truncate(query.page_size as usize);
+        items
+            .last()
+            .map(|item| ToDoItemCursor::after(item, &query.sort))
+    } else {
+        None
+    };
+
+    Ok(PaginatedResult::keyset(
+        items,
+        query.page_size,
+        total_items,
+        next_cursor,
+    ))
+}
+
+/// Boolean condition over a single row, as used for keyset predicates.
+type SortCondition =
+    Box<dyn BoxableExpression<domain::to_do_items::table, Pg, SqlType = Nullable<Bool>>>;
+
+/// Restricts the query to rows that sort after the cursor.
+///
+/// Compares the effective sort keys lexicographically: a row comes after the cursor when
+/// it equals the cursor on the first `n` keys and sorts after it on key `n + 1`.
+fn apply_cursor<'a>(
     query: domain::to_do_items::BoxedQuery<'a, Pg>,
-    params: &GetAllToDoItemsQuery,
+    cursor: &ToDoItemCursor,
 ) -> domain::to_do_items::BoxedQuery<'a, Pg> {
-    match (&params.sort.field, &params.sort.direction) {
-        (ToDoItemSortField::Id, SortDirection::Asc) => query.order(item_id.asc()),
-        (ToDoItemSortField::Id, SortDirection::Desc) => query.order(item_id.desc()),
-        (ToDoItemSortField::Title, SortDirection::Asc) => {
-            query.order((item_title.asc(), item_id.asc()))
+    let keys = cursor.sort.effective_keys();
+    let bounds = keys.iter().zip(&cursor.values).collect::<Vec<_>>();
+
+    let condition = bounds
+        .iter()
+        .enumerate()
+        .map(|(position, (key, value))| {
+            bounds[..position]
+                .iter()
+                .fold(sort_key_after(key, value), |term, (key, value)| {
+                    Box::new(sort_key_equals(key, value).and(term))
+                })
+        })
+        .reduce(|left, right| Box::new(left.or(right)));
+
+    match condition {
+        Some(condition) => query.filter(condition),
+        None => query,
+    }
+}
+
+fn sort_key_equals(key: &ToDoItemSortKey, value: &ToDoItemSortValue) -> SortCondition {
+    match (key.field, value) {
+        (ToDoItemSortField::Id, ToDoItemSortValue::Id(id)) => Box::new(item_id.eq(*id).nullable()),
+        (ToDoItemSortField::Title, ToDoItemSortValue::Text(Some(title))) => {
+            Box::new(item_title.eq(title.clone()))
+        }
+        (ToDoItemSortField::Title, ToDoItemSortValue::Text(None)) => {
+            Box::new(item_title.is_null().nullable())
+        }
+        (ToDoItemSortField::Status, ToDoItemSortValue::Status(status)) => {
+            Box::new(item_status.eq(*status).nullable())
+        }
+        (ToDoItemSortField::CreatedAt, ToDoItemSortValue::Timestamp(Some(created_at))) => {
+            Box::new(item_created_at.eq(*created_at).nullable())
+        }
+        (ToDoItemSortField::UpdatedAt, ToDoItemSortValue::Timestamp(Some(updated_at))) => {
+            Box::new(item_updated_at.eq(*updated_at).nullable())
+        }
+        (ToDoItemSortField::DueAt, ToDoItemSortValue::Timestamp(Some(due_at))) => {
+            Box::new(item_due_at.eq(*due_at))
+        }
+        (ToDoItemSortField::DueAt, ToDoItemSortValue::Timestamp(None)) => {
+            Box::new(item_due_at.is_null().nullable())
+        }
+        _ => no_rows(),
+    }
+}
+
+/// Matches rows that sort strictly after `value` on this key, honouring its direction
+/// and the placement of `NULL` values.
+fn sort_key_after(key: &ToDoItemSortKey, value: &ToDoItemSortValue) -> SortCondition {
+    let ascending = key.direction == SortDirection::Asc;
+    let nulls_last = key.nulls == NullsOrder::Last;
+
+    match (key.field, value) {
+        (ToDoItemSortField::Id, ToDoItemSortValue::Id(id)) if ascending => {
+            Box::new(item_id.gt(*id).nullable())
+        }
+        (ToDoItemSortField::Id, ToDoItemSortValue::Id(id)) => Box::new(item_id.lt(*id).nullable()),
+        (ToDoItemSortField::Title, ToDoItemSortValue::Text(Some(title))) => {
+            let after: SortCondition = if ascending {
+                Box::new(item_title.gt(title.clone()))
+            } else {
+                Box::new(item_title.lt(title.clone()))
+            };
+            if nulls_last {
+                Box::new(after.or(item_title.is_null()))
+            } else {
+                after
+            }
         }
-        (ToDoItemSortField::Title, SortDirection::Desc) => {
-            query.order((item_title.desc(), item_id.desc()))
+        (ToDoItemSortField::Title, ToDoItemSortValue::Text(None)) if nulls_last => no_rows(),
+        (ToDoItemSortField::Title, ToDoItemSortValue::Text(None)) => {
+            Box::new(item_title.is_not_null().nullable())
         }
+        (ToDoItemSortField::Status, ToDoItemSortValue::Status(status)) if ascending => {
+            Box::new(status_rank().gt(status.lifecycle_rank()).nullable())
+        }
+        (ToDoItemSortField::Status, ToDoItemSortValue::Status(status)) => {
+            Box::new(status_rank().lt(status.lifecycle_rank()).nullable())
+        }
+        (ToDoItemSortField::CreatedAt, ToDoItemSortValue::Timestamp(Some(created_at))) => {
+            if ascending {
+                Box::new(item_created_at.gt(*created_at).nullable())
+            } else {
+                Box::new(item_created_at.lt(*created_at).nullable())
+            }
+        }
+        (ToDoItemSortField::UpdatedAt, ToDoItemSortValue::Timestamp(Some(updated_at))) => {
+            if ascending {
+                Box::new(item_updated_at.gt(*updated_at).nullable())
+            } else {
+                Box::new(item_updated_at.lt(*updated_at).nullable())
+            }
+        }
+        (ToDoItemSortField::DueAt, ToDoItemSortValue::Timestamp(Some(due_at))) => {
+            let after: SortCondition = if ascending {
+                Box::new(item_due_at.gt(*due_at))
+            } else {
+                Box::new(item_due_at.lt(*due_at))
+            };
+            if nulls_last {
+                Box::new(after.or(item_due_at.is_null()))
+            } else {
+                after
+            }
+        }
+        (ToDoItemSortField::DueAt, ToDoItemSortValue::Timestamp(None)) if nulls_last => no_rows(),
+        (ToDoItemSortField::DueAt, ToDoItemSortValue::Timestamp(None)) => {
+            Box::new(item_due_at.is_not_null().nullable())
+        }
+        _ => no_rows(),
+    }
+}
+
+fn no_rows() -> SortCondition {
+    Box::new(sql::<Nullable<Bool>>("FALSE"))
+}
+
+fn status_rank() -> SqlLiteral<Integer> {
+    sql(&status_rank_sql())
+}
+
+/// Ranks statuses by their lifecycle position, matching `ToDoItemStatus::lifecycle_rank`.
+pub(crate) fn status_rank_sql() -> String {
+    let cases = ToDoItemStatus::ALL
+        .iter()
+        .map(|status| {
+            format!(
+                "WHEN '{}' THEN {}",
+                status.as_str(),
+                status.lifecycle_rank()
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!("CASE status {cases} END")
+}
+
+fn apply_sort<'a>(
+    mut query: domain::to_do_items::BoxedQuery<'a, Pg>,
+    params: &GetAllToDoItemsQuery,
+) -> domain::to_do_items::BoxedQuery<'a, Pg> {
+    if let Some(search) = params.search.clone().filter(|_| params.rank_by_relevance) {
+        query = query.then_order_by(
+            sql::<Float>(&format!(
+                "ts_rank(search_vector, {}",
+                ts_query_prefix(params.search_mode)
+            ))
+            .bind::<Text, _>(search)
+            .sql("))")
+            .desc(),
+        );
+    }
+
+    apply_sort_keys(query, &params.sort)
+}
+
+fn apply_sort_keys<'a>(
+    mut query: domain::to_do_items::BoxedQuery<'a, Pg>,
+    sort: &ToDoItemSort,
+) -> domain::to_do_items::BoxedQuery<'a, Pg> {
+    for key in sort.effective_keys() {
+        query = match (key.field, key.direction, key.nulls) {
+            (ToDoItemSortField::Id, SortDirection::Asc, _) => query.then_order_by(item_id.asc()),
+            (ToDoItemSortField::Id, SortDirection::Desc, _) => query.then_order_by(item_id.desc()),
+            (ToDoItemSortField::Title, SortDirection::Asc, NullsOrder::First) => {
+                query.then_order_by(item_title.asc().nulls_first())
+            }
+            (ToDoItemSortField::Title, SortDirection::Asc, NullsOrder::Last) => {
+                query.then_order_by(item_title.asc().nulls_last())
+            }
+            (ToDoItemSortField::Title, SortDirection::Desc, NullsOrder::First) => {
+                query.then_order_by(item_title.desc().nulls_first())
+            }
+            (ToDoItemSortField::Title, SortDirection::Desc, NullsOrder::Last) => {
+                query.then_order_by(item_title.desc().nulls_last())
+            }
+            (ToDoItemSortField::Status, SortDirection::Asc, _) => {
+                query.then_order_by(status_rank().asc())
+            }
+            (ToDoItemSortField::Status, SortDirection::Desc, _) => {
+                query.then_order_by(status_rank().desc())
+            }
+            (ToDoItemSortField::CreatedAt, SortDirection::Asc, _) => {
+                query.then_order_by(item_created_at.asc())
+            }
+            (ToDoItemSortField::CreatedAt, SortDirection::Desc, _) => {
+                query.then_order_by(item_created_at.desc())
+            }
+            (ToDoItemSortField::UpdatedAt, SortDirection::Asc, _) => {
+                query.then_order_by(item_updated_at.asc())
+            }
+            (ToDoItemSortField::UpdatedAt, SortDirection::Desc, _) => {
+                query.then_order_by(item_updated_at.desc())
+            }
+            (ToDoItemSortField::DueAt, SortDirection::Asc, NullsOrder::First) => {
+                query.then_order_by(item_due_at.asc().nulls_first())
+            }
+            (ToDoItemSortField::DueAt, SortDirection::Asc, NullsOrder::Last) => {
+                query.then_order_by(item_due_at.asc().nulls_last())
+            }
+            (ToDoItemSortField::DueAt, SortDirection::Desc, NullsOrder::First) => {
+                query.then_order_by(item_due_at.desc().nulls_first())
+            }
+            (ToDoItemSortField::DueAt, SortDirection::Desc, NullsOrder::Last) => {
+                query.then_order_by(item_due_at.desc().nulls_last())
+            }
+            (ToDoItemSortField::DeletedAt, SortDirection::Asc, _) => {
+                query.then_order_by(item_deleted_at.asc())
+            }
+            (ToDoItemSortField::DeletedAt, SortDirection::Desc, _) => {
+                query.then_order_by(item_deleted_at.desc())
+            }
+        };
     }
+
+    query
 }
 
-fn map_diesel_error(err: diesel::result::Error) -> crate::Error {
-    InternalError(format!("database operation failed: {err}"))
+pub(crate) fn map_diesel_error(err: diesel::result::Error) -> crate::Error {
+    crate::Error::from(err)
 }
 
 #[cfg(test)]
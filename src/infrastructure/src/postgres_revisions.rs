@@ -0,0 +1,191 @@
+use crate::errors::Error::{InternalError, ItemNotFound};
+use crate::postgres_repositories::map_diesel_error;
+use application::{AccessScope, ChangeType, PaginatedResult, ToDoItemRevision};
+use chrono::{DateTime, Utc};
+use diesel::dsl::{count_star, max};
+use diesel::pg::Pg;
+use diesel::{
+    ExpressionMethods, Insertable, OptionalExtension, PgConnection, QueryDsl, Queryable,
+    RunQueryDsl,
+};
+use domain::to_do_item_revisions::dsl::{
+    item_id as revision_item_id, owner_id as revision_owner_id, revision as revision_number,
+    tenant_id as revision_tenant_id, to_do_item_revisions, version as revision_version,
+};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+#[derive(Queryable, Insertable)]
+#[diesel(table_name = domain::to_do_item_revisions)]
+pub(crate) struct DbToDoItemRevision {
+    pub(crate) item_id: Uuid,
+    pub(crate) revision: i32,
+    pub(crate) version: i32,
+    pub(crate) change_type: String,
+    pub(crate) snapshot: serde_json::Value,
+    pub(crate) actor: Option<Uuid>,
+    pub(crate) request_id: Option<String>,
+    pub(crate) owner_id: Option<Uuid>,
+    pub(crate) tenant_id: Option<String>,
+    pub(crate) recorded_at: DateTime<Utc>,
+}
+
+impl TryFrom<DbToDoItemRevision> for ToDoItemRevision {
+    type Error = crate::Error;
+
+    fn try_from(row: DbToDoItemRevision) -> Result<Self, Self::Error> {
+        let invalid = |err: String| {
+            InternalError(format!(
+                "revision {} of to-do item {} is invalid: {err}",
+                row.revision, row.item_id
+            ))
+        };
+        let change_type = row.change_type.parse::<ChangeType>().map_err(invalid)?;
+        let snapshot =
+            serde_json::from_value(row.snapshot).map_err(|err| invalid(err.to_string()))?;
+
+        Ok(ToDoItemRevision {
+            item_id: row.item_id,
+            revision: row.revision,
+            change_type,
+            snapshot,
+            actor: row.actor,
+            request_id: row.request_id,
+            recorded_at: row.recorded_at,
+        })
+    }
+}
+
+/// Stores `revisions` numbered after the latest revision of their item. Callers hold the
+/// row lock of the item, so concurrent changes cannot pick the same number.
+pub(crate) fn append_revisions(
+    connection: &mut PgConnection,
+    revisions: Vec<ToDoItemRevision>,
+) -> std::result::Result<(), crate::Error> {
+    if revisions.is_empty() {
+        return Ok(());
+    }
+
+    let item_ids = revisions
+        .iter()
+        .map(|revision| revision.item_id)
+        .collect::<Vec<_>>();
+    let latest = to_do_item_revisions
+        .filter(revision_item_id.eq_any(&item_ids))
+        .group_by(revision_item_id)
+        .select((revision_item_id, max(revision_number)))
+        .load::<(Uuid, Option<i32>)>(connection)
+        .map_err(map_diesel_error)?
+        .into_iter()
+        .map(|(id, revision)| (id, revision.unwrap_or_default()))
+        .collect::<HashMap<_, _>>();
+
+    let rows = number_revisions(revisions, latest)?;
+    diesel::insert_into(to_do_item_revisions)
+        .values(&rows)
+        .execute(connection)
+        .map_err(map_diesel_error)?;
+    Ok(())
+}
+
+/// Turns `revisions` into rows numbered after the `latest` stored revision of each item.
+pub(crate) fn number_revisions(
+    revisions: Vec<ToDoItemRevision>,
+    mut latest: HashMap<Uuid, i32>,
+) -> std::result::Result<Vec<DbToDoItemRevision>, crate::Error> {
+    revisions
+        .into_iter()
+        .map(|revision| {
+            let number = latest.entry(revision.item_id).or_default();
+            *number += 1;
+            let snapshot = serde_json::to_value(&revision.snapshot)
+                .map_err(|err| InternalError(format!("failed to serialize revision: {err}")))?;
+
+            Ok(DbToDoItemRevision {
+                item_id: revision.item_id,
+                revision: *number,
+                version: revision.snapshot.version,
+                change_type: revision.change_type.to_string(),
+                snapshot,
+                actor: revision.actor,
+                request_id: revision.request_id,
+                owner_id: revision.snapshot.owner_id,
+                tenant_id: revision.snapshot.tenant_id,
+                recorded_at: revision.recorded_at,
+            })
+        })
+        .collect()
+}
+
+pub(crate) fn load_history(
+    connection: &mut PgConnection,
+    item_id: Uuid,
+    page: u32,
+    page_size: u32,
+    scope: &AccessScope,
+) -> std::result::Result<PaginatedResult<ToDoItemRevision>, crate::Error> {
+    let total_items = scoped_revisions(item_id, scope)
+        .select(count_star())
+        .first::<i64>(connection)
+        .map_err(map_diesel_error)?;
+
+    let items = scoped_revisions(item_id, scope)
+        .order(revision_number.asc())
+        .offset((i64::from(page) - 1) * i64::from(page_size))
+        .limit(i64::from(page_size))
+        .load::<DbToDoItemRevision>(connection)
+        .map_err(map_diesel_error)?
+        .into_iter()
+        .map(ToDoItemRevision::try_from)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(PaginatedResult::new(items, page, page_size, total_items))
+}
+
+pub(crate) fn find_revision(
+    connection: &mut PgConnection,
+    item_id: Uuid,
+    version: i32,
+    scope: &AccessScope,
+) -> std::result::Result<ToDoItemRevision, crate::Error> {
+    scoped_revisions(item_id, scope)
+        .filter(revision_version.eq(version))
+        .first::<DbToDoItemRevision>(connection)
+        .optional()
+        .map_err(map_diesel_error)?
+        .ok_or(ItemNotFound { id: item_id })
+        .and_then(ToDoItemRevision::try_from)
+}
+
+pub(crate) fn load_revision_range(
+    connection: &mut PgConnection,
+    item_id: Uuid,
+    from: i32,
+    to: i32,
+    scope: &AccessScope,
+) -> std::result::Result<Vec<ToDoItemRevision>, crate::Error> {
+    scoped_revisions(item_id, scope)
+        .filter(revision_version.between(from, to))
+        .order(revision_number.asc())
+        .load::<DbToDoItemRevision>(connection)
+        .map_err(map_diesel_error)?
+        .into_iter()
+        .map(ToDoItemRevision::try_from)
+        .collect()
+}
+
+fn scoped_revisions<'a>(
+    item_id: Uuid,
+    scope: &AccessScope,
+) -> domain::to_do_item_revisions::BoxedQuery<'a, Pg> {
+    let mut query = to_do_item_revisions
+        .filter(revision_item_id.eq(item_id))
+        .into_boxed::<Pg>();
+    if let Some(tenant_id) = scope.tenant_id.clone() {
+        query = query.filter(revision_tenant_id.eq(tenant_id));
+    }
+    if let Some(owner_id) = scope.owner_id {
+        query = query.filter(revision_owner_id.eq(owner_id));
+    }
+    query
+}
@@ -0,0 +1,363 @@
+use crate::db_metrics::{record_connection_wait, record_query};
+use crate::postgres_outbox::{
+    append_messages, delete_messages_of, fetch_pending_messages, mark_message_failed,
+    mark_message_published,
+};
+use crate::postgres_repositories::{
+    bind_all_tenants, bind_tenant, insert_item, insert_items, lock_active_by_id,
+    lock_deleted_by_id, lock_purgeable_ids, map_diesel_error, purge_items, restore_item,
+    soft_delete_item, update_item,
+};
+use crate::postgres_revisions::append_revisions;
+use crate::DbPool;
+use actix_web::web::Data;
+use application::{
+    AccessScope, ApplicationError, ApplicationResult, OutboxMessage, OutboxRepository,
+    RevisionRepository, ToDoItemCommandRepository, ToDoItemRevision, UnitOfWork, UnitOfWorkFactory,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use diesel::connection::{AnsiTransactionManager, TransactionManager};
+use diesel::r2d2::{ConnectionManager, PooledConnection};
+use diesel::PgConnection;
+use domain::{ToDoItem, ToDoItemEvent};
+use std::sync::{Arc, Mutex};
+use std::time::Instant;
+use tokio::runtime::Handle;
+use tokio::task;
+use uuid::Uuid;
+
+const REPOSITORY: &str = "unit_of_work";
+
+type PooledPgConnection = PooledConnection<ConnectionManager<PgConnection>>;
+
+/// Opens Diesel transactions that stay open across the async steps of a command handler.
+///
+/// Each unit of work pins one pooled connection and drives the same begin/commit/rollback
+/// steps that `PgConnection::transaction` performs around its closure.
+pub struct PostgresUnitOfWorkFactory {
+    pool: Data<DbPool>,
+    row_level_security: bool,
+}
+
+impl PostgresUnitOfWorkFactory {
+    pub fn new(pool: &Data<DbPool>) -> Self {
+        Self {
+            pool: pool.clone(),
+            row_level_security: false,
+        }
+    }
+
+    /// Binds each transaction to the tenant of its to-do item statements, for the
+    /// row-level security policies on `to_do_items`.
+    pub fn with_row_level_security(mut self, enabled: bool) -> Self {
+        self.row_level_security = enabled;
+        self
+    }
+}
+
+#[async_trait]
+impl UnitOfWorkFactory for PostgresUnitOfWorkFactory {
+    async fn begin(&self) -> ApplicationResult<Box<dyn UnitOfWork>> {
+        let pool = self.pool.clone();
+
+        let connection = task::spawn_blocking(move || {
+            let waiting = Instant::now();
+            let connection = pool.get();
+            record_connection_wait(REPOSITORY, waiting.elapsed());
+            let mut connection = connection.map_err(|err| {
+                ApplicationError::internal(format!("failed to acquire database connection: {err}"))
+            })?;
+            let started = Instant::now();
+            let result = AnsiTransactionManager::begin_transaction(&mut *connection)
+                .map_err(map_diesel_error);
+            record_query(REPOSITORY, "begin", started.elapsed(), &result);
+            result.map_err(ApplicationError::from)?;
+            Ok::<_, ApplicationError>(connection)
+        })
+        .await
+        .map_err(|err| {
+            ApplicationError::internal(format!("database task join failure: {err}"))
+        })??;
+
+        Ok(Box::new(PostgresUnitOfWork {
+            connection: Arc::new(Mutex::new(connection)),
+            finished: false,
+            row_level_security: self.row_level_security,
+        }))
+    }
+}
+
+struct PostgresUnitOfWork {
+    connection: Arc<Mutex<PooledPgConnection>>,
+    finished: bool,
+    row_level_security: bool,
+}
+
+impl PostgresUnitOfWork {
+    async fn run_db<T, F>(&self, operation: &'static str, query: F) -> ApplicationResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut PgConnection) -> std::result::Result<T, crate::Error> + Send + 'static,
+    {
+        let connection = self.connection.clone();
+
+        task::spawn_blocking(move || {
+            let mut connection = connection.lock().map_err(|_| {
+                ApplicationError::internal("unit of work connection lock is poisoned")
+            })?;
+            let started = Instant::now();
+            let result = query(&mut connection);
+            record_query(REPOSITORY, operation, started.elapsed(), &result);
+            result.map_err(ApplicationError::from)
+        })
+        .await
+        .map_err(|err| ApplicationError::internal(format!("database task join failure: {err}")))?
+    }
+
+    async fn run_in_tenant<T, F>(
+        &self,
+        operation: &'static str,
+        tenant_id: Option<String>,
+        query: F,
+    ) -> ApplicationResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut PgConnection) -> std::result::Result<T, crate::Error> + Send + 'static,
+    {
+        self.run_bound(
+            operation,
+            move |connection| bind_tenant(connection, tenant_id.as_deref()),
+            query,
+        )
+        .await
+    }
+
+    /// Like [`Self::run_in_tenant`] for the rows of every tenant, see [`bind_all_tenants`].
+    async fn run_across_tenants<T, F>(
+        &self,
+        operation: &'static str,
+        query: F,
+    ) -> ApplicationResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut PgConnection) -> std::result::Result<T, crate::Error> + Send + 'static,
+    {
+        self.run_bound(operation, bind_all_tenants, query).await
+    }
+
+    async fn run_bound<T, B, F>(
+        &self,
+        operation: &'static str,
+        bind: B,
+        query: F,
+    ) -> ApplicationResult<T>
+    where
+        T: Send + 'static,
+        B: FnOnce(&mut PgConnection) -> std::result::Result<(), crate::Error> + Send + 'static,
+        F: FnOnce(&mut PgConnection) -> std::result::Result<T, crate::Error> + Send + 'static,
+    {
+        let row_level_security = self.row_level_security;
+        self.run_db(operation, move |connection| {
+            if row_level_security {
+                bind(connection)?;
+            }
+            query(connection)
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl ToDoItemCommandRepository for PostgresUnitOfWork {
+    async fn get_for_update(
+        &self,
+        todo_item_id: Uuid,
+        scope: AccessScope,
+    ) -> ApplicationResult<ToDoItem> {
+        self.run_in_tenant(
+            "get_for_update",
+            scope.tenant_id.clone(),
+            move |connection| lock_active_by_id(connection, todo_item_id, &scope),
+        )
+        .await
+    }
+
+    async fn get_deleted_for_update(
+        &self,
+        todo_item_id: Uuid,
+        scope: AccessScope,
+    ) -> ApplicationResult<ToDoItem> {
+        self.run_in_tenant(
+            "get_deleted_for_update",
+            scope.tenant_id.clone(),
+            move |connection| lock_deleted_by_id(connection, todo_item_id, &scope),
+        )
+        .await
+    }
+
+    async fn create(&self, entity: ToDoItem) -> ApplicationResult<Uuid> {
+        self.run_in_tenant("create", entity.tenant_id.clone(), move |connection| {
+            insert_item(connection, &entity)
+        })
+        .await
+    }
+
+    async fn create_many(&self, entities: Vec<ToDoItem>) -> ApplicationResult<Vec<Uuid>> {
+        let tenant_id = entities.first().and_then(|entity| entity.tenant_id.clone());
+        self.run_in_tenant("create_many", tenant_id, move |connection| {
+            insert_items(connection, &entities)
+        })
+        .await
+    }
+
+    async fn update(&self, entity: ToDoItem) -> ApplicationResult<ToDoItem> {
+        self.run_in_tenant("update", entity.tenant_id.clone(), move |connection| {
+            update_item(connection, &entity)
+        })
+        .await
+    }
+
+    async fn delete(
+        &self,
+        todo_item_id: Uuid,
+        deleted_by: Option<Uuid>,
+        scope: AccessScope,
+    ) -> ApplicationResult<ToDoItem> {
+        self.run_in_tenant("delete", scope.tenant_id.clone(), move |connection| {
+            soft_delete_item(connection, todo_item_id, deleted_by, &scope)
+        })
+        .await
+    }
+
+    async fn restore(&self, entity: ToDoItem) -> ApplicationResult<Uuid> {
+        self.run_in_tenant("restore", entity.tenant_id.clone(), move |connection| {
+            restore_item(connection, &entity)
+        })
+        .await
+    }
+
+    async fn get_purgeable_ids(
+        &self,
+        deleted_before: DateTime<Utc>,
+        limit: i64,
+    ) -> ApplicationResult<Vec<Uuid>> {
+        self.run_across_tenants("get_purgeable_ids", move |connection| {
+            lock_purgeable_ids(connection, deleted_before, limit)
+        })
+        .await
+    }
+
+    async fn purge(&self, ids: Vec<Uuid>) -> ApplicationResult<()> {
+        self.run_across_tenants("purge", move |connection| purge_items(connection, &ids))
+            .await
+    }
+}
+
+#[async_trait]
+impl OutboxRepository for PostgresUnitOfWork {
+    async fn append(&self, events: Vec<ToDoItemEvent>) -> ApplicationResult<()> {
+        self.run_db("append_events", move |connection| {
+            append_messages(connection, events)
+        })
+        .await
+    }
+
+    async fn fetch_pending(&self, limit: i64) -> ApplicationResult<Vec<OutboxMessage>> {
+        self.run_db("fetch_pending", move |connection| {
+            fetch_pending_messages(connection, limit)
+        })
+        .await
+    }
+
+    async fn mark_published(&self, id: Uuid) -> ApplicationResult<()> {
+        self.run_db("mark_published", move |connection| {
+            mark_message_published(connection, id)
+        })
+        .await
+    }
+
+    async fn mark_failed(
+        &self,
+        id: Uuid,
+        error: String,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> ApplicationResult<()> {
+        self.run_db("mark_failed", move |connection| {
+            mark_message_failed(connection, id, error, retry_at)
+        })
+        .await
+    }
+
+    async fn purge_aggregates(&self, aggregate_ids: Vec<Uuid>) -> ApplicationResult<()> {
+        self.run_db("purge_events", move |connection| {
+            delete_messages_of(connection, &aggregate_ids)
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl RevisionRepository for PostgresUnitOfWork {
+    async fn append(&self, revisions: Vec<ToDoItemRevision>) -> ApplicationResult<()> {
+        let tenant_id = revisions
+            .first()
+            .and_then(|revision| revision.snapshot.tenant_id.clone());
+        self.run_in_tenant("append_revisions", tenant_id, move |connection| {
+            append_revisions(connection, revisions)
+        })
+        .await
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for PostgresUnitOfWork {
+    fn to_do_items(&self) -> &dyn ToDoItemCommandRepository {
+        self
+    }
+
+    fn outbox(&self) -> &dyn OutboxRepository {
+        self
+    }
+
+    fn revisions(&self) -> &dyn RevisionRepository {
+        self
+    }
+
+    async fn commit(mut self: Box<Self>) -> ApplicationResult<()> {
+        self.run_db("commit", |connection| {
+            AnsiTransactionManager::commit_transaction(connection).map_err(map_diesel_error)
+        })
+        .await?;
+        self.finished = true;
+        Ok(())
+    }
+
+    async fn rollback(mut self: Box<Self>) -> ApplicationResult<()> {
+        self.run_db("rollback", |connection| {
+            AnsiTransactionManager::rollback_transaction(connection).map_err(map_diesel_error)
+        })
+        .await?;
+        self.finished = true;
+        Ok(())
+    }
+}
+
+impl Drop for PostgresUnitOfWork {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        // A connection that goes back to the pool with an open transaction is discarded by
+        // r2d2, so rolling back here only keeps the connection reusable.
+        let connection = self.connection.clone();
+        if let Ok(handle) = Handle::try_current() {
+            handle.spawn_blocking(move || {
+                if let Ok(mut connection) = connection.lock() {
+                    let _ = AnsiTransactionManager::rollback_transaction(&mut **connection);
+                }
+            });
+        }
+    }
+}
@@ -0,0 +1,120 @@
+use crate::postgres_outbox::{
+    DbOutboxMessage, NewDbOutboxMessage, NO_EARLIER_MESSAGE_AWAITING_RETRY,
+};
+use crate::tokio_postgres_repositories::{execute, fetch, SqlQuery};
+use application::OutboxMessage;
+use chrono::{DateTime, Utc};
+use deadpool_postgres::GenericClient;
+use domain::ToDoItemEvent;
+use uuid::Uuid;
+
+pub(crate) async fn append_messages<C: GenericClient>(
+    client: &C,
+    events: Vec<ToDoItemEvent>,
+) -> std::result::Result<(), crate::Error> {
+    if events.is_empty() {
+        return Ok(());
+    }
+
+    let mut query = SqlQuery::new(
+        "INSERT INTO outbox (id, aggregate_id, event_type, payload, occurred_at) VALUES ",
+    )
+    .uncached();
+    for (position, event) in events.into_iter().enumerate() {
+        let message = NewDbOutboxMessage::try_from(event)?;
+        if position > 0 {
+            query.push(", ");
+        }
+        query
+            .push("(")
+            .bind(message.id)
+            .push(", ")
+            .bind(message.aggregate_id)
+            .push(", ")
+            .bind(message.event_type)
+            .push(", ")
+            .bind(message.payload)
+            .push(", ")
+            .bind(message.occurred_at)
+            .push(")");
+    }
+    execute(client, &query).await?;
+    Ok(())
+}
+
+pub(crate) async fn fetch_pending_messages<C: GenericClient>(
+    client: &C,
+    limit: i64,
+) -> std::result::Result<Vec<OutboxMessage>, crate::Error> {
+    let mut query = SqlQuery::new(
+        "SELECT id, aggregate_id, event_type, payload, occurred_at, published_at, attempts, \
+         last_error, next_attempt_at, failed_at FROM outbox WHERE published_at IS NULL \
+         AND failed_at IS NULL AND (next_attempt_at IS NULL OR next_attempt_at <= now()) AND ",
+    );
+    query
+        .push(NO_EARLIER_MESSAGE_AWAITING_RETRY)
+        .push(" ORDER BY occurred_at ASC, id ASC LIMIT ")
+        .bind(limit)
+        .push(" FOR UPDATE SKIP LOCKED");
+
+    fetch(client, &query)
+        .await?
+        .iter()
+        .map(|row| {
+            OutboxMessage::try_from(DbOutboxMessage {
+                id: row.get("id"),
+                aggregate_id: row.get("aggregate_id"),
+                event_type: row.get("event_type"),
+                payload: row.get("payload"),
+                occurred_at: row.get("occurred_at"),
+                _published_at: row.get("published_at"),
+                attempts: row.get("attempts"),
+                _last_error: row.get("last_error"),
+                _next_attempt_at: row.get("next_attempt_at"),
+                _failed_at: row.get("failed_at"),
+            })
+        })
+        .collect()
+}
+
+pub(crate) async fn mark_message_published<C: GenericClient>(
+    client: &C,
+    message_id: Uuid,
+) -> std::result::Result<(), crate::Error> {
+    let mut query = SqlQuery::new("UPDATE outbox SET published_at = ");
+    query
+        .bind(Utc::now())
+        .push(", attempts = attempts + 1, last_error = NULL WHERE id = ")
+        .bind(message_id);
+    execute(client, &query).await?;
+    Ok(())
+}
+
+pub(crate) async fn mark_message_failed<C: GenericClient>(
+    client: &C,
+    message_id: Uuid,
+    error: String,
+    retry_at: Option<DateTime<Utc>>,
+) -> std::result::Result<(), crate::Error> {
+    let mut query = SqlQuery::new("UPDATE outbox SET attempts = attempts + 1, last_error = ");
+    query
+        .bind(error)
+        .push(", next_attempt_at = ")
+        .bind(retry_at)
+        .push(", failed_at = ")
+        .bind(retry_at.is_none().then(Utc::now))
+        .push(" WHERE id = ")
+        .bind(message_id);
+    execute(client, &query).await?;
+    Ok(())
+}
+
+pub(crate) async fn delete_messages_of<C: GenericClient>(
+    client: &C,
+    aggregate_ids: &[Uuid],
+) -> std::result::Result<(), crate::Error> {
+    let mut query = SqlQuery::new("DELETE FROM outbox WHERE aggregate_id = ANY(");
+    query.bind(aggregate_ids.to_vec()).push(")");
+    execute(client, &query).await?;
+    Ok(())
+}
@@ -0,0 +1,1236 @@
+use crate::db_metrics::{observe, record_connection_wait};
+use crate::errors::Error::{InternalError, ItemNotFound, VersionConflict};
+use crate::postgres_repositories::{status_rank_sql, ts_query_prefix, INSERT_CHUNK_SIZE};
+use crate::tokio_postgres_revisions::{find_revision, load_history, load_revision_range};
+use crate::AsyncDbPool;
+use application::{
+    AccessScope, ApplicationError, ApplicationResult, DeletedToDoItemFilter, GetAllToDoItemsQuery,
+    GetDeletedToDoItemsForAuditQuery, KeysetPage, NullsOrder, PaginatedResult, SearchMode,
+    SortDirection, ToDoItemCommandRepository, ToDoItemCursor, ToDoItemFilter, ToDoItemMapper,
+    ToDoItemQueryRepository, ToDoItemRevision, ToDoItemSort, ToDoItemSortField, ToDoItemSortKey,
+    ToDoItemSortValue,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use deadpool_postgres::{GenericClient, Object, Transaction};
+use domain::{ToDoItem, ToDoItemStatus};
+use std::collections::HashMap;
+use std::time::Instant;
+use tokio_postgres::types::ToSql;
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+const REPOSITORY: &str = "to_do_items";
+
+/// Columns of `to_do_items` that `ToDoItemMapper` reads.
+const ITEM_COLUMNS: &str = "id, title, note, status, created_at, updated_at, due_at, version, \
+    deleted_at, deleted_by, restored_at, restored_by, owner_id, updated_by, tenant_id";
+
+/// Same statements as `PostgresToDoItemRepository`, sent through an async `tokio-postgres`
+/// pool instead of blocking threads.
+pub struct TokioPostgresToDoItemRepository {
+    pool: AsyncDbPool,
+    row_level_security: bool,
+}
+
+impl TokioPostgresToDoItemRepository {
+    pub fn new(pool: &AsyncDbPool) -> Self {
+        Self {
+            pool: pool.clone(),
+            row_level_security: false,
+        }
+    }
+
+    /// Binds every operation to its tenant before running it, for the row-level security
+    /// policies on `to_do_items`.
+    pub fn with_row_level_security(mut self, enabled: bool) -> Self {
+        self.row_level_security = enabled;
+        self
+    }
+
+    async fn connection(&self) -> ApplicationResult<Object> {
+        let waiting = Instant::now();
+        let connection = self.pool.get().await;
+        record_connection_wait(REPOSITORY, waiting.elapsed());
+        connection.map_err(|err| {
+            ApplicationError::internal(format!("failed to acquire database connection: {err}"))
+        })
+    }
+
+    /// Opens the transaction of an operation whose statements must apply together, bound
+    /// to `tenant_id` like in [`in_tenant`].
+    async fn begin<'c>(
+        &self,
+        connection: &'c mut Object,
+        tenant_id: Option<&str>,
+    ) -> std::result::Result<Transaction<'c>, crate::Error> {
+        let transaction = connection.transaction().await.map_err(map_postgres_error)?;
+        if self.row_level_security {
+            bind_tenant(&transaction, tenant_id).await?;
+        }
+        Ok(transaction)
+    }
+}
+
+async fn commit(transaction: Transaction<'_>) -> std::result::Result<(), crate::Error> {
+    transaction.commit().await.map_err(map_postgres_error)
+}
+
+/// Evaluates `$body` with `$client` borrowing `$connection`. With row-level security the
+/// statements run in a transaction that `$bind` binds first, so the binding never outlives
+/// the operation on a pooled connection; otherwise they run on their own, without the
+/// `BEGIN` and `COMMIT` round trips.
+macro_rules! in_tenant {
+    ($repository:ident, $connection:ident, $bind:ident($($arg:expr),*), |$client:ident| $body:expr) => {
+        if $repository.row_level_security {
+            let transaction = $connection.transaction().await.map_err(map_postgres_error)?;
+            $bind(&transaction $(, $arg)*).await?;
+            let $client = &transaction;
+            let result: std::result::Result<_, crate::Error> = $body;
+            let value = result?;
+            commit(transaction).await?;
+            Ok(value)
+        } else {
+            let $client = &$connection;
+            let result: std::result::Result<_, crate::Error> = $body;
+            result
+        }
+    };
+}
+
+#[async_trait]
+impl ToDoItemQueryRepository for TokioPostgresToDoItemRepository {
+    async fn get_all(
+        &self,
+        query: GetAllToDoItemsQuery,
+    ) -> ApplicationResult<PaginatedResult<ToDoItem>> {
+        let mut connection = self.connection().await?;
+        Ok(observe(REPOSITORY, "get_all", async {
+            in_tenant!(
+                self,
+                connection,
+                bind_tenant(query.scope.tenant_id.as_deref()),
+                |client| {
+                    let mut result = match &query.keyset {
+                        Some(keyset) => load_keyset_page(client, &query, keyset).await?,
+                        None => load_offset_page(client, &query).await?,
+                    };
+                    if let Some(search) = query.search.as_deref() {
+                        let highlights =
+                            load_highlights(client, &result.items, search, query.search_mode)
+                                .await?;
+                        result = result.with_highlights(highlights);
+                    }
+                    Ok(result)
+                }
+            )
+        })
+        .await?)
+    }
+
+    async fn get_by_id(
+        &self,
+        todo_item_id: Uuid,
+        scope: AccessScope,
+    ) -> ApplicationResult<ToDoItem> {
+        let mut connection = self.connection().await?;
+        let query = select_by_id(todo_item_id, false, &scope);
+        Ok(observe(REPOSITORY, "get_by_id", async {
+            in_tenant!(
+                self,
+                connection,
+                bind_tenant(scope.tenant_id.as_deref()),
+                |client| {
+                    let result = first_item(client, &query, todo_item_id).await?;
+                    Ok(result)
+                }
+            )
+        })
+        .await?)
+    }
+
+    async fn get_deleted_by_id_for_audit(
+        &self,
+        todo_item_id: Uuid,
+        scope: AccessScope,
+    ) -> ApplicationResult<ToDoItem> {
+        let mut connection = self.connection().await?;
+        let query = select_by_id(todo_item_id, true, &scope);
+        Ok(observe(REPOSITORY, "get_deleted_by_id_for_audit", async {
+            in_tenant!(
+                self,
+                connection,
+                bind_tenant(scope.tenant_id.as_deref()),
+                |client| {
+                    let result = first_item(client, &query, todo_item_id).await?;
+                    Ok(result)
+                }
+            )
+        })
+        .await?)
+    }
+
+    async fn get_deleted_for_audit(
+        &self,
+        query: GetDeletedToDoItemsForAuditQuery,
+    ) -> ApplicationResult<PaginatedResult<ToDoItem>> {
+        let mut connection = self.connection().await?;
+        Ok(observe(REPOSITORY, "get_deleted_for_audit", async {
+            in_tenant!(
+                self,
+                connection,
+                bind_tenant(query.scope.tenant_id.as_deref()),
+                |client| {
+                    let total_items =
+                        count(client, &build_deleted_query("count(*)", &query)).await?;
+
+                    let mut items_query = build_deleted_query(ITEM_COLUMNS, &query);
+                    items_query.push(" ORDER BY ");
+                    push_sort_keys(&mut items_query, &query.sort);
+                    items_query
+                        .push(" LIMIT ")
+                        .bind(query.limit())
+                        .push(" OFFSET ")
+                        .bind(query.offset());
+                    let items = map_items(fetch(client, &items_query).await?)?;
+
+                    Ok(PaginatedResult::new(
+                        items,
+                        query.page,
+                        query.page_size,
+                        total_items,
+                    ))
+                }
+            )
+        })
+        .await?)
+    }
+
+    async fn get_history(
+        &self,
+        todo_item_id: Uuid,
+        page: u32,
+        page_size: u32,
+        scope: AccessScope,
+    ) -> ApplicationResult<PaginatedResult<ToDoItemRevision>> {
+        let mut connection = self.connection().await?;
+        Ok(observe(REPOSITORY, "get_history", async {
+            in_tenant!(
+                self,
+                connection,
+                bind_tenant(scope.tenant_id.as_deref()),
+                |client| {
+                    let result =
+                        load_history(client, todo_item_id, page, page_size, &scope).await?;
+                    Ok(result)
+                }
+            )
+        })
+        .await?)
+    }
+
+    async fn get_revision(
+        &self,
+        todo_item_id: Uuid,
+        version: i32,
+        scope: AccessScope,
+    ) -> ApplicationResult<ToDoItemRevision> {
+        let mut connection = self.connection().await?;
+        Ok(observe(REPOSITORY, "get_revision", async {
+            in_tenant!(
+                self,
+                connection,
+                bind_tenant(scope.tenant_id.as_deref()),
+                |client| {
+                    let result = find_revision(client, todo_item_id, version, &scope).await?;
+                    Ok(result)
+                }
+            )
+        })
+        .await?)
+    }
+
+    async fn get_revisions(
+        &self,
+        todo_item_id: Uuid,
+        from: i32,
+        to: i32,
+        scope: AccessScope,
+    ) -> ApplicationResult<Vec<ToDoItemRevision>> {
+        let mut connection = self.connection().await?;
+        Ok(observe(REPOSITORY, "get_revisions", async {
+            in_tenant!(
+                self,
+                connection,
+                bind_tenant(scope.tenant_id.as_deref()),
+                |client| {
+                    let result =
+                        load_revision_range(client, todo_item_id, from, to, &scope).await?;
+                    Ok(result)
+                }
+            )
+        })
+        .await?)
+    }
+}
+
+#[async_trait]
+impl ToDoItemCommandRepository for TokioPostgresToDoItemRepository {
+    async fn get_for_update(
+        &self,
+        todo_item_id: Uuid,
+        scope: AccessScope,
+    ) -> ApplicationResult<ToDoItem> {
+        let mut connection = self.connection().await?;
+        Ok(observe(REPOSITORY, "get_for_update", async {
+            in_tenant!(
+                self,
+                connection,
+                bind_tenant(scope.tenant_id.as_deref()),
+                |client| {
+                    let result = lock_active_by_id(client, todo_item_id, &scope).await?;
+                    Ok(result)
+                }
+            )
+        })
+        .await?)
+    }
+
+    async fn get_deleted_for_update(
+        &self,
+        todo_item_id: Uuid,
+        scope: AccessScope,
+    ) -> ApplicationResult<ToDoItem> {
+        let mut connection = self.connection().await?;
+        Ok(observe(REPOSITORY, "get_deleted_for_update", async {
+            in_tenant!(
+                self,
+                connection,
+                bind_tenant(scope.tenant_id.as_deref()),
+                |client| {
+                    let result = lock_deleted_by_id(client, todo_item_id, &scope).await?;
+                    Ok(result)
+                }
+            )
+        })
+        .await?)
+    }
+
+    async fn create(&self, entity: ToDoItem) -> ApplicationResult<Uuid> {
+        let mut connection = self.connection().await?;
+        Ok(observe(REPOSITORY, "create", async {
+            in_tenant!(
+                self,
+                connection,
+                bind_tenant(entity.tenant_id.as_deref()),
+                |client| {
+                    let result = insert_item(client, &entity).await?;
+                    Ok(result)
+                }
+            )
+        })
+        .await?)
+    }
+
+    async fn create_many(&self, entities: Vec<ToDoItem>) -> ApplicationResult<Vec<Uuid>> {
+        let tenant_id = entities
+            .first()
+            .and_then(|entity| entity.tenant_id.as_deref());
+        let mut connection = self.connection().await?;
+        Ok(observe(REPOSITORY, "create_many", async {
+            let client = self.begin(&mut connection, tenant_id).await?;
+            let ids = insert_items(&client, &entities).await?;
+            commit(client).await?;
+            Ok(ids)
+        })
+        .await?)
+    }
+
+    async fn update(&self, entity: ToDoItem) -> ApplicationResult<ToDoItem> {
+        let mut connection = self.connection().await?;
+        Ok(observe(REPOSITORY, "update", async {
+            in_tenant!(
+                self,
+                connection,
+                bind_tenant(entity.tenant_id.as_deref()),
+                |client| {
+                    let result = update_item(client, &entity).await?;
+                    Ok(result)
+                }
+            )
+        })
+        .await?)
+    }
+
+    async fn delete(
+        &self,
+        todo_item_id: Uuid,
+        deleted_by: Option<Uuid>,
+        scope: AccessScope,
+    ) -> ApplicationResult<ToDoItem> {
+        let mut connection = self.connection().await?;
+        Ok(observe(REPOSITORY, "delete", async {
+            in_tenant!(
+                self,
+                connection,
+                bind_tenant(scope.tenant_id.as_deref()),
+                |client| {
+                    let result = soft_delete_item(client, todo_item_id, deleted_by, &scope).await?;
+                    Ok(result)
+                }
+            )
+        })
+        .await?)
+    }
+
+    async fn restore(&self, entity: ToDoItem) -> ApplicationResult<Uuid> {
+        let mut connection = self.connection().await?;
+        Ok(observe(REPOSITORY, "restore", async {
+            in_tenant!(
+                self,
+                connection,
+                bind_tenant(entity.tenant_id.as_deref()),
+                |client| {
+                    let result = restore_item(client, &entity).await?;
+                    Ok(result)
+                }
+            )
+        })
+        .await?)
+    }
+
+    async fn get_purgeable_ids(
+        &self,
+        deleted_before: DateTime<Utc>,
+        limit: i64,
+    ) -> ApplicationResult<Vec<Uuid>> {
+        let mut connection = self.connection().await?;
+        Ok(observe(REPOSITORY, "get_purgeable_ids", async {
+            in_tenant!(self, connection, bind_all_tenants(), |client| {
+                let result = lock_purgeable_ids(client, deleted_before, limit).await?;
+                Ok(result)
+            })
+        })
+        .await?)
+    }
+
+    async fn purge(&self, ids: Vec<Uuid>) -> ApplicationResult<()> {
+        let mut connection = self.connection().await?;
+        Ok(observe(REPOSITORY, "purge", async {
+            in_tenant!(self, connection, bind_all_tenants(), |client| {
+                let result = purge_items(client, &ids).await?;
+                Ok(result)
+            })
+        })
+        .await?)
+    }
+}
+
+type SqlParam = Box<dyn ToSql + Send + Sync>;
+
+/// Statement text and its positional parameters, assembled piece by piece the way Diesel
+/// builds boxed queries.
+pub(crate) struct SqlQuery {
+    sql: String,
+    params: Vec<SqlParam>,
+    cached: bool,
+}
+
+impl SqlQuery {
+    pub(crate) fn new(sql: &str) -> Self {
+        Self {
+            sql: sql.to_string(),
+            params: Vec::new(),
+            cached: true,
+        }
+    }
+
+    /// Skips the per-connection statement cache, for statements whose text depends on the
+    /// number of rows, such as multi-row inserts.
+    pub(crate) fn uncached(mut self) -> Self {
+        self.cached = false;
+        self
+    }
+
+    pub(crate) fn push(&mut self, sql: &str) -> &mut Self {
+        self.sql.push_str(sql);
+        self
+    }
+
+    /// Appends the placeholder of `value`.
+    pub(crate) fn bind<T>(&mut self, value: T) -> &mut Self
+    where
+        T: ToSql + Send + Sync + 'static,
+    {
+        self.params.push(Box::new(value));
+        self.sql.push_str(&format!("${}", self.params.len()));
+        self
+    }
+
+    fn params(&self) -> Vec<&(dyn ToSql + Sync)> {
+        self.params
+            .iter()
+            .map(|param| param.as_ref() as &(dyn ToSql + Sync))
+            .collect()
+    }
+}
+
+pub(crate) async fn fetch<C: GenericClient>(
+    client: &C,
+    query: &SqlQuery,
+) -> std::result::Result<Vec<Row>, crate::Error> {
+    let params = query.params();
+    if query.cached {
+        let statement = client
+            .prepare_cached(&query.sql)
+            .await
+            .map_err(map_postgres_error)?;
+        client.query(&statement, &params).await
+    } else {
+        client.query(query.sql.as_str(), &params).await
+    }
+    .map_err(map_postgres_error)
+}
+
+pub(crate) async fn execute<C: GenericClient>(
+    client: &C,
+    query: &SqlQuery,
+) -> std::result::Result<u64, crate::Error> {
+    let params = query.params();
+    if query.cached {
+        let statement = client
+            .prepare_cached(&query.sql)
+            .await
+            .map_err(map_postgres_error)?;
+        client.execute(&statement, &params).await
+    } else {
+        client.execute(query.sql.as_str(), &params).await
+    }
+    .map_err(map_postgres_error)
+}
+
+pub(crate) async fn count<C: GenericClient>(
+    client: &C,
+    query: &SqlQuery,
+) -> std::result::Result<i64, crate::Error> {
+    fetch(client, query)
+        .await?
+        .first()
+        .map(|row| row.get::<_, i64>(0))
+        .ok_or_else(|| InternalError("count query returned no row".into()))
+}
+
+fn map_items(rows: Vec<Row>) -> std::result::Result<Vec<ToDoItem>, crate::Error> {
+    ToDoItemMapper::from_vec(rows).map_err(|err| InternalError(err.to_string()))
+}
+
+/// Binds the current transaction to `tenant_id` for the row-level security policies on
+/// `to_do_items`, see [`crate::postgres_repositories::bind_tenant`].
+pub(crate) async fn bind_tenant<C: GenericClient>(
+    client: &C,
+    tenant_id: Option<&str>,
+) -> std::result::Result<(), crate::Error> {
+    set_tenant_binding(client, tenant_id.unwrap_or_default(), false).await
+}
+
+/// Opens the rows of every tenant to the current transaction, see
+/// [`crate::postgres_repositories::bind_all_tenants`].
+pub(crate) async fn bind_all_tenants<C: GenericClient>(
+    client: &C,
+) -> std::result::Result<(), crate::Error> {
+    set_tenant_binding(client, "", true).await
+}
+
+async fn set_tenant_binding<C: GenericClient>(
+    client: &C,
+    tenant_id: &str,
+    all_tenants: bool,
+) -> std::result::Result<(), crate::Error> {
+    let mut query = SqlQuery::new("SELECT set_config('app.tenant_id', ");
+    query
+        .bind(tenant_id.to_string())
+        .push(", true), set_config('app.all_tenants', ")
+        .bind(if all_tenants { "on" } else { "off" }.to_string())
+        .push(", true)");
+    execute(client, &query).await?;
+    Ok(())
+}
+
+pub(crate) async fn insert_item<C: GenericClient>(
+    client: &C,
+    entity: &ToDoItem,
+) -> std::result::Result<Uuid, crate::Error> {
+    insert_items(client, std::slice::from_ref(entity)).await?;
+    Ok(entity.id)
+}
+
+pub(crate) async fn insert_items<C: GenericClient>(
+    client: &C,
+    entities: &[ToDoItem],
+) -> std::result::Result<Vec<Uuid>, crate::Error> {
+    for chunk in entities.chunks(INSERT_CHUNK_SIZE) {
+        let mut query =
+            SqlQuery::new(&format!("INSERT INTO to_do_items ({ITEM_COLUMNS}) VALUES ")).uncached();
+        for (position, entity) in chunk.iter().enumerate() {
+            if position > 0 {
+                query.push(", ");
+            }
+            push_item_values(&mut query, entity);
+        }
+        execute(client, &query).await?;
+    }
+    Ok(entities.iter().map(|entity| entity.id).collect())
+}
+
+fn push_item_values(query: &mut SqlQuery, entity: &ToDoItem) {
+    query
+        .push("(")
+        .bind(entity.id)
+        .push(", ")
+        .bind(entity.title.clone())
+        .push(", ")
+        .bind(entity.note.clone())
+        .push(", ")
+        .bind(entity.status.as_str().to_string())
+        .push(", ")
+        .bind(entity.created_at)
+        .push(", ")
+        .bind(entity.updated_at)
+        .push(", ")
+        .bind(entity.due_at)
+        .push(", ")
+        .bind(entity.version)
+        .push(", ")
+        .bind(entity.deleted_at)
+        .push(", ")
+        .bind(entity.deleted_by)
+        .push(", ")
+        .bind(entity.restored_at)
+        .push(", ")
+        .bind(entity.restored_by)
+        .push(", ")
+        .bind(entity.owner_id)
+        .push(", ")
+        .bind(entity.updated_by)
+        .push(", ")
+        .bind(entity.tenant_id.clone())
+        .push(")");
+}
+
+/// Writes the changes of `entity` if its version is still current and returns the row as
+/// stored, with the new version and `updated_at`.
+pub(crate) async fn update_item<C: GenericClient>(
+    client: &C,
+    entity: &ToDoItem,
+) -> std::result::Result<ToDoItem, crate::Error> {
+    let mut query = SqlQuery::new("UPDATE to_do_items SET title = ");
+    query
+        .bind(entity.title.clone())
+        .push(", note = ")
+        .bind(entity.note.clone())
+        .push(", status = ")
+        .bind(entity.status.as_str().to_string())
+        .push(", due_at = ")
+        .bind(entity.due_at)
+        .push(", updated_at = ")
+        .bind(Utc::now())
+        .push(", updated_by = ")
+        .bind(entity.updated_by)
+        .push(", version = ")
+        .bind(entity.version + 1)
+        .push(" WHERE id = ")
+        .bind(entity.id)
+        .push(" AND version = ")
+        .bind(entity.version)
+        .push(" AND deleted_at IS NULL");
+    push_tenant_filter(&mut query, entity.tenant_id.as_deref());
+    query.push(&format!(" RETURNING {ITEM_COLUMNS}"));
+
+    if let Some(updated) = map_items(fetch(client, &query).await?)?.into_iter().next() {
+        return Ok(updated);
+    }
+    Err(version_error(
+        entity,
+        current_version(client, entity, false).await?,
+    ))
+}
+
+pub(crate) async fn soft_delete_item<C: GenericClient>(
+    client: &C,
+    todo_item_id: Uuid,
+    deleted_by: Option<Uuid>,
+    scope: &AccessScope,
+) -> std::result::Result<ToDoItem, crate::Error> {
+    let mut query = SqlQuery::new("UPDATE to_do_items SET deleted_at = ");
+    query
+        .bind(Utc::now())
+        .push(", deleted_by = ")
+        .bind(deleted_by)
+        .push(", version = version + 1 WHERE id = ")
+        .bind(todo_item_id)
+        .push(" AND deleted_at IS NULL");
+    push_scope_filter(&mut query, scope);
+    query.push(&format!(" RETURNING {ITEM_COLUMNS}"));
+
+    map_items(fetch(client, &query).await?)?
+        .into_iter()
+        .next()
+        .ok_or(ItemNotFound { id: todo_item_id })
+}
+
+/// Clears the deletion metadata of a soft-deleted item and records who restored it.
+pub(crate) async fn restore_item<C: GenericClient>(
+    client: &C,
+    entity: &ToDoItem,
+) -> std::result::Result<Uuid, crate::Error> {
+    let mut query = SqlQuery::new(
+        "UPDATE to_do_items SET deleted_at = NULL, deleted_by = NULL, restored_at = ",
+    );
+    query
+        .bind(entity.restored_at)
+        .push(", restored_by = ")
+        .bind(entity.restored_by)
+        .push(", updated_at = ")
+        .bind(entity.updated_at)
+        .push(", version = ")
+        .bind(entity.version + 1)
+        .push(" WHERE id = ")
+        .bind(entity.id)
+        .push(" AND version = ")
+        .bind(entity.version)
+        .push(" AND deleted_at IS NOT NULL");
+    push_tenant_filter(&mut query, entity.tenant_id.as_deref());
+
+    if execute(client, &query).await? == 1 {
+        return Ok(entity.id);
+    }
+    Err(version_error(
+        entity,
+        current_version(client, entity, true).await?,
+    ))
+}
+
+/// Reads the stored version of `entity` after a versioned write matched no row.
+async fn current_version<C: GenericClient>(
+    client: &C,
+    entity: &ToDoItem,
+    deleted: bool,
+) -> std::result::Result<Option<i32>, crate::Error> {
+    let mut query = SqlQuery::new("SELECT version FROM to_do_items WHERE id = ");
+    query.bind(entity.id).push(deleted_condition(deleted));
+    push_tenant_filter(&mut query, entity.tenant_id.as_deref());
+    query.push(" LIMIT 1");
+
+    Ok(fetch(client, &query)
+        .await?
+        .first()
+        .map(|row| row.get::<_, i32>(0)))
+}
+
+fn version_error(entity: &ToDoItem, actual_version: Option<i32>) -> crate::Error {
+    match actual_version {
+        Some(actual_version) => VersionConflict {
+            id: entity.id,
+            expected_version: entity.version,
+            actual_version,
+        },
+        None => ItemNotFound { id: entity.id },
+    }
+}
+
+/// Locks up to `limit` items soft-deleted before `deleted_before`, skipping rows that a
+/// concurrent purge already holds.
+pub(crate) async fn lock_purgeable_ids<C: GenericClient>(
+    client: &C,
+    deleted_before: DateTime<Utc>,
+    limit: i64,
+) -> std::result::Result<Vec<Uuid>, crate::Error> {
+    let mut query = SqlQuery::new("SELECT id FROM to_do_items WHERE deleted_at < ");
+    query
+        .bind(deleted_before)
+        .push(" ORDER BY deleted_at ASC, id ASC LIMIT ")
+        .bind(limit)
+        .push(" FOR UPDATE SKIP LOCKED");
+
+    Ok(fetch(client, &query)
+        .await?
+        .iter()
+        .map(|row| row.get::<_, Uuid>(0))
+        .collect())
+}
+
+pub(crate) async fn purge_items<C: GenericClient>(
+    client: &C,
+    ids: &[Uuid],
+) -> std::result::Result<(), crate::Error> {
+    let mut query = SqlQuery::new("DELETE FROM to_do_items WHERE id = ANY(");
+    query
+        .bind(ids.to_vec())
+        .push(") AND deleted_at IS NOT NULL");
+    execute(client, &query).await?;
+    Ok(())
+}
+
+/// Loads an active item and locks its row until the surrounding transaction ends, so the
+/// version check in a command handler cannot race with a concurrent writer.
+pub(crate) async fn lock_active_by_id<C: GenericClient>(
+    client: &C,
+    todo_item_id: Uuid,
+    scope: &AccessScope,
+) -> std::result::Result<ToDoItem, crate::Error> {
+    let mut query = select_by_id(todo_item_id, false, scope);
+    query.push(" FOR UPDATE");
+    first_item(client, &query, todo_item_id).await
+}
+
+/// Loads a soft-deleted item and locks its row until the surrounding transaction ends.
+pub(crate) async fn lock_deleted_by_id<C: GenericClient>(
+    client: &C,
+    todo_item_id: Uuid,
+    scope: &AccessScope,
+) -> std::result::Result<ToDoItem, crate::Error> {
+    let mut query = select_by_id(todo_item_id, true, scope);
+    query.push(" FOR UPDATE");
+    first_item(client, &query, todo_item_id).await
+}
+
+fn select_by_id(todo_item_id: Uuid, deleted: bool, scope: &AccessScope) -> SqlQuery {
+    let mut query = SqlQuery::new(&format!(
+        "SELECT {ITEM_COLUMNS} FROM to_do_items WHERE id = "
+    ));
+    query.bind(todo_item_id).push(deleted_condition(deleted));
+    push_scope_filter(&mut query, scope);
+    query.push(" LIMIT 1");
+    query
+}
+
+async fn first_item<C: GenericClient>(
+    client: &C,
+    query: &SqlQuery,
+    todo_item_id: Uuid,
+) -> std::result::Result<ToDoItem, crate::Error> {
+    map_items(fetch(client, query).await?)?
+        .into_iter()
+        .next()
+        .ok_or(ItemNotFound { id: todo_item_id })
+}
+
+fn deleted_condition(deleted: bool) -> &'static str {
+    if deleted {
+        " AND deleted_at IS NOT NULL"
+    } else {
+        " AND deleted_at IS NULL"
+    }
+}
+
+/// Restricts a statement to the rows visible within `scope`.
+fn push_scope_filter(query: &mut SqlQuery, scope: &AccessScope) {
+    push_tenant_filter(query, scope.tenant_id.as_deref());
+    match scope.owner_id {
+        Some(owner_id) if scope.include_unowned => {
+            query
+                .push(" AND (owner_id = ")
+                .bind(owner_id)
+                .push(" OR owner_id IS NULL)");
+        }
+        Some(owner_id) => {
+            query.push(" AND owner_id = ").bind(owner_id);
+        }
+        None => {}
+    }
+}
+
+/// Restricts a statement to the rows of `tenant_id`; `None` leaves it unrestricted.
+fn push_tenant_filter(query: &mut SqlQuery, tenant_id: Option<&str>) {
+    if let Some(tenant_id) = tenant_id {
+        query.push(" AND tenant_id = ").bind(tenant_id.to_string());
+    }
+}
+
+fn build_filtered_query(select: &str, params: &GetAllToDoItemsQuery) -> SqlQuery {
+    let mut query = SqlQuery::new(&format!(
+        "SELECT {select} FROM to_do_items WHERE deleted_at IS NULL"
+    ));
+    push_scope_filter(&mut query, &params.scope);
+    if let Some(search) = params.search.clone() {
+        push_search_condition(&mut query, search, params.search_mode);
+    }
+    push_filter(&mut query, &params.filter);
+    query
+}
+
+fn build_deleted_query(select: &str, params: &GetDeletedToDoItemsForAuditQuery) -> SqlQuery {
+    let mut query = SqlQuery::new(&format!(
+        "SELECT {select} FROM to_do_items WHERE deleted_at IS NOT NULL"
+    ));
+    push_scope_filter(&mut query, &params.scope);
+    if let Some(search) = params.search.clone() {
+        push_search_condition(&mut query, search, params.search_mode);
+    }
+    push_deleted_filter(&mut query, &params.filter);
+    query
+}
+
+/// Matches rows whose `search_vector` matches `search`.
+fn push_search_condition(query: &mut SqlQuery, search: String, mode: SearchMode) {
+    query
+        .push(&format!(" AND search_vector @@ {}", ts_query_prefix(mode)))
+        .bind(search)
+        .push(")");
+}
+
+fn push_filter(query: &mut SqlQuery, filter: &ToDoItemFilter) {
+    if !filter.statuses.is_empty() {
+        let statuses = filter
+            .statuses
+            .iter()
+            .map(|status| status.as_str().to_string())
+            .collect::<Vec<_>>();
+        query.push(" AND status = ANY(").bind(statuses).push(")");
+    }
+    if let Some(due_after) = filter.due_after {
+        query.push(" AND due_at >= ").bind(due_after);
+    }
+    if let Some(due_before) = filter.due_before {
+        query.push(" AND due_at < ").bind(due_before);
+    }
+    if let Some(created_after) = filter.created_after {
+        query.push(" AND created_at >= ").bind(created_after);
+    }
+    if let Some(created_before) = filter.created_before {
+        query.push(" AND created_at < ").bind(created_before);
+    }
+    if let Some(updated_since) = filter.updated_since {
+        query.push(" AND updated_at >= ").bind(updated_since);
+    }
+    if filter.overdue {
+        query
+            .push(" AND due_at < ")
+            .bind(Utc::now())
+            .push(" AND status <> ")
+            .bind(ToDoItemStatus::Done.as_str().to_string());
+    }
+}
+
+fn push_deleted_filter(query: &mut SqlQuery, filter: &DeletedToDoItemFilter) {
+    if let Some(deleted_by) = filter.deleted_by {
+        query.push(" AND deleted_by = ").bind(deleted_by);
+    }
+    if let Some(deleted_after) = filter.deleted_after {
+        query.push(" AND deleted_at >= ").bind(deleted_after);
+    }
+    if let Some(deleted_before) = filter.deleted_before {
+        query.push(" AND deleted_at < ").bind(deleted_before);
+    }
+}
+
+async fn load_offset_page<C: GenericClient>(
+    client: &C,
+    query: &GetAllToDoItemsQuery,
+) -> std::result::Result<PaginatedResult<ToDoItem>, crate::Error> {
+    let total_items = count(client, &build_filtered_query("count(*)", query)).await?;
+
+    let mut items_query = build_filtered_query(ITEM_COLUMNS, query);
+    push_sort(&mut items_query, query);
+    items_query
+        .push(" LIMIT ")
+        .bind(query.limit())
+        .push(" OFFSET ")
+        .bind(query.offset());
+    let items = map_items(fetch(client, &items_query).await?)?;
+
+    Ok(PaginatedResult::new(
+        items,
+        query.page,
+        query.page_size,
+        total_items,
+    ))
+}
+
+/// Builds `ts_headline` snippets for the items of the current page only, so highlighting
+/// never runs over the whole result set.
+async fn load_highlights<C: GenericClient>(
+    client: &C,
+    items: &[ToDoItem],
+    search: &str,
+    mode: SearchMode,
+) -> std::result::Result<HashMap<Uuid, String>, crate::Error> {
+    if items.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let mut query = SqlQuery::new(&format!(
+        "SELECT id, ts_headline('english', concat_ws(' ', title, note), {}",
+        ts_query_prefix(mode)
+    ));
+    query
+        .bind(search.to_string())
+        .push("), 'StartSel=<mark>, StopSel=</mark>, MaxWords=20, MinWords=5') FROM to_do_items WHERE id = ANY(")
+        .bind(items.iter().map(|item| item.id).collect::<Vec<_>>())
+        .push(")");
+
+    Ok(fetch(client, &query)
+        .await?
+        .iter()
+        .map(|row| (row.get::<_, Uuid>(0), row.get::<_, String>(1)))
+        .collect())
+}
+
+/// Loads one row more than requested to find out whether a next page exists, so the
+/// cursor is only returned when there is something left to read.
+async fn load_keyset_page<C: GenericClient>(
+    client: &C,
+    query: &GetAllToDoItemsQuery,
+    keyset: &KeysetPage,
+) -> std::result::Result<PaginatedResult<ToDoItem>, crate::Error> {
+    let total_items = if keyset.include_total {
+        Some(count(client, &build_filtered_query("count(*)", query)).await?)
+    } else {
+        None
+    };
+
+    let mut items_query = build_filtered_query(ITEM_COLUMNS, query);
+    if let Some(cursor) = &keyset.after {
+        push_cursor(&mut items_query, cursor);
+    }
+    push_sort(&mut items_query, query);
+    items_query.push(" LIMIT ").bind(query.limit() + 1);
+    let mut items = map_items(fetch(client, &items_query).await?)?;
+
+    let next_cursor = if items.len() > query.page_size as usize {
+        items.truncate(query.page_size as usize);
+        items
+            .last()
+            .map(|item| ToDoItemCursor::after(item, &query.sort))
+    } else {
+        None
+    };
+
+    Ok(PaginatedResult::keyset(
+        items,
+        query.page_size,
+        total_items,
+        next_cursor,
+    ))
+}
+
+/// Restricts the query to rows that sort after the cursor.
+///
+/// Compares the effective sort keys lexicographically: a row comes after the cursor when
+/// it equals the cursor on the first `n` keys and sorts after it on key `n + 1`.
+fn push_cursor(query: &mut SqlQuery, cursor: &ToDoItemCursor) {
+    let keys = cursor.sort.effective_keys();
+    let bounds = keys.iter().zip(&cursor.values).collect::<Vec<_>>();
+    if bounds.is_empty() {
+        return;
+    }
+
+    query.push(" AND (");
+    for (position, (key, value)) in bounds.iter().enumerate() {
+        if position > 0 {
+            query.push(" OR ");
+        }
+        query.push("(");
+        for (key, value) in &bounds[..position] {
+            push_sort_key_equals(query, key, value);
+            query.push(" AND ");
+        }
+        push_sort_key_after(query, key, value);
+        query.push(")");
+    }
+    query.push(")");
+}
+
+fn push_sort_key_equals(query: &mut SqlQuery, key: &ToDoItemSortKey, value: &ToDoItemSortValue) {
+    match (key.field, value) {
+        (ToDoItemSortField::Id, ToDoItemSortValue::Id(id)) => query.push("id = ").bind(*id),
+        (ToDoItemSortField::Title, ToDoItemSortValue::Text(Some(title))) => {
+            query.push("title = ").bind(title.clone())
+        }
+        (ToDoItemSortField::Title, ToDoItemSortValue::Text(None)) => query.push("title IS NULL"),
+        (ToDoItemSortField::Status, ToDoItemSortValue::Status(status)) => {
+            query.push("status = ").bind(status.as_str().to_string())
+        }
+        (ToDoItemSortField::CreatedAt, ToDoItemSortValue::Timestamp(Some(created_at))) => {
+            query.push("created_at = ").bind(*created_at)
+        }
+        (ToDoItemSortField::UpdatedAt, ToDoItemSortValue::Timestamp(Some(updated_at))) => {
+            query.push("updated_at = ").bind(*updated_at)
+        }
+        (ToDoItemSortField::DueAt, ToDoItemSortValue::Timestamp(Some(due_at))) => {
+            query.push("due_at = ").bind(*due_at)
+        }
+        (ToDoItemSortField::DueAt, ToDoItemSortValue::Timestamp(None)) => {
+            query.push("due_at IS NULL")
+        }
+        _ => query.push("FALSE"),
+    };
+}
+
+/// Matches rows that sort strictly after `value` on this key, honouring its direction
+/// and the placement of `NULL` values.
+fn push_sort_key_after(query: &mut SqlQuery, key: &ToDoItemSortKey, value: &ToDoItemSortValue) {
+    let comparison = match key.direction {
+        SortDirection::Asc => " > ",
+        SortDirection::Desc => " < ",
+    };
+    let nulls_last = key.nulls == NullsOrder::Last;
+
+    match (key.field, value) {
+        (ToDoItemSortField::Id, ToDoItemSortValue::Id(id)) => {
+            query.push("id").push(comparison).bind(*id)
+        }
+        (ToDoItemSortField::Title, ToDoItemSortValue::Text(Some(title))) => {
+            query.push("(title").push(comparison).bind(title.clone());
+            if nulls_last {
+                query.push(" OR title IS NULL");
+            }
+            query.push(")")
+        }
+        (ToDoItemSortField::Title, ToDoItemSortValue::Text(None)) if nulls_last => {
+            query.push("FALSE")
+        }
+        (ToDoItemSortField::Title, ToDoItemSortValue::Text(None)) => {
+            query.push("title IS NOT NULL")
+        }
+        (ToDoItemSortField::Status, ToDoItemSortValue::Status(status)) => query
+            .push(&status_rank_sql())
+            .push(comparison)
+            .bind(status.lifecycle_rank()),
+        (ToDoItemSortField::CreatedAt, ToDoItemSortValue::Timestamp(Some(created_at))) => {
+            query.push("created_at").push(comparison).bind(*created_at)
+        }
+        (ToDoItemSortField::UpdatedAt, ToDoItemSortValue::Timestamp(Some(updated_at))) => {
+            query.push("updated_at").push(comparison).bind(*updated_at)
+        }
+        (ToDoItemSortField::DueAt, ToDoItemSortValue::Timestamp(Some(due_at))) => {
+            query.push("(due_at").push(comparison).bind(*due_at);
+            if nulls_last {
+                query.push(" OR due_at IS NULL");
+            }
+            query.push(")")
+        }
+        (ToDoItemSortField::DueAt, ToDoItemSortValue::Timestamp(None)) if nulls_last => {
+            query.push("FALSE")
+        }
+        (ToDoItemSortField::DueAt, ToDoItemSortValue::Timestamp(None)) => {
+            query.push("due_at IS NOT NULL")
+        }
+        _ => query.push("FALSE"),
+    };
+}
+
+fn push_sort(query: &mut SqlQuery, params: &GetAllToDoItemsQuery) {
+    query.push(" ORDER BY ");
+    if let Some(search) = params.search.clone().filter(|_| params.rank_by_relevance) {
+        query
+            .push(&format!(
+                "ts_rank(search_vector, {}",
+                ts_query_prefix(params.search_mode)
+            ))
+            .bind(search)
+            .push(")) DESC, ");
+    }
+
+    push_sort_keys(query, &params.sort);
+}
+
+fn push_sort_keys(query: &mut SqlQuery, sort: &ToDoItemSort) {
+    let terms = sort
+        .effective_keys()
+        .iter()
+        .map(sort_term)
+        .collect::<Vec<_>>()
+        .join(", ");
+    query.push(&terms);
+}
+
+fn sort_term(key: &ToDoItemSortKey) -> String {
+    let direction = match key.direction {
+        SortDirection::Asc => "ASC",
+        SortDirection::Desc => "DESC",
+    };
+    let nulls = match key.nulls {
+        NullsOrder::First => "NULLS FIRST",
+        NullsOrder::Last => "NULLS LAST",
+    };
+
+    match key.field {
+        ToDoItemSortField::Id => format!("id {direction}"),
+        ToDoItemSortField::Title => format!("title {direction} {nulls}"),
+        ToDoItemSortField::Status => format!("{} {direction}", status_rank_sql()),
+        ToDoItemSortField::CreatedAt => format!("created_at {direction}"),
+        ToDoItemSortField::UpdatedAt => format!("updated_at {direction}"),
+        ToDoItemSortField::DueAt => format!("due_at {direction} {nulls}"),
+        ToDoItemSortField::DeletedAt => format!("deleted_at {direction}"),
+    }
+}
+
+pub(crate) fn map_postgres_error(err: tokio_postgres::Error) -> crate::Error {
+    InternalError(format!("database operation failed: {err}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keyset_cursor_compares_sort_keys_lexicographically() {
+        let sort = ToDoItemSort::new(vec![ToDoItemSortKey::new(
+            ToDoItemSortField::CreatedAt,
+            SortDirection::Desc,
+        )]);
+        let item = ToDoItem::new("title".into(), "note".into());
+        let cursor = ToDoItemCursor::after(&item, &sort);
+
+        let mut query = SqlQuery::new("SELECT id FROM to_do_items WHERE deleted_at IS NULL");
+        push_cursor(&mut query, &cursor);
+        query.push(" ORDER BY ");
+        push_sort_keys(&mut query, &sort);
+
+        assert_eq!(
+            query.sql,
+            "SELECT id FROM to_do_items WHERE deleted_at IS NULL AND ((created_at < $1) \
+             OR (created_at = $2 AND id < $3)) ORDER BY created_at DESC, id DESC"
+        );
+        assert_eq!(query.params.len(), 3);
+    }
+
+    #[test]
+    fn scope_and_filters_bind_every_value() {
+        let owner = Uuid::new_v4();
+        let params = GetAllToDoItemsQuery::new(1, 20, Some("milk".into()), ToDoItemSort::default())
+            .with_filter(ToDoItemFilter {
+                statuses: vec![ToDoItemStatus::Pending],
+                overdue: true,
+                ..ToDoItemFilter::default()
+            })
+            .within(AccessScope::owned_by(owner).in_tenant(Some("acme".into())));
+
+        let query = build_filtered_query("count(*)", &params);
+
+        assert_eq!(
+            query.sql,
+            "SELECT count(*) FROM to_do_items WHERE deleted_at IS NULL AND tenant_id = $1 \
+             AND owner_id = $2 AND search_vector @@ plainto_tsquery('english', $3) \
+             AND status = ANY($4) AND due_at < $5 AND status <> $6"
+        );
+        assert_eq!(query.params.len(), 6);
+    }
+
+    #[test]
+    fn shared_unowned_items_widen_the_owner_filter() {
+        let mut query = SqlQuery::new("SELECT count(*) FROM to_do_items WHERE deleted_at IS NULL");
+
+        push_scope_filter(
+            &mut query,
+            &AccessScope::owned_by(Uuid::new_v4()).with_unowned(true),
+        );
+
+        assert_eq!(
+            query.sql,
+            "SELECT count(*) FROM to_do_items WHERE deleted_at IS NULL \
+             AND (owner_id = $1 OR owner_id IS NULL)"
+        );
+    }
+}
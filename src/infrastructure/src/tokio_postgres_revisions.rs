@@ -0,0 +1,165 @@
+use crate::errors::Error::ItemNotFound;
+use crate::postgres_revisions::{number_revisions, DbToDoItemRevision};
+use crate::tokio_postgres_repositories::{count, execute, fetch, SqlQuery};
+use application::{AccessScope, PaginatedResult, ToDoItemRevision};
+use deadpool_postgres::GenericClient;
+use std::collections::HashMap;
+use tokio_postgres::Row;
+use uuid::Uuid;
+
+const REVISION_COLUMNS: &str = "item_id, revision, version, change_type, snapshot, actor, \
+    request_id, owner_id, tenant_id, recorded_at";
+
+impl From<&Row> for DbToDoItemRevision {
+    fn from(row: &Row) -> Self {
+        Self {
+            item_id: row.get("item_id"),
+            revision: row.get("revision"),
+            version: row.get("version"),
+            change_type: row.get("change_type"),
+            snapshot: row.get("snapshot"),
+            actor: row.get("actor"),
+            request_id: row.get("request_id"),
+            owner_id: row.get("owner_id"),
+            tenant_id: row.get("tenant_id"),
+            recorded_at: row.get("recorded_at"),
+        }
+    }
+}
+
+/// Stores `revisions` numbered after the latest revision of their item. Callers hold the
+/// row lock of the item, so concurrent changes cannot pick the same number.
+pub(crate) async fn append_revisions<C: GenericClient>(
+    client: &C,
+    revisions: Vec<ToDoItemRevision>,
+) -> std::result::Result<(), crate::Error> {
+    if revisions.is_empty() {
+        return Ok(());
+    }
+
+    let item_ids = revisions
+        .iter()
+        .map(|revision| revision.item_id)
+        .collect::<Vec<_>>();
+    let mut latest_query = SqlQuery::new(
+        "SELECT item_id, max(revision) FROM to_do_item_revisions WHERE item_id = ANY(",
+    );
+    latest_query.bind(item_ids).push(") GROUP BY item_id");
+    let latest = fetch(client, &latest_query)
+        .await?
+        .iter()
+        .map(|row| {
+            let revision = row.get::<_, Option<i32>>(1);
+            (row.get::<_, Uuid>(0), revision.unwrap_or_default())
+        })
+        .collect::<HashMap<_, _>>();
+
+    let mut query = SqlQuery::new(&format!(
+        "INSERT INTO to_do_item_revisions ({REVISION_COLUMNS}) VALUES "
+    ))
+    .uncached();
+    for (position, row) in number_revisions(revisions, latest)?.into_iter().enumerate() {
+        if position > 0 {
+            query.push(", ");
+        }
+        query
+            .push("(")
+            .bind(row.item_id)
+            .push(", ")
+            .bind(row.revision)
+            .push(", ")
+            .bind(row.version)
+            .push(", ")
+            .bind(row.change_type)
+            .push(", ")
+            .bind(row.snapshot)
+            .push(", ")
+            .bind(row.actor)
+            .push(", ")
+            .bind(row.request_id)
+            .push(", ")
+            .bind(row.owner_id)
+            .push(", ")
+            .bind(row.tenant_id)
+            .push(", ")
+            .bind(row.recorded_at)
+            .push(")");
+    }
+    execute(client, &query).await?;
+    Ok(())
+}
+
+pub(crate) async fn load_history<C: GenericClient>(
+    client: &C,
+    item_id: Uuid,
+    page: u32,
+    page_size: u32,
+    scope: &AccessScope,
+) -> std::result::Result<PaginatedResult<ToDoItemRevision>, crate::Error> {
+    let total_items = count(client, &scoped_revisions("count(*)", item_id, scope)).await?;
+
+    let mut query = scoped_revisions(REVISION_COLUMNS, item_id, scope);
+    query
+        .push(" ORDER BY revision ASC LIMIT ")
+        .bind(i64::from(page_size))
+        .push(" OFFSET ")
+        .bind((i64::from(page) - 1) * i64::from(page_size));
+    let items = map_revisions(fetch(client, &query).await?)?;
+
+    Ok(PaginatedResult::new(items, page, page_size, total_items))
+}
+
+pub(crate) async fn find_revision<C: GenericClient>(
+    client: &C,
+    item_id: Uuid,
+    version: i32,
+    scope: &AccessScope,
+) -> std::result::Result<ToDoItemRevision, crate::Error> {
+    let mut query = scoped_revisions(REVISION_COLUMNS, item_id, scope);
+    query.push(" AND version = ").bind(version).push(" LIMIT 1");
+
+    fetch(client, &query)
+        .await?
+        .first()
+        .map(DbToDoItemRevision::from)
+        .ok_or(ItemNotFound { id: item_id })
+        .and_then(ToDoItemRevision::try_from)
+}
+
+pub(crate) async fn load_revision_range<C: GenericClient>(
+    client: &C,
+    item_id: Uuid,
+    from: i32,
+    to: i32,
+    scope: &AccessScope,
+) -> std::result::Result<Vec<ToDoItemRevision>, crate::Error> {
+    let mut query = scoped_revisions(REVISION_COLUMNS, item_id, scope);
+    query
+        .push(" AND version BETWEEN ")
+        .bind(from)
+        .push(" AND ")
+        .bind(to)
+        .push(" ORDER BY revision ASC");
+
+    map_revisions(fetch(client, &query).await?)
+}
+
+fn map_revisions(rows: Vec<Row>) -> std::result::Result<Vec<ToDoItemRevision>, crate::Error> {
+    rows.iter()
+        .map(|row| ToDoItemRevision::try_from(DbToDoItemRevision::from(row)))
+        .collect()
+}
+
+fn scoped_revisions(select: &str, item_id: Uuid, scope: &AccessScope) -> SqlQuery {
+    let mut query = SqlQuery::new(&format!(
+        "SELECT {select} FROM to_do_item_revisions WHERE item_id = "
+    ));
+    query.bind(item_id);
+    if let Some(tenant_id) = scope.tenant_id.clone() {
+        query.push(" AND tenant_id = ").bind(tenant_id);
+    }
+    if let Some(owner_id) = scope.owner_id {
+        query.push(" AND owner_id = ").bind(owner_id);
+    }
+    query
+}
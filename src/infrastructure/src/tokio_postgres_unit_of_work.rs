@@ -0,0 +1,330 @@
+use crate::db_metrics::{observe, record_connection_wait};
+use crate::tokio_postgres_outbox::{
+    append_messages, delete_messages_of, fetch_pending_messages, mark_message_failed,
+    mark_message_published,
+};
+use crate::tokio_postgres_repositories::{
+    bind_all_tenants, bind_tenant, insert_item, insert_items, lock_active_by_id,
+    lock_deleted_by_id, lock_purgeable_ids, map_postgres_error, purge_items, restore_item,
+    soft_delete_item, update_item,
+};
+use crate::tokio_postgres_revisions::append_revisions;
+use crate::AsyncDbPool;
+use application::{
+    AccessScope, ApplicationError, ApplicationResult, OutboxMessage, OutboxRepository,
+    RevisionRepository, ToDoItemCommandRepository, ToDoItemRevision, UnitOfWork, UnitOfWorkFactory,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use deadpool_postgres::{GenericClient, Object};
+use domain::{ToDoItem, ToDoItemEvent};
+use std::sync::Arc;
+use std::time::Instant;
+use tokio::runtime::Handle;
+use tokio::sync::{Mutex, MutexGuard};
+use uuid::Uuid;
+
+const REPOSITORY: &str = "unit_of_work";
+
+/// Opens transactions on the async `tokio-postgres` pool that stay open across the steps
+/// of a command handler.
+///
+/// Each unit of work pins one pooled client and issues `BEGIN`, `COMMIT` and `ROLLBACK`
+/// itself, since a `tokio_postgres::Transaction` cannot outlive the borrow of its client.
+pub struct TokioPostgresUnitOfWorkFactory {
+    pool: AsyncDbPool,
+    row_level_security: bool,
+}
+
+impl TokioPostgresUnitOfWorkFactory {
+    pub fn new(pool: &AsyncDbPool) -> Self {
+        Self {
+            pool: pool.clone(),
+            row_level_security: false,
+        }
+    }
+
+    /// Binds each transaction to the tenant of its to-do item statements, for the
+    /// row-level security policies on `to_do_items`.
+    pub fn with_row_level_security(mut self, enabled: bool) -> Self {
+        self.row_level_security = enabled;
+        self
+    }
+}
+
+#[async_trait]
+impl UnitOfWorkFactory for TokioPostgresUnitOfWorkFactory {
+    async fn begin(&self) -> ApplicationResult<Box<dyn UnitOfWork>> {
+        let waiting = Instant::now();
+        let client = self.pool.get().await;
+        record_connection_wait(REPOSITORY, waiting.elapsed());
+        let client = client.map_err(|err| {
+            ApplicationError::internal(format!("failed to acquire database connection: {err}"))
+        })?;
+        observe(REPOSITORY, "begin", async {
+            client
+                .batch_execute("BEGIN")
+                .await
+                .map_err(map_postgres_error)
+        })
+        .await?;
+
+        Ok(Box::new(TokioPostgresUnitOfWork {
+            client: Arc::new(Mutex::new(client)),
+            finished: false,
+            row_level_security: self.row_level_security,
+        }))
+    }
+}
+
+struct TokioPostgresUnitOfWork {
+    client: Arc<Mutex<Object>>,
+    finished: bool,
+    row_level_security: bool,
+}
+
+impl TokioPostgresUnitOfWork {
+    async fn client_in_tenant(
+        &self,
+        tenant_id: Option<&str>,
+    ) -> ApplicationResult<MutexGuard<'_, Object>> {
+        let client = self.client.lock().await;
+        if self.row_level_security {
+            bind_tenant(&*client, tenant_id).await?;
+        }
+        Ok(client)
+    }
+
+    /// Like [`Self::client_in_tenant`] for the rows of every tenant, see
+    /// [`bind_all_tenants`].
+    async fn client_across_tenants(&self) -> ApplicationResult<MutexGuard<'_, Object>> {
+        let client = self.client.lock().await;
+        if self.row_level_security {
+            bind_all_tenants(&*client).await?;
+        }
+        Ok(client)
+    }
+
+    async fn finish(&mut self, operation: &'static str, statement: &str) -> ApplicationResult<()> {
+        observe(REPOSITORY, operation, async {
+            let client = self.client.lock().await;
+            client
+                .batch_execute(statement)
+                .await
+                .map_err(map_postgres_error)
+        })
+        .await?;
+        self.finished = true;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ToDoItemCommandRepository for TokioPostgresUnitOfWork {
+    async fn get_for_update(
+        &self,
+        todo_item_id: Uuid,
+        scope: AccessScope,
+    ) -> ApplicationResult<ToDoItem> {
+        let client = self.client_in_tenant(scope.tenant_id.as_deref()).await?;
+        Ok(observe(
+            REPOSITORY,
+            "get_for_update",
+            lock_active_by_id(&*client, todo_item_id, &scope),
+        )
+        .await?)
+    }
+
+    async fn get_deleted_for_update(
+        &self,
+        todo_item_id: Uuid,
+        scope: AccessScope,
+    ) -> ApplicationResult<ToDoItem> {
+        let client = self.client_in_tenant(scope.tenant_id.as_deref()).await?;
+        Ok(observe(
+            REPOSITORY,
+            "get_deleted_for_update",
+            lock_deleted_by_id(&*client, todo_item_id, &scope),
+        )
+        .await?)
+    }
+
+    async fn create(&self, entity: ToDoItem) -> ApplicationResult<Uuid> {
+        let client = self.client_in_tenant(entity.tenant_id.as_deref()).await?;
+        Ok(observe(REPOSITORY, "create", insert_item(&*client, &entity)).await?)
+    }
+
+    async fn create_many(&self, entities: Vec<ToDoItem>) -> ApplicationResult<Vec<Uuid>> {
+        let tenant_id = entities
+            .first()
+            .and_then(|entity| entity.tenant_id.as_deref());
+        let client = self.client_in_tenant(tenant_id).await?;
+        Ok(observe(REPOSITORY, "create_many", insert_items(&*client, &entities)).await?)
+    }
+
+    async fn update(&self, entity: ToDoItem) -> ApplicationResult<ToDoItem> {
+        let client = self.client_in_tenant(entity.tenant_id.as_deref()).await?;
+        Ok(observe(REPOSITORY, "update", update_item(&*client, &entity)).await?)
+    }
+
+    async fn delete(
+        &self,
+        todo_item_id: Uuid,
+        deleted_by: Option<Uuid>,
+        scope: AccessScope,
+    ) -> ApplicationResult<ToDoItem> {
+        let client = self.client_in_tenant(scope.tenant_id.as_deref()).await?;
+        Ok(observe(
+            REPOSITORY,
+            "delete",
+            soft_delete_item(&*client, todo_item_id, deleted_by, &scope),
+        )
+        .await?)
+    }
+
+    async fn restore(&self, entity: ToDoItem) -> ApplicationResult<Uuid> {
+        let client = self.client_in_tenant(entity.tenant_id.as_deref()).await?;
+        Ok(observe(REPOSITORY, "restore", restore_item(&*client, &entity)).await?)
+    }
+
+    async fn get_purgeable_ids(
+        &self,
+        deleted_before: DateTime<Utc>,
+        limit: i64,
+    ) -> ApplicationResult<Vec<Uuid>> {
+        let client = self.client_across_tenants().await?;
+        Ok(observe(
+            REPOSITORY,
+            "get_purgeable_ids",
+            lock_purgeable_ids(&*client, deleted_before, limit),
+        )
+        .await?)
+    }
+
+    async fn purge(&self, ids: Vec<Uuid>) -> ApplicationResult<()> {
+        let client = self.client_across_tenants().await?;
+        Ok(observe(REPOSITORY, "purge", purge_items(&*client, &ids)).await?)
+    }
+}
+
+#[async_trait]
+impl OutboxRepository for TokioPostgresUnitOfWork {
+    async fn append(&self, events: Vec<ToDoItemEvent>) -> ApplicationResult<()> {
+        let client = self.client.lock().await;
+        Ok(observe(
+            REPOSITORY,
+            "append_events",
+            append_messages(&*client, events),
+        )
+        .await?)
+    }
+
+    async fn fetch_pending(&self, limit: i64) -> ApplicationResult<Vec<OutboxMessage>> {
+        let client = self.client.lock().await;
+        Ok(observe(
+            REPOSITORY,
+            "fetch_pending",
+            fetch_pending_messages(&*client, limit),
+        )
+        .await?)
+    }
+
+    async fn mark_published(&self, id: Uuid) -> ApplicationResult<()> {
+        let client = self.client.lock().await;
+        Ok(observe(
+            REPOSITORY,
+            "mark_published",
+            mark_message_published(&*client, id),
+        )
+        .await?)
+    }
+
+    async fn mark_failed(
+        &self,
+        id: Uuid,
+        error: String,
+        retry_at: Option<DateTime<Utc>>,
+    ) -> ApplicationResult<()> {
+        let client = self.client.lock().await;
+        Ok(observe(
+            REPOSITORY,
+            "mark_failed",
+            mark_message_failed(&*client, id, error, retry_at),
+        )
+        .await?)
+    }
+
+    async fn purge_aggregates(&self, aggregate_ids: Vec<Uuid>) -> ApplicationResult<()> {
+        let client = self.client.lock().await;
+        Ok(observe(
+            REPOSITORY,
+            "purge_events",
+            delete_messages_of(&*client, &aggregate_ids),
+        )
+        .await?)
+    }
+}
+
+#[async_trait]
+impl RevisionRepository for TokioPostgresUnitOfWork {
+    async fn append(&self, revisions: Vec<ToDoItemRevision>) -> ApplicationResult<()> {
+        let tenant_id = revisions
+            .first()
+            .and_then(|revision| revision.snapshot.tenant_id.clone());
+        let client = self.client_in_tenant(tenant_id.as_deref()).await?;
+        Ok(observe(
+            REPOSITORY,
+            "append_revisions",
+            append_revisions(&*client, revisions),
+        )
+        .await?)
+    }
+}
+
+#[async_trait]
+impl UnitOfWork for TokioPostgresUnitOfWork {
+    fn to_do_items(&self) -> &dyn ToDoItemCommandRepository {
+        self
+    }
+
+    fn outbox(&self) -> &dyn OutboxRepository {
+        self
+    }
+
+    fn revisions(&self) -> &dyn RevisionRepository {
+        self
+    }
+
+    async fn commit(mut self: Box<Self>) -> ApplicationResult<()> {
+        self.finish("commit", "COMMIT").await
+    }
+
+    async fn rollback(mut self: Box<Self>) -> ApplicationResult<()> {
+        self.finish("rollback", "ROLLBACK").await
+    }
+}
+
+impl Drop for TokioPostgresUnitOfWork {
+    fn drop(&mut self) {
+        if self.finished {
+            return;
+        }
+
+        // The pool hands clients out again without checking their transaction state, so
+        // the client only goes back once the rollback has run. Without a runtime to run
+        // it on, the connection is closed instead.
+        let client = self.client.clone();
+        match Handle::try_current() {
+            Ok(handle) => {
+                handle.spawn(async move {
+                    let _ = client.lock().await.batch_execute("ROLLBACK").await;
+                });
+            }
+            Err(_) => {
+                if let Ok(client) = Arc::try_unwrap(client) {
+                    drop(Object::take(client.into_inner()));
+                }
+            }
+        }
+    }
+}
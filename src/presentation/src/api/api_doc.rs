@@ -1,11 +1,19 @@
 use crate::api::api_metrics::__path_metrics;
+use crate::api::app::__path_batch;
 use crate::api::app::__path_create;
 use crate::api::app::__path_delete;
 use crate::api::app::__path_get_all;
+use crate::api::app::__path_get_all_deleted_for_audit;
 use crate::api::app::__path_get_by_id;
 use crate::api::app::__path_get_deleted_by_id_for_audit;
+use crate::api::app::__path_get_diff;
+use crate::api::app::__path_get_history;
+use crate::api::app::__path_get_revision;
+use crate::api::app::__path_patch;
+use crate::api::app::__path_restore;
 use crate::api::app::__path_update;
-use utoipa::OpenApi;
+use utoipa::openapi::security::{ApiKey, ApiKeyValue, HttpAuthScheme, HttpBuilder, SecurityScheme};
+use utoipa::{Modify, OpenApi};
 
 #[derive(OpenApi)]
 #[openapi(
@@ -20,14 +28,50 @@ use utoipa::OpenApi;
         get_all,
         create,
         update,
+        patch,
         get_by_id,
         delete,
+        restore,
+        batch,
+        get_all_deleted_for_audit,
         get_deleted_by_id_for_audit,
+        get_history,
+        get_revision,
+        get_diff,
         metrics
-    )
+    ),
+    modifiers(&SecurityAddon)
 )]
 pub struct ApiDoc;
 
+/// Registers the bearer JWT, API key and audit token schemes referenced by the endpoints.
+///
+/// Bearer and API key requirements list the scopes the default authorization policy asks for.
+struct SecurityAddon;
+
+impl Modify for SecurityAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "bearer_auth",
+            SecurityScheme::Http(
+                HttpBuilder::new()
+                    .scheme(HttpAuthScheme::Bearer)
+                    .bearer_format("JWT")
+                    .build(),
+            ),
+        );
+        components.add_security_scheme(
+            "api_key",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-Api-Key"))),
+        );
+        components.add_security_scheme(
+            "audit_token",
+            SecurityScheme::ApiKey(ApiKey::Header(ApiKeyValue::new("X-Audit-Token"))),
+        );
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::ApiDoc;
@@ -73,4 +117,117 @@ mod tests {
             Value::String("Exposes Prometheus-compatible runtime metrics.".to_string())
         );
     }
+
+    #[test]
+    fn openapi_documents_merge_patch_endpoint() {
+        let openapi = ApiDoc::openapi();
+        let openapi_json = serde_json::to_value(&openapi).expect("OpenAPI should serialize");
+        let patch = &openapi_json["paths"]["/api/v1/to-do-items/{id}"]["patch"];
+
+        assert!(patch["requestBody"]["content"]["application/merge-patch+json"].is_object());
+        assert!(patch["responses"]["412"].is_object());
+        assert!(patch["responses"]["415"].is_object());
+    }
+
+    #[test]
+    fn openapi_documents_restore_endpoint() {
+        let openapi = ApiDoc::openapi();
+        let openapi_json = serde_json::to_value(&openapi).expect("OpenAPI should serialize");
+        let restore = &openapi_json["paths"]["/api/v1/to-do-items/{id}/restore"]["post"];
+
+        assert!(restore["responses"]["200"].is_object());
+        assert!(restore["responses"]["409"].is_object());
+    }
+
+    #[test]
+    fn openapi_documents_list_filter_parameters() {
+        let openapi = ApiDoc::openapi();
+        let openapi_json = serde_json::to_value(&openapi).expect("OpenAPI should serialize");
+        let parameters = openapi_json["paths"]["/api/v1/to-do-items"]["get"]["parameters"]
+            .as_array()
+            .expect("parameters should be an array")
+            .iter()
+            .filter_map(|parameter| parameter["name"].as_str())
+            .collect::<Vec<_>>();
+
+        for name in [
+            "status",
+            "due_after",
+            "due_before",
+            "created_after",
+            "created_before",
+            "updated_since",
+            "overdue",
+        ] {
+            assert!(parameters.contains(&name), "{name} should be documented");
+        }
+    }
+
+    #[test]
+    fn openapi_documents_batch_endpoint_with_multi_status() {
+        let openapi = ApiDoc::openapi();
+        let openapi_json = serde_json::to_value(&openapi).expect("OpenAPI should serialize");
+        let responses = &openapi_json["paths"]["/api/v1/to-do-items:batch"]["post"]["responses"];
+
+        assert!(responses["207"]["description"]
+            .as_str()
+            .expect("207 response should be documented")
+            .contains("424"));
+    }
+
+    #[test]
+    fn openapi_documents_idempotency_key_on_create() {
+        let openapi = ApiDoc::openapi();
+        let openapi_json = serde_json::to_value(&openapi).expect("OpenAPI should serialize");
+        let create = &openapi_json["paths"]["/api/v1/to-do-items"]["post"];
+
+        assert!(create["parameters"]
+            .as_array()
+            .expect("parameters should be an array")
+            .iter()
+            .any(|parameter| parameter["name"] == Value::String("Idempotency-Key".into())));
+        assert!(create["responses"]["422"].is_object());
+    }
+
+    #[test]
+    fn openapi_documents_bearer_authentication_on_todo_endpoints() {
+        let openapi = serde_json::to_value(ApiDoc::openapi()).expect("OpenAPI should serialize");
+
+        let scheme = &openapi["components"]["securitySchemes"]["bearer_auth"];
+        assert_eq!(scheme["scheme"], "bearer");
+        assert_eq!(scheme["bearerFormat"], "JWT");
+
+        let delete = &openapi["paths"]["/api/v1/to-do-items/{id}"]["delete"];
+        assert!(delete["security"][0].get("bearer_auth").is_some());
+        assert!(delete["responses"].get("401").is_some());
+    }
+
+    #[test]
+    fn openapi_documents_required_scopes_per_operation() {
+        let openapi = serde_json::to_value(ApiDoc::openapi()).expect("OpenAPI should serialize");
+        let paths = &openapi["paths"];
+
+        let get = &paths["/api/v1/to-do-items/{id}"]["get"];
+        assert_eq!(
+            get["security"][0]["bearer_auth"],
+            serde_json::json!(["todo:read"])
+        );
+        assert!(get["responses"].get("403").is_some());
+        assert_eq!(
+            paths["/api/v1/to-do-items:batch"]["post"]["security"][0]["bearer_auth"],
+            serde_json::json!(["todo:write"])
+        );
+        assert_eq!(
+            get["security"][1]["api_key"],
+            serde_json::json!(["todo:read"])
+        );
+
+        let audit = &paths["/api/v1/audit/to-do-items/{id}"]["get"]["security"][0];
+        assert_eq!(audit["bearer_auth"], serde_json::json!(["audit:read"]));
+        assert!(audit.get("audit_token").is_some());
+        assert_eq!(
+            openapi["components"]["securitySchemes"]["audit_token"]["name"],
+            "X-Audit-Token"
+        );
+    }
 }
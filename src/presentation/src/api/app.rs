@@ -1,96 +1,169 @@
-use actix_web::http::header::{ETAG, IF_MATCH};
+use actix_web::http::header::{ACCEPT, CACHE_CONTROL, CONTENT_TYPE, ETAG, IF_MATCH};
 use actix_web::web::Data;
-use actix_web::{delete, post, put};
-use actix_web::{get, web, HttpResponse, Result};
+use actix_web::{delete, patch, post, put};
+use actix_web::{get, web, HttpResponse, HttpResponseBuilder, Result};
 use application::{
-    Audit, DeleteToDoItemCommand, GetAllToDoItemsQuery, GetDeletedToDoItemForAuditQuery,
-    GetToDoItemQuery, ToDoItemService,
+    AccessScope, DeleteToDoItemCommand, GetAllToDoItemsQuery, GetDeletedToDoItemForAuditQuery,
+    GetDeletedToDoItemsForAuditQuery, GetToDoItemDiffQuery, GetToDoItemHistoryQuery,
+    GetToDoItemQuery, GetToDoItemRevisionQuery, HttpCache, RestoreToDoItemCommand, ToDoItemService,
 };
 use uuid::Uuid;
 use validator::Validate;
 
+use crate::auth::{AuditAuthenticator, Caller};
+use crate::conditional::{collection_etag, is_not_modified, item_etag, last_modified};
 use crate::errors::HttpError;
 use crate::requests::{
-    parse_audit_token_header, parse_optional_delete_actor_id, CreateToDoItemRequest,
-    GetAllToDoItemsQueryRequest, UpdateToDoItemRequest,
+    parse_etag_version, BatchToDoItemsRequest, CreateToDoItemRequest, GetAllToDoItemsQueryRequest,
+    GetDeletedToDoItemsForAuditQueryRequest, GetToDoItemDiffQueryRequest,
+    GetToDoItemHistoryQueryRequest, PatchToDoItemRequest, UpdateToDoItemRequest,
 };
 use crate::responses::{
-    AuditToDoItemResponse, ProblemDetailsResponse, ToDoItemResponse, ToDoItemsPageResponse,
+    AuditToDoItemResponse, AuditToDoItemsPageResponse, BatchToDoItemsResponse,
+    JsonPatchOperationResponse, ProblemDetailsResponse, ToDoItemDiffResponse,
+    ToDoItemHistoryPageResponse, ToDoItemResponse, ToDoItemRevisionResponse, ToDoItemsPageResponse,
 };
 
 const TODO: &str = "todo";
+const MERGE_PATCH_CONTENT_TYPE: &str = "application/merge-patch+json";
+const JSON_PATCH_CONTENT_TYPE: &str = "application/json-patch+json";
 
 /// Retrieves a paginated list of active to-do items with optional text search.
+///
+/// Supports offset pagination and opt-in cursor (keyset) pagination.
 #[utoipa::path(
     context_path = "/api/v1/to-do-items",
     tag = TODO,
+    security(("bearer_auth" = ["todo:read"]), ("api_key" = ["todo:read"])),
     responses(
-        (status = 200, description = "List active to-do items filtered by the optional search term. Responses include X-Request-Id.", body = ToDoItemsPageResponse),
-        (status = 400, description = "Validation error for blank or malformed query parameters. Responses include X-Request-Id.", body = ProblemDetailsResponse)
+        (status = 200, description = "List active to-do items filtered by the optional search term. Cursor pages include next_cursor while more items follow. Responses include X-Request-Id, a weak collection ETag and Cache-Control.", body = ToDoItemsPageResponse),
+        (status = 304, description = "The page still matches the If-None-Match collection ETag. Responses include X-Request-Id."),
+        (status = 400, description = "Validation error for blank or malformed query parameters, or an invalid cursor. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 401, description = "Missing or invalid bearer token or API key. Responses include X-Request-Id and WWW-Authenticate: Bearer.", body = ProblemDetailsResponse),
+        (status = 403, description = "The caller lacks the scope or role the authorization policy requires. Responses include X-Request-Id.", body = ProblemDetailsResponse)
     ),
-    params(GetAllToDoItemsQueryRequest)
+    params(
+        GetAllToDoItemsQueryRequest,
+        ("If-None-Match" = Option<String>, Header, description = "Collection ETag of a previously fetched page")
+    )
 )]
 #[get("")]
 pub async fn get_all(
     service: Data<ToDoItemService>,
+    cache: Data<HttpCache>,
+    request: actix_web::HttpRequest,
+    caller: Caller,
     query: web::Query<GetAllToDoItemsQueryRequest>,
 ) -> Result<HttpResponse, HttpError> {
     query.validate()?;
     query.validate_search().map_err(HttpError::bad_request)?;
     query.validate_sort().map_err(HttpError::bad_request)?;
     let handler = service.get_all_query_handler();
-    let query: GetAllToDoItemsQuery = query.to_query().map_err(HttpError::bad_request)?;
-    let data = ToDoItemsPageResponse::from(handler.execute(query).await?);
+    let query: GetAllToDoItemsQuery = query
+        .to_query()
+        .map_err(HttpError::bad_request)?
+        .within(caller.scope);
+    let result = handler.execute(query).await?;
+    let etag = collection_etag(request.query_string(), &result);
 
-    Ok(HttpResponse::Ok().json(data))
+    let not_modified = is_not_modified(&request, &etag, None);
+    let mut response = if not_modified {
+        HttpResponse::NotModified()
+    } else {
+        HttpResponse::Ok()
+    };
+    response.insert_header((ETAG, etag.to_string()));
+    insert_cache_control(&mut response, &cache.list_cache_control);
+    if not_modified {
+        return Ok(response.finish());
+    }
+
+    Ok(response.json(ToDoItemsPageResponse::from(result)))
 }
 
 /// Retrieves a to-do item by Id.
 #[utoipa::path(
     context_path = "/api/v1/to-do-items",
     tag = TODO,
+    security(("bearer_auth" = ["todo:read"]), ("api_key" = ["todo:read"])),
     responses(
-        (status = 200, description = "Get todo item by id. Responses include X-Request-Id.", body = ToDoItemResponse),
+        (status = 200, description = "Get todo item by id. Responses include X-Request-Id, ETag, Last-Modified and Cache-Control.", body = ToDoItemResponse),
+        (status = 304, description = "The item still matches If-None-Match, or has not changed since If-Modified-Since. Responses include X-Request-Id."),
+        (status = 401, description = "Missing or invalid bearer token or API key. Responses include X-Request-Id and WWW-Authenticate: Bearer.", body = ProblemDetailsResponse),
+        (status = 403, description = "The caller lacks the scope or role the authorization policy requires. Responses include X-Request-Id.", body = ProblemDetailsResponse),
         (status = 404, description = "Todo item not found. Responses include X-Request-Id.", body = ProblemDetailsResponse),
         (status = 500, description = "Unexpected internal error. Responses include X-Request-Id.", body = ProblemDetailsResponse)
     ),
     params(
-        ("id" = Uuid, Path, description = "Id of the to-do item")
+        ("id" = Uuid, Path, description = "Id of the to-do item"),
+        ("If-None-Match" = Option<String>, Header, description = "ETag of a previously fetched version"),
+        ("If-Modified-Since" = Option<String>, Header, description = "Last-Modified of a previously fetched version; ignored when If-None-Match is sent")
     ),
 )]
 #[get("/{id}")]
 pub async fn get_by_id(
     service: Data<ToDoItemService>,
+    cache: Data<HttpCache>,
+    request: actix_web::HttpRequest,
+    caller: Caller,
     id: web::Path<Uuid>,
 ) -> Result<HttpResponse, HttpError> {
     let handler = service.get_query_handler();
     let item = handler
-        .execute(GetToDoItemQuery::new(id.into_inner()))
+        .execute(GetToDoItemQuery::new(id.into_inner()).within(caller.scope))
         .await?;
-    let etag = format_etag(item.version);
-    let data = ToDoItemResponse::from(item);
+    let etag = item_etag(item.version);
 
-    Ok(HttpResponse::Ok().insert_header((ETAG, etag)).json(data))
+    let not_modified = is_not_modified(&request, &etag, Some(item.updated_at));
+    let mut response = if not_modified {
+        HttpResponse::NotModified()
+    } else {
+        HttpResponse::Ok()
+    };
+    response
+        .insert_header((ETAG, etag.to_string()))
+        .insert_header(last_modified(item.updated_at));
+    insert_cache_control(&mut response, &cache.item_cache_control);
+    if not_modified {
+        return Ok(response.finish());
+    }
+
+    Ok(response.json(ToDoItemResponse::from(item)))
 }
 
 /// Creates a new to-do item.
 #[utoipa::path(
     context_path = "/api/v1/to-do-items",
     tag = TODO,
+    security(("bearer_auth" = ["todo:write"]), ("api_key" = ["todo:write"])),
     responses(
-        (status = 201, description = "Create todo item. Responses include X-Request-Id.", body = Uuid),
-        (status = 400, description = "Validation error. Responses include X-Request-Id.", body = ProblemDetailsResponse)
+        (status = 201, description = "Create todo item. Retries with the same Idempotency-Key replay this response with Idempotent-Replayed: true. Responses include X-Request-Id.", body = Uuid),
+        (status = 400, description = "Validation error or malformed Idempotency-Key. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 401, description = "Missing or invalid bearer token or API key. Responses include X-Request-Id and WWW-Authenticate: Bearer.", body = ProblemDetailsResponse),
+        (status = 403, description = "The caller lacks the scope or role the authorization policy requires. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 409, description = "A request with the same Idempotency-Key is still being processed. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 422, description = "Idempotency-Key was already used for a different request. Responses include X-Request-Id.", body = ProblemDetailsResponse)
+    ),
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Client-chosen key that makes retries of this request safe")
     ),
     request_body = CreateToDoItemRequest,
 )]
 #[post("")]
 pub async fn create(
     service: Data<ToDoItemService>,
+    caller: Caller,
     item: web::Json<CreateToDoItemRequest>,
 ) -> Result<HttpResponse, HttpError> {
     item.validate()?;
     let handler = service.create_command_handler();
-    let data = handler.execute(item.to_command()).await?;
+    let command = item
+        .to_command()
+        .map_err(HttpError::bad_request)?
+        .with_owner(caller.id)
+        .with_tenant(caller.tenant_id())
+        .with_request_id(caller.request_id);
+    let data = handler.execute(command).await?;
 
     Ok(HttpResponse::Created().json(data))
 }
@@ -99,10 +172,14 @@ pub async fn create(
 #[utoipa::path(
     context_path = "/api/v1/to-do-items",
     tag = TODO,
+    security(("bearer_auth" = ["todo:write"]), ("api_key" = ["todo:write"])),
     responses(
         (status = 200, description = "Update todo item. Responses include X-Request-Id."),
         (status = 400, description = "Validation error. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 401, description = "Missing or invalid bearer token or API key. Responses include X-Request-Id and WWW-Authenticate: Bearer.", body = ProblemDetailsResponse),
+        (status = 403, description = "The caller lacks the scope or role the authorization policy requires. Responses include X-Request-Id.", body = ProblemDetailsResponse),
         (status = 404, description = "Todo item not found. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 409, description = "Status transition is not allowed from the current status. Responses include X-Request-Id.", body = ProblemDetailsResponse),
         (status = 412, description = "Stale If-Match precondition. Responses include X-Request-Id.", body = ProblemDetailsResponse),
         (status = 428, description = "Missing If-Match precondition. Responses include X-Request-Id.", body = ProblemDetailsResponse),
         (status = 500, description = "Unexpected internal error. Responses include X-Request-Id.", body = ProblemDetailsResponse)
@@ -117,6 +194,7 @@ pub async fn update(
     service: Data<ToDoItemService>,
     id: web::Path<Uuid>,
     request: actix_web::HttpRequest,
+    caller: Caller,
     item: web::Json<UpdateToDoItemRequest>,
 ) -> Result<HttpResponse, HttpError> {
     item.validate()?;
@@ -124,7 +202,63 @@ pub async fn update(
     let version = parse_if_match(&request)?;
     let id = id.into_inner();
 
-    handler.execute(item.to_command(id, version)).await?;
+    let command = item
+        .to_command(id, version)
+        .map_err(HttpError::bad_request)?
+        .with_updated_by(caller.id)
+        .with_request_id(caller.request_id)
+        .within(caller.scope);
+
+    handler.execute(command).await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header((ETAG, format_etag(version + 1)))
+        .finish())
+}
+
+/// Partially updates a to-do item by Id using JSON Merge Patch (RFC 7396).
+#[utoipa::path(
+    context_path = "/api/v1/to-do-items",
+    tag = TODO,
+    security(("bearer_auth" = ["todo:write"]), ("api_key" = ["todo:write"])),
+    responses(
+        (status = 200, description = "Patch todo item. Responses include X-Request-Id and the new ETag."),
+        (status = 400, description = "Validation error, null for a required member or unknown member. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 401, description = "Missing or invalid bearer token or API key. Responses include X-Request-Id and WWW-Authenticate: Bearer.", body = ProblemDetailsResponse),
+        (status = 403, description = "The caller lacks the scope or role the authorization policy requires. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 404, description = "Todo item not found. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 409, description = "Status transition is not allowed from the current status. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 412, description = "Stale If-Match precondition. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 415, description = "Content-Type is not application/merge-patch+json. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 428, description = "Missing If-Match precondition. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 500, description = "Unexpected internal error. Responses include X-Request-Id.", body = ProblemDetailsResponse)
+    ),
+    params(
+        ("id", description = "Id of the to-do item to patch"),
+        ("If-Match" = String, Header, description = "ETag of the version being patched")
+    ),
+    request_body(content = PatchToDoItemRequest, content_type = "application/merge-patch+json"),
+)]
+#[patch("/{id}")]
+pub async fn patch(
+    service: Data<ToDoItemService>,
+    id: web::Path<Uuid>,
+    request: actix_web::HttpRequest,
+    caller: Caller,
+    item: web::Json<PatchToDoItemRequest>,
+) -> Result<HttpResponse, HttpError> {
+    ensure_merge_patch_content_type(&request)?;
+    let handler = service.patch_command_handler();
+    let version = parse_if_match(&request)?;
+
+    let command = item
+        .to_command(id.into_inner(), version)
+        .map_err(HttpError::bad_request)?
+        .with_updated_by(caller.id)
+        .with_request_id(caller.request_id)
+        .within(caller.scope);
+
+    handler.execute(command).await?;
 
     Ok(HttpResponse::Ok()
         .insert_header((ETAG, format_etag(version + 1)))
@@ -135,8 +269,11 @@ pub async fn update(
 #[utoipa::path(
     context_path = "/api/v1/to-do-items",
     tag = TODO,
+    security(("bearer_auth" = ["todo:write"]), ("api_key" = ["todo:write"])),
     responses(
         (status = 200, description = "Delete todo item. Responses include X-Request-Id."),
+        (status = 401, description = "Missing or invalid bearer token or API key. Responses include X-Request-Id and WWW-Authenticate: Bearer.", body = ProblemDetailsResponse),
+        (status = 403, description = "The caller lacks the scope or role the authorization policy requires. Responses include X-Request-Id.", body = ProblemDetailsResponse),
         (status = 500, description = "Unexpected internal error. Responses include X-Request-Id.", body = ProblemDetailsResponse)
     ),
     params(
@@ -147,25 +284,152 @@ pub async fn update(
 pub async fn delete(
     service: Data<ToDoItemService>,
     id: web::Path<Uuid>,
-    request: actix_web::HttpRequest,
+    caller: Caller,
 ) -> Result<HttpResponse, HttpError> {
-    let deleted_by = parse_optional_delete_actor_id(&request).map_err(HttpError::bad_request)?;
     let handler = service.delete_command_handler();
 
     handler
-        .execute(DeleteToDoItemCommand::new(id.into_inner(), deleted_by))
+        .execute(
+            DeleteToDoItemCommand::new(id.into_inner(), caller.id)
+                .with_request_id(caller.request_id)
+                .within(caller.scope),
+        )
         .await?;
 
     Ok(HttpResponse::from(HttpResponse::Ok()))
 }
 
+/// Restores a soft-deleted to-do item by Id.
+#[utoipa::path(
+    context_path = "/api/v1/to-do-items",
+    tag = TODO,
+    security(("bearer_auth" = ["todo:write"]), ("api_key" = ["todo:write"])),
+    responses(
+        (status = 200, description = "Restore deleted todo item. Responses include X-Request-Id and the new ETag.", body = ToDoItemResponse),
+        (status = 401, description = "Missing or invalid bearer token or API key. Responses include X-Request-Id and WWW-Authenticate: Bearer.", body = ProblemDetailsResponse),
+        (status = 403, description = "The caller lacks the scope or role the authorization policy requires. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 404, description = "Todo item not found. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 409, description = "Todo item is not deleted. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 500, description = "Unexpected internal error. Responses include X-Request-Id.", body = ProblemDetailsResponse)
+    ),
+    params(
+        ("id", description = "Id of the to-do item to restore")
+    )
+)]
+#[post("/{id}/restore")]
+pub async fn restore(
+    service: Data<ToDoItemService>,
+    id: web::Path<Uuid>,
+    caller: Caller,
+) -> Result<HttpResponse, HttpError> {
+    let handler = service.restore_command_handler();
+
+    let item = handler
+        .execute(
+            RestoreToDoItemCommand::new(id.into_inner(), caller.id)
+                .with_request_id(caller.request_id)
+                .within(caller.scope),
+        )
+        .await?;
+
+    Ok(HttpResponse::Ok()
+        .insert_header((ETAG, format_etag(item.version)))
+        .json(ToDoItemResponse::from(item)))
+}
+
+/// Creates, updates and deletes many to-do items in one request.
+///
+/// Every operation gets its own result. With `atomic` the whole batch is rolled back when
+/// one operation fails.
+#[utoipa::path(
+    context_path = "/api/v1/to-do-items:batch",
+    tag = TODO,
+    security(("bearer_auth" = ["todo:write"]), ("api_key" = ["todo:write"])),
+    responses(
+        (status = 207, description = "Per-operation results in request order, each with its own status and either the item id and ETag or problem details. Operations skipped by a failed atomic batch report 424. Responses include X-Request-Id.", body = BatchToDoItemsResponse),
+        (status = 400, description = "Validation error in any of the operations. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 401, description = "Missing or invalid bearer token or API key. Responses include X-Request-Id and WWW-Authenticate: Bearer.", body = ProblemDetailsResponse),
+        (status = 403, description = "The caller lacks the scope or role the authorization policy requires. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 500, description = "Unexpected internal error. Responses include X-Request-Id.", body = ProblemDetailsResponse)
+    ),
+    params(
+        ("Idempotency-Key" = Option<String>, Header, description = "Client-chosen key that makes retries of this request safe")
+    ),
+    request_body = BatchToDoItemsRequest,
+)]
+#[post("")]
+pub async fn batch(
+    service: Data<ToDoItemService>,
+    caller: Caller,
+    batch: web::Json<BatchToDoItemsRequest>,
+) -> Result<HttpResponse, HttpError> {
+    batch.validate()?;
+    let handler = service.batch_command_handler();
+    let command = batch.to_command(&caller).map_err(HttpError::bad_request)?;
+    let data = BatchToDoItemsResponse::from(handler.execute(command).await?);
+
+    Ok(HttpResponse::MultiStatus().json(data))
+}
+
+/// Retrieves a paginated list of deleted to-do items for audit purposes.
+///
+/// Filters on who deleted the items and when, with optional text search. Most recently
+/// deleted items come first unless another sort is requested.
+#[utoipa::path(
+    context_path = "/api/v1/audit/to-do-items",
+    tag = TODO,
+    security(
+        ("bearer_auth" = ["audit:read"], "audit_token" = []),
+        ("api_key" = ["audit:read"], "audit_token" = [])
+    ),
+    responses(
+        (status = 200, description = "List deleted todo items for audit. Responses include X-Request-Id.", body = AuditToDoItemsPageResponse),
+        (status = 400, description = "Validation error for blank or malformed query parameters. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 401, description = "Missing or invalid audit token. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 403, description = "The caller lacks the scope or role the authorization policy requires. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 500, description = "Unexpected internal error. Responses include X-Request-Id.", body = ProblemDetailsResponse)
+    ),
+    params(
+        ("X-Audit-Token" = String, Header, description = "Audit access token"),
+        GetDeletedToDoItemsForAuditQueryRequest
+    ),
+)]
+#[get("")]
+pub async fn get_all_deleted_for_audit(
+    service: Data<ToDoItemService>,
+    audit: Data<AuditAuthenticator>,
+    cache: Data<HttpCache>,
+    params: web::Query<GetDeletedToDoItemsForAuditQueryRequest>,
+    request: actix_web::HttpRequest,
+    caller: Caller,
+) -> Result<HttpResponse, HttpError> {
+    audit.ensure_audit_token(&request)?;
+    params.validate()?;
+
+    let handler = service.get_all_deleted_for_audit_query_handler();
+    let query: GetDeletedToDoItemsForAuditQuery = params
+        .to_query()
+        .map_err(HttpError::bad_request)?
+        .within(AccessScope::unrestricted().in_tenant(caller.tenant_id()));
+    let items = handler.execute(query).await?;
+
+    let mut response = HttpResponse::Ok();
+    insert_cache_control(&mut response, &cache.audit_cache_control);
+    Ok(response.json(AuditToDoItemsPageResponse::from(items)))
+}
+
 /// Retrieves a deleted to-do item by Id for audit purposes.
 #[utoipa::path(
     context_path = "/api/v1/audit/to-do-items",
     tag = TODO,
+    security(
+        ("bearer_auth" = ["audit:read"], "audit_token" = []),
+        ("api_key" = ["audit:read"], "audit_token" = [])
+    ),
     responses(
         (status = 200, description = "Get deleted todo item by id for audit. Responses include X-Request-Id.", body = AuditToDoItemResponse),
         (status = 401, description = "Missing or invalid audit token. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 403, description = "The caller lacks the scope or role the authorization policy requires. Responses include X-Request-Id.", body = ProblemDetailsResponse),
         (status = 404, description = "Deleted todo item not found. Responses include X-Request-Id.", body = ProblemDetailsResponse),
         (status = 500, description = "Unexpected internal error. Responses include X-Request-Id.", body = ProblemDetailsResponse)
     ),
@@ -177,34 +441,228 @@ pub async fn delete(
 #[get("/{id}")]
 pub async fn get_deleted_by_id_for_audit(
     service: Data<ToDoItemService>,
-    audit: Data<Audit>,
+    audit: Data<AuditAuthenticator>,
+    cache: Data<HttpCache>,
     id: web::Path<Uuid>,
     request: actix_web::HttpRequest,
+    caller: Caller,
 ) -> Result<HttpResponse, HttpError> {
-    let provided_token = parse_audit_token_header(&request)
-        .ok_or_else(|| HttpError::unauthorized("missing X-Audit-Token header"))?;
-    let configured_token = audit
-        .token
-        .as_ref()
-        .filter(|token| !token.trim().is_empty())
-        .ok_or_else(|| HttpError::unauthorized("audit endpoint is not configured"))?;
-    if configured_token != &provided_token {
-        return Err(HttpError::unauthorized("invalid audit token"));
-    }
+    audit.ensure_audit_token(&request)?;
 
     let handler = service.get_deleted_for_audit_query_handler();
     let item = handler
-        .execute(GetDeletedToDoItemForAuditQuery::new(id.into_inner()))
+        .execute(
+            GetDeletedToDoItemForAuditQuery::new(id.into_inner())
+                .within(AccessScope::unrestricted().in_tenant(caller.tenant_id())),
+        )
         .await?;
     let data = AuditToDoItemResponse::from(item);
 
-    Ok(HttpResponse::Ok().json(data))
+    let mut response = HttpResponse::Ok();
+    insert_cache_control(&mut response, &cache.audit_cache_control);
+    Ok(response.json(data))
+}
+
+/// Retrieves the change history of a to-do item, oldest revision first, for audit purposes.
+///
+/// Deleted items keep their history until they are purged.
+#[utoipa::path(
+    context_path = "/api/v1/audit/to-do-items",
+    tag = TODO,
+    security(
+        ("bearer_auth" = ["audit:read"], "audit_token" = []),
+        ("api_key" = ["audit:read"], "audit_token" = [])
+    ),
+    responses(
+        (status = 200, description = "Get the revisions of a todo item. Responses include X-Request-Id.", body = ToDoItemHistoryPageResponse),
+        (status = 400, description = "Invalid pagination parameters. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 401, description = "Missing or invalid audit token. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 403, description = "The caller lacks the scope or role the authorization policy requires. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 404, description = "Todo item has no recorded history. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 500, description = "Unexpected internal error. Responses include X-Request-Id.", body = ProblemDetailsResponse)
+    ),
+    params(
+        ("id" = Uuid, Path, description = "Id of the to-do item"),
+        ("X-Audit-Token" = String, Header, description = "Audit access token"),
+        GetToDoItemHistoryQueryRequest
+    ),
+)]
+#[get("/{id}/history")]
+pub async fn get_history(
+    service: Data<ToDoItemService>,
+    audit: Data<AuditAuthenticator>,
+    cache: Data<HttpCache>,
+    id: web::Path<Uuid>,
+    params: web::Query<GetToDoItemHistoryQueryRequest>,
+    request: actix_web::HttpRequest,
+    caller: Caller,
+) -> Result<HttpResponse, HttpError> {
+    audit.ensure_audit_token(&request)?;
+    params.validate()?;
+
+    let handler = service.get_history_query_handler();
+    let history = handler
+        .execute(
+            GetToDoItemHistoryQuery::new(id.into_inner(), params.page, params.page_size)
+                .within(AccessScope::unrestricted().in_tenant(caller.tenant_id())),
+        )
+        .await?;
+
+    let mut response = HttpResponse::Ok();
+    insert_cache_control(&mut response, &cache.history_cache_control);
+    Ok(response.json(ToDoItemHistoryPageResponse::from(history)))
+}
+
+/// Retrieves the revision that left a to-do item at a version, for audit purposes.
+#[utoipa::path(
+    context_path = "/api/v1/audit/to-do-items",
+    tag = TODO,
+    security(
+        ("bearer_auth" = ["audit:read"], "audit_token" = []),
+        ("api_key" = ["audit:read"], "audit_token" = [])
+    ),
+    responses(
+        (status = 200, description = "Get a revision of a todo item. Responses include X-Request-Id.", body = ToDoItemRevisionResponse),
+        (status = 401, description = "Missing or invalid audit token. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 403, description = "The caller lacks the scope or role the authorization policy requires. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 404, description = "Revision not found. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 500, description = "Unexpected internal error. Responses include X-Request-Id.", body = ProblemDetailsResponse)
+    ),
+    params(
+        ("id" = Uuid, Path, description = "Id of the to-do item"),
+        ("version" = i32, Path, description = "Item version the revision produced, as sent in its ETag"),
+        ("X-Audit-Token" = String, Header, description = "Audit access token")
+    ),
+)]
+#[get("/{id}/history/{version}")]
+pub async fn get_revision(
+    service: Data<ToDoItemService>,
+    audit: Data<AuditAuthenticator>,
+    cache: Data<HttpCache>,
+    path: web::Path<(Uuid, i32)>,
+    request: actix_web::HttpRequest,
+    caller: Caller,
+) -> Result<HttpResponse, HttpError> {
+    audit.ensure_audit_token(&request)?;
+    let (id, version) = path.into_inner();
+
+    let handler = service.get_revision_query_handler();
+    let revision = handler
+        .execute(
+            GetToDoItemRevisionQuery::new(id, version)
+                .within(AccessScope::unrestricted().in_tenant(caller.tenant_id())),
+        )
+        .await?;
+
+    let mut response = HttpResponse::Ok();
+    insert_cache_control(&mut response, &cache.revision_cache_control);
+    Ok(response.json(ToDoItemRevisionResponse::from(revision)))
+}
+
+/// Compares two versions of a to-do item field by field for audit purposes.
+///
+/// Each change names the last revision in the range that made it, with its actor and time.
+/// Send `Accept: application/json-patch+json` for an RFC 6902 patch from the `from`
+/// snapshot to the `to` snapshot instead.
+#[utoipa::path(
+    context_path = "/api/v1/audit/to-do-items",
+    tag = TODO,
+    security(
+        ("bearer_auth" = ["audit:read"], "audit_token" = []),
+        ("api_key" = ["audit:read"], "audit_token" = [])
+    ),
+    responses(
+        (status = 200, description = "Field-level diff between two revisions. Responses include X-Request-Id.", content(
+            (ToDoItemDiffResponse = "application/json"),
+            (Vec<JsonPatchOperationResponse> = "application/json-patch+json")
+        )),
+        (status = 400, description = "Invalid version range. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 401, description = "Missing or invalid audit token. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 403, description = "The caller lacks the scope or role the authorization policy requires. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 404, description = "One of the revisions does not exist. Responses include X-Request-Id.", body = ProblemDetailsResponse),
+        (status = 500, description = "Unexpected internal error. Responses include X-Request-Id.", body = ProblemDetailsResponse)
+    ),
+    params(
+        ("id" = Uuid, Path, description = "Id of the to-do item"),
+        ("X-Audit-Token" = String, Header, description = "Audit access token"),
+        GetToDoItemDiffQueryRequest
+    ),
+)]
+#[get("/{id}/diff")]
+pub async fn get_diff(
+    service: Data<ToDoItemService>,
+    audit: Data<AuditAuthenticator>,
+    cache: Data<HttpCache>,
+    id: web::Path<Uuid>,
+    params: web::Query<GetToDoItemDiffQueryRequest>,
+    request: actix_web::HttpRequest,
+    caller: Caller,
+) -> Result<HttpResponse, HttpError> {
+    audit.ensure_audit_token(&request)?;
+    params.validate()?;
+    if params.from > params.to {
+        return Err(HttpError::bad_request("from must not be after to"));
+    }
+
+    let handler = service.get_diff_query_handler();
+    let diff = handler
+        .execute(
+            GetToDoItemDiffQuery::new(id.into_inner(), params.from, params.to)
+                .within(AccessScope::unrestricted().in_tenant(caller.tenant_id())),
+        )
+        .await?;
+
+    let mut response = HttpResponse::Ok();
+    insert_cache_control(&mut response, &cache.diff_cache_control);
+    if accepts_json_patch(&request) {
+        return Ok(response
+            .content_type(JSON_PATCH_CONTENT_TYPE)
+            .json(JsonPatchOperationResponse::from_diff(&diff)));
+    }
+    Ok(response.json(ToDoItemDiffResponse::from(diff)))
+}
+
+fn accepts_json_patch(request: &actix_web::HttpRequest) -> bool {
+    request
+        .headers()
+        .get_all(ACCEPT)
+        .filter_map(|value| value.to_str().ok())
+        .flat_map(|value| value.split(','))
+        .filter_map(|media_type| media_type.split(';').next())
+        .any(|media_type| {
+            media_type
+                .trim()
+                .eq_ignore_ascii_case(JSON_PATCH_CONTENT_TYPE)
+        })
 }
 
 fn format_etag(version: i32) -> String {
     format!("\"{version}\"")
 }
 
+fn insert_cache_control(response: &mut HttpResponseBuilder, policy: &str) {
+    if !policy.trim().is_empty() {
+        response.insert_header((CACHE_CONTROL, policy.trim()));
+    }
+}
+
+#[allow(clippy::result_large_err)]
+fn ensure_merge_patch_content_type(request: &actix_web::HttpRequest) -> Result<(), HttpError> {
+    let content_type = request
+        .headers()
+        .get(CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(|value| value.trim().to_ascii_lowercase());
+
+    match content_type.as_deref() {
+        Some(MERGE_PATCH_CONTENT_TYPE) => Ok(()),
+        _ => Err(HttpError::unsupported_media_type(format!(
+            "PATCH requires Content-Type {MERGE_PATCH_CONTENT_TYPE}"
+        ))),
+    }
+}
+
 #[allow(clippy::result_large_err)]
 fn parse_if_match(request: &actix_web::HttpRequest) -> Result<i32, HttpError> {
     let raw = request
@@ -221,13 +679,6 @@ fn parse_if_match(request: &actix_web::HttpRequest) -> Result<i32, HttpError> {
         ));
     }
 
-    let normalized = normalized
-        .strip_prefix("W/")
-        .unwrap_or(normalized)
-        .trim()
-        .trim_matches('"');
-
-    normalized
-        .parse::<i32>()
-        .map_err(|_| HttpError::bad_request("If-Match header must contain an integer ETag"))
+    parse_etag_version(normalized)
+        .ok_or_else(|| HttpError::bad_request("If-Match header must contain an integer ETag"))
 }
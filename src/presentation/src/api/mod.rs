@@ -10,9 +10,16 @@ pub use api_metrics::metrics;
 
 pub use api_doc::ApiDoc;
 
+pub use app::batch;
 pub use app::create;
 pub use app::delete;
 pub use app::get_all;
+pub use app::get_all_deleted_for_audit;
 pub use app::get_by_id;
 pub use app::get_deleted_by_id_for_audit;
+pub use app::get_diff;
+pub use app::get_history;
+pub use app::get_revision;
+pub use app::patch;
+pub use app::restore;
 pub use app::update;
@@ -0,0 +1,254 @@
+use actix_web::dev::ServiceRequest;
+use actix_web::http::header::{HeaderName, AUTHORIZATION};
+use anyhow::{bail, Result};
+use application::{ApiKey, ApiKeyRepository, ApiKeys, Tenancy};
+use chrono::{DateTime, Duration, Utc};
+use metrics::{counter, gauge};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tracing::warn;
+use uuid::Uuid;
+
+use super::Principal;
+use crate::errors::HttpError;
+
+const KEY_PREFIX: &str = "tk_";
+/// Characters of a key, `tk_` included, that are stored in clear to identify it.
+const DISPLAYED_KEY_LENGTH: usize = 11;
+
+/// Claim under which the name of the API key is exposed on its [`Principal`].
+const API_KEY_CLAIM: &str = "api_key";
+
+/// Returns a new random key together with the record to store for it. The key itself is
+/// not kept anywhere and has to be handed to the caller right away.
+pub fn generate_api_key(
+    name: impl Into<String>,
+    scopes: Vec<String>,
+    tenant_id: Option<String>,
+    expires_at: Option<DateTime<Utc>>,
+) -> (String, ApiKey) {
+    let secret = format!(
+        "{KEY_PREFIX}{}{}",
+        Uuid::new_v4().simple(),
+        Uuid::new_v4().simple()
+    );
+    let key = ApiKey::new(
+        name,
+        &secret[..DISPLAYED_KEY_LENGTH],
+        hash_api_key(&secret),
+        scopes,
+        tenant_id,
+        expires_at,
+    );
+    (secret, key)
+}
+
+pub fn hash_api_key(secret: &str) -> String {
+    hex::encode(Sha256::digest(secret.as_bytes()))
+}
+
+/// Verifies API keys sent by service-to-service callers against the stored key hashes.
+///
+/// The tenant of a key is exposed under the tenancy `claim`, like the tenant of a token.
+pub struct ApiKeyAuthenticator {
+    enabled: bool,
+    header: HeaderName,
+    last_used_interval: Duration,
+    tenant_claim: String,
+    repository: Arc<dyn ApiKeyRepository>,
+}
+
+impl ApiKeyAuthenticator {
+    pub fn from_settings(
+        settings: &ApiKeys,
+        tenancy: &Tenancy,
+        repository: Arc<dyn ApiKeyRepository>,
+    ) -> Result<Self> {
+        let Ok(header) = HeaderName::try_from(settings.header.trim()) else {
+            bail!("invalid API key header name {:?}", settings.header);
+        };
+
+        Ok(Self {
+            enabled: settings.enabled,
+            header,
+            last_used_interval: Duration::seconds(
+                i64::try_from(settings.last_used_interval_secs).unwrap_or(i64::MAX),
+            ),
+            tenant_claim: tenancy.claim.trim().to_string(),
+            repository,
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Returns the API key sent with the request, if any, or a client-facing reason why
+    /// it cannot be read.
+    pub fn presented_key(&self, request: &ServiceRequest) -> Option<Result<String, String>> {
+        if let Some(value) = request.headers().get(&self.header) {
+            let key = value
+                .to_str()
+                .map(|value| value.trim().to_string())
+                .map_err(|_| format!("{} header must be valid ASCII", self.header));
+            return Some(key);
+        }
+
+        let (scheme, key) = request
+            .headers()
+            .get(AUTHORIZATION)?
+            .to_str()
+            .ok()?
+            .split_once(' ')?;
+        scheme
+            .eq_ignore_ascii_case("apikey")
+            .then(|| Ok(key.trim().to_string()))
+    }
+
+    /// Returns the principal of an active key, or the error to answer the request with.
+    pub async fn authenticate(&self, secret: &str) -> std::result::Result<Principal, HttpError> {
+        let Some(key) = self.repository.find_by_hash(&hash_api_key(secret)).await? else {
+            record_outcome("unknown", "rejected_unknown");
+            return Err(HttpError::unauthorized("unknown API key"));
+        };
+
+        let now = Utc::now();
+        if key.is_revoked() {
+            record_outcome(&key.name, "rejected_revoked");
+            return Err(HttpError::unauthorized("API key has been revoked"));
+        }
+        if key.is_expired(now) {
+            record_outcome(&key.name, "rejected_expired");
+            return Err(HttpError::unauthorized("API key has expired"));
+        }
+
+        record_outcome(&key.name, "accepted");
+        gauge!("api_key_last_used_timestamp_seconds", "key" => key.name.clone())
+            .set(now.timestamp() as f64);
+        if key
+            .last_used_at
+            .is_none_or(|last_used_at| now - last_used_at >= self.last_used_interval)
+        {
+            // Bookkeeping only; a failed write must not fail the request.
+            if let Err(err) = self.repository.record_use(key.id, now).await {
+                warn!(error = %err, key = %key.name, "failed to record API key use");
+            }
+        }
+
+        let tenant = key
+            .tenant_id
+            .map(|tenant_id| (self.tenant_claim.clone(), tenant_id));
+        Ok(Principal::new(key.id).with_scopes(key.scopes).with_claims(
+            [(API_KEY_CLAIM.to_string(), key.name)]
+                .into_iter()
+                .chain(tenant),
+        ))
+    }
+}
+
+fn record_outcome(key: &str, outcome: &'static str) {
+    counter!(
+        "api_key_authentications_total",
+        "key" => key.to_string(),
+        "outcome" => outcome
+    )
+    .increment(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+    use application::{InMemoryApiKeyRepository, Settings};
+
+    fn authenticator(repository: Arc<InMemoryApiKeyRepository>) -> ApiKeyAuthenticator {
+        let settings = Settings::default();
+        ApiKeyAuthenticator::from_settings(&settings.api_keys, &settings.tenancy, repository)
+            .unwrap()
+    }
+
+    #[test]
+    fn generated_keys_are_stored_as_hash_and_prefix_only() {
+        let (secret, key) = generate_api_key("batch", vec!["todo:read".into()], None, None);
+
+        assert!(secret.starts_with(KEY_PREFIX));
+        assert_eq!(secret.len(), KEY_PREFIX.len() + 64);
+        assert_eq!(key.prefix, secret[..DISPLAYED_KEY_LENGTH]);
+        assert_eq!(key.key_hash, hash_api_key(&secret));
+        assert!(!key.key_hash.contains(&secret[KEY_PREFIX.len()..]));
+    }
+
+    #[test]
+    fn keys_are_read_from_the_header_or_the_api_key_scheme() {
+        let authenticator = authenticator(Arc::new(InMemoryApiKeyRepository::new()));
+        let header = TestRequest::default()
+            .insert_header(("X-Api-Key", "tk_one"))
+            .to_srv_request();
+        let scheme = TestRequest::default()
+            .insert_header(("Authorization", "ApiKey tk_two"))
+            .to_srv_request();
+        let bearer = TestRequest::default()
+            .insert_header(("Authorization", "Bearer token"))
+            .to_srv_request();
+
+        assert_eq!(
+            authenticator.presented_key(&header),
+            Some(Ok("tk_one".into()))
+        );
+        assert_eq!(
+            authenticator.presented_key(&scheme),
+            Some(Ok("tk_two".into()))
+        );
+        assert_eq!(authenticator.presented_key(&bearer), None);
+    }
+
+    #[actix_web::test]
+    async fn active_keys_yield_a_principal_and_record_their_use() {
+        let repository = Arc::new(InMemoryApiKeyRepository::new());
+        let (secret, key) =
+            generate_api_key("batch", vec!["todo:read".into()], Some("acme".into()), None);
+        repository.create(key.clone()).await.unwrap();
+
+        let principal = authenticator(repository.clone())
+            .authenticate(&secret)
+            .await
+            .unwrap();
+
+        assert_eq!(principal.id, key.id);
+        assert!(principal.has_scope("todo:read"));
+        assert_eq!(principal.claim(API_KEY_CLAIM), Some("batch"));
+        assert_eq!(principal.claim("tenant_id"), Some("acme"));
+        let stored = repository.list().await.unwrap();
+        assert!(stored[0].last_used_at.is_some());
+    }
+
+    #[actix_web::test]
+    async fn unknown_revoked_and_expired_keys_are_rejected() {
+        let repository = Arc::new(InMemoryApiKeyRepository::new());
+        let (revoked_secret, revoked) = generate_api_key("revoked", Vec::new(), None, None);
+        let (expired_secret, expired) = generate_api_key(
+            "expired",
+            Vec::new(),
+            None,
+            Some(Utc::now() - Duration::minutes(1)),
+        );
+        repository.create(revoked.clone()).await.unwrap();
+        repository.create(expired).await.unwrap();
+        repository.revoke(revoked.id, Utc::now()).await.unwrap();
+        let authenticator = authenticator(repository);
+
+        for secret in [
+            "tk_unknown",
+            revoked_secret.as_str(),
+            expired_secret.as_str(),
+        ] {
+            let HttpError::Problem(rejection) =
+                authenticator.authenticate(secret).await.unwrap_err();
+            assert_eq!(
+                rejection.status.map(|status| status.as_u16()),
+                Some(401),
+                "{secret}"
+            );
+        }
+    }
+}
@@ -0,0 +1,266 @@
+use actix_web::HttpRequest;
+use anyhow::{bail, Result};
+use application::{Audit, AuditCredential};
+use argon2::password_hash::PasswordHash;
+use argon2::{Argon2, PasswordVerifier};
+use chrono::{DateTime, Utc};
+use metrics::counter;
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+use tracing::info;
+
+use crate::errors::HttpError;
+use crate::requests::parse_audit_token_header;
+
+enum TokenHash {
+    Sha256([u8; 32]),
+    /// Argon2 PHC string, validated when the credential is loaded.
+    Argon2(String),
+}
+
+struct StoredCredential {
+    name: String,
+    hash: TokenHash,
+    not_before: Option<DateTime<Utc>>,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+impl StoredCredential {
+    fn from_settings(credential: &AuditCredential) -> Result<Self> {
+        let name = credential.name.trim();
+        if name.is_empty() {
+            bail!("audit credentials need a name");
+        }
+        let token_hash = credential.token_hash.trim();
+        let hash = if token_hash.starts_with("$argon2") {
+            if let Err(err) = PasswordHash::new(token_hash) {
+                bail!("invalid Argon2 hash of audit credential {name:?}: {err}");
+            }
+            TokenHash::Argon2(token_hash.to_string())
+        } else {
+            match hex::decode(token_hash).ok().map(<[u8; 32]>::try_from) {
+                Some(Ok(digest)) => TokenHash::Sha256(digest),
+                _ => bail!(
+                    "token_hash of audit credential {name:?} must be a hex SHA-256 digest or an Argon2 hash"
+                ),
+            }
+        };
+        if let (Some(not_before), Some(expires_at)) = (credential.not_before, credential.expires_at)
+        {
+            if not_before >= expires_at {
+                bail!("audit credential {name:?} expires before it becomes valid");
+            }
+        }
+
+        Ok(Self {
+            name: name.to_string(),
+            hash,
+            not_before: credential.not_before,
+            expires_at: credential.expires_at,
+        })
+    }
+
+    fn matches(&self, token: &str, digest: &[u8; 32]) -> bool {
+        match &self.hash {
+            TokenHash::Sha256(expected) => expected.ct_eq(digest).into(),
+            TokenHash::Argon2(expected) => PasswordHash::new(expected).is_ok_and(|expected| {
+                Argon2::default()
+                    .verify_password(token.as_bytes(), &expected)
+                    .is_ok()
+            }),
+        }
+    }
+}
+
+/// Verifies `X-Audit-Token` headers against the configured audit credentials.
+///
+/// Tokens are only kept as hashes. SHA-256 digests are compared in constant time and
+/// every credential is checked, so the response time does not tell which one matched.
+pub struct AuditAuthenticator {
+    credentials: Vec<StoredCredential>,
+}
+
+impl AuditAuthenticator {
+    pub fn from_settings(settings: &Audit) -> Result<Self> {
+        let mut credentials: Vec<StoredCredential> = Vec::new();
+        for credential in &settings.credentials {
+            let credential = StoredCredential::from_settings(credential)?;
+            if credentials
+                .iter()
+                .any(|existing| existing.name == credential.name)
+            {
+                bail!("duplicate audit credential {:?}", credential.name);
+            }
+            credentials.push(credential);
+        }
+
+        Ok(Self { credentials })
+    }
+
+    /// Returns the name of the credential `token` belongs to, if it is valid at `now`.
+    #[allow(clippy::result_large_err)]
+    pub fn authenticate(&self, token: &str, now: DateTime<Utc>) -> Result<&str, HttpError> {
+        if self.credentials.is_empty() {
+            return Err(HttpError::unauthorized("audit endpoint is not configured"));
+        }
+
+        let digest: [u8; 32] = Sha256::digest(token.as_bytes()).into();
+        let matched = self.credentials.iter().fold(None, |matched, credential| {
+            let matches = credential.matches(token, &digest);
+            matched.or(matches.then_some(credential))
+        });
+        let Some(credential) = matched else {
+            record_outcome("unknown", "rejected_unknown");
+            return Err(HttpError::unauthorized("invalid audit token"));
+        };
+
+        if credential
+            .not_before
+            .is_some_and(|not_before| now < not_before)
+        {
+            record_outcome(&credential.name, "rejected_not_yet_valid");
+            return Err(HttpError::unauthorized("audit token is not valid yet"));
+        }
+        if credential
+            .expires_at
+            .is_some_and(|expires_at| now >= expires_at)
+        {
+            record_outcome(&credential.name, "rejected_expired");
+            return Err(HttpError::unauthorized("audit token has expired"));
+        }
+
+        record_outcome(&credential.name, "accepted");
+        Ok(&credential.name)
+    }
+
+    /// Checks the audit token sent with `request` and logs which credential was used.
+    #[allow(clippy::result_large_err)]
+    pub fn ensure_audit_token(&self, request: &HttpRequest) -> Result<(), HttpError> {
+        let token = parse_audit_token_header(request)
+            .ok_or_else(|| HttpError::unauthorized("missing X-Audit-Token header"))?;
+        let credential = self.authenticate(&token, Utc::now())?;
+        info!(
+            credential,
+            method = %request.method(),
+            path = request.path(),
+            "audit access"
+        );
+
+        Ok(())
+    }
+}
+
+fn record_outcome(credential: &str, outcome: &'static str) {
+    counter!(
+        "audit_authentications_total",
+        "credential" => credential.to_string(),
+        "outcome" => outcome
+    )
+    .increment(1);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use argon2::password_hash::{PasswordHasher, SaltString};
+    use argon2::{Algorithm, Params, Version};
+    use chrono::Duration;
+
+    fn sha256(token: &str) -> String {
+        hex::encode(Sha256::digest(token.as_bytes()))
+    }
+
+    fn authenticator(credentials: Vec<AuditCredential>) -> AuditAuthenticator {
+        AuditAuthenticator::from_settings(&Audit::new(credentials)).unwrap()
+    }
+
+    fn status(error: HttpError) -> Option<u16> {
+        let HttpError::Problem(problem) = error;
+        problem.status.map(|status| status.as_u16())
+    }
+
+    #[test]
+    fn overlapping_credentials_are_both_accepted_until_they_expire() {
+        let now = Utc::now();
+        let authenticator = authenticator(vec![
+            AuditCredential::new("previous", &sha256("old-token"))
+                .valid_between(None, Some(now + Duration::hours(1))),
+            AuditCredential::new("current", &sha256("new-token"))
+                .valid_between(Some(now - Duration::hours(1)), None),
+        ]);
+
+        assert_eq!(
+            authenticator.authenticate("old-token", now).unwrap(),
+            "previous"
+        );
+        assert_eq!(
+            authenticator.authenticate("new-token", now).unwrap(),
+            "current"
+        );
+
+        let later = now + Duration::hours(2);
+        assert_eq!(
+            status(authenticator.authenticate("old-token", later).unwrap_err()),
+            Some(401)
+        );
+        assert_eq!(
+            authenticator.authenticate("new-token", later).unwrap(),
+            "current"
+        );
+    }
+
+    #[test]
+    fn unknown_and_not_yet_valid_tokens_are_rejected() {
+        let now = Utc::now();
+        let authenticator = authenticator(vec![AuditCredential::new("next", &sha256("token"))
+            .valid_between(Some(now + Duration::days(1)), None)]);
+
+        for token in ["token", "other-token"] {
+            assert_eq!(
+                status(authenticator.authenticate(token, now).unwrap_err()),
+                Some(401),
+                "{token}"
+            );
+        }
+    }
+
+    #[test]
+    fn argon2_hashes_are_verified() {
+        let argon2 = Argon2::new(
+            Algorithm::Argon2id,
+            Version::V0x13,
+            Params::new(8, 1, 1, None).unwrap(),
+        );
+        let salt = SaltString::encode_b64(b"audit-test-salt").unwrap();
+        let hash = argon2
+            .hash_password(b"argon-token", &salt)
+            .unwrap()
+            .to_string();
+        let authenticator = authenticator(vec![AuditCredential::new("argon", &hash)]);
+
+        assert_eq!(
+            authenticator
+                .authenticate("argon-token", Utc::now())
+                .unwrap(),
+            "argon"
+        );
+        assert!(authenticator.authenticate("other", Utc::now()).is_err());
+    }
+
+    #[test]
+    fn invalid_credentials_are_rejected_at_startup() {
+        let invalid = [
+            vec![AuditCredential::new("plain", "local-audit-token")],
+            vec![
+                AuditCredential::new("twice", &sha256("one")),
+                AuditCredential::new("twice", &sha256("two")),
+            ],
+            vec![AuditCredential::new("backwards", &sha256("token"))
+                .valid_between(Some(Utc::now()), Some(Utc::now() - Duration::days(1)))],
+        ];
+
+        for credentials in invalid {
+            assert!(AuditAuthenticator::from_settings(&Audit::new(credentials)).is_err());
+        }
+    }
+}
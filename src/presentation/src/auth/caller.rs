@@ -0,0 +1,137 @@
+use actix_web::dev::Payload;
+use actix_web::web::Data;
+use actix_web::{FromRequest, HttpMessage, HttpRequest};
+use application::AccessScope;
+use std::convert::Infallible;
+use std::future::{ready, Ready};
+use uuid::Uuid;
+
+use super::{AuthorizationPolicy, Principal};
+use crate::request_id::RequestId;
+use crate::tenancy::Tenant;
+
+/// Identity recorded on the items a request changes, and the items it may access.
+///
+/// Authenticated callers are restricted to their own items, plus unowned items when the
+/// [`AuthorizationPolicy`] shares them, unless they hold its admin role. Requests served
+/// while authentication is disabled have no identity and are unrestricted. With
+/// multi-tenancy, every caller is confined to the [`Tenant`] of the request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Caller {
+    pub id: Option<Uuid>,
+    pub scope: AccessScope,
+    pub request_id: Option<String>,
+}
+
+impl Caller {
+    pub fn anonymous() -> Self {
+        Self {
+            id: None,
+            scope: AccessScope::unrestricted(),
+            request_id: None,
+        }
+    }
+
+    pub fn from_principal(principal: &Principal, policy: Option<&AuthorizationPolicy>) -> Self {
+        let is_admin = policy.is_some_and(|policy| policy.is_admin(principal));
+        let share_unowned = policy.is_some_and(AuthorizationPolicy::shares_unowned_items);
+        Self {
+            id: Some(principal.id),
+            scope: if is_admin {
+                AccessScope::unrestricted()
+            } else {
+                AccessScope::owned_by(principal.id).with_unowned(share_unowned)
+            },
+            request_id: None,
+        }
+    }
+
+    pub fn in_tenant(mut self, tenant: Option<Tenant>) -> Self {
+        self.scope = self.scope.in_tenant(tenant.map(|Tenant(id)| id));
+        self
+    }
+
+    pub fn with_request_id(mut self, request_id: Option<RequestId>) -> Self {
+        self.request_id = request_id.map(|RequestId(id)| id);
+        self
+    }
+
+    pub fn tenant_id(&self) -> Option<String> {
+        self.scope.tenant_id.clone()
+    }
+}
+
+impl FromRequest for Caller {
+    type Error = Infallible;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(request: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        let policy = request.app_data::<Data<AuthorizationPolicy>>();
+        let extensions = request.extensions();
+        let caller = match extensions.get::<Principal>() {
+            Some(principal) => Self::from_principal(principal, policy.map(|policy| &***policy)),
+            None => Self::anonymous(),
+        };
+        ready(Ok(caller
+            .in_tenant(extensions.get::<Tenant>().cloned())
+            .with_request_id(extensions.get::<RequestId>().cloned())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use application::{Authorization, Settings};
+
+    #[test]
+    fn only_the_admin_role_lifts_the_owner_restriction() {
+        let policy =
+            AuthorizationPolicy::from_settings(&Settings::default().authorization).unwrap();
+        let user = Principal::new(Uuid::new_v4()).with_roles(["editor"]);
+        let admin = Principal::new(Uuid::new_v4()).with_roles(["admin"]);
+
+        assert_eq!(
+            Caller::from_principal(&user, Some(&policy)).scope,
+            AccessScope::owned_by(user.id)
+        );
+        assert_eq!(
+            Caller::from_principal(&admin, Some(&policy)).scope,
+            AccessScope::unrestricted()
+        );
+        assert_eq!(
+            Caller::from_principal(&admin, None).scope,
+            AccessScope::owned_by(admin.id)
+        );
+        assert_eq!(Caller::anonymous().id, None);
+    }
+
+    #[test]
+    fn shared_unowned_items_are_added_to_the_owner_scope() {
+        let mut settings = serde_json::to_value(&Settings::default().authorization).unwrap();
+        settings["unowned_items"] = "shared".into();
+        let settings: Authorization = serde_json::from_value(settings).unwrap();
+        let policy = AuthorizationPolicy::from_settings(&settings).unwrap();
+        let user = Principal::new(Uuid::new_v4());
+
+        assert_eq!(
+            Caller::from_principal(&user, Some(&policy)).scope,
+            AccessScope::owned_by(user.id).with_unowned(true)
+        );
+    }
+
+    #[test]
+    fn the_tenant_confines_admins_too() {
+        let policy =
+            AuthorizationPolicy::from_settings(&Settings::default().authorization).unwrap();
+        let admin = Principal::new(Uuid::new_v4()).with_roles(["admin"]);
+
+        let caller =
+            Caller::from_principal(&admin, Some(&policy)).in_tenant(Some(Tenant("acme".into())));
+
+        assert_eq!(
+            caller.scope,
+            AccessScope::unrestricted().in_tenant(Some("acme".into()))
+        );
+        assert_eq!(caller.tenant_id().as_deref(), Some("acme"));
+    }
+}
@@ -0,0 +1,139 @@
+use anyhow::{anyhow, bail, Context, Result};
+use jsonwebtoken::jwk::{AlgorithmParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm};
+use jsonwebtoken::{Algorithm, DecodingKey};
+use std::fs;
+use std::path::PathBuf;
+
+/// Key that may verify tokens signed with `algorithm`, optionally bound to a key id.
+#[derive(Clone)]
+pub struct VerificationKey {
+    pub kid: Option<String>,
+    pub algorithm: Algorithm,
+    pub key: DecodingKey,
+}
+
+impl VerificationKey {
+    /// A key without an id matches any token of its algorithm.
+    pub fn matches(&self, algorithm: Algorithm, kid: Option<&str>) -> bool {
+        self.algorithm == algorithm
+            && match (self.kid.as_deref(), kid) {
+                (Some(expected), Some(kid)) => expected == kid,
+                _ => true,
+            }
+    }
+}
+
+/// Supplies the keys that bearer tokens are verified against.
+pub trait KeySource: Send + Sync {
+    fn load(&self) -> Result<Vec<VerificationKey>>;
+}
+
+/// Shared HS256 secret.
+pub struct SecretKeySource {
+    secret: String,
+}
+
+impl SecretKeySource {
+    pub fn new(secret: impl Into<String>) -> Self {
+        Self {
+            secret: secret.into(),
+        }
+    }
+}
+
+impl KeySource for SecretKeySource {
+    fn load(&self) -> Result<Vec<VerificationKey>> {
+        Ok(vec![VerificationKey {
+            kid: None,
+            algorithm: Algorithm::HS256,
+            key: DecodingKey::from_secret(self.secret.as_bytes()),
+        }])
+    }
+}
+
+/// RSA (RS256) or P-256 (ES256) public key in a PEM file.
+pub struct PemFileKeySource {
+    path: PathBuf,
+}
+
+impl PemFileKeySource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl KeySource for PemFileKeySource {
+    fn load(&self) -> Result<Vec<VerificationKey>> {
+        let pem = fs::read(&self.path)
+            .with_context(|| format!("failed to read public key {}", self.path.display()))?;
+        let (algorithm, key) = match DecodingKey::from_rsa_pem(&pem) {
+            Ok(key) => (Algorithm::RS256, key),
+            Err(_) => (
+                Algorithm::ES256,
+                DecodingKey::from_ec_pem(&pem).map_err(|err| {
+                    anyhow!(
+                        "{} is neither an RSA nor an EC public key: {err}",
+                        self.path.display()
+                    )
+                })?,
+            ),
+        };
+
+        Ok(vec![VerificationKey {
+            kid: None,
+            algorithm,
+            key,
+        }])
+    }
+}
+
+/// JSON Web Key Set stored in a local file.
+pub struct JwksFileKeySource {
+    path: PathBuf,
+}
+
+impl JwksFileKeySource {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+}
+
+impl KeySource for JwksFileKeySource {
+    fn load(&self) -> Result<Vec<VerificationKey>> {
+        let content = fs::read_to_string(&self.path)
+            .with_context(|| format!("failed to read JWKS {}", self.path.display()))?;
+        let set: JwkSet = serde_json::from_str(&content)
+            .with_context(|| format!("{} is not a valid JWKS", self.path.display()))?;
+
+        set.keys
+            .iter()
+            .map(|jwk| {
+                Ok(VerificationKey {
+                    kid: jwk.common.key_id.clone(),
+                    algorithm: jwk_algorithm(jwk)?,
+                    key: DecodingKey::from_jwk(jwk)?,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Uses the declared `alg`, or infers it from the key type when it is missing.
+fn jwk_algorithm(jwk: &Jwk) -> Result<Algorithm> {
+    let algorithm = match (jwk.common.key_algorithm, &jwk.algorithm) {
+        (Some(KeyAlgorithm::HS256), _) => Algorithm::HS256,
+        (Some(KeyAlgorithm::RS256), _) => Algorithm::RS256,
+        (Some(KeyAlgorithm::ES256), _) => Algorithm::ES256,
+        (Some(other), _) => bail!("unsupported JWK algorithm {other:?}"),
+        (None, AlgorithmParameters::OctetKey(_)) => Algorithm::HS256,
+        (None, AlgorithmParameters::RSA(_)) => Algorithm::RS256,
+        (None, AlgorithmParameters::EllipticCurve(params))
+            if params.curve == EllipticCurve::P256 =>
+        {
+            Algorithm::ES256
+        }
+        (None, _) => bail!("unsupported JWK key type"),
+    };
+
+    Ok(algorithm)
+}
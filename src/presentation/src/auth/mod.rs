@@ -0,0 +1,15 @@
+mod api_keys;
+mod audit;
+mod authenticator;
+mod caller;
+mod keys;
+mod policy;
+mod principal;
+
+pub use api_keys::{generate_api_key, ApiKeyAuthenticator};
+pub use audit::AuditAuthenticator;
+pub use authenticator::{authentication_middleware, Authenticator};
+pub use caller::Caller;
+pub use keys::{JwksFileKeySource, KeySource, PemFileKeySource, SecretKeySource, VerificationKey};
+pub use policy::{authorization_middleware, AuthorizationPolicy};
+pub use principal::Principal;
@@ -0,0 +1,254 @@
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::Method;
+use actix_web::middleware::Next;
+use actix_web::web::Data;
+use actix_web::{Error, HttpMessage};
+use anyhow::{anyhow, bail, Result};
+use application::{Authorization, AuthorizationRule};
+
+use super::Principal;
+use crate::errors::HttpError;
+
+struct Rule {
+    path: String,
+    methods: Vec<Method>,
+    scopes: Vec<String>,
+    roles: Vec<String>,
+}
+
+impl Rule {
+    fn from_settings(rule: &AuthorizationRule) -> Result<Self> {
+        let methods = rule
+            .methods
+            .iter()
+            .map(|method| {
+                Method::from_bytes(method.trim().to_ascii_uppercase().as_bytes()).map_err(|_| {
+                    anyhow!("invalid HTTP method {method:?} in rule for {}", rule.path)
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            path: rule.path.trim_end_matches('/').to_string(),
+            methods,
+            scopes: rule.scopes.clone(),
+            roles: rule.roles.clone(),
+        })
+    }
+
+    fn matches(&self, method: &Method, path: &str) -> bool {
+        let path_matches = path
+            .strip_prefix(&self.path)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with('/'));
+
+        path_matches && (self.methods.is_empty() || self.methods.contains(method))
+    }
+
+    fn allows(&self, principal: &Principal) -> bool {
+        let unrestricted = self.scopes.is_empty() && self.roles.is_empty();
+        let has_scopes =
+            !self.scopes.is_empty() && self.scopes.iter().all(|scope| principal.has_scope(scope));
+        let has_role = self.roles.iter().any(|role| principal.has_role(role));
+
+        unrestricted || has_scopes || has_role
+    }
+
+    fn requirement(&self) -> String {
+        let mut alternatives = Vec::new();
+        if !self.scopes.is_empty() {
+            alternatives.push(format!("scope {}", self.scopes.join(" and ")));
+        }
+        if !self.roles.is_empty() {
+            alternatives.push(format!("role {}", self.roles.join(" or ")));
+        }
+        alternatives.join(" or ")
+    }
+}
+
+/// Route-to-scope policy evaluated against the authenticated [`Principal`].
+pub struct AuthorizationPolicy {
+    enabled: bool,
+    rules: Vec<Rule>,
+    admin_role: String,
+    share_unowned_items: bool,
+}
+
+impl AuthorizationPolicy {
+    pub fn from_settings(settings: &Authorization) -> Result<Self> {
+        let rules = settings
+            .rules
+            .iter()
+            .map(Rule::from_settings)
+            .collect::<Result<_>>()?;
+        let share_unowned_items = match settings.unowned_items.trim() {
+            "admins_only" => false,
+            "shared" => true,
+            other => bail!("unsupported authorization.unowned_items: {other}"),
+        };
+
+        Ok(Self {
+            enabled: settings.enabled,
+            rules,
+            admin_role: settings.admin_role.trim().to_string(),
+            share_unowned_items,
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Whether `principal` may see and change every user's to-do items.
+    pub fn is_admin(&self, principal: &Principal) -> bool {
+        !self.admin_role.is_empty() && principal.has_role(&self.admin_role)
+    }
+
+    /// Whether every caller may access items that have no owner.
+    pub fn shares_unowned_items(&self) -> bool {
+        self.share_unowned_items
+    }
+
+    /// Applies the first rule matching the request, or returns why access is denied.
+    pub fn authorize(
+        &self,
+        method: &Method,
+        path: &str,
+        principal: &Principal,
+    ) -> std::result::Result<(), String> {
+        let Some(rule) = self.rules.iter().find(|rule| rule.matches(method, path)) else {
+            return Err(format!("no authorization rule covers {method} {path}"));
+        };
+
+        if rule.allows(principal) {
+            Ok(())
+        } else {
+            Err(format!("{method} {path} requires {}", rule.requirement()))
+        }
+    }
+}
+
+/// Answers `403` to authenticated requests the [`AuthorizationPolicy`] does not allow.
+///
+/// Requests without a principal pass through, so the policy only applies while
+/// authentication is enabled.
+pub async fn authorization_middleware(
+    request: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let policy = request
+        .app_data::<Data<AuthorizationPolicy>>()
+        .filter(|policy| policy.is_enabled())
+        .cloned();
+    let principal = request.extensions().get::<Principal>().cloned();
+    let (Some(policy), Some(principal)) = (policy, principal) else {
+        return next.call(request).await.map(|r| r.map_into_boxed_body());
+    };
+
+    match policy.authorize(request.method(), request.path(), &principal) {
+        Ok(()) => next.call(request).await.map(|r| r.map_into_boxed_body()),
+        Err(detail) => Ok(request.error_response(HttpError::forbidden(detail))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::middleware::from_fn;
+    use actix_web::test::{call_service, init_service, TestRequest};
+    use actix_web::{web, App, HttpResponse};
+    use application::Settings;
+    use uuid::Uuid;
+
+    fn default_policy() -> AuthorizationPolicy {
+        AuthorizationPolicy::from_settings(&Settings::default().authorization).unwrap()
+    }
+
+    fn caller(scopes: &[&str]) -> Principal {
+        Principal::new(Uuid::new_v4()).with_scopes(scopes.iter().copied())
+    }
+
+    #[test]
+    fn default_policy_separates_read_write_and_audit_scopes() {
+        let policy = default_policy();
+        let reader = caller(&["todo:read"]);
+        let writer = caller(&["todo:write"]);
+        let auditor = caller(&["audit:read"]);
+        let admin = Principal::new(Uuid::new_v4()).with_roles(["admin"]);
+        let item = format!("/api/v1/to-do-items/{}", Uuid::new_v4());
+
+        assert!(policy
+            .authorize(&Method::GET, "/api/v1/to-do-items", &reader)
+            .is_ok());
+        assert!(policy.authorize(&Method::GET, &item, &reader).is_ok());
+        assert!(policy.authorize(&Method::DELETE, &item, &reader).is_err());
+        assert!(policy.authorize(&Method::PATCH, &item, &writer).is_ok());
+        assert!(policy.authorize(&Method::GET, &item, &writer).is_err());
+        assert!(policy
+            .authorize(&Method::POST, "/api/v1/to-do-items:batch", &writer)
+            .is_ok());
+        assert!(policy
+            .authorize(&Method::GET, "/api/v1/audit/to-do-items/1", &reader)
+            .is_err());
+        assert!(policy
+            .authorize(&Method::GET, "/api/v1/audit/to-do-items/1", &auditor)
+            .is_ok());
+        assert!(policy.authorize(&Method::DELETE, &item, &admin).is_ok());
+    }
+
+    #[test]
+    fn unmatched_routes_are_denied_and_denials_name_the_requirement() {
+        let policy = default_policy();
+        let reader = caller(&["todo:read"]);
+
+        assert_eq!(
+            policy.authorize(&Method::GET, "/api/v1/to-do-itemsx", &reader),
+            Err("no authorization rule covers GET /api/v1/to-do-itemsx".to_string())
+        );
+        assert_eq!(
+            policy.authorize(&Method::PUT, "/api/v1/to-do-items/1", &reader),
+            Err("PUT /api/v1/to-do-items/1 requires scope todo:write or role admin".to_string())
+        );
+    }
+
+    #[test]
+    fn rules_with_unknown_methods_are_rejected() {
+        let rule = AuthorizationRule::new("/api", &["GE T"], &[], &[]);
+
+        assert!(Rule::from_settings(&rule).is_err());
+    }
+
+    #[actix_web::test]
+    async fn forbidden_requests_get_403_problem_details() {
+        let app = init_service(
+            App::new().app_data(Data::new(default_policy())).service(
+                web::scope("/api/v1/to-do-items")
+                    .wrap(from_fn(authorization_middleware))
+                    .route("", web::get().to(HttpResponse::Ok)),
+            ),
+        )
+        .await;
+        let request = |scopes: &[&str]| {
+            let request = TestRequest::get().uri("/api/v1/to-do-items").to_request();
+            request.extensions_mut().insert(caller(scopes));
+            request
+        };
+
+        let denied = call_service(&app, request(&["todo:write"])).await;
+        let allowed = call_service(&app, request(&["todo:read"])).await;
+        let anonymous = call_service(
+            &app,
+            TestRequest::get().uri("/api/v1/to-do-items").to_request(),
+        )
+        .await;
+
+        assert_eq!(denied.status(), StatusCode::FORBIDDEN);
+        assert_eq!(
+            denied.headers().get("content-type").unwrap(),
+            "application/problem+json"
+        );
+        assert_eq!(allowed.status(), StatusCode::OK);
+        assert_eq!(anonymous.status(), StatusCode::OK);
+    }
+}
@@ -0,0 +1,76 @@
+use actix_web::dev::Payload;
+use actix_web::{FromRequest, HttpMessage, HttpRequest};
+use std::collections::{BTreeMap, BTreeSet};
+use std::future::{ready, Ready};
+use uuid::Uuid;
+
+use crate::errors::HttpError;
+
+/// Caller authenticated by a bearer token.
+///
+/// Extracting it from a request that was not authenticated fails with `401`; handlers that
+/// also serve requests while authentication is disabled take `Option<Principal>`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Principal {
+    /// Token subject.
+    pub id: Uuid,
+    /// Space-separated `scope` claim.
+    pub scopes: BTreeSet<String>,
+    /// `roles` claim.
+    pub roles: BTreeSet<String>,
+    /// Other string-valued claims, such as the tenant the caller belongs to.
+    pub claims: BTreeMap<String, String>,
+}
+
+impl Principal {
+    pub fn new(id: Uuid) -> Self {
+        Self {
+            id,
+            scopes: BTreeSet::new(),
+            roles: BTreeSet::new(),
+            claims: BTreeMap::new(),
+        }
+    }
+
+    pub fn with_scopes<S: Into<String>>(mut self, scopes: impl IntoIterator<Item = S>) -> Self {
+        self.scopes = scopes.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_roles<S: Into<String>>(mut self, roles: impl IntoIterator<Item = S>) -> Self {
+        self.roles = roles.into_iter().map(Into::into).collect();
+        self
+    }
+
+    pub fn with_claims(mut self, claims: impl IntoIterator<Item = (String, String)>) -> Self {
+        self.claims = claims.into_iter().collect();
+        self
+    }
+
+    pub fn claim(&self, name: &str) -> Option<&str> {
+        self.claims.get(name).map(String::as_str)
+    }
+
+    pub fn has_scope(&self, scope: &str) -> bool {
+        self.scopes.contains(scope)
+    }
+
+    pub fn has_role(&self, role: &str) -> bool {
+        self.roles.contains(role)
+    }
+}
+
+impl FromRequest for Principal {
+    type Error = HttpError;
+    type Future = Ready<Result<Self, Self::Error>>;
+
+    fn from_request(request: &HttpRequest, _payload: &mut Payload) -> Self::Future {
+        ready(
+            request
+                .extensions()
+                .get::<Principal>()
+                .cloned()
+                .ok_or_else(|| HttpError::unauthorized("authentication required")),
+        )
+    }
+}
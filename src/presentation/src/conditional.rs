@@ -0,0 +1,118 @@
+use actix_web::http::header::{
+    EntityTag, Header, HttpDate, IfModifiedSince, IfNoneMatch, LastModified,
+};
+use actix_web::HttpRequest;
+use application::PaginatedResult;
+use chrono::{DateTime, Utc};
+use domain::ToDoItem;
+use sha2::{Digest, Sha256};
+use std::time::SystemTime;
+
+/// Strong ETag of a single item, derived from its version.
+pub(crate) fn item_etag(version: i32) -> EntityTag {
+    EntityTag::new_strong(version.to_string())
+}
+
+/// Weak ETag of a list page.
+///
+/// Covers the query string, the matching total and every item's id and version together
+/// with the latest `updated_at`, so any change to the page content yields a new tag.
+pub(crate) fn collection_etag(query: &str, result: &PaginatedResult<ToDoItem>) -> EntityTag {
+    let mut hasher = Sha256::new();
+    hasher.update(query);
+    hasher.update(format!("|{:?}|", result.total_items));
+    if let Some(latest) = result.items.iter().map(|item| item.updated_at).max() {
+        hasher.update(latest.timestamp_micros().to_be_bytes());
+    }
+    for item in &result.items {
+        hasher.update(item.id.as_bytes());
+        hasher.update(item.version.to_be_bytes());
+    }
+
+    let digest = hasher.finalize();
+    EntityTag::new_weak(hex::encode(&digest[..16]))
+}
+
+pub(crate) fn last_modified(updated_at: DateTime<Utc>) -> LastModified {
+    LastModified(HttpDate::from(SystemTime::from(updated_at)))
+}
+
+/// Evaluates `If-None-Match`, falling back to `If-Modified-Since` only when the former is
+/// absent, as RFC 9110 requires. Returns `true` when the client copy is still current.
+pub(crate) fn is_not_modified(
+    request: &HttpRequest,
+    etag: &EntityTag,
+    updated_at: Option<DateTime<Utc>>,
+) -> bool {
+    if request.headers().contains_key(IfNoneMatch::name()) {
+        return match IfNoneMatch::parse(request) {
+            Ok(IfNoneMatch::Any) => true,
+            Ok(IfNoneMatch::Items(tags)) => tags.iter().any(|tag| tag.weak_eq(etag)),
+            Err(_) => false,
+        };
+    }
+
+    match (updated_at, IfModifiedSince::parse(request)) {
+        (Some(updated_at), Ok(IfModifiedSince(since))) => {
+            // HTTP dates have second precision.
+            updated_at.timestamp() <= DateTime::<Utc>::from(SystemTime::from(since)).timestamp()
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::header::{IF_MODIFIED_SINCE, IF_NONE_MATCH};
+    use actix_web::test::TestRequest;
+    use chrono::Duration;
+
+    #[test]
+    fn if_none_match_matches_weak_and_strong_forms() {
+        let etag = item_etag(3);
+
+        for header in ["\"3\"", "W/\"3\"", "\"1\", \"3\"", "*"] {
+            let request = TestRequest::default()
+                .insert_header((IF_NONE_MATCH, header))
+                .to_http_request();
+            assert!(is_not_modified(&request, &etag, None), "{header}");
+        }
+
+        let request = TestRequest::default()
+            .insert_header((IF_NONE_MATCH, "\"2\""))
+            .to_http_request();
+        assert!(!is_not_modified(&request, &etag, None));
+    }
+
+    #[test]
+    fn if_modified_since_is_ignored_when_if_none_match_is_present() {
+        let updated_at = Utc::now() - Duration::hours(1);
+        let since = last_modified(Utc::now()).to_string();
+
+        let request = TestRequest::default()
+            .insert_header((IF_MODIFIED_SINCE, since.clone()))
+            .to_http_request();
+        assert!(is_not_modified(&request, &item_etag(1), Some(updated_at)));
+
+        let request = TestRequest::default()
+            .insert_header((IF_MODIFIED_SINCE, since))
+            .insert_header((IF_NONE_MATCH, "\"2\""))
+            .to_http_request();
+        assert!(!is_not_modified(&request, &item_etag(1), Some(updated_at)));
+    }
+
+    #[test]
+    fn collection_etag_changes_with_item_versions_and_query() {
+        let mut item = ToDoItem::new("title".into(), "note".into());
+        let page = |item: &ToDoItem| PaginatedResult::new(vec![item.clone()], 1, 10, 1);
+        let original = collection_etag("page=1", &page(&item));
+
+        assert!(original.weak);
+        assert_eq!(original, collection_etag("page=1", &page(&item)));
+        assert_ne!(original, collection_etag("page=2", &page(&item)));
+
+        item.version += 1;
+        assert_ne!(original, collection_etag("page=1", &page(&item)));
+    }
+}
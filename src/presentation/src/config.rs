@@ -1,5 +1,9 @@
 use crate::api;
+use crate::auth::{authentication_middleware, authorization_middleware};
 use crate::errors::HttpError;
+use crate::idempotency::idempotency_middleware;
+use crate::tenancy::tenant_middleware;
+use actix_web::middleware::from_fn;
 use actix_web::web;
 use actix_web::ResponseError;
 use actix_web::{error::InternalError, error::JsonPayloadError, error::QueryPayloadError, Error};
@@ -12,17 +16,42 @@ pub fn configure(cfg: &mut web::ServiceConfig) {
     cfg.service(api::metrics);
     cfg.service(
         web::scope("/api/v1")
+            .service(
+                web::scope("/to-do-items:batch")
+                    .wrap(from_fn(idempotency_middleware))
+                    .wrap(from_fn(tenant_middleware))
+                    .wrap(from_fn(authorization_middleware))
+                    .wrap(from_fn(authentication_middleware))
+                    .app_data(batch_json_config())
+                    .service(api::batch),
+            )
             .service(
                 web::scope("/to-do-items")
+                    .wrap(from_fn(idempotency_middleware))
+                    .wrap(from_fn(tenant_middleware))
+                    .wrap(from_fn(authorization_middleware))
+                    .wrap(from_fn(authentication_middleware))
                     .service(api::get_all)
                     .service(api::create)
                     .service(api::get_by_id)
                     .service(api::update)
-                    .service(api::delete),
+                    .service(api::patch)
+                    .service(api::delete)
+                    .service(api::restore),
             )
             .service(
                 web::scope("/audit")
-                    .service(web::scope("/to-do-items").service(api::get_deleted_by_id_for_audit)),
+                    .wrap(from_fn(tenant_middleware))
+                    .wrap(from_fn(authorization_middleware))
+                    .wrap(from_fn(authentication_middleware))
+                    .service(
+                        web::scope("/to-do-items")
+                            .service(api::get_all_deleted_for_audit)
+                            .service(api::get_deleted_by_id_for_audit)
+                            .service(api::get_history)
+                            .service(api::get_revision)
+                            .service(api::get_diff),
+                    ),
             )
             .service(
                 web::scope("/healthz")
@@ -39,6 +68,13 @@ fn json_config() -> web::JsonConfig {
         .error_handler(|err: JsonPayloadError, _req| map_payload_error(err.into()))
 }
 
+/// Batches carry up to a thousand items, so they get a larger body limit than single items.
+fn batch_json_config() -> web::JsonConfig {
+    web::JsonConfig::default()
+        .limit(2 * 1024 * 1024)
+        .error_handler(|err: JsonPayloadError, _req| map_payload_error(err.into()))
+}
+
 fn query_config() -> web::QueryConfig {
     web::QueryConfig::default()
         .error_handler(|err: QueryPayloadError, _req| map_payload_error(err.into()))
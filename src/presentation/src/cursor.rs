@@ -0,0 +1,170 @@
+use crate::requests::parse_sort;
+use application::{
+    NullsOrder, SortDirection, ToDoItemCursor, ToDoItemSort, ToDoItemSortField, ToDoItemSortKey,
+    ToDoItemSortValue,
+};
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Wire format of a keyset cursor. Clients treat the encoded value as opaque.
+///
+/// `values` holds one entry per effective sort key, the trailing `id` tiebreaker included.
+#[derive(Deserialize, Serialize)]
+struct CursorPayload {
+    sort: String,
+    values: Vec<Value>,
+}
+
+pub fn encode_cursor(cursor: &ToDoItemCursor) -> String {
+    let payload = CursorPayload {
+        sort: format_sort(&cursor.sort),
+        values: cursor.values.iter().map(encode_value).collect(),
+    };
+    let json = serde_json::to_vec(&payload).expect("cursor payload serializes to JSON");
+
+    URL_SAFE_NO_PAD.encode(json)
+}
+
+pub fn decode_cursor(value: &str) -> Result<ToDoItemCursor, String> {
+    let invalid = || "cursor is invalid".to_string();
+    let json = URL_SAFE_NO_PAD
+        .decode(value.trim())
+        .map_err(|_| invalid())?;
+    let payload = serde_json::from_slice::<CursorPayload>(&json).map_err(|_| invalid())?;
+    let sort = parse_sort(&payload.sort).map_err(|_| invalid())?;
+
+    let keys = sort.effective_keys();
+    if keys.len() != payload.values.len() {
+        return Err(invalid());
+    }
+    let values = keys
+        .iter()
+        .zip(payload.values)
+        .map(|(key, value)| decode_value(key.field, value).ok_or_else(invalid))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    Ok(ToDoItemCursor { sort, values })
+}
+
+fn encode_value(value: &ToDoItemSortValue) -> Value {
+    match value {
+        ToDoItemSortValue::Id(id) => Value::from(id.to_string()),
+        ToDoItemSortValue::Text(text) => text.clone().map_or(Value::Null, Value::from),
+        ToDoItemSortValue::Status(status) => Value::from(status.as_str()),
+        ToDoItemSortValue::Timestamp(timestamp) => {
+            timestamp.map_or(Value::Null, |timestamp| Value::from(timestamp.to_rfc3339()))
+        }
+    }
+}
+
+fn decode_value(field: ToDoItemSortField, value: Value) -> Option<ToDoItemSortValue> {
+    let text = match value {
+        Value::String(text) => Some(text),
+        Value::Null if field.is_nullable() => None,
+        _ => return None,
+    };
+
+    match field {
+        ToDoItemSortField::Id => text?.parse().ok().map(ToDoItemSortValue::Id),
+        ToDoItemSortField::Title => Some(ToDoItemSortValue::Text(text)),
+        ToDoItemSortField::Status => text?.parse().ok().map(ToDoItemSortValue::Status),
+        ToDoItemSortField::CreatedAt
+        | ToDoItemSortField::UpdatedAt
+        | ToDoItemSortField::DueAt
+        | ToDoItemSortField::DeletedAt => {
+            let timestamp = match text {
+                Some(text) => Some(text.parse::<DateTime<Utc>>().ok()?),
+                None => None,
+            };
+            Some(ToDoItemSortValue::Timestamp(timestamp))
+        }
+    }
+}
+
+/// Formats the sort in the query parameter syntax, spelling out the `NULL` placement of
+/// nullable fields so the cursor does not depend on defaults.
+fn format_sort(sort: &ToDoItemSort) -> String {
+    sort.keys
+        .iter()
+        .map(format_sort_key)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn format_sort_key(key: &ToDoItemSortKey) -> String {
+    let field = match key.field {
+        ToDoItemSortField::Id => "id",
+        ToDoItemSortField::Title => "title",
+        ToDoItemSortField::Status => "status",
+        ToDoItemSortField::CreatedAt => "created_at",
+        ToDoItemSortField::UpdatedAt => "updated_at",
+        ToDoItemSortField::DueAt => "due_at",
+        ToDoItemSortField::DeletedAt => "deleted_at",
+    };
+    let direction = match key.direction {
+        SortDirection::Asc => "asc",
+        SortDirection::Desc => "desc",
+    };
+
+    if !key.field.is_nullable() {
+        return format!("{field}:{direction}");
+    }
+    let nulls = match key.nulls {
+        NullsOrder::First => "nulls_first",
+        NullsOrder::Last => "nulls_last",
+    };
+
+    format!("{field}:{direction}:{nulls}")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use domain::{ToDoItem, ToDoItemStatus};
+    use uuid::Uuid;
+
+    #[test]
+    fn cursor_round_trips_through_opaque_encoding() {
+        let mut item = ToDoItem::new("Buy milk & eggs".to_string(), "note".to_string());
+        item.status = ToDoItemStatus::InProgress;
+        let sort = ToDoItemSort::new(vec![
+            ToDoItemSortKey::new(ToDoItemSortField::Status, SortDirection::Asc),
+            ToDoItemSortKey::new(ToDoItemSortField::DueAt, SortDirection::Asc)
+                .with_nulls(NullsOrder::First),
+            ToDoItemSortKey::new(ToDoItemSortField::Title, SortDirection::Desc),
+            ToDoItemSortKey::new(ToDoItemSortField::UpdatedAt, SortDirection::Desc),
+        ]);
+        let cursor = ToDoItemCursor::after(&item, &sort);
+
+        let encoded = encode_cursor(&cursor);
+
+        assert!(encoded
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_'));
+        assert_eq!(decode_cursor(&encoded), Ok(cursor));
+    }
+
+    #[test]
+    fn decode_cursor_rejects_tampered_values() {
+        let id = Uuid::new_v4();
+        let encode = |json: String| URL_SAFE_NO_PAD.encode(json);
+
+        assert!(decode_cursor("not a cursor").is_err());
+        assert!(decode_cursor(&encode(r#"{"sort":"note:asc","values":[]}"#.into())).is_err());
+        assert!(decode_cursor(&encode(format!(
+            r#"{{"sort":"status:asc","values":["{id}"]}}"#
+        )))
+        .is_err());
+        assert!(decode_cursor(&encode(format!(
+            r#"{{"sort":"status:asc","values":["archived","{id}"]}}"#
+        )))
+        .is_err());
+        assert!(decode_cursor(&encode(format!(
+            r#"{{"sort":"status:asc","values":["done","{id}"]}}"#
+        )))
+        .is_ok());
+    }
+}
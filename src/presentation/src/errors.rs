@@ -31,6 +31,15 @@ impl HttpError {
         )
     }
 
+    pub fn conflict(detail: impl Into<String>) -> Self {
+        HttpError::Problem(
+            ProblemDetails::new()
+                .with_status(HttpStatusCode::CONFLICT)
+                .with_title("Conflict")
+                .with_detail(detail.into()),
+        )
+    }
+
     pub fn precondition_failed(detail: impl Into<String>) -> Self {
         HttpError::Problem(
             ProblemDetails::new()
@@ -49,6 +58,24 @@ impl HttpError {
         )
     }
 
+    pub fn unsupported_media_type(detail: impl Into<String>) -> Self {
+        HttpError::Problem(
+            ProblemDetails::new()
+                .with_status(HttpStatusCode::UNSUPPORTED_MEDIA_TYPE)
+                .with_title("Unsupported Media Type")
+                .with_detail(detail.into()),
+        )
+    }
+
+    pub fn unprocessable_entity(detail: impl Into<String>) -> Self {
+        HttpError::Problem(
+            ProblemDetails::new()
+                .with_status(HttpStatusCode::UNPROCESSABLE_ENTITY)
+                .with_title("Unprocessable Entity")
+                .with_detail(detail.into()),
+        )
+    }
+
     pub fn unauthorized(detail: impl Into<String>) -> Self {
         HttpError::Problem(
             ProblemDetails::new()
@@ -57,6 +84,15 @@ impl HttpError {
                 .with_detail(detail.into()),
         )
     }
+
+    pub fn forbidden(detail: impl Into<String>) -> Self {
+        HttpError::Problem(
+            ProblemDetails::new()
+                .with_status(HttpStatusCode::FORBIDDEN)
+                .with_title("Forbidden")
+                .with_detail(detail.into()),
+        )
+    }
 }
 
 impl Display for HttpError {
@@ -109,6 +145,8 @@ impl From<ApplicationError> for HttpError {
                     .with_detail(err.to_string()),
             ),
             ApplicationError::Conflict { .. } => HttpError::precondition_failed(err.to_string()),
+            ApplicationError::InvalidStatusTransition { .. }
+            | ApplicationError::NotDeleted { .. } => HttpError::conflict(err.to_string()),
             ApplicationError::Internal { .. } => {
                 HttpError::internal_server_error("an internal error occurred")
             }
@@ -121,6 +159,7 @@ mod tests {
     use super::*;
     use actix_web::body::to_bytes;
     use actix_web::ResponseError;
+    use domain::ToDoItemStatus;
     use uuid::Uuid;
 
     #[actix_web::test]
@@ -139,6 +178,42 @@ mod tests {
         assert!(body.contains("todo item with id"));
     }
 
+    #[actix_web::test]
+    async fn maps_invalid_status_transitions_to_409_problem_details() {
+        let error = HttpError::from(ApplicationError::InvalidStatusTransition {
+            id: Uuid::nil(),
+            from: ToDoItemStatus::Pending,
+            to: ToDoItemStatus::Done,
+        });
+
+        let response = error.error_response();
+
+        assert_eq!(
+            response.status().as_u16(),
+            HttpStatusCode::CONFLICT.as_u16()
+        );
+        let body = to_bytes(response.into_body()).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains("\"status\":409"));
+        assert!(body.contains("from status pending to done"));
+    }
+
+    #[actix_web::test]
+    async fn maps_not_deleted_application_errors_to_409_problem_details() {
+        let error = HttpError::from(ApplicationError::NotDeleted { id: Uuid::nil() });
+
+        let response = error.error_response();
+
+        assert_eq!(
+            response.status().as_u16(),
+            HttpStatusCode::CONFLICT.as_u16()
+        );
+        let body = to_bytes(response.into_body()).await.unwrap();
+        let body = String::from_utf8(body.to_vec()).unwrap();
+        assert!(body.contains("\"status\":409"));
+        assert!(body.contains("is not deleted"));
+    }
+
     #[actix_web::test]
     async fn sanitizes_internal_application_errors() {
         let error = HttpError::from(ApplicationError::internal("db exploded"));
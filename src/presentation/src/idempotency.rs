@@ -0,0 +1,388 @@
+use actix_web::body::{to_bytes, BoxBody, MessageBody};
+use actix_web::dev::{Payload, ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HeaderValue, CONTENT_TYPE, ETAG, LOCATION};
+use actix_web::http::{Method, StatusCode};
+use actix_web::middleware::Next;
+use actix_web::web::{Bytes, BytesMut, Data};
+use actix_web::{Error, HttpMessage, HttpResponse, ResponseError};
+use application::{Idempotency, IdempotencyRecord, IdempotencyRepository, IdempotentResponse};
+use chrono::{Duration, Utc};
+use futures_util::StreamExt;
+use sha2::{Digest, Sha256};
+use tracing::warn;
+use uuid::fmt::Hyphenated;
+
+use crate::auth::Principal;
+use crate::errors::HttpError;
+use crate::tenancy::{Tenant, MAX_TENANT_LENGTH};
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+pub const IDEMPOTENT_REPLAYED_HEADER: &str = "Idempotent-Replayed";
+const MAX_KEY_LENGTH: usize = 255;
+/// Length of the stored `<tenant>:<principal>:<key>`, which sizes `idempotency_keys.key`.
+const MAX_SCOPED_KEY_LENGTH: usize =
+    MAX_TENANT_LENGTH + 1 + Hyphenated::LENGTH + 1 + MAX_KEY_LENGTH;
+/// Large enough for the biggest accepted body, a full batch request.
+const MAX_BODY_BYTES: usize = 2 * 1024 * 1024;
+const REPLAYED_HEADERS: [HeaderName; 3] = [CONTENT_TYPE, ETAG, LOCATION];
+
+/// Answers retried unsafe requests carrying an `Idempotency-Key` header from the stored
+/// response instead of running them again.
+///
+/// A key reused for a different request returns `422`, and a retry that arrives while the
+/// first request is still running returns `409`. Server errors are not stored, so such
+/// requests can be retried with the same key. Keys of authenticated requests only match
+/// requests of the same principal and tenant.
+pub async fn idempotency_middleware(
+    mut request: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let settings = request.app_data::<Data<Idempotency>>().cloned();
+    let repository = request
+        .app_data::<Data<dyn IdempotencyRepository>>()
+        .cloned();
+    let (Some(settings), Some(repository)) = (settings, repository) else {
+        return next.call(request).await.map(|r| r.map_into_boxed_body());
+    };
+    if !settings.enabled || !is_unsafe(request.method()) {
+        return next.call(request).await.map(|r| r.map_into_boxed_body());
+    }
+    let key = match parse_idempotency_key(&request) {
+        Ok(Some(key)) => match scoped_key(&request, key) {
+            Ok(key) => key,
+            Err(err) => return Ok(request.error_response(err)),
+        },
+        Ok(None) => return next.call(request).await.map(|r| r.map_into_boxed_body()),
+        Err(err) => return Ok(request.error_response(err)),
+    };
+
+    let body = match read_body(&mut request).await {
+        Ok(body) => body,
+        Err(err) => return Ok(request.error_response(err)),
+    };
+    let fingerprint = fingerprint(&request, &body);
+    request.set_payload(Payload::from(body));
+
+    let expires_at = Utc::now() + Duration::seconds(settings.ttl_secs as i64);
+    let record = IdempotencyRecord::new(key.clone(), fingerprint.clone(), expires_at);
+    match repository.reserve(record).await {
+        Ok(None) => {}
+        Ok(Some(existing)) if existing.fingerprint != fingerprint => {
+            return Ok(
+                request.error_response(HttpError::unprocessable_entity(format!(
+                    "{IDEMPOTENCY_KEY_HEADER} was already used for a different request"
+                ))),
+            );
+        }
+        Ok(Some(existing)) => {
+            let response = match existing.response {
+                Some(stored) => replay(stored),
+                None => HttpError::conflict(format!(
+                    "a request with this {IDEMPOTENCY_KEY_HEADER} is still being processed"
+                ))
+                .error_response(),
+            };
+            return Ok(request.into_response(response));
+        }
+        Err(err) => return Ok(request.error_response(HttpError::from(err))),
+    }
+
+    let response = match next.call(request).await {
+        Ok(response) => response,
+        Err(err) => {
+            release(repository.as_ref(), &key).await;
+            return Err(err);
+        }
+    };
+    let (request, response) = response.into_parts();
+    let (response, body) = response.into_parts();
+    let body = match to_bytes(body).await {
+        Ok(body) => body,
+        Err(err) => {
+            release(repository.as_ref(), &key).await;
+            return Err(actix_web::error::ErrorInternalServerError(err.into()));
+        }
+    };
+
+    if response.status().is_server_error() {
+        release(repository.as_ref(), &key).await;
+    } else {
+        let stored = IdempotentResponse {
+            status: response.status().as_u16(),
+            headers: REPLAYED_HEADERS
+                .iter()
+                .filter_map(|name| {
+                    let value = response.headers().get(name)?.to_str().ok()?;
+                    Some((name.to_string(), value.to_string()))
+                })
+                .collect(),
+            body: body.to_vec(),
+        };
+        if let Err(err) = repository.complete(&key, stored).await {
+            warn!(error = %err, "failed to store idempotent response");
+        }
+    }
+
+    Ok(ServiceResponse::new(request, response.set_body(body)).map_into_boxed_body())
+}
+
+fn is_unsafe(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::POST | Method::PUT | Method::PATCH | Method::DELETE
+    )
+}
+
+#[allow(clippy::result_large_err)]
+fn parse_idempotency_key(request: &ServiceRequest) -> Result<Option<String>, HttpError> {
+    let Some(value) = request.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+        return Ok(None);
+    };
+    let key = value
+        .to_str()
+        .map_err(|_| {
+            HttpError::bad_request(format!(
+                "{IDEMPOTENCY_KEY_HEADER} header must be valid ASCII"
+            ))
+        })?
+        .trim();
+
+    if key.is_empty() || key.len() > MAX_KEY_LENGTH {
+        return Err(HttpError::bad_request(format!(
+            "{IDEMPOTENCY_KEY_HEADER} header must be between 1 and {MAX_KEY_LENGTH} characters"
+        )));
+    }
+
+    Ok(Some(key.to_string()))
+}
+
+/// Keys are namespaced by the tenant and the authenticated principal, so clients can't
+/// collide on them.
+#[allow(clippy::result_large_err)]
+fn scoped_key(request: &ServiceRequest, key: String) -> Result<String, HttpError> {
+    let extensions = request.extensions();
+    let key = match extensions.get::<Principal>() {
+        Some(principal) => format!("{}:{key}", principal.id),
+        None => key,
+    };
+    let key = match extensions.get::<Tenant>() {
+        Some(Tenant(tenant)) => format!("{tenant}:{key}"),
+        None => key,
+    };
+    if key.len() > MAX_SCOPED_KEY_LENGTH {
+        return Err(HttpError::bad_request(format!(
+            "{IDEMPOTENCY_KEY_HEADER} header is too long"
+        )));
+    }
+
+    Ok(key)
+}
+
+async fn read_body(request: &mut ServiceRequest) -> Result<Bytes, HttpError> {
+    let mut payload = request.take_payload();
+    let mut body = BytesMut::new();
+    while let Some(chunk) = payload.next().await {
+        let chunk = chunk.map_err(|err| HttpError::bad_request(err.to_string()))?;
+        if body.len() + chunk.len() > MAX_BODY_BYTES {
+            return Err(HttpError::bad_request("request body is too large"));
+        }
+        body.extend_from_slice(&chunk);
+    }
+
+    Ok(body.freeze())
+}
+
+/// Hash of the method, path, query and body identifying the original request.
+fn fingerprint(request: &ServiceRequest, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(request.method().as_str());
+    hasher.update(b"\n");
+    hasher.update(request.uri().path());
+    hasher.update(b"?");
+    hasher.update(request.query_string());
+    hasher.update(b"\n");
+    hasher.update(body);
+    hex::encode(hasher.finalize())
+}
+
+fn replay(stored: IdempotentResponse) -> HttpResponse {
+    let status = StatusCode::from_u16(stored.status).unwrap_or(StatusCode::OK);
+    let mut response = HttpResponse::build(status);
+    for (name, value) in stored.headers {
+        if let (Ok(name), Ok(value)) = (HeaderName::try_from(name), HeaderValue::try_from(value)) {
+            response.insert_header((name, value));
+        }
+    }
+
+    response
+        .insert_header((IDEMPOTENT_REPLAYED_HEADER, "true"))
+        .body(stored.body)
+}
+
+async fn release(repository: &dyn IdempotencyRepository, key: &str) {
+    if let Err(err) = repository.release(key).await {
+        warn!(error = %err, "failed to release idempotency key");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::middleware::from_fn;
+    use actix_web::{test, web, App};
+    use application::{InMemoryIdempotencyRepository, Settings};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use uuid::Uuid;
+
+    async fn create(calls: Data<AtomicUsize>, body: Bytes) -> HttpResponse {
+        let call = calls.fetch_add(1, Ordering::SeqCst);
+        if body.as_ref() == b"fail" {
+            return HttpResponse::InternalServerError().finish();
+        }
+        HttpResponse::Created()
+            .insert_header((ETAG, "\"1\""))
+            .body(format!("created {call}"))
+    }
+
+    macro_rules! init_app {
+        ($calls:expr) => {{
+            let repository: Arc<dyn IdempotencyRepository> =
+                Arc::new(InMemoryIdempotencyRepository::new());
+            test::init_service(
+                App::new()
+                    .app_data(Data::new(Settings::default().idempotency.clone()))
+                    .app_data(Data::from(repository))
+                    .app_data($calls.clone())
+                    .service(
+                        web::scope("")
+                            .wrap(from_fn(idempotency_middleware))
+                            .route("/items", web::post().to(create)),
+                    ),
+            )
+            .await
+        }};
+    }
+
+    fn post(key: &str, body: &'static str) -> test::TestRequest {
+        test::TestRequest::post()
+            .uri("/items")
+            .insert_header((IDEMPOTENCY_KEY_HEADER, key))
+            .set_payload(body)
+    }
+
+    #[actix_web::test]
+    async fn retry_replays_stored_response_without_running_handler_again() {
+        let calls = Data::new(AtomicUsize::new(0));
+        let app = init_app!(calls);
+
+        let first = test::call_service(&app, post("key-1", "item").to_request()).await;
+        assert_eq!(first.status(), StatusCode::CREATED);
+        assert_eq!(test::read_body(first).await, Bytes::from("created 0"));
+
+        let retry = test::call_service(&app, post("key-1", "item").to_request()).await;
+        assert_eq!(retry.status(), StatusCode::CREATED);
+        assert_eq!(retry.headers().get(ETAG).unwrap(), "\"1\"");
+        assert_eq!(
+            retry.headers().get(IDEMPOTENT_REPLAYED_HEADER).unwrap(),
+            "true"
+        );
+        assert_eq!(test::read_body(retry).await, Bytes::from("created 0"));
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[actix_web::test]
+    async fn reused_key_with_different_payload_returns_422() {
+        let calls = Data::new(AtomicUsize::new(0));
+        let app = init_app!(calls);
+
+        test::call_service(&app, post("key-1", "item").to_request()).await;
+        let response = test::call_service(&app, post("key-1", "other").to_request()).await;
+
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[actix_web::test]
+    async fn server_errors_are_not_stored() {
+        let calls = Data::new(AtomicUsize::new(0));
+        let app = init_app!(calls);
+
+        test::call_service(&app, post("key-1", "fail").to_request()).await;
+        let retry = test::call_service(&app, post("key-1", "fail").to_request()).await;
+
+        assert_eq!(retry.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        assert!(retry.headers().get(IDEMPOTENT_REPLAYED_HEADER).is_none());
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[actix_web::test]
+    async fn requests_without_key_are_not_deduplicated() {
+        let calls = Data::new(AtomicUsize::new(0));
+        let app = init_app!(calls);
+
+        for _ in 0..2 {
+            let request = test::TestRequest::post()
+                .uri("/items")
+                .set_payload("item")
+                .to_request();
+            test::call_service(&app, request).await;
+        }
+
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[actix_web::test]
+    async fn keys_are_scoped_to_the_authenticated_principal() {
+        let calls = Data::new(AtomicUsize::new(0));
+        let app = init_app!(calls);
+        let as_principal = |id: Uuid| {
+            let request = post("key-1", "item").to_request();
+            request.extensions_mut().insert(Principal::new(id));
+            request
+        };
+        let alice = Uuid::new_v4();
+
+        test::call_service(&app, as_principal(alice)).await;
+        let other = test::call_service(&app, as_principal(Uuid::new_v4())).await;
+        let retry = test::call_service(&app, as_principal(alice)).await;
+
+        assert_eq!(other.status(), StatusCode::CREATED);
+        assert!(other.headers().get(IDEMPOTENT_REPLAYED_HEADER).is_none());
+        assert_eq!(
+            retry.headers().get(IDEMPOTENT_REPLAYED_HEADER).unwrap(),
+            "true"
+        );
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+
+    #[actix_web::test]
+    async fn longest_key_of_a_tenant_and_principal_is_accepted() {
+        let calls = Data::new(AtomicUsize::new(0));
+        let app = init_app!(calls);
+        let request = |key: &str| {
+            let request = post(key, "item").to_request();
+            request
+                .extensions_mut()
+                .insert(Principal::new(Uuid::new_v4()));
+            request
+                .extensions_mut()
+                .insert(Tenant("t".repeat(MAX_TENANT_LENGTH)));
+            request
+        };
+
+        let longest = test::call_service(&app, request(&"k".repeat(MAX_KEY_LENGTH))).await;
+        let too_long = test::call_service(&app, request(&"k".repeat(MAX_KEY_LENGTH + 1))).await;
+
+        assert_eq!(longest.status(), StatusCode::CREATED);
+        assert_eq!(too_long.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[actix_web::test]
+    async fn scoped_key_limit_matches_the_key_column() {
+        let migration = include_str!(
+            "../../infrastructure/src/migrations/2026-10-18-095000_create_idempotency_keys/up.sql"
+        );
+        assert!(migration.contains(&format!("\"key\" varchar({MAX_SCOPED_KEY_LENGTH})")));
+    }
+}
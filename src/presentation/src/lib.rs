@@ -1,9 +1,23 @@
 mod api;
+mod auth;
+mod conditional;
 mod config;
+mod cursor;
 mod errors;
+mod idempotency;
+mod request_id;
 mod requests;
 mod responses;
+mod tenancy;
 
 pub use api::ApiDoc;
+pub use auth::{
+    authentication_middleware, authorization_middleware, generate_api_key, ApiKeyAuthenticator,
+    AuditAuthenticator, Authenticator, AuthorizationPolicy, JwksFileKeySource, KeySource,
+    PemFileKeySource, Principal, SecretKeySource, VerificationKey,
+};
 pub use config::configure;
 pub use errors::HttpError;
+pub use idempotency::idempotency_middleware;
+pub use request_id::RequestId;
+pub use tenancy::{is_valid_tenant, tenant_middleware, Tenant, TenantResolver};
@@ -0,0 +1,4 @@
+/// Correlation id of the current request, placed in the request extensions by the
+/// observability middleware and recorded on the revisions the request produces.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
@@ -1,16 +1,42 @@
 use actix_web::HttpRequest;
 use application::{
-    CreateToDoItemCommand, GetAllToDoItemsQuery, SortDirection, ToDoItemSort, ToDoItemSortField,
+    AccessScope, BatchOperation, BatchToDoItemsCommand, CreateToDoItemCommand,
+    DeleteToDoItemCommand, DeletedToDoItemFilter, GetAllToDoItemsQuery,
+    GetDeletedToDoItemsForAuditQuery, KeysetPage, NullsOrder, PatchToDoItemCommand, SearchMode,
+    SortDirection, ToDoItemFilter, ToDoItemSort, ToDoItemSortField, ToDoItemSortKey,
     UpdateToDoItemCommand,
 };
 use chrono::{DateTime, Utc};
-use serde::{Deserialize, Serialize};
+use domain::ToDoItemStatus;
+use serde::{Deserialize, Deserializer, Serialize};
 use utoipa::{IntoParams, ToSchema};
 use uuid::Uuid;
 use validator::{Validate, ValidationError};
 
+use crate::auth::Caller;
+use crate::cursor::decode_cursor;
+
 const DEFAULT_PAGE: u32 = 1;
 const DEFAULT_PAGE_SIZE: u32 = 20;
+const RELEVANCE_SORT: &str = "relevance";
+
+const SORT_FIELDS: &[(&str, ToDoItemSortField)] = &[
+    ("id", ToDoItemSortField::Id),
+    ("title", ToDoItemSortField::Title),
+    ("status", ToDoItemSortField::Status),
+    ("created_at", ToDoItemSortField::CreatedAt),
+    ("updated_at", ToDoItemSortField::UpdatedAt),
+    ("due_at", ToDoItemSortField::DueAt),
+];
+const DELETED_SORT_FIELDS: &[(&str, ToDoItemSortField)] = &[
+    ("id", ToDoItemSortField::Id),
+    ("title", ToDoItemSortField::Title),
+    ("status", ToDoItemSortField::Status),
+    ("created_at", ToDoItemSortField::CreatedAt),
+    ("updated_at", ToDoItemSortField::UpdatedAt),
+    ("due_at", ToDoItemSortField::DueAt),
+    ("deleted_at", ToDoItemSortField::DeletedAt),
+];
 
 fn validate_not_blank(value: &str) -> Result<(), ValidationError> {
     if value.trim().is_empty() {
@@ -32,13 +58,49 @@ fn default_status() -> String {
     "pending".into()
 }
 
-fn validate_status(value: &str) -> Result<(), ValidationError> {
-    match value.trim().to_ascii_lowercase().as_str() {
-        "pending" | "in_progress" | "done" => Ok(()),
-        _ => Err(ValidationError::new("invalid_status")),
+fn parse_status(value: &str) -> Result<ToDoItemStatus, String> {
+    value
+        .trim()
+        .to_ascii_lowercase()
+        .parse()
+        .map_err(|_| "status must be one of: pending, in_progress, done".to_string())
+}
+
+/// Keeps `null` distinguishable from a missing member: missing stays `None`, `null` becomes `Some(None)`.
+fn deserialize_present<'de, T, D>(deserializer: D) -> Result<Option<Option<T>>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    Option::<T>::deserialize(deserializer).map(Some)
+}
+
+fn patch_text(
+    field: &str,
+    value: &Option<Option<String>>,
+    max: usize,
+) -> Result<Option<String>, String> {
+    match value {
+        None => Ok(None),
+        Some(None) => Err(format!("{field} cannot be null")),
+        Some(Some(value)) => {
+            let length = value.chars().count();
+            if value.trim().is_empty() || length > max {
+                return Err(format!(
+                    "{field} must be a non-blank string of at most {max} characters"
+                ));
+            }
+            Ok(Some(value.clone()))
+        }
     }
 }
 
+fn validate_status(value: &str) -> Result<(), ValidationError> {
+    parse_status(value)
+        .map(|_| ())
+        .map_err(|_| ValidationError::new("invalid_status"))
+}
+
 #[readonly::make]
 #[derive(Deserialize, Serialize, ToSchema, Validate)]
 pub struct CreateToDoItemRequest {
@@ -66,12 +128,70 @@ pub struct UpdateToDoItemRequest {
     #[validate(length(min = 1, max = 1000), custom(function = "validate_not_blank"))]
     pub note: String,
     /// Lifecycle status. Supported values: `pending`, `in_progress`, `done`.
+    /// Allowed transitions are `pending -> in_progress -> done`, `in_progress -> pending` and `done -> pending`.
     #[validate(custom(function = "validate_status"))]
     pub status: String,
     /// Optional due date in RFC 3339 format.
     pub due_at: Option<DateTime<Utc>>,
 }
 
+/// One operation of a batch request, tagged by `op`.
+#[derive(Deserialize, Serialize, ToSchema)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BatchOperationRequest {
+    /// Creates a new to-do item.
+    Create { item: CreateToDoItemRequest },
+    /// Replaces a to-do item. `if_match` carries the ETag of the version being updated.
+    Update {
+        id: Uuid,
+        if_match: String,
+        item: UpdateToDoItemRequest,
+    },
+    /// Soft-deletes a to-do item. With `if_match` the delete fails on a stale version.
+    Delete {
+        id: Uuid,
+        #[serde(default)]
+        if_match: Option<String>,
+    },
+}
+
+#[readonly::make]
+#[derive(Deserialize, Serialize, ToSchema, Validate)]
+pub struct BatchToDoItemsRequest {
+    /// Roll back every operation when one of them fails.
+    #[serde(default)]
+    pub atomic: bool,
+    /// Operations applied in request order.
+    #[validate(length(min = 1, max = 1000))]
+    pub operations: Vec<BatchOperationRequest>,
+}
+
+/// JSON Merge Patch (RFC 7396) document for a to-do item.
+///
+/// Omitted members are left unchanged. `due_at: null` clears the due date; `null` is rejected
+/// for the other members because they cannot be removed.
+#[readonly::make]
+#[derive(Deserialize, ToSchema, Default)]
+#[serde(deny_unknown_fields)]
+pub struct PatchToDoItemRequest {
+    /// The title of the to-do item
+    #[serde(default, deserialize_with = "deserialize_present")]
+    #[schema(value_type = Option<String>)]
+    pub title: Option<Option<String>>,
+    /// The note of the to-do item
+    #[serde(default, deserialize_with = "deserialize_present")]
+    #[schema(value_type = Option<String>)]
+    pub note: Option<Option<String>>,
+    /// Lifecycle status. Supported values: `pending`, `in_progress`, `done`.
+    #[serde(default, deserialize_with = "deserialize_present")]
+    #[schema(value_type = Option<String>)]
+    pub status: Option<Option<String>>,
+    /// Due date in RFC 3339 format. Send `null` to clear it.
+    #[serde(default, deserialize_with = "deserialize_present")]
+    #[schema(value_type = Option<DateTime<Utc>>, nullable)]
+    pub due_at: Option<Option<DateTime<Utc>>>,
+}
+
 #[readonly::make]
 #[derive(Deserialize, Serialize, IntoParams, ToSchema, Validate)]
 #[into_params(parameter_in = Query)]
@@ -84,13 +204,53 @@ pub struct GetAllToDoItemsQueryRequest {
     #[serde(default = "default_page_size")]
     #[validate(range(min = 1, max = 100))]
     pub page_size: u32,
-    /// Optional case-insensitive search across title and note. Blank values are rejected with `400 Bad Request`.
+    /// Optional full-text search across title and note, matching word forms such as `milk` for
+    /// `milks`. Blank values are rejected with `400 Bad Request`.
     #[serde(default)]
     #[validate(length(max = 100))]
     pub search: Option<String>,
-    /// Sort order. Supported values: `id:asc`, `id:desc`, `title:asc`, `title:desc`.
+    /// How `search` is interpreted: `plain` (default, all words), `phrase` (words in order) or
+    /// `websearch` (quoted phrases, `or`, `-word` exclusions).
+    #[serde(default)]
+    pub search_mode: Option<String>,
+    /// Comma-separated sort keys applied in order, each `field:direction`, for example
+    /// `status:asc,due_at:asc`. Fields: `id`, `title`, `status`, `created_at`, `updated_at`,
+    /// `due_at`; directions: `asc`, `desc`. `title` and `due_at` accept a third part,
+    /// `nulls_first` or `nulls_last`; by default empty values come last when ascending and
+    /// first when descending. `status` sorts in lifecycle order and ties are broken by `id`.
+    /// `relevance` orders search matches best first; it requires `search` and offset pagination.
     #[serde(default)]
     pub sort: Option<String>,
+    /// Comma-separated statuses to include, for example `pending,in_progress`.
+    #[serde(default)]
+    pub status: Option<String>,
+    /// Only items due at or after this RFC 3339 timestamp.
+    #[serde(default)]
+    pub due_after: Option<DateTime<Utc>>,
+    /// Only items due before this RFC 3339 timestamp.
+    #[serde(default)]
+    pub due_before: Option<DateTime<Utc>>,
+    /// Only items created at or after this RFC 3339 timestamp.
+    #[serde(default)]
+    pub created_after: Option<DateTime<Utc>>,
+    /// Only items created before this RFC 3339 timestamp.
+    #[serde(default)]
+    pub created_before: Option<DateTime<Utc>>,
+    /// Only items updated at or after this RFC 3339 timestamp.
+    #[serde(default)]
+    pub updated_since: Option<DateTime<Utc>>,
+    /// When `true`, only items that are past their due date and not done.
+    #[serde(default)]
+    pub overdue: bool,
+    /// Pagination mode: `offset` (default) or `cursor`. Sending `cursor` implies cursor mode.
+    #[serde(default)]
+    pub pagination: Option<String>,
+    /// Opaque `next_cursor` of the previous page. `page` is ignored in cursor mode.
+    #[serde(default)]
+    pub cursor: Option<String>,
+    /// Cursor mode only: also count all matching items. Offset pages are always counted.
+    #[serde(default)]
+    pub include_total: bool,
 }
 
 impl Default for GetAllToDoItemsQueryRequest {
@@ -99,8 +259,113 @@ impl Default for GetAllToDoItemsQueryRequest {
             page: default_page(),
             page_size: default_page_size(),
             search: None,
+            search_mode: None,
             sort: None,
+            status: None,
+            due_after: None,
+            due_before: None,
+            created_after: None,
+            created_before: None,
+            updated_since: None,
+            overdue: false,
+            pagination: None,
+            cursor: None,
+            include_total: false,
+        }
+    }
+}
+
+#[readonly::make]
+#[derive(Deserialize, Serialize, IntoParams, ToSchema, Validate)]
+#[into_params(parameter_in = Query)]
+pub struct GetToDoItemHistoryQueryRequest {
+    /// One-based page number.
+    #[serde(default = "default_page")]
+    #[validate(range(min = 1, max = 10_000))]
+    pub page: u32,
+    /// Number of revisions returned per page.
+    #[serde(default = "default_page_size")]
+    #[validate(range(min = 1, max = 100))]
+    pub page_size: u32,
+}
+
+#[readonly::make]
+#[derive(Deserialize, Serialize, IntoParams, ToSchema, Validate)]
+#[into_params(parameter_in = Query)]
+pub struct GetToDoItemDiffQueryRequest {
+    /// Item version to compare from.
+    #[validate(range(min = 1))]
+    pub from: i32,
+    /// Item version to compare to; must not be lower than `from`.
+    #[validate(range(min = 1))]
+    pub to: i32,
+}
+
+#[readonly::make]
+#[derive(Deserialize, Serialize, IntoParams, ToSchema, Validate)]
+#[into_params(parameter_in = Query)]
+pub struct GetDeletedToDoItemsForAuditQueryRequest {
+    /// One-based page number.
+    #[serde(default = "default_page")]
+    #[validate(range(min = 1, max = 10_000))]
+    pub page: u32,
+    /// Number of items returned per page.
+    #[serde(default = "default_page_size")]
+    #[validate(range(min = 1, max = 100))]
+    pub page_size: u32,
+    /// Optional full-text search across title and note. Blank values are rejected with
+    /// `400 Bad Request`.
+    #[serde(default)]
+    #[validate(length(max = 100))]
+    pub search: Option<String>,
+    /// How `search` is interpreted: `plain` (default), `phrase` or `websearch`.
+    #[serde(default)]
+    pub search_mode: Option<String>,
+    /// Comma-separated sort keys applied in order, each `field:direction`. Fields: `id`,
+    /// `title`, `status`, `created_at`, `updated_at`, `due_at`, `deleted_at`; directions:
+    /// `asc`, `desc`. Defaults to `deleted_at:desc`, most recently deleted first.
+    #[serde(default)]
+    pub sort: Option<String>,
+    /// Only items deleted by this user.
+    #[serde(default)]
+    pub deleted_by: Option<Uuid>,
+    /// Only items deleted at or after this RFC 3339 timestamp.
+    #[serde(default)]
+    pub deleted_after: Option<DateTime<Utc>>,
+    /// Only items deleted before this RFC 3339 timestamp.
+    #[serde(default)]
+    pub deleted_before: Option<DateTime<Utc>>,
+}
+
+impl GetDeletedToDoItemsForAuditQueryRequest {
+    pub fn to_query(&self) -> Result<GetDeletedToDoItemsForAuditQuery, String> {
+        if let Some(value) = self.search.as_ref() {
+            validate_not_blank(value).map_err(|err| err.to_string())?;
         }
+        let search_mode = parse_search_mode(self.search.as_deref(), self.search_mode.as_deref())?;
+        let sort = match self.sort.as_deref() {
+            Some(value) => parse_sort_among(value, DELETED_SORT_FIELDS)?,
+            None => GetDeletedToDoItemsForAuditQuery::default_sort(),
+        };
+        ensure_ordered_range(
+            "deleted_after",
+            self.deleted_after,
+            "deleted_before",
+            self.deleted_before,
+        )?;
+
+        Ok(GetDeletedToDoItemsForAuditQuery::new(
+            self.page,
+            self.page_size,
+            self.search.as_ref().map(|value| value.trim().to_string()),
+            sort,
+        )
+        .with_search_mode(search_mode)
+        .with_filter(DeletedToDoItemFilter {
+            deleted_by: self.deleted_by,
+            deleted_after: self.deleted_after,
+            deleted_before: self.deleted_before,
+        }))
     }
 }
 
@@ -120,60 +385,304 @@ impl GetAllToDoItemsQueryRequest {
     pub fn validate_sort(&self) -> Result<(), String> {
         if let Some(value) = self.sort.as_ref() {
             validate_not_blank(value).map_err(|err| err.to_string())?;
-            parse_sort(value)?;
+            if !self.sorts_by_relevance() {
+                parse_sort(value)?;
+            }
         }
 
         Ok(())
     }
 
+    fn sorts_by_relevance(&self) -> bool {
+        self.sort
+            .as_deref()
+            .is_some_and(|value| value.trim().eq_ignore_ascii_case(RELEVANCE_SORT))
+    }
+
+    fn search_mode(&self) -> Result<SearchMode, String> {
+        parse_search_mode(self.search.as_deref(), self.search_mode.as_deref())
+    }
+
+    pub fn to_filter(&self) -> Result<ToDoItemFilter, String> {
+        let statuses = match self.status.as_deref() {
+            None => Vec::new(),
+            Some(value) => value
+                .split(',')
+                .map(parse_status)
+                .collect::<Result<Vec<_>, _>>()?,
+        };
+        ensure_ordered_range("due_after", self.due_after, "due_before", self.due_before)?;
+        ensure_ordered_range(
+            "created_after",
+            self.created_after,
+            "created_before",
+            self.created_before,
+        )?;
+
+        Ok(ToDoItemFilter {
+            statuses,
+            due_after: self.due_after,
+            due_before: self.due_before,
+            created_after: self.created_after,
+            created_before: self.created_before,
+            updated_since: self.updated_since,
+            overdue: self.overdue,
+        })
+    }
+
     pub fn to_query(&self) -> Result<GetAllToDoItemsQuery, String> {
-        Ok(GetAllToDoItemsQuery::new(
-            self.page,
-            self.page_size,
-            self.normalized_search(),
-            self.sort
-                .as_deref()
-                .map(parse_sort)
-                .transpose()?
-                .unwrap_or_default(),
-        ))
+        let search_mode = self.search_mode()?;
+        let filter = self.to_filter()?;
+        if self.sorts_by_relevance() {
+            if self.search.is_none() {
+                return Err("relevance sort requires search".to_string());
+            }
+            if self.uses_cursor_pagination()? {
+                return Err("relevance sort is not supported with cursor pagination".to_string());
+            }
+
+            return Ok(GetAllToDoItemsQuery::new(
+                self.page,
+                self.page_size,
+                self.normalized_search(),
+                ToDoItemSort::default(),
+            )
+            .with_search_mode(search_mode)
+            .with_relevance_ranking()
+            .with_filter(filter));
+        }
+
+        let requested_sort = self.sort.as_deref().map(parse_sort).transpose()?;
+        if !self.uses_cursor_pagination()? {
+            return Ok(GetAllToDoItemsQuery::new(
+                self.page,
+                self.page_size,
+                self.normalized_search(),
+                requested_sort.unwrap_or_default(),
+            )
+            .with_search_mode(search_mode)
+            .with_filter(filter));
+        }
+
+        let after = self.cursor.as_deref().map(decode_cursor).transpose()?;
+        let sort = match (&after, requested_sort) {
+            (Some(cursor), Some(sort)) if cursor.sort != sort => {
+                return Err("cursor does not match the requested sort".to_string())
+            }
+            (Some(cursor), _) => cursor.sort.clone(),
+            (None, sort) => sort.unwrap_or_default(),
+        };
+
+        Ok(
+            GetAllToDoItemsQuery::new(DEFAULT_PAGE, self.page_size, self.normalized_search(), sort)
+                .with_search_mode(search_mode)
+                .with_filter(filter)
+                .with_keyset(KeysetPage {
+                    after,
+                    include_total: self.include_total,
+                }),
+        )
+    }
+
+    fn uses_cursor_pagination(&self) -> Result<bool, String> {
+        let mode = self
+            .pagination
+            .as_ref()
+            .map(|value| value.trim().to_ascii_lowercase());
+
+        match mode.as_deref() {
+            None => Ok(self.cursor.is_some()),
+            Some("cursor") => Ok(true),
+            Some("offset") if self.cursor.is_some() => {
+                Err("cursor cannot be combined with offset pagination".to_string())
+            }
+            Some("offset") => Ok(false),
+            Some(_) => Err("pagination must be one of: offset, cursor".to_string()),
+        }
     }
 }
 
 impl CreateToDoItemRequest {
-    pub fn to_command(&self) -> CreateToDoItemCommand {
-        CreateToDoItemCommand::new(
+    pub fn to_command(&self) -> Result<CreateToDoItemCommand, String> {
+        Ok(CreateToDoItemCommand::new(
             self.title.clone(),
             self.note.clone(),
-            self.status.trim().to_ascii_lowercase(),
+            parse_status(&self.status)?,
             self.due_at,
-        )
+        ))
     }
 }
 
 impl UpdateToDoItemRequest {
-    pub fn to_command(&self, id: Uuid, version: i32) -> UpdateToDoItemCommand {
-        UpdateToDoItemCommand::new(
+    pub fn to_command(&self, id: Uuid, version: i32) -> Result<UpdateToDoItemCommand, String> {
+        Ok(UpdateToDoItemCommand::new(
             id,
             self.title.clone(),
             self.note.clone(),
-            self.status.trim().to_ascii_lowercase(),
+            parse_status(&self.status)?,
             self.due_at,
             version,
-        )
+        ))
+    }
+}
+
+impl BatchToDoItemsRequest {
+    /// Validates every operation up front so a malformed entry rejects the whole request.
+    pub fn to_command(&self, caller: &Caller) -> Result<BatchToDoItemsCommand, String> {
+        let operations = self
+            .operations
+            .iter()
+            .enumerate()
+            .map(|(index, operation)| {
+                operation
+                    .to_operation(caller)
+                    .map_err(|err| format!("operations[{index}]: {err}"))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(BatchToDoItemsCommand::new(operations, self.atomic))
+    }
+}
+
+impl BatchOperationRequest {
+    fn to_operation(&self, caller: &Caller) -> Result<BatchOperation, String> {
+        match self {
+            BatchOperationRequest::Create { item } => {
+                item.validate().map_err(|err| err.to_string())?;
+                Ok(BatchOperation::Create(
+                    item.to_command()?
+                        .with_owner(caller.id)
+                        .with_tenant(caller.tenant_id())
+                        .with_request_id(caller.request_id.clone()),
+                ))
+            }
+            BatchOperationRequest::Update { id, if_match, item } => {
+                item.validate().map_err(|err| err.to_string())?;
+                let version = parse_batch_if_match(if_match)?;
+                Ok(BatchOperation::Update(
+                    item.to_command(*id, version)?
+                        .with_updated_by(caller.id)
+                        .with_request_id(caller.request_id.clone())
+                        .within(caller.scope.clone()),
+                ))
+            }
+            BatchOperationRequest::Delete { id, if_match } => Ok(BatchOperation::Delete {
+                command: DeleteToDoItemCommand::new(*id, caller.id)
+                    .with_request_id(caller.request_id.clone())
+                    .within(caller.scope.clone()),
+                version: if_match.as_deref().map(parse_batch_if_match).transpose()?,
+            }),
+        }
+    }
+}
+
+fn parse_batch_if_match(value: &str) -> Result<i32, String> {
+    parse_etag_version(value).ok_or_else(|| "if_match must contain an integer ETag".to_string())
+}
+
+/// Reads the version out of an ETag such as `"3"` or `W/"3"`.
+pub(crate) fn parse_etag_version(value: &str) -> Option<i32> {
+    let value = value.trim();
+    value
+        .strip_prefix("W/")
+        .unwrap_or(value)
+        .trim()
+        .trim_matches('"')
+        .parse::<i32>()
+        .ok()
+}
+
+impl PatchToDoItemRequest {
+    pub fn to_command(&self, id: Uuid, version: i32) -> Result<PatchToDoItemCommand, String> {
+        let status = match &self.status {
+            None => None,
+            Some(None) => return Err("status cannot be null".to_string()),
+            Some(Some(value)) => Some(parse_status(value)?),
+        };
+
+        Ok(PatchToDoItemCommand {
+            id,
+            title: patch_text("title", &self.title, 120)?,
+            note: patch_text("note", &self.note, 1000)?,
+            status,
+            due_at: self.due_at,
+            version,
+            updated_by: None,
+            scope: AccessScope::default(),
+            request_id: None,
+        })
+    }
+}
+
+fn ensure_ordered_range(
+    lower_name: &str,
+    lower: Option<DateTime<Utc>>,
+    upper_name: &str,
+    upper: Option<DateTime<Utc>>,
+) -> Result<(), String> {
+    match (lower, upper) {
+        (Some(lower), Some(upper)) if lower >= upper => {
+            Err(format!("{lower_name} must be earlier than {upper_name}"))
+        }
+        _ => Ok(()),
     }
 }
 
-fn parse_sort(value: &str) -> Result<ToDoItemSort, String> {
+fn parse_search_mode(search: Option<&str>, value: Option<&str>) -> Result<SearchMode, String> {
+    let Some(value) = value else {
+        return Ok(SearchMode::default());
+    };
+    if search.is_none() {
+        return Err("search_mode requires search".to_string());
+    }
+
+    match value.trim().to_ascii_lowercase().as_str() {
+        "plain" => Ok(SearchMode::Plain),
+        "phrase" => Ok(SearchMode::Phrase),
+        "websearch" => Ok(SearchMode::Websearch),
+        _ => Err("search_mode must be one of: plain, phrase, websearch".to_string()),
+    }
+}
+
+pub(crate) fn parse_sort(value: &str) -> Result<ToDoItemSort, String> {
+    parse_sort_among(value, SORT_FIELDS)
+}
+
+fn parse_sort_among(
+    value: &str,
+    fields: &[(&str, ToDoItemSortField)],
+) -> Result<ToDoItemSort, String> {
     let normalized = value.trim().to_ascii_lowercase();
-    let (field, direction) = normalized
-        .split_once(':')
-        .ok_or_else(|| "sort must use the format field:direction".to_string())?;
-
-    let field = match field {
-        "id" => ToDoItemSortField::Id,
-        "title" => ToDoItemSortField::Title,
-        _ => return Err("sort field must be one of: id, title".to_string()),
+    let mut keys: Vec<ToDoItemSortKey> = Vec::new();
+
+    for part in normalized.split(',') {
+        let key = parse_sort_key(part.trim(), fields)?;
+        if keys.iter().any(|existing| existing.field == key.field) {
+            return Err("sort fields must not repeat".to_string());
+        }
+        keys.push(key);
+    }
+
+    Ok(ToDoItemSort::new(keys))
+}
+
+fn parse_sort_key(
+    value: &str,
+    fields: &[(&str, ToDoItemSortField)],
+) -> Result<ToDoItemSortKey, String> {
+    let format_error = || "sort must use the format field:direction[:nulls]".to_string();
+    let mut parts = value.split(':');
+    let (Some(field), Some(direction)) = (parts.next(), parts.next()) else {
+        return Err(format_error());
+    };
+    let nulls = parts.next();
+    if parts.next().is_some() {
+        return Err(format_error());
+    }
+
+    let Some(&(_, field)) = fields.iter().find(|(name, _)| *name == field) else {
+        let names = fields.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+        return Err(format!("sort field must be one of: {}", names.join(", ")));
     };
 
     let direction = match direction {
@@ -182,11 +691,19 @@ fn parse_sort(value: &str) -> Result<ToDoItemSort, String> {
         _ => return Err("sort direction must be one of: asc, desc".to_string()),
     };
 
-    Ok(ToDoItemSort { field, direction })
+    let key = ToDoItemSortKey::new(field, direction);
+    match nulls {
+        None => Ok(key),
+        Some(_) if !field.is_nullable() => {
+            Err("nulls placement is only supported for title and due_at".to_string())
+        }
+        Some("nulls_first") => Ok(key.with_nulls(NullsOrder::First)),
+        Some("nulls_last") => Ok(key.with_nulls(NullsOrder::Last)),
+        Some(_) => Err("nulls placement must be one of: nulls_first, nulls_last".to_string()),
+    }
 }
 
 pub const AUDIT_TOKEN_HEADER: &str = "X-Audit-Token";
-pub const DELETE_ACTOR_ID_HEADER: &str = "X-Actor-Id";
 
 pub fn parse_audit_token_header(request: &HttpRequest) -> Option<String> {
     request
@@ -197,27 +714,10 @@ pub fn parse_audit_token_header(request: &HttpRequest) -> Option<String> {
         .filter(|value| !value.is_empty())
 }
 
-pub fn parse_optional_delete_actor_id(request: &HttpRequest) -> Result<Option<Uuid>, String> {
-    let Some(raw_value) = request.headers().get(DELETE_ACTOR_ID_HEADER) else {
-        return Ok(None);
-    };
-
-    let value = raw_value
-        .to_str()
-        .map_err(|_| "X-Actor-Id header must be valid ASCII".to_string())?
-        .trim();
-    if value.is_empty() {
-        return Ok(None);
-    }
-
-    Uuid::parse_str(value)
-        .map(Some)
-        .map_err(|_| "X-Actor-Id header must be a valid UUID".to_string())
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
+    use domain::ToDoItem;
 
     #[test]
     fn create_request_rejects_blank_title() {
@@ -260,7 +760,7 @@ mod tests {
             page: 1,
             page_size: 20,
             search: Some("   ".into()),
-            sort: None,
+            ..Default::default()
         };
 
         assert!(query.validate_search().is_err());
@@ -272,7 +772,7 @@ mod tests {
             page: 1,
             page_size: 20,
             search: Some("  milk  ".into()),
-            sort: None,
+            ..Default::default()
         };
 
         let mapped = query.to_query().expect("query should map");
@@ -284,11 +784,193 @@ mod tests {
         let query = GetAllToDoItemsQueryRequest {
             page: 1,
             page_size: 20,
-            search: None,
-            sort: Some("status:asc".into()),
+            sort: Some("note:asc".into()),
+            ..Default::default()
+        };
+        let repeated_field = GetAllToDoItemsQueryRequest {
+            sort: Some("status:asc,status:desc".into()),
+            ..Default::default()
+        };
+        let nulls_on_required_field = GetAllToDoItemsQueryRequest {
+            sort: Some("created_at:asc:nulls_first".into()),
+            ..Default::default()
         };
 
         assert!(query.validate_sort().is_err());
+        assert!(repeated_field.validate_sort().is_err());
+        assert!(nulls_on_required_field.validate_sort().is_err());
+    }
+
+    #[test]
+    fn query_request_maps_search_mode_and_relevance_sort() {
+        let query = GetAllToDoItemsQueryRequest {
+            page: 3,
+            search: Some("milk -bread".into()),
+            search_mode: Some("websearch".into()),
+            sort: Some("Relevance".into()),
+            ..Default::default()
+        };
+
+        assert!(query.validate_sort().is_ok());
+        let mapped = query.to_query().expect("query should map");
+
+        assert_eq!(mapped.page, 3);
+        assert_eq!(mapped.search_mode, SearchMode::Websearch);
+        assert!(mapped.rank_by_relevance);
+        assert_eq!(mapped.sort, ToDoItemSort::default());
+    }
+
+    #[test]
+    fn query_request_rejects_invalid_search_options() {
+        let relevance_without_search = GetAllToDoItemsQueryRequest {
+            sort: Some("relevance".into()),
+            ..Default::default()
+        };
+        let relevance_with_cursor = GetAllToDoItemsQueryRequest {
+            search: Some("milk".into()),
+            sort: Some("relevance".into()),
+            pagination: Some("cursor".into()),
+            ..Default::default()
+        };
+        let unknown_mode = GetAllToDoItemsQueryRequest {
+            search: Some("milk".into()),
+            search_mode: Some("fuzzy".into()),
+            ..Default::default()
+        };
+        let mode_without_search = GetAllToDoItemsQueryRequest {
+            search_mode: Some("phrase".into()),
+            ..Default::default()
+        };
+
+        assert!(relevance_without_search.to_query().is_err());
+        assert!(relevance_with_cursor.to_query().is_err());
+        assert!(unknown_mode.to_query().is_err());
+        assert!(mode_without_search.to_query().is_err());
+    }
+
+    #[test]
+    fn query_request_maps_multi_key_sort() {
+        let query = GetAllToDoItemsQueryRequest {
+            sort: Some("status:asc, due_at:desc:nulls_last,created_at:desc".into()),
+            ..Default::default()
+        };
+
+        let mapped = query.to_query().expect("query should map");
+
+        assert_eq!(
+            mapped.sort.keys,
+            vec![
+                ToDoItemSortKey::new(ToDoItemSortField::Status, SortDirection::Asc),
+                ToDoItemSortKey::new(ToDoItemSortField::DueAt, SortDirection::Desc)
+                    .with_nulls(NullsOrder::Last),
+                ToDoItemSortKey::new(ToDoItemSortField::CreatedAt, SortDirection::Desc),
+            ]
+        );
+    }
+
+    #[test]
+    fn list_query_maps_structured_filters() {
+        let due_after = "2026-10-19T00:00:00Z".parse().expect("valid timestamp");
+        let due_before = "2026-10-26T00:00:00Z".parse().expect("valid timestamp");
+        let query = GetAllToDoItemsQueryRequest {
+            status: Some("pending, IN_PROGRESS".into()),
+            due_after: Some(due_after),
+            due_before: Some(due_before),
+            overdue: true,
+            ..Default::default()
+        };
+
+        let filter = query.to_query().expect("query should map").filter;
+
+        assert_eq!(
+            filter.statuses,
+            vec![ToDoItemStatus::Pending, ToDoItemStatus::InProgress]
+        );
+        assert_eq!(filter.due_after, Some(due_after));
+        assert_eq!(filter.due_before, Some(due_before));
+        assert!(filter.overdue);
+    }
+
+    #[test]
+    fn list_query_rejects_unknown_status_and_inverted_ranges() {
+        let unknown_status = GetAllToDoItemsQueryRequest {
+            status: Some("pending,archived".into()),
+            ..Default::default()
+        };
+        let inverted_created = GetAllToDoItemsQueryRequest {
+            created_after: Some("2026-10-20T00:00:00Z".parse().expect("valid timestamp")),
+            created_before: Some("2026-10-19T00:00:00Z".parse().expect("valid timestamp")),
+            ..Default::default()
+        };
+
+        assert!(unknown_status.to_query().is_err());
+        assert_eq!(
+            inverted_created.to_query().err().as_deref(),
+            Some("created_after must be earlier than created_before")
+        );
+    }
+
+    #[test]
+    fn list_query_defaults_to_offset_pagination() {
+        let query = GetAllToDoItemsQueryRequest::default()
+            .to_query()
+            .expect("query should map");
+
+        assert_eq!(query.keyset, None);
+    }
+
+    #[test]
+    fn list_query_cursor_mode_takes_sort_from_cursor() {
+        let mut item = ToDoItem::new("milk".into(), "note".into());
+        item.due_at = Some(item.created_at);
+        let cursor = application::ToDoItemCursor::after(
+            &item,
+            &parse_sort("title:asc,due_at:asc").expect("valid sort"),
+        );
+        let query = GetAllToDoItemsQueryRequest {
+            page: 7,
+            cursor: Some(crate::cursor::encode_cursor(&cursor)),
+            include_total: true,
+            ..Default::default()
+        };
+
+        let mapped = query.to_query().expect("query should map");
+
+        assert_eq!(mapped.page, 1);
+        assert_eq!(mapped.sort, cursor.sort);
+        assert_eq!(
+            mapped.keyset,
+            Some(KeysetPage {
+                after: Some(cursor),
+                include_total: true,
+            })
+        );
+    }
+
+    #[test]
+    fn list_query_rejects_inconsistent_cursor_parameters() {
+        let cursor = crate::cursor::encode_cursor(&application::ToDoItemCursor::after(
+            &ToDoItem::new("title".into(), "note".into()),
+            &ToDoItemSort::default(),
+        ));
+        let mismatched_sort = GetAllToDoItemsQueryRequest {
+            sort: Some("title:desc".into()),
+            cursor: Some(cursor.clone()),
+            ..Default::default()
+        };
+        let offset_with_cursor = GetAllToDoItemsQueryRequest {
+            pagination: Some("offset".into()),
+            cursor: Some(cursor),
+            ..Default::default()
+        };
+        let unknown_mode = GetAllToDoItemsQueryRequest {
+            pagination: Some("seek".into()),
+            ..Default::default()
+        };
+
+        assert!(mismatched_sort.to_query().is_err());
+        assert!(offset_with_cursor.to_query().is_err());
+        assert!(unknown_mode.to_query().is_err());
     }
 
     #[test]
@@ -298,14 +980,20 @@ mod tests {
             page_size: 10,
             search: Some("  note  ".into()),
             sort: Some("title:desc".into()),
+            ..Default::default()
         };
 
         let mapped = query.to_query().expect("query should map");
         assert_eq!(mapped.page, 2);
         assert_eq!(mapped.page_size, 10);
         assert_eq!(mapped.search.as_deref(), Some("note"));
-        assert_eq!(mapped.sort.field, ToDoItemSortField::Title);
-        assert_eq!(mapped.sort.direction, SortDirection::Desc);
+        assert_eq!(
+            mapped.sort.keys,
+            vec![ToDoItemSortKey::new(
+                ToDoItemSortField::Title,
+                SortDirection::Desc
+            )]
+        );
     }
 
     #[test]
@@ -317,11 +1005,11 @@ mod tests {
             due_at: None,
         };
 
-        let command = request.to_command();
+        let command = request.to_command().expect("request should map");
 
         assert_eq!(command.title, "title");
         assert_eq!(command.note, "note");
-        assert_eq!(command.status, "in_progress");
+        assert_eq!(command.status, ToDoItemStatus::InProgress);
     }
 
     #[test]
@@ -334,11 +1022,103 @@ mod tests {
         };
         let id = Uuid::new_v4();
 
-        let command = request.to_command(id, 4);
+        let command = request.to_command(id, 4).expect("request should map");
 
         assert_eq!(command.id, id);
         assert_eq!(command.version, 4);
-        assert_eq!(command.status, "done");
+        assert_eq!(command.status, ToDoItemStatus::Done);
+    }
+
+    #[test]
+    fn patch_request_distinguishes_missing_and_null_due_at() {
+        let missing: PatchToDoItemRequest =
+            serde_json::from_str(r#"{"status":"in_progress"}"#).expect("valid patch");
+        let cleared: PatchToDoItemRequest =
+            serde_json::from_str(r#"{"due_at":null}"#).expect("valid patch");
+        let id = Uuid::new_v4();
+
+        let missing = missing.to_command(id, 2).expect("patch should map");
+        let cleared = cleared.to_command(id, 2).expect("patch should map");
+
+        assert_eq!(missing.status, Some(ToDoItemStatus::InProgress));
+        assert_eq!(missing.due_at, None);
+        assert_eq!(cleared.due_at, Some(None));
+        assert_eq!(cleared.title, None);
+    }
+
+    #[test]
+    fn patch_request_rejects_null_or_blank_required_fields() {
+        let null_title: PatchToDoItemRequest =
+            serde_json::from_str(r#"{"title":null}"#).expect("valid json");
+        let blank_note: PatchToDoItemRequest =
+            serde_json::from_str(r#"{"note":"  "}"#).expect("valid json");
+        let bad_status: PatchToDoItemRequest =
+            serde_json::from_str(r#"{"status":"archived"}"#).expect("valid json");
+
+        assert!(null_title.to_command(Uuid::new_v4(), 1).is_err());
+        assert!(blank_note.to_command(Uuid::new_v4(), 1).is_err());
+        assert!(bad_status.to_command(Uuid::new_v4(), 1).is_err());
+    }
+
+    #[test]
+    fn patch_request_rejects_unknown_members() {
+        let result = serde_json::from_str::<PatchToDoItemRequest>(r#"{"version":3}"#);
+
+        assert!(result.is_err());
+    }
+
+    fn deleted_items_request(query: &str) -> GetDeletedToDoItemsForAuditQueryRequest {
+        actix_web::web::Query::<GetDeletedToDoItemsForAuditQueryRequest>::from_query(query)
+            .expect("query string should deserialize")
+            .into_inner()
+    }
+
+    #[test]
+    fn deleted_items_request_maps_filters_and_defaults_to_latest_deletions_first() {
+        let deleted_by = Uuid::new_v4();
+        let mapped = deleted_items_request(&format!(
+            "page=2&search=milk&deleted_by={deleted_by}\
+             &deleted_after=2026-01-01T00:00:00Z&deleted_before=2026-02-01T00:00:00Z"
+        ))
+        .to_query()
+        .expect("query should map");
+
+        assert_eq!(mapped.page, 2);
+        assert_eq!(mapped.search.as_deref(), Some("milk"));
+        assert_eq!(mapped.filter.deleted_by, Some(deleted_by));
+        assert!(mapped.filter.deleted_after < mapped.filter.deleted_before);
+        assert_eq!(
+            mapped.sort,
+            GetDeletedToDoItemsForAuditQuery::default_sort()
+        );
+
+        let sorted = deleted_items_request("sort=deleted_at:asc,title:desc")
+            .to_query()
+            .expect("query should map");
+        assert_eq!(
+            sorted.sort.keys,
+            vec![
+                ToDoItemSortKey::new(ToDoItemSortField::DeletedAt, SortDirection::Asc),
+                ToDoItemSortKey::new(ToDoItemSortField::Title, SortDirection::Desc),
+            ]
+        );
+    }
+
+    #[test]
+    fn deleted_items_request_rejects_invalid_parameters() {
+        for query in [
+            "search=%20%20",
+            "search_mode=phrase",
+            "sort=note:asc",
+            "deleted_after=2026-02-01T00:00:00Z&deleted_before=2026-01-01T00:00:00Z",
+        ] {
+            assert!(deleted_items_request(query).to_query().is_err(), "{query}");
+        }
+        let active_items = GetAllToDoItemsQueryRequest {
+            sort: Some("deleted_at:desc".into()),
+            ..Default::default()
+        };
+        assert!(active_items.validate_sort().is_err());
     }
 
     #[test]
@@ -353,15 +1133,6 @@ mod tests {
         assert!(request.validate().is_err());
     }
 
-    #[test]
-    fn parse_optional_delete_actor_id_rejects_invalid_uuid() {
-        let request = actix_web::test::TestRequest::default()
-            .insert_header((DELETE_ACTOR_ID_HEADER, "bad-uuid"))
-            .to_http_request();
-
-        assert!(parse_optional_delete_actor_id(&request).is_err());
-    }
-
     #[test]
     fn parse_audit_token_header_returns_trimmed_value() {
         let request = actix_web::test::TestRequest::default()
@@ -371,4 +1142,71 @@ mod tests {
         let token = parse_audit_token_header(&request);
         assert_eq!(token.as_deref(), Some("token"));
     }
+
+    #[test]
+    fn batch_request_maps_operations_in_order() {
+        let id = Uuid::new_v4();
+        let actor = Uuid::new_v4();
+        let request: BatchToDoItemsRequest = serde_json::from_value(serde_json::json!({
+            "atomic": true,
+            "operations": [
+                { "op": "create", "item": { "title": "title", "note": "note" } },
+                {
+                    "op": "update",
+                    "id": id,
+                    "if_match": "W/\"3\"",
+                    "item": { "title": "title", "note": "note", "status": "in_progress" }
+                },
+                { "op": "delete", "id": id }
+            ]
+        }))
+        .expect("batch request");
+
+        let caller = Caller {
+            id: Some(actor),
+            scope: AccessScope::owned_by(actor),
+            request_id: None,
+        };
+        let command = request.to_command(&caller).expect("batch command");
+
+        assert!(command.atomic);
+        assert!(matches!(
+            &command.operations[0],
+            BatchOperation::Create(create) if create.owner_id == Some(actor)
+        ));
+        assert!(matches!(
+            &command.operations[1],
+            BatchOperation::Update(update) if update.id == id
+                && update.version == 3
+                && update.updated_by == Some(actor)
+                && update.scope == caller.scope
+        ));
+        assert_eq!(
+            command.operations[2],
+            BatchOperation::Delete {
+                command: DeleteToDoItemCommand::new(id, Some(actor)).within(caller.scope),
+                version: None,
+            }
+        );
+    }
+
+    #[test]
+    fn batch_request_rejects_invalid_operation_with_its_index() {
+        let request: BatchToDoItemsRequest = serde_json::from_value(serde_json::json!({
+            "operations": [
+                { "op": "create", "item": { "title": "title", "note": "note" } },
+                { "op": "delete", "id": Uuid::new_v4(), "if_match": "*" }
+            ]
+        }))
+        .expect("batch request");
+
+        let error = request
+            .to_command(&Caller::anonymous())
+            .expect_err("invalid if_match");
+
+        assert_eq!(
+            error,
+            "operations[1]: if_match must contain an integer ETag"
+        );
+    }
 }
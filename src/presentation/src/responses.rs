@@ -1,10 +1,16 @@
-use application::PaginatedResult;
+use application::{
+    BatchOperationOutcome, BatchOperationResult, PaginatedResult, ToDoItemDiff, ToDoItemRevision,
+};
 use chrono::{DateTime, Utc};
 use domain::ToDoItem;
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
 use utoipa::ToSchema;
 use uuid::Uuid;
 
+use crate::cursor::encode_cursor;
+use crate::errors::HttpError;
+
 #[readonly::make]
 #[derive(Deserialize, Serialize, ToSchema)]
 pub struct ToDoItemResponse {
@@ -22,6 +28,12 @@ pub struct ToDoItemResponse {
     pub updated_at: DateTime<Utc>,
     /// Optional due timestamp in UTC.
     pub due_at: Option<DateTime<Utc>>,
+    /// User that owns the item; absent for items created without authentication.
+    pub owner_id: Option<Uuid>,
+    /// Search snippet of title and note with matched words wrapped in `<mark>` tags.
+    /// Only present in search results. The item text is not HTML-escaped.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highlight: Option<String>,
 }
 
 impl From<ToDoItem> for ToDoItemResponse {
@@ -30,10 +42,12 @@ impl From<ToDoItem> for ToDoItemResponse {
             id: item.id,
             title: item.title,
             note: item.note,
-            status: item.status,
+            status: item.status.to_string(),
             created_at: item.created_at,
             updated_at: item.updated_at,
             due_at: item.due_at,
+            owner_id: item.owner_id,
+            highlight: None,
         }
     }
 }
@@ -59,6 +73,14 @@ pub struct AuditToDoItemResponse {
     pub deleted_at: Option<DateTime<Utc>>,
     /// Optional actor that performed deletion.
     pub deleted_by: Option<Uuid>,
+    /// User that owns the item.
+    pub owner_id: Option<Uuid>,
+    /// Optional actor that performed the last update.
+    pub updated_by: Option<Uuid>,
+    /// Time of the last restore in UTC.
+    pub restored_at: Option<DateTime<Utc>>,
+    /// Optional actor that performed the last restore.
+    pub restored_by: Option<Uuid>,
 }
 
 impl From<ToDoItem> for AuditToDoItemResponse {
@@ -67,27 +89,219 @@ impl From<ToDoItem> for AuditToDoItemResponse {
             id: item.id,
             title: item.title,
             note: item.note,
-            status: item.status,
+            status: item.status.to_string(),
             created_at: item.created_at,
             updated_at: item.updated_at,
             due_at: item.due_at,
             deleted_at: item.deleted_at,
             deleted_by: item.deleted_by,
+            owner_id: item.owner_id,
+            updated_by: item.updated_by,
+            restored_at: item.restored_at,
+            restored_by: item.restored_by,
+        }
+    }
+}
+
+#[readonly::make]
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct ToDoItemRevisionResponse {
+    /// The id of the to-do item
+    pub item_id: Uuid,
+    /// One-based number of the change among the recorded revisions of the item.
+    pub revision: i32,
+    /// Version of the item after the change; identifies the revision in `history/{version}`.
+    pub version: i32,
+    /// Kind of change: `created`, `updated`, `deleted` or `restored`.
+    pub change_type: String,
+    /// Caller that made the change; absent for changes made without authentication.
+    pub actor: Option<Uuid>,
+    /// Request id of the request that made the change.
+    pub request_id: Option<String>,
+    /// Time the change was recorded in UTC.
+    pub recorded_at: DateTime<Utc>,
+    /// The item as the change left it.
+    pub snapshot: AuditToDoItemResponse,
+}
+
+impl From<ToDoItemRevision> for ToDoItemRevisionResponse {
+    fn from(revision: ToDoItemRevision) -> Self {
+        Self {
+            item_id: revision.item_id,
+            revision: revision.revision,
+            version: revision.snapshot.version,
+            change_type: revision.change_type.to_string(),
+            actor: revision.actor,
+            request_id: revision.request_id,
+            recorded_at: revision.recorded_at,
+            snapshot: AuditToDoItemResponse::from(revision.snapshot),
         }
     }
 }
 
+#[readonly::make]
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct AuditToDoItemsPageResponse {
+    /// Current page of deleted to-do items.
+    pub items: Vec<AuditToDoItemResponse>,
+    /// Pagination metadata for the current result set.
+    pub meta: PaginationMetaResponse,
+}
+
+impl From<PaginatedResult<ToDoItem>> for AuditToDoItemsPageResponse {
+    fn from(result: PaginatedResult<ToDoItem>) -> Self {
+        Self {
+            items: result
+                .items
+                .into_iter()
+                .map(AuditToDoItemResponse::from)
+                .collect(),
+            meta: PaginationMetaResponse {
+                page: result.page,
+                page_size: result.page_size,
+                total_items: result.total_items,
+                total_pages: result.total_pages,
+            },
+        }
+    }
+}
+
+#[readonly::make]
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct ToDoItemHistoryPageResponse {
+    /// Current page of revisions, oldest first.
+    pub items: Vec<ToDoItemRevisionResponse>,
+    /// Pagination metadata for the history.
+    pub meta: PaginationMetaResponse,
+}
+
+impl From<PaginatedResult<ToDoItemRevision>> for ToDoItemHistoryPageResponse {
+    fn from(result: PaginatedResult<ToDoItemRevision>) -> Self {
+        Self {
+            items: result
+                .items
+                .into_iter()
+                .map(ToDoItemRevisionResponse::from)
+                .collect(),
+            meta: PaginationMetaResponse {
+                page: result.page,
+                page_size: result.page_size,
+                total_items: result.total_items,
+                total_pages: result.total_pages,
+            },
+        }
+    }
+}
+
+#[readonly::make]
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct FieldChangeResponse {
+    /// Name of the changed member of the revision snapshot.
+    pub field: String,
+    /// Value in the `from` revision.
+    pub old_value: Value,
+    /// Value in the `to` revision.
+    pub new_value: Value,
+    /// Last revision in the compared range that changed the field.
+    pub revision: i32,
+    /// Caller that made that change.
+    pub changed_by: Option<Uuid>,
+    /// Time that change was recorded in UTC.
+    pub changed_at: DateTime<Utc>,
+}
+
+#[readonly::make]
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct ToDoItemDiffResponse {
+    /// The id of the to-do item
+    pub item_id: Uuid,
+    /// Revision compared from.
+    pub from_revision: i32,
+    /// Revision compared to.
+    pub to_revision: i32,
+    /// Item version in the `from` revision.
+    pub from_version: i32,
+    /// Item version in the `to` revision.
+    pub to_version: i32,
+    /// Fields whose value differs between the two revisions.
+    pub changes: Vec<FieldChangeResponse>,
+}
+
+impl From<ToDoItemDiff> for ToDoItemDiffResponse {
+    fn from(diff: ToDoItemDiff) -> Self {
+        Self {
+            item_id: diff.to.item_id,
+            from_revision: diff.from.revision,
+            to_revision: diff.to.revision,
+            from_version: diff.from.snapshot.version,
+            to_version: diff.to.snapshot.version,
+            changes: diff
+                .changes
+                .into_iter()
+                .map(|change| FieldChangeResponse {
+                    field: change.field.to_string(),
+                    old_value: change.old_value,
+                    new_value: change.new_value,
+                    revision: change.revision,
+                    changed_by: change.changed_by,
+                    changed_at: change.changed_at,
+                })
+                .collect(),
+        }
+    }
+}
+
+/// RFC 6902 operation on the `snapshot` document of a revision.
+#[readonly::make]
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct JsonPatchOperationResponse {
+    /// `test` or `replace`.
+    pub op: String,
+    /// JSON Pointer of the member.
+    pub path: String,
+    /// Expected value for `test`, new value for `replace`.
+    pub value: Value,
+}
+
+impl JsonPatchOperationResponse {
+    /// Turns `diff` into a patch that checks each changed member of the `from` snapshot
+    /// before replacing it, so replaying it on any other document fails.
+    pub fn from_diff(diff: &ToDoItemDiff) -> Vec<Self> {
+        diff.changes
+            .iter()
+            .flat_map(|change| {
+                let path = format!("/{}", change.field);
+                [
+                    Self {
+                        op: "test".to_string(),
+                        path: path.clone(),
+                        value: change.old_value.clone(),
+                    },
+                    Self {
+                        op: "replace".to_string(),
+                        path,
+                        value: change.new_value.clone(),
+                    },
+                ]
+            })
+            .collect()
+    }
+}
+
 #[readonly::make]
 #[derive(Deserialize, Serialize, ToSchema)]
 pub struct PaginationMetaResponse {
-    /// One-based page number.
-    pub page: u32,
+    /// One-based page number. Omitted in cursor pagination.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page: Option<u32>,
     /// Number of items requested per page.
     pub page_size: u32,
-    /// Total number of matching items.
-    pub total_items: i64,
-    /// Total number of pages for the current filter.
-    pub total_pages: u32,
+    /// Total number of matching items. Omitted in cursor pagination unless `include_total` is set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_items: Option<i64>,
+    /// Total number of pages for the current filter. Omitted whenever `total_items` is.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_pages: Option<u32>,
 }
 
 #[readonly::make]
@@ -97,15 +311,24 @@ pub struct ToDoItemsPageResponse {
     pub items: Vec<ToDoItemResponse>,
     /// Pagination metadata for the current result set.
     pub meta: PaginationMetaResponse,
+    /// Opaque cursor of the next page in cursor pagination. Omitted on the last page.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub next_cursor: Option<String>,
 }
 
 impl From<PaginatedResult<ToDoItem>> for ToDoItemsPageResponse {
-    fn from(result: PaginatedResult<ToDoItem>) -> Self {
+    fn from(mut result: PaginatedResult<ToDoItem>) -> Self {
         Self {
             items: result
                 .items
                 .into_iter()
-                .map(ToDoItemResponse::from)
+                .map(|item| {
+                    let highlight = result.highlights.remove(&item.id);
+                    ToDoItemResponse {
+                        highlight,
+                        ..ToDoItemResponse::from(item)
+                    }
+                })
                 .collect(),
             meta: PaginationMetaResponse {
                 page: result.page,
@@ -113,6 +336,7 @@ impl From<PaginatedResult<ToDoItem>> for ToDoItemsPageResponse {
                 total_items: result.total_items,
                 total_pages: result.total_pages,
             },
+            next_cursor: result.next_cursor.as_ref().map(encode_cursor),
         }
     }
 }
@@ -127,3 +351,118 @@ pub struct ProblemDetailsResponse {
     /// Human-readable explanation specific to this occurrence of the problem.
     pub detail: String,
 }
+
+impl From<HttpError> for ProblemDetailsResponse {
+    fn from(err: HttpError) -> Self {
+        let HttpError::Problem(problem) = err;
+        Self {
+            title: problem.title,
+            status: problem.status.map_or(500, |status| status.as_u16()),
+            detail: problem.detail.unwrap_or_default(),
+        }
+    }
+}
+
+#[readonly::make]
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct BatchOperationResultResponse {
+    /// Position of the operation in the request.
+    pub index: usize,
+    /// HTTP status the operation would have produced on its own.
+    pub status: u16,
+    /// Id of the affected to-do item. Omitted for failed operations.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<Uuid>,
+    /// New ETag of created or updated items.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub etag: Option<String>,
+    /// Problem details of failed or not applied operations.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub problem: Option<ProblemDetailsResponse>,
+}
+
+impl BatchOperationResultResponse {
+    fn new(index: usize, result: BatchOperationResult) -> Self {
+        let applied = |status: u16, id: Uuid, version: Option<i32>| Self {
+            index,
+            status,
+            id: Some(id),
+            etag: version.map(|version| format!("\"{version}\"")),
+            problem: None,
+        };
+        let failed = |problem: ProblemDetailsResponse| Self {
+            index,
+            status: problem.status,
+            id: None,
+            etag: None,
+            problem: Some(problem),
+        };
+
+        match result {
+            BatchOperationResult::Applied(BatchOperationOutcome::Created { id }) => {
+                applied(201, id, Some(1))
+            }
+            BatchOperationResult::Applied(BatchOperationOutcome::Updated { id, version }) => {
+                applied(200, id, Some(version))
+            }
+            BatchOperationResult::Applied(BatchOperationOutcome::Deleted { id }) => {
+                applied(200, id, None)
+            }
+            BatchOperationResult::Failed(err) => failed(HttpError::from(err).into()),
+            BatchOperationResult::NotApplied => failed(ProblemDetailsResponse {
+                title: Some("Failed Dependency".to_string()),
+                status: 424,
+                detail: "not applied because another operation of the atomic batch failed"
+                    .to_string(),
+            }),
+        }
+    }
+}
+
+#[readonly::make]
+#[derive(Deserialize, Serialize, ToSchema)]
+pub struct BatchToDoItemsResponse {
+    /// Per-operation results in request order.
+    pub results: Vec<BatchOperationResultResponse>,
+}
+
+impl From<Vec<BatchOperationResult>> for BatchToDoItemsResponse {
+    fn from(results: Vec<BatchOperationResult>) -> Self {
+        Self {
+            results: results
+                .into_iter()
+                .enumerate()
+                .map(|(index, result)| BatchOperationResultResponse::new(index, result))
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use application::{ChangeType, ToDoItemRevision};
+    use serde_json::json;
+
+    #[test]
+    fn json_patch_tests_each_old_value_before_replacing_it() {
+        let mut item = ToDoItem::new("title".to_string(), "note".to_string());
+        let from = ToDoItemRevision::new(&item, ChangeType::Created, None, None);
+        item.title = Some("renamed".to_string());
+        let to = ToDoItemRevision {
+            revision: 2,
+            ..ToDoItemRevision::new(&item, ChangeType::Updated, None, None)
+        };
+        let diff = ToDoItemDiff::between(vec![from, to]).unwrap();
+
+        let patch = serde_json::to_value(JsonPatchOperationResponse::from_diff(&diff)).unwrap();
+
+        assert_eq!(
+            patch,
+            json!([
+                { "op": "test", "path": "/title", "value": "title" },
+                { "op": "replace", "path": "/title", "value": "renamed" }
+            ])
+        );
+    }
+}
@@ -0,0 +1,310 @@
+use actix_web::body::{BoxBody, MessageBody};
+use actix_web::dev::{ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderName, HOST};
+use actix_web::middleware::Next;
+use actix_web::web::Data;
+use actix_web::{Error, HttpMessage};
+use anyhow::{bail, Result};
+use application::Tenancy;
+
+use crate::auth::Principal;
+use crate::errors::HttpError;
+
+pub(crate) const MAX_TENANT_LENGTH: usize = 64;
+
+/// Organization the current request acts for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Tenant(pub String);
+
+enum TenantSource {
+    Header(HeaderName),
+    Claim,
+    Host { trust_forwarded: bool },
+}
+
+/// Resolves the [`Tenant`] of a request from the source chosen in [`Tenancy`].
+///
+/// Header and host tenants of authenticated requests are checked against the `claim` of
+/// the principal, so callers cannot act for another organization by picking its tenant.
+pub struct TenantResolver {
+    enabled: bool,
+    source: TenantSource,
+    claim: String,
+}
+
+impl TenantResolver {
+    pub fn from_settings(settings: &Tenancy) -> Result<Self> {
+        let source = match settings.source.trim().to_ascii_lowercase().as_str() {
+            "header" => match HeaderName::try_from(settings.header.trim()) {
+                Ok(name) => TenantSource::Header(name),
+                Err(_) => bail!("invalid tenancy header name {:?}", settings.header),
+            },
+            "claim" => TenantSource::Claim,
+            "host" => TenantSource::Host {
+                trust_forwarded: settings.trust_forwarded_host,
+            },
+            other => bail!("unknown tenancy source {other:?}; expected header, claim or host"),
+        };
+        if settings.claim.trim().is_empty() {
+            bail!("tenancy.claim must name a token claim");
+        }
+
+        Ok(Self {
+            enabled: settings.enabled,
+            source,
+            claim: settings.claim.trim().to_string(),
+        })
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Returns the tenant of the request, or the error to answer it with.
+    #[allow(clippy::result_large_err)]
+    pub fn resolve(&self, request: &ServiceRequest) -> Result<Tenant, HttpError> {
+        let principal = request.extensions().get::<Principal>().cloned();
+        let claimed = principal
+            .as_ref()
+            .map(|principal| {
+                principal.claim(&self.claim).ok_or_else(|| {
+                    HttpError::forbidden(format!("token is missing the {} claim", self.claim))
+                })
+            })
+            .transpose()?;
+        let tenant = match &self.source {
+            TenantSource::Header(name) => request
+                .headers()
+                .get(name)
+                .ok_or_else(|| HttpError::bad_request(format!("missing {name} header")))?
+                .to_str()
+                .map_err(|_| HttpError::bad_request(format!("{name} header must be valid ASCII")))?
+                .trim()
+                .to_string(),
+            TenantSource::Claim => claimed.map(str::to_string).ok_or_else(|| {
+                HttpError::forbidden(format!("token is missing the {} claim", self.claim))
+            })?,
+            TenantSource::Host { trust_forwarded } => {
+                let host = if *trust_forwarded {
+                    request.connection_info().host().to_ascii_lowercase()
+                } else {
+                    request_host(request)
+                };
+                host.split(['.', ':'])
+                    .next()
+                    .unwrap_or_default()
+                    .to_string()
+            }
+        };
+
+        if !is_valid_tenant(&tenant) {
+            return Err(HttpError::bad_request(format!(
+                "tenant id must be 1 to {MAX_TENANT_LENGTH} letters, digits, '-' or '_'"
+            )));
+        }
+        if claimed.is_some_and(|claimed| claimed != tenant) {
+            return Err(HttpError::forbidden(
+                "requested tenant does not match the tenant of the token",
+            ));
+        }
+        Ok(Tenant(tenant))
+    }
+}
+
+/// Host the client connected to, from the `Host` header or the request URI, ignoring the
+/// forwarding headers any client can set.
+fn request_host(request: &ServiceRequest) -> String {
+    request
+        .headers()
+        .get(HOST)
+        .and_then(|host| host.to_str().ok())
+        .or_else(|| request.uri().host())
+        .unwrap_or_default()
+        .to_ascii_lowercase()
+}
+
+/// Whether `tenant` can identify a tenant: 1 to 64 ASCII letters, digits, `-` or `_`.
+pub fn is_valid_tenant(tenant: &str) -> bool {
+    (1..=MAX_TENANT_LENGTH).contains(&tenant.len())
+        && tenant
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Attaches the [`Tenant`] of each request, rejecting requests whose tenant cannot be
+/// resolved.
+///
+/// Runs after authentication, so tenants can be taken from token claims. Passes requests
+/// through unchanged while multi-tenancy is disabled.
+pub async fn tenant_middleware(
+    request: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    let Some(resolver) = request
+        .app_data::<Data<TenantResolver>>()
+        .filter(|resolver| resolver.is_enabled())
+        .cloned()
+    else {
+        return next.call(request).await.map(|r| r.map_into_boxed_body());
+    };
+
+    match resolver.resolve(&request) {
+        Ok(tenant) => {
+            request.extensions_mut().insert(tenant);
+            next.call(request).await.map(|r| r.map_into_boxed_body())
+        }
+        Err(err) => Ok(request.error_response(err)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::http::StatusCode;
+    use actix_web::middleware::from_fn;
+    use actix_web::test::{call_service, init_service, TestRequest};
+    use actix_web::{web, App, HttpRequest, HttpResponse, ResponseError};
+    use application::Settings;
+    use uuid::Uuid;
+
+    fn resolver(source: TenantSource) -> TenantResolver {
+        TenantResolver {
+            enabled: true,
+            source,
+            claim: "tenant_id".into(),
+        }
+    }
+
+    fn principal_of(tenant: &str) -> Principal {
+        Principal::new(Uuid::new_v4()).with_claims([("tenant_id".to_string(), tenant.to_string())])
+    }
+
+    #[test]
+    fn tenants_come_from_header_claim_or_host() {
+        let header = TestRequest::default()
+            .insert_header(("X-Tenant-Id", " acme "))
+            .to_srv_request();
+        let claim = TestRequest::default().to_srv_request();
+        claim.extensions_mut().insert(principal_of("globex"));
+        let host = TestRequest::default()
+            .insert_header(("Host", "Initech.todo.example.com:8080"))
+            .to_srv_request();
+
+        let header_source = TenantSource::Header(HeaderName::from_static("x-tenant-id"));
+
+        assert_eq!(
+            resolver(header_source).resolve(&header).ok(),
+            Some(Tenant("acme".into()))
+        );
+        assert_eq!(
+            resolver(TenantSource::Claim).resolve(&claim).ok(),
+            Some(Tenant("globex".into()))
+        );
+        assert_eq!(
+            resolver(TenantSource::Host {
+                trust_forwarded: false
+            })
+            .resolve(&host)
+            .ok(),
+            Some(Tenant("initech".into()))
+        );
+    }
+
+    #[test]
+    fn header_tenants_of_authenticated_requests_must_match_the_token() {
+        let request = |principal: Principal| {
+            let request = TestRequest::default()
+                .insert_header(("X-Tenant-Id", "acme"))
+                .to_srv_request();
+            request.extensions_mut().insert(principal);
+            request
+        };
+        let resolver = resolver(TenantSource::Header(HeaderName::from_static("x-tenant-id")));
+
+        assert_eq!(
+            resolver.resolve(&request(principal_of("acme"))).ok(),
+            Some(Tenant("acme".into()))
+        );
+        let other = resolver
+            .resolve(&request(principal_of("globex")))
+            .unwrap_err();
+        let unclaimed = resolver
+            .resolve(&request(Principal::new(Uuid::new_v4())))
+            .unwrap_err();
+        assert_eq!(other.error_response().status(), StatusCode::FORBIDDEN);
+        assert_eq!(unclaimed.error_response().status(), StatusCode::FORBIDDEN);
+    }
+
+    #[test]
+    fn forwarded_hosts_are_only_trusted_when_configured() {
+        let request = || {
+            TestRequest::default()
+                .insert_header(("Host", "acme.todo.example.com"))
+                .insert_header(("X-Forwarded-Host", "globex.todo.example.com"))
+                .to_srv_request()
+        };
+        let resolve = |trust_forwarded| {
+            resolver(TenantSource::Host { trust_forwarded })
+                .resolve(&request())
+                .ok()
+        };
+
+        assert_eq!(resolve(false), Some(Tenant("acme".into())));
+        assert_eq!(resolve(true), Some(Tenant("globex".into())));
+    }
+
+    #[test]
+    fn unknown_sources_are_rejected() {
+        let mut settings = serde_json::to_value(&Settings::default().tenancy).unwrap();
+        settings["source"] = "cookie".into();
+        let settings: Tenancy = serde_json::from_value(settings).unwrap();
+
+        assert!(TenantResolver::from_settings(&settings).is_err());
+    }
+
+    #[actix_web::test]
+    async fn requests_without_a_valid_tenant_get_problem_details() {
+        async fn echo(request: HttpRequest) -> HttpResponse {
+            let tenant = request.extensions().get::<Tenant>().cloned();
+            HttpResponse::Ok().body(tenant.map(|Tenant(id)| id).unwrap_or_default())
+        }
+        let app = init_service(
+            App::new()
+                .app_data(Data::new(resolver(TenantSource::Header(
+                    HeaderName::from_static("x-tenant-id"),
+                ))))
+                .service(
+                    web::scope("")
+                        .wrap(from_fn(tenant_middleware))
+                        .route("/", web::get().to(echo)),
+                ),
+        )
+        .await;
+
+        let missing = call_service(&app, TestRequest::get().uri("/").to_request()).await;
+        let invalid = call_service(
+            &app,
+            TestRequest::get()
+                .uri("/")
+                .insert_header(("X-Tenant-Id", "acme corp"))
+                .to_request(),
+        )
+        .await;
+        let valid = call_service(
+            &app,
+            TestRequest::get()
+                .uri("/")
+                .insert_header(("X-Tenant-Id", "acme"))
+                .to_request(),
+        )
+        .await;
+
+        assert_eq!(missing.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(invalid.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(valid.status(), StatusCode::OK);
+        assert_eq!(
+            actix_web::body::to_bytes(valid.into_body()).await.unwrap(),
+            "acme"
+        );
+    }
+}
@@ -0,0 +1,242 @@
+use anyhow::{bail, Context, Result};
+use application::{ApiKey, ApiKeyRepository};
+use chrono::{Duration, Utc};
+use std::fmt::Write;
+use std::sync::Arc;
+use uuid::Uuid;
+
+const USAGE: &str = "usage:
+  starter api-keys create <name> [--scopes \"<scope> ...\"] [--tenant <tenant>] [--expires-in-days <days>]
+  starter api-keys list
+  starter api-keys revoke <id>";
+
+/// Management action of the `api-keys` subcommand.
+#[derive(Debug, PartialEq)]
+pub enum ApiKeyCommand {
+    Create {
+        name: String,
+        scopes: Vec<String>,
+        tenant: Option<String>,
+        expires_in_days: Option<i64>,
+    },
+    List,
+    Revoke {
+        id: Uuid,
+    },
+}
+
+impl ApiKeyCommand {
+    /// Parses the arguments that follow `api-keys` on the command line.
+    pub fn parse(args: &[String]) -> Result<Self> {
+        match args {
+            [action, rest @ ..] if action == "create" => Self::parse_create(rest),
+            [action] if action == "list" => Ok(Self::List),
+            [action, id] if action == "revoke" => Ok(Self::Revoke {
+                id: Uuid::parse_str(id).with_context(|| format!("invalid API key id {id:?}"))?,
+            }),
+            _ => bail!("{USAGE}"),
+        }
+    }
+
+    fn parse_create(args: &[String]) -> Result<Self> {
+        let Some((name, mut options)) = args.split_first() else {
+            bail!("{USAGE}");
+        };
+        if name.trim().is_empty() || name.starts_with("--") {
+            bail!("{USAGE}");
+        }
+
+        let mut scopes = Vec::new();
+        let mut tenant = None;
+        let mut expires_in_days = None;
+        while let [option, value, rest @ ..] = options {
+            match option.as_str() {
+                "--scopes" => scopes.extend(
+                    value
+                        .split([' ', ','])
+                        .filter(|scope| !scope.is_empty())
+                        .map(str::to_string),
+                ),
+                "--tenant" => {
+                    if !presentation::is_valid_tenant(value) {
+                        bail!("invalid tenant {value:?}");
+                    }
+                    tenant = Some(value.to_string());
+                }
+                "--expires-in-days" => {
+                    let days = value
+                        .parse::<i64>()
+                        .ok()
+                        .filter(|days| *days > 0)
+                        .with_context(|| format!("invalid number of days {value:?}"))?;
+                    expires_in_days = Some(days);
+                }
+                _ => bail!("unknown option {option}\n{USAGE}"),
+            }
+            options = rest;
+        }
+        if !options.is_empty() {
+            bail!("{USAGE}");
+        }
+
+        Ok(Self::Create {
+            name: name.trim().to_string(),
+            scopes,
+            tenant,
+            expires_in_days,
+        })
+    }
+}
+
+/// Runs `command` against `repository` and returns the text to print.
+pub async fn execute(
+    command: ApiKeyCommand,
+    repository: Arc<dyn ApiKeyRepository>,
+) -> Result<String> {
+    match command {
+        ApiKeyCommand::Create {
+            name,
+            scopes,
+            tenant,
+            expires_in_days,
+        } => {
+            let expires_at = expires_in_days.map(|days| Utc::now() + Duration::days(days));
+            let (secret, key) = presentation::generate_api_key(name, scopes, tenant, expires_at);
+            let summary = describe(&key);
+            repository.create(key).await?;
+            Ok(format!(
+                "{summary}\n{secret}\nStore this key now; it cannot be shown again."
+            ))
+        }
+        ApiKeyCommand::List => {
+            let keys = repository.list().await?;
+            if keys.is_empty() {
+                return Ok("no API keys".to_string());
+            }
+            Ok(keys.iter().map(describe).collect::<Vec<_>>().join("\n"))
+        }
+        ApiKeyCommand::Revoke { id } => {
+            if !repository.revoke(id, Utc::now()).await? {
+                bail!("no active API key with id {id}");
+            }
+            Ok(format!("revoked API key {id}"))
+        }
+    }
+}
+
+fn describe(key: &ApiKey) -> String {
+    let status = if key.is_revoked() {
+        "revoked"
+    } else if key.is_expired(Utc::now()) {
+        "expired"
+    } else {
+        "active"
+    };
+    let mut line = format!(
+        "{}  {}  {}...  {}  scopes: {}",
+        key.id,
+        key.name,
+        key.prefix,
+        status,
+        key.scopes.join(" ")
+    );
+    if let Some(tenant_id) = &key.tenant_id {
+        let _ = write!(line, "  tenant: {tenant_id}");
+    }
+    if let Some(expires_at) = key.expires_at {
+        let _ = write!(line, "  expires: {}", expires_at.to_rfc3339());
+    }
+    if let Some(last_used_at) = key.last_used_at {
+        let _ = write!(line, "  last used: {}", last_used_at.to_rfc3339());
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use application::InMemoryApiKeyRepository;
+
+    fn args(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn parses_create_list_and_revoke() {
+        let id = Uuid::new_v4();
+
+        assert_eq!(
+            ApiKeyCommand::parse(&args(&[
+                "create",
+                "nightly-export",
+                "--scopes",
+                "todo:read todo:write",
+                "--tenant",
+                "acme",
+                "--expires-in-days",
+                "30"
+            ]))
+            .unwrap(),
+            ApiKeyCommand::Create {
+                name: "nightly-export".into(),
+                scopes: vec!["todo:read".into(), "todo:write".into()],
+                tenant: Some("acme".into()),
+                expires_in_days: Some(30),
+            }
+        );
+        assert_eq!(
+            ApiKeyCommand::parse(&args(&["list"])).unwrap(),
+            ApiKeyCommand::List
+        );
+        assert_eq!(
+            ApiKeyCommand::parse(&args(&["revoke", &id.to_string()])).unwrap(),
+            ApiKeyCommand::Revoke { id }
+        );
+        assert!(ApiKeyCommand::parse(&args(&["create"])).is_err());
+        assert!(ApiKeyCommand::parse(&args(&["create", "job", "--expires-in-days", "0"])).is_err());
+        assert!(ApiKeyCommand::parse(&args(&["create", "job", "--tenant", "a/b"])).is_err());
+        assert!(ApiKeyCommand::parse(&args(&["revoke", "not-a-uuid"])).is_err());
+    }
+
+    #[tokio::test]
+    async fn created_keys_are_listed_without_their_secret_and_can_be_revoked() {
+        let repository = Arc::new(InMemoryApiKeyRepository::new());
+        let created = execute(
+            ApiKeyCommand::parse(&args(&[
+                "create",
+                "job",
+                "--scopes",
+                "todo:read",
+                "--tenant",
+                "acme",
+            ]))
+            .unwrap(),
+            repository.clone(),
+        )
+        .await
+        .unwrap();
+        let secret = created
+            .lines()
+            .find(|line| line.starts_with("tk_"))
+            .unwrap();
+        let id = repository.list().await.unwrap()[0].id;
+
+        let listed = execute(ApiKeyCommand::List, repository.clone())
+            .await
+            .unwrap();
+        assert!(listed.contains("job") && listed.contains("active"));
+        assert!(listed.contains("tenant: acme"));
+        assert!(!listed.contains(secret));
+
+        execute(ApiKeyCommand::Revoke { id }, repository.clone())
+            .await
+            .unwrap();
+        let listed = execute(ApiKeyCommand::List, repository.clone())
+            .await
+            .unwrap();
+        assert!(listed.contains("revoked"));
+        assert!(execute(ApiKeyCommand::Revoke { id }, repository)
+            .await
+            .is_err());
+    }
+}
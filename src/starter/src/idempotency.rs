@@ -0,0 +1,26 @@
+use application::{Idempotency, IdempotencyRepository};
+use chrono::Utc;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+/// Removes expired idempotency keys once per TTL until the runtime shuts down.
+///
+/// Expired keys are already ignored on lookup; this only keeps the table from growing.
+pub fn spawn_cleanup(
+    settings: &Idempotency,
+    repository: Arc<dyn IdempotencyRepository>,
+) -> JoinHandle<()> {
+    let interval = std::time::Duration::from_secs(settings.ttl_secs.max(1));
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+            match repository.purge_expired(Utc::now()).await {
+                Ok(purged) => debug!(purged, "removed expired idempotency keys"),
+                Err(err) => warn!(error = %err, "failed to remove expired idempotency keys"),
+            }
+        }
+    })
+}
@@ -1,11 +1,16 @@
+mod api_keys;
+mod idempotency;
 mod observability;
+mod outbox;
+mod repositories;
+mod retention;
 
 use actix_web::dev::Server;
 use actix_web::middleware::from_fn;
 use actix_web::{web, App, HttpServer};
 use anyhow::Result;
-use application::{Settings, ToDoItemService};
-use infrastructure::PostgresToDoItemRepository;
+use application::{ApiKeyRepository, IdempotencyRepository, Settings, ToDoItemService};
+use infrastructure::{PostgresApiKeyRepository, PostgresIdempotencyRepository};
 use std::sync::Arc;
 use tracing::{debug, info};
 use tracing_actix_web::TracingLogger;
@@ -13,6 +18,8 @@ use utoipa::OpenApi;
 use utoipa_actix_web::AppExt;
 use utoipa_swagger_ui::SwaggerUi;
 
+pub use api_keys::ApiKeyCommand;
+
 pub async fn run() -> Result<Server> {
     let settings = Settings::default().load()?;
     run_internal(&settings).await
@@ -23,6 +30,44 @@ pub async fn run_with_config(path: &str) -> Result<Server> {
     run_internal(&settings).await
 }
 
+/// Runs the retention purge once and returns the number of purged items.
+pub async fn purge() -> Result<usize> {
+    let settings = Settings::default().load()?;
+    purge_internal(&settings).await
+}
+
+pub async fn purge_with_config(path: &str) -> Result<usize> {
+    let settings = Settings::with_path(path).load()?;
+    purge_internal(&settings).await
+}
+
+async fn purge_internal(settings: &Settings) -> Result<usize> {
+    observability::init_tracing(settings)?;
+
+    let pool = infrastructure::configure(settings).await?;
+    let repositories = repositories::build_repositories(settings, &web::Data::new(pool))?;
+
+    Ok(retention::purge_expired(&settings.retention, repositories.unit_of_work).await?)
+}
+
+/// Creates, lists or revokes API keys and returns the text to print.
+pub async fn manage_api_keys(command: ApiKeyCommand) -> Result<String> {
+    let settings = Settings::default().load()?;
+    manage_api_keys_internal(&settings, command).await
+}
+
+pub async fn manage_api_keys_with_config(path: &str, command: ApiKeyCommand) -> Result<String> {
+    let settings = Settings::with_path(path).load()?;
+    manage_api_keys_internal(&settings, command).await
+}
+
+async fn manage_api_keys_internal(settings: &Settings, command: ApiKeyCommand) -> Result<String> {
+    let pool = infrastructure::configure(settings).await?;
+    let repository = Arc::new(PostgresApiKeyRepository::new(&web::Data::new(pool)));
+
+    api_keys::execute(command, repository).await
+}
+
 async fn run_internal(settings: &Settings) -> Result<Server> {
     observability::init_tracing(settings)?;
     let observability_config = observability::ObservabilityConfig::from_settings(settings)?;
@@ -32,15 +77,60 @@ async fn run_internal(settings: &Settings) -> Result<Server> {
     debug!("with configuration: {:?}", &settings);
 
     let pool = infrastructure::configure(settings).await?;
+    let pool_data = web::Data::new(pool.clone());
+
+    let repositories::Repositories {
+        query: repository,
+        unit_of_work,
+        pools,
+    } = repositories::build_repositories(settings, &pool_data)?;
+    info!(
+        "Using the {} database driver",
+        settings.database.driver.trim()
+    );
+
+    if settings.observability.metrics_enabled {
+        observability::spawn_pool_metrics(pools);
+    }
+
+    if settings.outbox.enabled {
+        outbox::spawn_dispatcher(&settings.outbox, unit_of_work.clone())?;
+    }
+
+    if settings.retention.enabled {
+        retention::spawn_purger(&settings.retention, unit_of_work.clone());
+    }
+
+    let idempotency_repository: Arc<dyn IdempotencyRepository> =
+        Arc::new(PostgresIdempotencyRepository::new(&pool_data));
+    if settings.idempotency.enabled {
+        idempotency::spawn_cleanup(&settings.idempotency, idempotency_repository.clone());
+    }
 
-    // Create repository with Arc for thread safety
-    let repository = Arc::new(PostgresToDoItemRepository::new(&web::Data::new(
-        pool.clone(),
-    )));
+    let api_key_repository: Arc<dyn ApiKeyRepository> =
+        Arc::new(PostgresApiKeyRepository::new(&pool_data));
 
     // Create service with explicit command/query dependencies.
-    let todo_service = ToDoItemService::new(repository.clone(), repository);
-    let audit_settings = settings.audit.clone();
+    let todo_service = ToDoItemService::new(repository, unit_of_work);
+    let idempotency_settings = settings.idempotency.clone();
+    let http_cache_settings = settings.http_cache.clone();
+    let authenticator = web::Data::new(presentation::Authenticator::from_settings(
+        &settings.authentication,
+    )?);
+    let authorization_policy = web::Data::new(presentation::AuthorizationPolicy::from_settings(
+        &settings.authorization,
+    )?);
+    let api_key_authenticator = web::Data::new(presentation::ApiKeyAuthenticator::from_settings(
+        &settings.api_keys,
+        &settings.tenancy,
+        api_key_repository,
+    )?);
+    let audit_authenticator = web::Data::new(presentation::AuditAuthenticator::from_settings(
+        &settings.audit,
+    )?);
+    let tenant_resolver = web::Data::new(presentation::TenantResolver::from_settings(
+        &settings.tenancy,
+    )?);
     let observability_settings = observability_config.clone();
     let metrics_handle = prometheus_handle.clone();
 
@@ -59,7 +149,14 @@ async fn run_internal(settings: &Settings) -> Result<Server> {
             })
             .app_data(web::Data::new(pool.clone()))
             .app_data(web::Data::new(todo_service.clone()))
-            .app_data(web::Data::new(audit_settings.clone()))
+            .app_data(audit_authenticator.clone())
+            .app_data(web::Data::new(idempotency_settings.clone()))
+            .app_data(web::Data::new(http_cache_settings.clone()))
+            .app_data(authenticator.clone())
+            .app_data(api_key_authenticator.clone())
+            .app_data(authorization_policy.clone())
+            .app_data(tenant_resolver.clone())
+            .app_data(web::Data::from(idempotency_repository.clone()))
             .into_app()
     })
     .bind(&settings.service.http_url)?
@@ -1,12 +1,26 @@
 extern crate infrastructure;
 extern crate presentation;
 
-use anyhow::Result;
-use starter::run;
+use anyhow::{bail, Result};
+use starter::{manage_api_keys, purge, run, ApiKeyCommand};
 #[actix_web::main]
 async fn main() -> Result<()> {
     dotenv::dotenv().ok();
-    run().await?.await?;
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    match args.first().map(String::as_str) {
+        None => run().await?.await?,
+        Some("purge") => {
+            let purged = purge().await?;
+            println!("purged {purged} soft-deleted to-do items");
+        }
+        Some("api-keys") => {
+            println!(
+                "{}",
+                manage_api_keys(ApiKeyCommand::parse(&args[1..])?).await?
+            );
+        }
+        Some(other) => bail!("unknown subcommand: {other}"),
+    }
 
     Ok(())
 }
@@ -2,17 +2,21 @@ use actix_web::body::MessageBody;
 use actix_web::dev::{ServiceRequest, ServiceResponse};
 use actix_web::http::header::{HeaderName, HeaderValue};
 use actix_web::middleware::Next;
-use actix_web::Error;
+use actix_web::{Error, HttpMessage};
 use anyhow::{anyhow, Context, Result};
 use application::Settings;
+use infrastructure::MonitoredPool;
 use metrics::{counter, histogram};
 use metrics_exporter_prometheus::{PrometheusBuilder, PrometheusHandle};
 use std::sync::OnceLock;
-use std::time::Instant;
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
 use tracing::info;
 use tracing_subscriber::EnvFilter;
 use uuid::Uuid;
 
+const POOL_METRICS_INTERVAL: Duration = Duration::from_secs(5);
+
 static TRACING_INIT: OnceLock<()> = OnceLock::new();
 static PROMETHEUS_HANDLE: OnceLock<Result<PrometheusHandle, String>> = OnceLock::new();
 
@@ -72,6 +76,19 @@ pub fn init_prometheus_recorder() -> Result<PrometheusHandle> {
     }
 }
 
+/// Refreshes the `db_pool_*` gauges of `pools` until the runtime shuts down.
+pub fn spawn_pool_metrics(pools: Vec<(&'static str, MonitoredPool)>) -> JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(POOL_METRICS_INTERVAL);
+        loop {
+            interval.tick().await;
+            for (name, pool) in &pools {
+                pool.record(name);
+            }
+        }
+    })
+}
+
 pub async fn observability_middleware(
     request: ServiceRequest,
     next: Next<impl MessageBody>,
@@ -86,6 +103,9 @@ pub async fn observability_middleware(
     let start = Instant::now();
     let request_id = extract_or_generate_request_id(&request, &config.request_id_header_name);
     let request_path = request.path().to_string();
+    request
+        .extensions_mut()
+        .insert(presentation::RequestId(request_id.clone()));
 
     let mut response = next.call(request).await?;
     let status = response.status();
@@ -0,0 +1,88 @@
+use anyhow::{bail, Result};
+use application::{
+    DispatchOutboxCommand, DispatchOutboxCommandHandler, EventPublisher, Outbox, OutboxRetryPolicy,
+    UnitOfWorkFactory,
+};
+use infrastructure::{LogEventPublisher, NdjsonFileEventPublisher};
+use metrics::counter;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::warn;
+
+pub fn build_publisher(
+    publisher: &str,
+    file_path: &str,
+) -> Result<Arc<dyn EventPublisher + Send + Sync>> {
+    match publisher.trim() {
+        "log" => Ok(Arc::new(LogEventPublisher)),
+        "file" => Ok(Arc::new(NdjsonFileEventPublisher::new(file_path))),
+        other => bail!("unsupported outbox publisher: {other}"),
+    }
+}
+
+/// Polls the outbox and publishes pending events until the runtime shuts down.
+///
+/// Full batches are drained back to back; the dispatcher only waits for the next tick
+/// once fewer messages than a batch are due, none of them could be published, or
+/// dispatching fails.
+pub fn spawn_dispatcher(
+    settings: &Outbox,
+    unit_of_work: Arc<dyn UnitOfWorkFactory + Send + Sync>,
+) -> Result<JoinHandle<()>> {
+    let handler = DispatchOutboxCommandHandler::new(
+        unit_of_work,
+        build_publisher(&settings.publisher, &settings.file_path)?,
+    )
+    .with_retry_policy(OutboxRetryPolicy::new(
+        settings.max_attempts.max(1),
+        Duration::from_millis(settings.retry_base_ms),
+        Duration::from_millis(settings.retry_max_ms),
+    ));
+    let batch_size = settings.batch_size.max(1);
+    let poll_interval = Duration::from_millis(settings.poll_interval_ms.max(1));
+
+    Ok(tokio::spawn(async move {
+        let mut interval = tokio::time::interval(poll_interval);
+        loop {
+            interval.tick().await;
+            loop {
+                match handler
+                    .execute(DispatchOutboxCommand::new(batch_size))
+                    .await
+                {
+                    Ok(dispatch) => {
+                        counter!("outbox_messages_published_total")
+                            .increment(dispatch.published as u64);
+                        counter!("outbox_message_retries_total").increment(dispatch.retried as u64);
+                        counter!("outbox_messages_failed_total").increment(dispatch.failed as u64);
+                        if (dispatch.fetched as i64) < batch_size || dispatch.published == 0 {
+                            break;
+                        }
+                    }
+                    Err(err) => {
+                        counter!("outbox_dispatch_failures_total").increment(1);
+                        warn!(error = %err, "failed to dispatch outbox messages");
+                        break;
+                    }
+                }
+            }
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_publisher_supports_log_and_file_publishers() {
+        assert!(build_publisher("log", "events.ndjson").is_ok());
+        assert!(build_publisher("file", "events.ndjson").is_ok());
+    }
+
+    #[test]
+    fn build_publisher_rejects_unknown_publisher() {
+        assert!(build_publisher("kafka", "events.ndjson").is_err());
+    }
+}
@@ -0,0 +1,112 @@
+use actix_web::web;
+use anyhow::{bail, Result};
+use application::{Settings, ToDoItemQueryRepository, UnitOfWorkFactory};
+use infrastructure::{
+    DbPool, MonitoredPool, PostgresToDoItemRepository, PostgresUnitOfWorkFactory,
+    TokioPostgresToDoItemRepository, TokioPostgresUnitOfWorkFactory,
+};
+use std::sync::Arc;
+
+pub struct Repositories {
+    pub query: Arc<dyn ToDoItemQueryRepository + Send + Sync>,
+    pub unit_of_work: Arc<dyn UnitOfWorkFactory + Send + Sync>,
+    /// Every connection pool in use, by the `pool` label of its gauges.
+    pub pools: Vec<(&'static str, MonitoredPool)>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum DatabaseDriver {
+    Diesel,
+    TokioPostgres,
+}
+
+impl DatabaseDriver {
+    fn parse(driver: &str) -> Result<Self> {
+        match driver.trim() {
+            "diesel" => Ok(Self::Diesel),
+            "tokio_postgres" => Ok(Self::TokioPostgres),
+            other => bail!("unsupported database driver: {other}"),
+        }
+    }
+}
+
+/// Builds the to-do item repositories of the configured `database.driver`.
+///
+/// The `tokio_postgres` driver opens its own async pool; `pool` keeps serving the other
+/// repositories and the readiness probe either way. With `database.read_pool` enabled the
+/// query repository runs on a separate read-only pool of the same driver.
+pub fn build_repositories(settings: &Settings, pool: &web::Data<DbPool>) -> Result<Repositories> {
+    let row_level_security = settings.tenancy.row_level_security;
+
+    let mut pools = vec![("primary", MonitoredPool::Diesel(pool.get_ref().clone()))];
+
+    match DatabaseDriver::parse(&settings.database.driver)? {
+        DatabaseDriver::Diesel => {
+            let read_pool = match infrastructure::configure_read_pool(settings)? {
+                Some(read_pool) => {
+                    pools.push(("read", MonitoredPool::Diesel(read_pool.clone())));
+                    web::Data::new(read_pool)
+                }
+                None => pool.clone(),
+            };
+            Ok(Repositories {
+                query: Arc::new(
+                    PostgresToDoItemRepository::new(&read_pool)
+                        .with_row_level_security(row_level_security),
+                ),
+                unit_of_work: Arc::new(
+                    PostgresUnitOfWorkFactory::new(pool)
+                        .with_row_level_security(row_level_security),
+                ),
+                pools,
+            })
+        }
+        DatabaseDriver::TokioPostgres => {
+            let pool = infrastructure::configure_async_pool(settings)?;
+            pools.push(("async", MonitoredPool::TokioPostgres(pool.clone())));
+            let read_pool = match infrastructure::configure_async_read_pool(settings)? {
+                Some(read_pool) => {
+                    pools.push((
+                        "async_read",
+                        MonitoredPool::TokioPostgres(read_pool.clone()),
+                    ));
+                    read_pool
+                }
+                None => pool.clone(),
+            };
+            Ok(Repositories {
+                query: Arc::new(
+                    TokioPostgresToDoItemRepository::new(&read_pool)
+                        .with_row_level_security(row_level_security),
+                ),
+                unit_of_work: Arc::new(
+                    TokioPostgresUnitOfWorkFactory::new(&pool)
+                        .with_row_level_security(row_level_security),
+                ),
+                pools,
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_supports_diesel_and_tokio_postgres_drivers() {
+        assert_eq!(
+            DatabaseDriver::parse("diesel").unwrap(),
+            DatabaseDriver::Diesel
+        );
+        assert_eq!(
+            DatabaseDriver::parse(" tokio_postgres ").unwrap(),
+            DatabaseDriver::TokioPostgres
+        );
+    }
+
+    #[test]
+    fn parse_rejects_unknown_driver() {
+        assert!(DatabaseDriver::parse("sqlx").is_err());
+    }
+}
@@ -0,0 +1,72 @@
+use application::{
+    ApplicationResult, PurgeDeletedToDoItemsCommand, PurgeDeletedToDoItemsCommandHandler,
+    Retention, UnitOfWorkFactory,
+};
+use chrono::{DateTime, Duration, Utc};
+use metrics::counter;
+use std::sync::Arc;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+pub fn purge_cutoff(now: DateTime<Utc>, retention_days: i64) -> DateTime<Utc> {
+    now - Duration::days(retention_days.max(0))
+}
+
+/// Purges every item whose retention period has expired and records the purged rows.
+pub async fn purge_expired(
+    settings: &Retention,
+    unit_of_work: Arc<dyn UnitOfWorkFactory + Send + Sync>,
+) -> ApplicationResult<usize> {
+    let handler = PurgeDeletedToDoItemsCommandHandler::new(unit_of_work);
+    let deleted_before = purge_cutoff(Utc::now(), settings.retention_days);
+
+    let purged = handler
+        .execute(PurgeDeletedToDoItemsCommand::new(
+            deleted_before,
+            settings.batch_size,
+        ))
+        .await?;
+    counter!("todo_items_purged_total").increment(purged as u64);
+    info!(purged, %deleted_before, "purged expired soft-deleted to-do items");
+
+    Ok(purged)
+}
+
+/// Runs the retention purge on a fixed interval until the runtime shuts down.
+pub fn spawn_purger(
+    settings: &Retention,
+    unit_of_work: Arc<dyn UnitOfWorkFactory + Send + Sync>,
+) -> JoinHandle<()> {
+    let settings = settings.clone();
+    let interval = std::time::Duration::from_secs(settings.interval_secs.max(1));
+
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            interval.tick().await;
+            if let Err(err) = purge_expired(&settings, unit_of_work.clone()).await {
+                counter!("todo_items_purge_failures_total").increment(1);
+                warn!(error = %err, "failed to purge expired to-do items");
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn purge_cutoff_subtracts_retention_days() {
+        let now = Utc::now();
+
+        assert_eq!(purge_cutoff(now, 30), now - Duration::days(30));
+    }
+
+    #[test]
+    fn purge_cutoff_treats_negative_retention_as_zero() {
+        let now = Utc::now();
+
+        assert_eq!(purge_cutoff(now, -5), now);
+    }
+}
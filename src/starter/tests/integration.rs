@@ -3,8 +3,12 @@ mod utils;
 #[cfg(test)]
 mod tests {
     use crate::utils::test_server;
+    use application::Settings;
     use chrono::DateTime;
     use ctor::dtor;
+    use diesel::connection::SimpleConnection;
+    use diesel::sql_types::Text;
+    use diesel::RunQueryDsl;
     use reqwest::StatusCode;
     use serde_json::json;
     use serde_json::Value;
@@ -12,7 +16,8 @@ mod tests {
     use std::collections::HashMap;
     use uuid::Uuid;
 
-    use crate::{prepare_test_environment, utils::test_server::TEST_SERVER_ONCE};
+    use crate::prepare_test_environment;
+    use crate::utils::test_server::Driver;
 
     const WEB_SERVER_PATH: &str = "http://localhost:8181/api/v1/";
     const METRICS_PATH: &str = "http://localhost:8181/metrics";
@@ -20,8 +25,8 @@ mod tests {
 
     #[dtor]
     fn cleanup() {
-        if let Some(server) = TEST_SERVER_ONCE.get() {
-            let id = server.container().id();
+        if let Some(container) = test_server::container() {
+            let id = container.id();
             let _ = std::process::Command::new("docker")
                 .arg("kill")
                 .arg(id)
@@ -29,6 +34,44 @@ mod tests {
         }
     }
 
+    /// Runs each listed test against the server of every database driver, as
+    /// `<test>::diesel` and `<test>::tokio_postgres`.
+    macro_rules! test_each_driver {
+        ($($test:ident),* $(,)?) => {$(
+            mod $test {
+                use super::*;
+
+                #[serial]
+                #[tokio::test]
+                async fn diesel() {
+                    super::$test(Driver::Diesel).await;
+                }
+
+                #[serial]
+                #[tokio::test]
+                async fn tokio_postgres() {
+                    super::$test(Driver::TokioPostgres).await;
+                }
+            }
+        )*};
+    }
+
+    test_each_driver!(
+        test_get_all,
+        test_get_by_id,
+        test_create,
+        test_update,
+        test_delete,
+        test_restore_makes_deleted_item_visible_again,
+        test_patch_updates_only_provided_fields_and_clears_due_at,
+        test_batch_reports_per_item_results_and_rolls_back_atomic_batches,
+        test_update_rejects_stale_version,
+        test_history_records_every_change_of_an_item,
+        test_diff_lists_changed_fields_and_renders_json_patch,
+        test_purge_removes_expired_deleted_items,
+        test_read_pool_rejects_writes_and_applies_session_timeouts,
+    );
+
     #[serial]
     #[tokio::test]
     async fn start_server_and_test() {
@@ -36,13 +79,12 @@ mod tests {
         assert!(client.get(WEB_SERVER_PATH).send().await.is_ok());
     }
 
-    #[serial]
-    #[tokio::test]
-    async fn test_get_all() {
-        let client = prepare_test_environment!();
+    async fn test_get_all(driver: Driver) {
+        let client = prepare_test_environment!(driver);
+        let web_server_path = driver.api_path();
 
         let response = client
-            .get(WEB_SERVER_PATH.to_owned() + "to-do-items")
+            .get(web_server_path.to_owned() + "to-do-items")
             .send()
             .await
             .expect("Failed to execute request.");
@@ -130,17 +172,16 @@ mod tests {
         assert!(response.status().is_success());
     }
 
-    #[serial]
-    #[tokio::test]
-    async fn test_get_by_id() {
-        let client = prepare_test_environment!();
+    async fn test_get_by_id(driver: Driver) {
+        let client = prepare_test_environment!(driver);
+        let web_server_path = driver.api_path();
         let mut map_create = HashMap::new();
         map_create.insert("title", "title1");
         map_create.insert("note", "note1");
         map_create.insert("status", "pending");
 
         let id = client
-            .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
+            .post(web_server_path.to_owned() + "to-do-items")
             .json(&map_create)
             .send()
             .await
@@ -150,7 +191,7 @@ mod tests {
             .expect("Failed to deserialize response.");
 
         let response = client
-            .get(WEB_SERVER_PATH.to_owned() + format!("to-do-items/{id}").as_str())
+            .get(web_server_path.to_owned() + format!("to-do-items/{id}").as_str())
             .send()
             .await
             .expect("Failed to execute request.");
@@ -170,15 +211,84 @@ mod tests {
 
     #[serial]
     #[tokio::test]
-    async fn test_create() {
+    async fn test_conditional_get_returns_not_modified() {
         let client = prepare_test_environment!();
+        let id = client
+            .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
+            .json(&json!({ "title": "conditional", "note": "note" }))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json::<Uuid>()
+            .await
+            .expect("Failed to deserialize response.");
+        let item_path = WEB_SERVER_PATH.to_owned() + format!("to-do-items/{id}").as_str();
+
+        let response = client
+            .get(item_path.as_str())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(response.status(), StatusCode::OK);
+        let headers = response.headers();
+        let etag = headers["etag"]
+            .to_str()
+            .expect("ETag must be ascii")
+            .to_string();
+        let last_modified = headers["last-modified"]
+            .to_str()
+            .expect("Last-Modified must be ascii")
+            .to_string();
+        assert_eq!(headers["cache-control"], "private, no-cache");
+
+        let response = client
+            .get(item_path.as_str())
+            .header("If-None-Match", etag.as_str())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+        assert_eq!(response.headers()["etag"], etag.as_str());
+
+        let response = client
+            .get(item_path.as_str())
+            .header("If-Modified-Since", last_modified.as_str())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+
+        let list_path = WEB_SERVER_PATH.to_owned() + "to-do-items?page=1&page_size=5";
+        let response = client
+            .get(list_path.as_str())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        let list_etag = response.headers()["etag"]
+            .to_str()
+            .expect("ETag must be ascii")
+            .to_string();
+        assert!(list_etag.starts_with("W/"));
+
+        let response = client
+            .get(list_path.as_str())
+            .header("If-None-Match", list_etag.as_str())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(response.status(), StatusCode::NOT_MODIFIED);
+    }
+
+    async fn test_create(driver: Driver) {
+        let client = prepare_test_environment!(driver);
+        let web_server_path = driver.api_path();
         let mut map = HashMap::new();
         map.insert("title", "title1");
         map.insert("note", "note1");
         map.insert("status", "pending");
 
         let response = client
-            .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
+            .post(web_server_path.to_owned() + "to-do-items")
             .json(&map)
             .send()
             .await
@@ -187,6 +297,57 @@ mod tests {
         assert!(response.status().is_success());
     }
 
+    #[serial]
+    #[tokio::test]
+    async fn test_create_with_idempotency_key_replays_response() {
+        let client = prepare_test_environment!();
+        let key = Uuid::new_v4().to_string();
+        let payload = json!({ "title": "idempotent", "note": "note" });
+
+        let first = client
+            .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
+            .header("Idempotency-Key", key.as_str())
+            .json(&payload)
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(first.status(), StatusCode::CREATED);
+        let first_id = first
+            .json::<Uuid>()
+            .await
+            .expect("Failed to deserialize response.");
+
+        let retry = client
+            .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
+            .header("Idempotency-Key", key.as_str())
+            .json(&payload)
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(retry.status(), StatusCode::CREATED);
+        assert_eq!(
+            retry
+                .headers()
+                .get("idempotent-replayed")
+                .expect("replayed header must be present"),
+            "true"
+        );
+        let retry_id = retry
+            .json::<Uuid>()
+            .await
+            .expect("Failed to deserialize response.");
+        assert_eq!(retry_id, first_id);
+
+        let mismatch = client
+            .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
+            .header("Idempotency-Key", key.as_str())
+            .json(&json!({ "title": "different", "note": "note" }))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(mismatch.status(), StatusCode::UNPROCESSABLE_ENTITY);
+    }
+
     #[serial]
     #[tokio::test]
     async fn test_create_returns_command_acknowledgement_not_read_model() {
@@ -259,17 +420,16 @@ mod tests {
         assert!(body["due_at"].is_null());
     }
 
-    #[serial]
-    #[tokio::test]
-    async fn test_update() {
-        let client = prepare_test_environment!();
+    async fn test_update(driver: Driver) {
+        let client = prepare_test_environment!(driver);
+        let web_server_path = driver.api_path();
         let mut map_create = HashMap::new();
         map_create.insert("title", "title1");
         map_create.insert("note", "note1");
         map_create.insert("status", "pending");
 
         let id = client
-            .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
+            .post(web_server_path.to_owned() + "to-do-items")
             .json(&map_create)
             .send()
             .await
@@ -279,7 +439,7 @@ mod tests {
             .expect("Failed to deserialize response.");
 
         let item_response = client
-            .get(WEB_SERVER_PATH.to_owned() + format!("to-do-items/{id}").as_str())
+            .get(web_server_path.to_owned() + format!("to-do-items/{id}").as_str())
             .send()
             .await
             .expect("Failed to execute request.");
@@ -301,7 +461,7 @@ mod tests {
 
         let due_at = "2030-01-15T12:00:00Z";
         let response = client
-            .put(WEB_SERVER_PATH.to_owned() + format!("to-do-items/{id}").as_str())
+            .put(web_server_path.to_owned() + format!("to-do-items/{id}").as_str())
             .header("If-Match", etag)
             .json(&json!({
                 "title": "title1",
@@ -323,7 +483,7 @@ mod tests {
         );
 
         let updated_item = client
-            .get(WEB_SERVER_PATH.to_owned() + format!("to-do-items/{id}").as_str())
+            .get(web_server_path.to_owned() + format!("to-do-items/{id}").as_str())
             .send()
             .await
             .expect("Failed to execute request.")
@@ -341,17 +501,16 @@ mod tests {
         );
     }
 
-    #[serial]
-    #[tokio::test]
-    async fn test_delete() {
-        let client = prepare_test_environment!();
+    async fn test_delete(driver: Driver) {
+        let client = prepare_test_environment!(driver);
+        let web_server_path = driver.api_path();
         let mut map_create = HashMap::new();
         map_create.insert("title", "title1");
         map_create.insert("note", "note1");
         map_create.insert("status", "pending");
 
         let id = client
-            .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
+            .post(web_server_path.to_owned() + "to-do-items")
             .json(&map_create)
             .send()
             .await
@@ -361,12 +520,33 @@ mod tests {
             .expect("Failed to deserialize response.");
 
         let response = client
-            .delete(WEB_SERVER_PATH.to_owned() + format!("to-do-items/{id}").as_str())
+            .delete(web_server_path.to_owned() + format!("to-do-items/{id}").as_str())
             .send()
             .await
             .expect("Failed to execute request.");
 
         assert!(response.status().is_success());
+
+        let item = client
+            .get(web_server_path.to_owned() + format!("audit/to-do-items/{id}").as_str())
+            .header("X-Audit-Token", AUDIT_TOKEN)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        let revision = client
+            .get(web_server_path.to_owned() + format!("audit/to-do-items/{id}/history/2").as_str())
+            .header("X-Audit-Token", AUDIT_TOKEN)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        assert!(item["deleted_at"].is_string());
+        assert_eq!(revision["snapshot"]["deleted_at"], item["deleted_at"]);
     }
 
     #[serial]
@@ -392,7 +572,10 @@ mod tests {
 
         let response = client
             .delete(WEB_SERVER_PATH.to_owned() + format!("to-do-items/{id}").as_str())
-            .header("X-Actor-Id", actor_id.to_string())
+            .header(
+                "Authorization",
+                test_server::bearer_with_roles(actor_id, test_server::TEST_SCOPES, &["admin"]),
+            )
             .send()
             .await
             .expect("Failed to execute request.");
@@ -424,6 +607,220 @@ mod tests {
         assert_eq!(list_body["meta"]["total_items"], 0);
     }
 
+    async fn test_restore_makes_deleted_item_visible_again(driver: Driver) {
+        let client = prepare_test_environment!(driver);
+        let web_server_path = driver.api_path();
+        let actor_id = Uuid::new_v4();
+
+        let id = client
+            .post(web_server_path.to_owned() + "to-do-items")
+            .json(&json!({
+                "title": "restorable",
+                "note": "note1",
+                "status": "pending"
+            }))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json::<Uuid>()
+            .await
+            .expect("Failed to deserialize response.");
+
+        let delete_response = client
+            .delete(web_server_path.to_owned() + format!("to-do-items/{id}").as_str())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert!(delete_response.status().is_success());
+
+        let restore_response = client
+            .post(web_server_path.to_owned() + format!("to-do-items/{id}/restore").as_str())
+            .header(
+                "Authorization",
+                test_server::bearer_with_roles(actor_id, test_server::TEST_SCOPES, &["admin"]),
+            )
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        assert_eq!(restore_response.status(), StatusCode::OK);
+        assert_eq!(
+            restore_response
+                .headers()
+                .get("ETag")
+                .and_then(|value| value.to_str().ok()),
+            Some("\"3\"")
+        );
+        let body = restore_response
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        assert_eq!(body["id"], id.to_string());
+
+        let get_response = client
+            .get(web_server_path.to_owned() + format!("to-do-items/{id}").as_str())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(get_response.status(), StatusCode::OK);
+
+        let audit_response = client
+            .get(web_server_path.to_owned() + format!("audit/to-do-items/{id}").as_str())
+            .header("X-Audit-Token", AUDIT_TOKEN)
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(audit_response.status(), StatusCode::NOT_FOUND);
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_restore_rejects_active_and_missing_items() {
+        let client = prepare_test_environment!();
+
+        let id = client
+            .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
+            .json(&json!({
+                "title": "still-active",
+                "note": "note1",
+                "status": "pending"
+            }))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json::<Uuid>()
+            .await
+            .expect("Failed to deserialize response.");
+
+        let active_response = client
+            .post(WEB_SERVER_PATH.to_owned() + format!("to-do-items/{id}/restore").as_str())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(active_response.status(), StatusCode::CONFLICT);
+        let body = active_response
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        assert!(body["detail"]
+            .as_str()
+            .expect("detail should be a string")
+            .contains("is not deleted"));
+
+        let missing_response = client
+            .post(
+                WEB_SERVER_PATH.to_owned()
+                    + format!("to-do-items/{}/restore", Uuid::new_v4()).as_str(),
+            )
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(missing_response.status(), StatusCode::NOT_FOUND);
+    }
+
+    async fn test_purge_removes_expired_deleted_items(driver: Driver) {
+        let client = prepare_test_environment!(driver);
+        let web_server_path = driver.api_path();
+
+        let id = client
+            .post(web_server_path.to_owned() + "to-do-items")
+            .json(&json!({
+                "title": "purgeable",
+                "note": "note1",
+                "status": "pending"
+            }))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json::<Uuid>()
+            .await
+            .expect("Failed to deserialize response.");
+
+        let delete_response = client
+            .delete(web_server_path.to_owned() + format!("to-do-items/{id}").as_str())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert!(delete_response.status().is_success());
+
+        std::env::set_var("MICROSERVICE__RETENTION__RETENTION_DAYS", "0");
+        std::env::set_var("MICROSERVICE__DATABASE__DRIVER", driver.name());
+        let purged = starter::purge_with_config("./../../").await;
+        std::env::remove_var("MICROSERVICE__RETENTION__RETENTION_DAYS");
+        std::env::remove_var("MICROSERVICE__DATABASE__DRIVER");
+        assert!(purged.expect("purge should succeed") >= 1);
+
+        let audit_response = client
+            .get(web_server_path.to_owned() + format!("audit/to-do-items/{id}").as_str())
+            .header("X-Audit-Token", AUDIT_TOKEN)
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(audit_response.status(), StatusCode::NOT_FOUND);
+
+        let restore_response = client
+            .post(web_server_path.to_owned() + format!("to-do-items/{id}/restore").as_str())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(restore_response.status(), StatusCode::NOT_FOUND);
+    }
+
+    async fn test_read_pool_rejects_writes_and_applies_session_timeouts(driver: Driver) {
+        prepare_test_environment!(driver);
+
+        std::env::set_var("MICROSERVICE__DATABASE__READ_POOL__ENABLED", "true");
+        std::env::set_var("MICROSERVICE__DATABASE__STATEMENT_TIMEOUT_MS", "1234");
+        let settings = Settings::with_path(test_server::CONFIG_FILE_PATH).load();
+        std::env::remove_var("MICROSERVICE__DATABASE__READ_POOL__ENABLED");
+        std::env::remove_var("MICROSERVICE__DATABASE__STATEMENT_TIMEOUT_MS");
+        let settings = settings.expect("settings should load");
+
+        let (statement_timeout, write_error) = match driver {
+            Driver::Diesel => {
+                let pool = infrastructure::configure_read_pool(&settings)
+                    .expect("read pool should build")
+                    .expect("read pool should be enabled");
+                let mut connection = pool.get().expect("Failed to get a read connection.");
+                let statement_timeout = diesel::select(diesel::dsl::sql::<Text>(
+                    "current_setting('statement_timeout')",
+                ))
+                .get_result::<String>(&mut connection)
+                .expect("Failed to read statement_timeout.");
+                let write_error = connection
+                    .batch_execute("DELETE FROM to_do_items WHERE false")
+                    .expect_err("a write on the read pool should fail");
+                (statement_timeout, write_error.to_string())
+            }
+            Driver::TokioPostgres => {
+                let pool = infrastructure::configure_async_read_pool(&settings)
+                    .expect("read pool should build")
+                    .expect("read pool should be enabled");
+                let client = pool.get().await.expect("Failed to get a read connection.");
+                let statement_timeout = client
+                    .query_one("SHOW statement_timeout", &[])
+                    .await
+                    .expect("Failed to read statement_timeout.")
+                    .get::<_, String>(0);
+                let write_error = client
+                    .batch_execute("DELETE FROM to_do_items WHERE false")
+                    .await
+                    .expect_err("a write on the read pool should fail");
+                let message = write_error
+                    .as_db_error()
+                    .map(|error| error.message().to_owned())
+                    .unwrap_or_else(|| write_error.to_string());
+                (statement_timeout, message)
+            }
+        };
+
+        assert_eq!(statement_timeout, "1234ms");
+        assert!(
+            write_error.contains("read-only transaction"),
+            "unexpected error: {write_error}"
+        );
+    }
+
     #[serial]
     #[tokio::test]
     async fn test_update_deleted_item_returns_not_found() {
@@ -493,7 +890,10 @@ mod tests {
 
         let delete_response = client
             .delete(WEB_SERVER_PATH.to_owned() + format!("to-do-items/{id}").as_str())
-            .header("X-Actor-Id", actor_id.to_string())
+            .header(
+                "Authorization",
+                test_server::bearer_with_roles(actor_id, test_server::TEST_SCOPES, &["admin"]),
+            )
             .send()
             .await
             .expect("Failed to execute request.");
@@ -562,13 +962,13 @@ mod tests {
 
     #[serial]
     #[tokio::test]
-    async fn test_audit_endpoint_supports_delete_without_actor() {
+    async fn test_audit_credentials_rotate_expire_and_are_recorded_per_access() {
         let client = prepare_test_environment!();
 
         let id = client
             .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
             .json(&json!({
-                "title": "audit-no-actor",
+                "title": "audit-rotation",
                 "note": "note1",
                 "status": "pending"
             }))
@@ -578,61 +978,694 @@ mod tests {
             .json::<Uuid>()
             .await
             .expect("Failed to deserialize response.");
-
         let delete_response = client
             .delete(WEB_SERVER_PATH.to_owned() + format!("to-do-items/{id}").as_str())
             .send()
             .await
             .expect("Failed to execute request.");
         assert!(delete_response.status().is_success());
+        let audit_path = WEB_SERVER_PATH.to_owned() + format!("audit/to-do-items/{id}").as_str();
+        let series = [
+            r#"audit_authentications_total{credential="local",outcome="accepted"}"#,
+            r#"audit_authentications_total{credential="rotated",outcome="accepted"}"#,
+            r#"audit_authentications_total{credential="expired",outcome="rejected_expired"}"#,
+        ];
+        let mut before = Vec::new();
+        for name in series {
+            before.push(fetch_metric_value(&client, name).await);
+        }
 
-        let audit_response = client
-            .get(WEB_SERVER_PATH.to_owned() + format!("audit/to-do-items/{id}").as_str())
-            .header("X-Audit-Token", AUDIT_TOKEN)
+        for token in [AUDIT_TOKEN, test_server::ROTATED_AUDIT_TOKEN] {
+            let response = client
+                .get(audit_path.as_str())
+                .header("X-Audit-Token", token)
+                .send()
+                .await
+                .expect("Failed to execute request.");
+            assert_eq!(response.status(), StatusCode::OK, "{token}");
+        }
+        let expired = client
+            .get(audit_path.as_str())
+            .header("X-Audit-Token", test_server::EXPIRED_AUDIT_TOKEN)
             .send()
             .await
             .expect("Failed to execute request.");
-
-        assert_eq!(audit_response.status(), StatusCode::OK);
-        let body = audit_response
-            .json::<Value>()
-            .await
-            .expect("Failed to deserialize response.");
-        assert!(body["deleted_at"].is_string());
-        assert!(body["deleted_by"].is_null());
+        assert_eq!(expired.status(), StatusCode::UNAUTHORIZED);
+
+        for (name, before) in series.into_iter().zip(before) {
+            assert_eq!(
+                fetch_metric_value(&client, name).await,
+                before + 1.0,
+                "{name}"
+            );
+        }
     }
 
     #[serial]
     #[tokio::test]
-    async fn test_create_rejects_blank_title() {
+    async fn test_audit_endpoint_records_default_principal_as_deleter() {
         let client = prepare_test_environment!();
 
-        let response = client
+        let id = client
             .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
             .json(&json!({
-                "title": "   ",
+                "title": "audit-default-principal",
                 "note": "note1",
                 "status": "pending"
             }))
             .send()
             .await
-            .expect("Failed to execute request.");
+            .expect("Failed to execute request.")
+            .json::<Uuid>()
+            .await
+            .expect("Failed to deserialize response.");
 
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let delete_response = client
+            .delete(WEB_SERVER_PATH.to_owned() + format!("to-do-items/{id}").as_str())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert!(delete_response.status().is_success());
+
+        let audit_response = client
+            .get(WEB_SERVER_PATH.to_owned() + format!("audit/to-do-items/{id}").as_str())
+            .header("X-Audit-Token", AUDIT_TOKEN)
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        assert_eq!(audit_response.status(), StatusCode::OK);
+        let body = audit_response
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        assert!(body["deleted_at"].is_string());
+        assert_eq!(body["deleted_by"], test_server::TEST_SUBJECT.to_string());
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_audit_lists_deleted_items_with_filters() {
+        let client = prepare_test_environment!();
+
+        let mut ids = Vec::new();
+        for title in ["archived alpha", "archived beta", "archived gamma"] {
+            let id = client
+                .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
+                .json(&json!({ "title": title, "note": "note", "status": "pending" }))
+                .send()
+                .await
+                .expect("Failed to execute request.")
+                .json::<Uuid>()
+                .await
+                .expect("Failed to deserialize response.");
+            ids.push(id);
+        }
+        for id in &ids[..2] {
+            let response = client
+                .delete(WEB_SERVER_PATH.to_owned() + format!("to-do-items/{id}").as_str())
+                .send()
+                .await
+                .expect("Failed to execute request.");
+            assert!(response.status().is_success());
+        }
+
+        let list = |query: String| {
+            client
+                .get(WEB_SERVER_PATH.to_owned() + "audit/to-do-items?" + query.as_str())
+                .header("X-Audit-Token", AUDIT_TOKEN)
+                .send()
+        };
+        let body = list(format!(
+            "search=archived&deleted_by={}",
+            test_server::TEST_SUBJECT
+        ))
+        .await
+        .expect("Failed to execute request.")
+        .json::<Value>()
+        .await
+        .expect("Failed to deserialize response.");
+        assert_eq!(body["meta"]["total_items"], 2);
+        assert_eq!(body["items"][0]["id"], ids[1].to_string());
+        assert_eq!(body["items"][1]["id"], ids[0].to_string());
+        assert!(body["items"][0]["deleted_at"].is_string());
+
+        let body = list("search=alpha&sort=title:asc".to_string())
+            .await
+            .expect("Failed to execute request.")
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        assert_eq!(body["meta"]["total_items"], 1);
+        assert_eq!(body["items"][0]["title"], "archived alpha");
+
+        let body = list("deleted_after=2999-01-01T00:00:00Z".to_string())
+            .await
+            .expect("Failed to execute request.")
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        assert_eq!(body["meta"]["total_items"], 0);
+
+        let invalid_response = list("sort=note:asc".to_string())
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(invalid_response.status(), StatusCode::BAD_REQUEST);
+
+        let unauthorized_response = client
+            .get(WEB_SERVER_PATH.to_owned() + "audit/to-do-items")
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(unauthorized_response.status(), StatusCode::UNAUTHORIZED);
+    }
+
+    async fn test_history_records_every_change_of_an_item(driver: Driver) {
+        let client = prepare_test_environment!(driver);
+        let web_server_path = driver.api_path();
+
+        let id = client
+            .post(web_server_path.to_owned() + "to-do-items")
+            .header("X-Request-Id", "history-create")
+            .json(&json!({
+                "title": "history",
+                "note": "note1",
+                "status": "pending"
+            }))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json::<Uuid>()
+            .await
+            .expect("Failed to deserialize response.");
+        let update_response = client
+            .put(web_server_path.to_owned() + format!("to-do-items/{id}").as_str())
+            .header("If-Match", "\"1\"")
+            .json(&json!({
+                "title": "history renamed",
+                "note": "note1",
+                "status": "in_progress"
+            }))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(update_response.status(), StatusCode::OK);
+        for path in [
+            format!("to-do-items/{id}"),
+            format!("to-do-items/{id}/restore"),
+        ] {
+            let request = if path.ends_with("restore") {
+                client.post(web_server_path.to_owned() + path.as_str())
+            } else {
+                client.delete(web_server_path.to_owned() + path.as_str())
+            };
+            let response = request.send().await.expect("Failed to execute request.");
+            assert!(response.status().is_success());
+        }
+
+        let history_response = client
+            .get(web_server_path.to_owned() + format!("audit/to-do-items/{id}/history").as_str())
+            .header("X-Audit-Token", AUDIT_TOKEN)
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        assert_eq!(history_response.status(), StatusCode::OK);
+        let body = history_response
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        assert_eq!(body["meta"]["total_items"], 4);
+        let changes = body["items"]
+            .as_array()
+            .expect("history items")
+            .iter()
+            .map(|revision| {
+                (
+                    revision["revision"].as_i64().unwrap_or_default(),
+                    revision["version"].as_i64().unwrap_or_default(),
+                    revision["change_type"].as_str().unwrap_or_default(),
+                )
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            changes,
+            [
+                (1, 1, "created"),
+                (2, 2, "updated"),
+                (3, 3, "deleted"),
+                (4, 4, "restored")
+            ]
+        );
+        assert_eq!(body["items"][0]["request_id"], "history-create");
+        assert_eq!(
+            body["items"][0]["actor"],
+            test_server::TEST_SUBJECT.to_string()
+        );
+        assert!(body["items"][2]["snapshot"]["deleted_at"].is_string());
+
+        let revision_response = client
+            .get(web_server_path.to_owned() + format!("audit/to-do-items/{id}/history/2").as_str())
+            .header("X-Audit-Token", AUDIT_TOKEN)
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        assert_eq!(revision_response.status(), StatusCode::OK);
+        let revision = revision_response
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        assert_eq!(revision["version"], 2);
+        assert_eq!(revision["snapshot"]["title"], "history renamed");
+        assert_eq!(revision["snapshot"]["status"], "in_progress");
+
+        let deleted_response = client
+            .get(web_server_path.to_owned() + format!("audit/to-do-items/{id}/history/3").as_str())
+            .header("X-Audit-Token", AUDIT_TOKEN)
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        let deleted = deleted_response
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        assert_eq!(deleted["change_type"], "deleted");
+        assert_eq!(deleted["version"], 3);
+
+        let missing_response = client
+            .get(web_server_path.to_owned() + format!("audit/to-do-items/{id}/history/9").as_str())
+            .header("X-Audit-Token", AUDIT_TOKEN)
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(missing_response.status(), StatusCode::NOT_FOUND);
+    }
+
+    async fn test_diff_lists_changed_fields_and_renders_json_patch(driver: Driver) {
+        let client = prepare_test_environment!(driver);
+        let web_server_path = driver.api_path();
+
+        let id = client
+            .post(web_server_path.to_owned() + "to-do-items")
+            .json(&json!({
+                "title": "diff",
+                "note": "note1",
+                "status": "pending"
+            }))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json::<Uuid>()
+            .await
+            .expect("Failed to deserialize response.");
+        let update_response = client
+            .put(web_server_path.to_owned() + format!("to-do-items/{id}").as_str())
+            .header("If-Match", "\"1\"")
+            .json(&json!({
+                "title": "diff renamed",
+                "note": "note1",
+                "status": "in_progress"
+            }))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(update_response.status(), StatusCode::OK);
+        let diff_path = format!("audit/to-do-items/{id}/diff?from=1&to=2");
+
+        let diff_response = client
+            .get(web_server_path.to_owned() + diff_path.as_str())
+            .header("X-Audit-Token", AUDIT_TOKEN)
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        assert_eq!(diff_response.status(), StatusCode::OK);
+        let diff = diff_response
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        assert_eq!(diff["from_version"], 1);
+        assert_eq!(diff["to_version"], 2);
+        let changes = diff["changes"].as_array().expect("diff changes");
+        let fields = changes
+            .iter()
+            .map(|change| change["field"].as_str().unwrap_or_default())
+            .collect::<Vec<_>>();
+        assert_eq!(fields, ["title", "status", "updated_by"]);
+        assert_eq!(changes[0]["old_value"], "diff");
+        assert_eq!(changes[0]["new_value"], "diff renamed");
+        assert_eq!(changes[0]["revision"], 2);
+        assert_eq!(
+            changes[0]["changed_by"],
+            test_server::TEST_SUBJECT.to_string()
+        );
+
+        let patch_response = client
+            .get(web_server_path.to_owned() + diff_path.as_str())
+            .header("X-Audit-Token", AUDIT_TOKEN)
+            .header("Accept", "application/json-patch+json")
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        assert_eq!(patch_response.status(), StatusCode::OK);
+        assert_eq!(
+            patch_response
+                .headers()
+                .get("Content-Type")
+                .and_then(|value| value.to_str().ok()),
+            Some("application/json-patch+json")
+        );
+        let patch = patch_response
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        assert_eq!(
+            patch[1],
+            json!({ "op": "replace", "path": "/title", "value": "diff renamed" })
+        );
+
+        let reversed_response = client
+            .get(
+                web_server_path.to_owned()
+                    + format!("audit/to-do-items/{id}/diff?from=2&to=1").as_str(),
+            )
+            .header("X-Audit-Token", AUDIT_TOKEN)
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(reversed_response.status(), StatusCode::BAD_REQUEST);
+
+        let item = client
+            .get(web_server_path.to_owned() + format!("to-do-items/{id}").as_str())
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        let revision = client
+            .get(web_server_path.to_owned() + format!("audit/to-do-items/{id}/history/2").as_str())
+            .header("X-Audit-Token", AUDIT_TOKEN)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        assert_eq!(revision["snapshot"]["updated_at"], item["updated_at"]);
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_read_routes_send_their_configured_cache_control() {
+        let client = prepare_test_environment!();
+
+        let id = client
+            .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
+            .json(&json!({
+                "title": "cache",
+                "note": "note1",
+                "status": "pending"
+            }))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json::<Uuid>()
+            .await
+            .expect("Failed to deserialize response.");
+        let deleted_id = client
+            .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
+            .json(&json!({
+                "title": "cache deleted",
+                "note": "note1",
+                "status": "pending"
+            }))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json::<Uuid>()
+            .await
+            .expect("Failed to deserialize response.");
+        let delete_response = client
+            .delete(WEB_SERVER_PATH.to_owned() + format!("to-do-items/{deleted_id}").as_str())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert!(delete_response.status().is_success());
+
+        for (path, expected) in [
+            ("to-do-items".to_string(), "private, no-cache"),
+            (format!("to-do-items/{id}"), "private, no-cache"),
+            (
+                format!("audit/to-do-items/{id}/history"),
+                test_server::HISTORY_CACHE_CONTROL,
+            ),
+            (
+                format!("audit/to-do-items/{id}/history/1"),
+                test_server::REVISION_CACHE_CONTROL,
+            ),
+            (
+                format!("audit/to-do-items/{id}/diff?from=1&to=1"),
+                test_server::DIFF_CACHE_CONTROL,
+            ),
+            ("audit/to-do-items".to_string(), "no-store"),
+            (format!("audit/to-do-items/{deleted_id}"), "no-store"),
+        ] {
+            let response = client
+                .get(WEB_SERVER_PATH.to_owned() + path.as_str())
+                .header("X-Audit-Token", AUDIT_TOKEN)
+                .send()
+                .await
+                .expect("Failed to execute request.");
+
+            assert_eq!(response.status(), StatusCode::OK, "{path}");
+            assert_eq!(response.headers()["cache-control"], expected, "{path}");
+        }
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_create_rejects_blank_title() {
+        let client = prepare_test_environment!();
+
+        let response = client
+            .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
+            .json(&json!({
+                "title": "   ",
+                "note": "note1",
+                "status": "pending"
+            }))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_update_rejects_blank_note() {
+        let client = prepare_test_environment!();
+        let mut map_create = HashMap::new();
+        map_create.insert("title", "title1");
+        map_create.insert("note", "note1");
+        map_create.insert("status", "pending");
+
+        let id = client
+            .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
+            .json(&map_create)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json::<Uuid>()
+            .await
+            .expect("Failed to deserialize response.");
+
+        let response = client
+            .put(WEB_SERVER_PATH.to_owned() + format!("to-do-items/{id}").as_str())
+            .header("If-Match", "\"1\"")
+            .json(&json!({
+                "title": "title1",
+                "note": "   ",
+                "status": "pending"
+            }))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    async fn test_update_rejects_stale_version(driver: Driver) {
+        let client = prepare_test_environment!(driver);
+        let web_server_path = driver.api_path();
+        let mut map_create = HashMap::new();
+        map_create.insert("title", "title1");
+        map_create.insert("note", "note1");
+        map_create.insert("status", "pending");
+
+        let id = client
+            .post(web_server_path.to_owned() + "to-do-items")
+            .json(&map_create)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json::<Uuid>()
+            .await
+            .expect("Failed to deserialize response.");
+
+        let item_response = client
+            .get(web_server_path.to_owned() + format!("to-do-items/{id}").as_str())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        let etag = item_response
+            .headers()
+            .get("etag")
+            .expect("ETag header must be present")
+            .to_str()
+            .expect("ETag must be ascii")
+            .to_string();
+
+        let response = client
+            .put(web_server_path.to_owned() + format!("to-do-items/{id}").as_str())
+            .header("If-Match", etag.clone())
+            .json(&json!({
+                "title": "title2",
+                "note": "note2",
+                "status": "in_progress"
+            }))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let stale_response = client
+            .put(web_server_path.to_owned() + format!("to-do-items/{id}").as_str())
+            .header("If-Match", etag)
+            .json(&json!({
+                "title": "title3",
+                "note": "note3",
+                "status": "done"
+            }))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        assert_eq!(stale_response.status(), StatusCode::PRECONDITION_FAILED);
+
+        let body = stale_response
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        assert_eq!(body["status"], json!(412));
+        assert_eq!(body["title"], "Precondition Failed");
+        assert!(body["detail"].as_str().unwrap().contains("stale version"));
+    }
+
+    async fn test_batch_reports_per_item_results_and_rolls_back_atomic_batches(driver: Driver) {
+        let client = prepare_test_environment!(driver);
+        let web_server_path = driver.api_path();
+        let batch_path = web_server_path.to_owned() + "to-do-items:batch";
+
+        let response = client
+            .post(batch_path.as_str())
+            .json(&json!({
+                "operations": [
+                    { "op": "create", "item": { "title": "batch1", "note": "note" } },
+                    { "op": "create", "item": { "title": "batch2", "note": "note" } }
+                ]
+            }))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(response.status(), StatusCode::MULTI_STATUS);
+        let body = response
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        assert_eq!(body["results"][0]["status"], json!(201));
+        assert_eq!(body["results"][1]["etag"], json!("\"1\""));
+        let first: Uuid = serde_json::from_value(body["results"][0]["id"].clone())
+            .expect("created id must be a uuid");
+        let second: Uuid = serde_json::from_value(body["results"][1]["id"].clone())
+            .expect("created id must be a uuid");
+
+        let response = client
+            .post(batch_path.as_str())
+            .json(&json!({
+                "operations": [
+                    {
+                        "op": "update",
+                        "id": first,
+                        "if_match": "\"1\"",
+                        "item": { "title": "batch1-updated", "note": "note", "status": "in_progress" }
+                    },
+                    { "op": "delete", "id": second, "if_match": "\"7\"" }
+                ]
+            }))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        let body = response
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        assert_eq!(body["results"][0]["status"], json!(200));
+        assert_eq!(body["results"][0]["etag"], json!("\"2\""));
+        assert_eq!(body["results"][1]["status"], json!(412));
+        assert_eq!(body["results"][1]["problem"]["status"], json!(412));
+
+        let response = client
+            .post(batch_path.as_str())
+            .json(&json!({
+                "atomic": true,
+                "operations": [
+                    { "op": "delete", "id": first },
+                    { "op": "update", "id": second, "if_match": "\"9\"", "item": { "title": "t", "note": "n", "status": "pending" } }
+                ]
+            }))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        let body = response
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        assert_eq!(body["results"][0]["status"], json!(424));
+        assert_eq!(body["results"][1]["status"], json!(412));
+
+        let response = client
+            .get(web_server_path.to_owned() + format!("to-do-items/{first}").as_str())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = client
+            .post(batch_path.as_str())
+            .json(&json!({ "operations": [] }))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
 
     #[serial]
     #[tokio::test]
-    async fn test_update_rejects_blank_note() {
+    async fn test_update_rejects_illegal_status_transition() {
         let client = prepare_test_environment!();
-        let mut map_create = HashMap::new();
-        map_create.insert("title", "title1");
-        map_create.insert("note", "note1");
-        map_create.insert("status", "pending");
 
         let id = client
             .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
-            .json(&map_create)
+            .json(&json!({
+                "title": "transition",
+                "note": "note1",
+                "status": "pending"
+            }))
             .send()
             .await
             .expect("Failed to execute request.")
@@ -644,29 +1677,44 @@ mod tests {
             .put(WEB_SERVER_PATH.to_owned() + format!("to-do-items/{id}").as_str())
             .header("If-Match", "\"1\"")
             .json(&json!({
-                "title": "title1",
-                "note": "   ",
-                "status": "pending"
+                "title": "transition",
+                "note": "note1",
+                "status": "done"
             }))
             .send()
             .await
             .expect("Failed to execute request.");
 
-        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+        let body = response
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        assert_eq!(body["status"], json!(409));
+        assert_eq!(body["title"], "Conflict");
+
+        let item = client
+            .get(WEB_SERVER_PATH.to_owned() + format!("to-do-items/{id}").as_str())
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        assert_eq!(item["status"], "pending");
     }
 
-    #[serial]
-    #[tokio::test]
-    async fn test_update_rejects_stale_version() {
-        let client = prepare_test_environment!();
-        let mut map_create = HashMap::new();
-        map_create.insert("title", "title1");
-        map_create.insert("note", "note1");
-        map_create.insert("status", "pending");
+    async fn test_patch_updates_only_provided_fields_and_clears_due_at(driver: Driver) {
+        let client = prepare_test_environment!(driver);
+        let web_server_path = driver.api_path();
 
         let id = client
-            .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
-            .json(&map_create)
+            .post(web_server_path.to_owned() + "to-do-items")
+            .json(&json!({
+                "title": "patch",
+                "note": "patch note",
+                "due_at": "2030-01-01T00:00:00Z"
+            }))
             .send()
             .await
             .expect("Failed to execute request.")
@@ -674,54 +1722,83 @@ mod tests {
             .await
             .expect("Failed to deserialize response.");
 
-        let item_response = client
-            .get(WEB_SERVER_PATH.to_owned() + format!("to-do-items/{id}").as_str())
+        let response = client
+            .patch(web_server_path.to_owned() + format!("to-do-items/{id}").as_str())
+            .header("If-Match", "\"1\"")
+            .header("Content-Type", "application/merge-patch+json")
+            .body(r#"{"status":"in_progress","due_at":null}"#)
             .send()
             .await
             .expect("Failed to execute request.");
-        let etag = item_response
-            .headers()
-            .get("etag")
-            .expect("ETag header must be present")
-            .to_str()
-            .expect("ETag must be ascii")
-            .to_string();
 
-        let response = client
-            .put(WEB_SERVER_PATH.to_owned() + format!("to-do-items/{id}").as_str())
-            .header("If-Match", etag.clone())
-            .json(&json!({
-                "title": "title2",
-                "note": "note2",
-                "status": "in_progress"
-            }))
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(
+            response.headers().get("etag").and_then(|v| v.to_str().ok()),
+            Some("\"2\"")
+        );
+
+        let item = client
+            .get(web_server_path.to_owned() + format!("to-do-items/{id}").as_str())
             .send()
             .await
-            .expect("Failed to execute request.");
+            .expect("Failed to execute request.")
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        assert_eq!(item["title"], "patch");
+        assert_eq!(item["note"], "patch note");
+        assert_eq!(item["status"], "in_progress");
+        assert!(item["due_at"].is_null());
+    }
 
-        assert_eq!(response.status(), StatusCode::OK);
+    #[serial]
+    #[tokio::test]
+    async fn test_patch_requires_merge_patch_content_type_and_if_match() {
+        let client = prepare_test_environment!();
 
-        let stale_response = client
-            .put(WEB_SERVER_PATH.to_owned() + format!("to-do-items/{id}").as_str())
-            .header("If-Match", etag)
+        let id = client
+            .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
             .json(&json!({
-                "title": "title3",
-                "note": "note3",
-                "status": "done"
+                "title": "patch",
+                "note": "patch note"
             }))
             .send()
             .await
+            .expect("Failed to execute request.")
+            .json::<Uuid>()
+            .await
+            .expect("Failed to deserialize response.");
+
+        let wrong_content_type = client
+            .patch(WEB_SERVER_PATH.to_owned() + format!("to-do-items/{id}").as_str())
+            .header("If-Match", "\"1\"")
+            .json(&json!({ "note": "changed" }))
+            .send()
+            .await
             .expect("Failed to execute request.");
+        assert_eq!(
+            wrong_content_type.status(),
+            StatusCode::UNSUPPORTED_MEDIA_TYPE
+        );
 
-        assert_eq!(stale_response.status(), StatusCode::PRECONDITION_FAILED);
+        let missing_if_match = client
+            .patch(WEB_SERVER_PATH.to_owned() + format!("to-do-items/{id}").as_str())
+            .header("Content-Type", "application/merge-patch+json")
+            .body(r#"{"note":"changed"}"#)
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(missing_if_match.status(), StatusCode::PRECONDITION_REQUIRED);
 
-        let body = stale_response
-            .json::<Value>()
+        let stale = client
+            .patch(WEB_SERVER_PATH.to_owned() + format!("to-do-items/{id}").as_str())
+            .header("If-Match", "\"7\"")
+            .header("Content-Type", "application/merge-patch+json")
+            .body(r#"{"note":"changed"}"#)
+            .send()
             .await
-            .expect("Failed to deserialize response.");
-        assert_eq!(body["status"], json!(412));
-        assert_eq!(body["title"], "Precondition Failed");
-        assert!(body["detail"].as_str().unwrap().contains("stale version"));
+            .expect("Failed to execute request.");
+        assert_eq!(stale.status(), StatusCode::PRECONDITION_FAILED);
     }
 
     #[serial]
@@ -786,6 +1863,286 @@ mod tests {
         assert_eq!(body["meta"]["page_size"], 10);
     }
 
+    #[serial]
+    #[tokio::test]
+    async fn test_get_all_cursor_pagination_walks_all_pages_in_sort_order() {
+        let client = prepare_test_environment!();
+        let marker = format!("keyset{}", Uuid::new_v4().simple());
+        for title in ["c", "a", "b", "a"] {
+            let response = client
+                .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
+                .json(&json!({
+                    "title": format!("{marker}-{title}"),
+                    "note": "note",
+                    "status": "pending"
+                }))
+                .send()
+                .await
+                .expect("Failed to execute request.");
+            assert!(response.status().is_success());
+        }
+
+        let mut titles = Vec::new();
+        let mut url = format!(
+            "to-do-items?pagination=cursor&page_size=3&sort=title:asc&search={marker}&include_total=true"
+        );
+        let mut pages = 0;
+        loop {
+            let response = client
+                .get(WEB_SERVER_PATH.to_owned() + url.as_str())
+                .send()
+                .await
+                .expect("Failed to execute request.");
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = response
+                .json::<Value>()
+                .await
+                .expect("Failed to deserialize response.");
+            assert!(body["meta"].get("page").is_none());
+            assert_eq!(body["meta"]["total_items"], 4);
+            titles.extend(
+                body["items"]
+                    .as_array()
+                    .expect("items should be an array")
+                    .iter()
+                    .map(|item| item["title"].as_str().unwrap_or_default().to_string()),
+            );
+            pages += 1;
+
+            match body["next_cursor"].as_str() {
+                Some(cursor) => {
+                    url = format!(
+                        "to-do-items?page_size=3&search={marker}&include_total=true&cursor={cursor}"
+                    )
+                }
+                None => break,
+            }
+        }
+
+        assert_eq!(pages, 2);
+        assert_eq!(
+            titles,
+            ["a", "a", "b", "c"]
+                .iter()
+                .map(|title| format!("{marker}-{title}"))
+                .collect::<Vec<_>>()
+        );
+
+        let response = client
+            .get(WEB_SERVER_PATH.to_owned() + "to-do-items?cursor=not-a-cursor")
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_get_all_sorts_by_multiple_keys_with_offset_and_cursor_pages() {
+        let client = prepare_test_environment!();
+        let marker = format!("multisort{}", Uuid::new_v4().simple());
+        let now = chrono::Utc::now();
+        let items = [
+            ("done", "done", Some(now + chrono::Duration::days(1))),
+            (
+                "pending-late",
+                "pending",
+                Some(now + chrono::Duration::days(5)),
+            ),
+            ("in-progress", "in_progress", None),
+            ("pending-undated", "pending", None),
+            (
+                "pending-soon",
+                "pending",
+                Some(now + chrono::Duration::days(1)),
+            ),
+        ];
+        for (title, status, due_at) in items {
+            let response = client
+                .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
+                .json(&json!({
+                    "title": format!("{marker}-{title}"),
+                    "note": "note",
+                    "status": status,
+                    "due_at": due_at
+                }))
+                .send()
+                .await
+                .expect("Failed to execute request.");
+            assert!(response.status().is_success());
+        }
+        let expected = [
+            "pending-undated",
+            "pending-soon",
+            "pending-late",
+            "in-progress",
+            "done",
+        ]
+        .iter()
+        .map(|title| format!("{marker}-{title}"))
+        .collect::<Vec<_>>();
+        let sort = "status:asc,due_at:asc:nulls_first";
+
+        let response = client
+            .get(
+                WEB_SERVER_PATH.to_owned()
+                    + format!("to-do-items?search={marker}&sort={sort}").as_str(),
+            )
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(response.status(), StatusCode::OK);
+        let body = response
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        let titles = body["items"]
+            .as_array()
+            .expect("items should be an array")
+            .iter()
+            .map(|item| item["title"].as_str().unwrap_or_default().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(titles, expected);
+
+        let mut titles = Vec::new();
+        let mut url =
+            format!("to-do-items?pagination=cursor&page_size=2&search={marker}&sort={sort}");
+        loop {
+            let response = client
+                .get(WEB_SERVER_PATH.to_owned() + url.as_str())
+                .send()
+                .await
+                .expect("Failed to execute request.");
+            assert_eq!(response.status(), StatusCode::OK);
+            let body = response
+                .json::<Value>()
+                .await
+                .expect("Failed to deserialize response.");
+            titles.extend(
+                body["items"]
+                    .as_array()
+                    .expect("items should be an array")
+                    .iter()
+                    .map(|item| item["title"].as_str().unwrap_or_default().to_string()),
+            );
+
+            match body["next_cursor"].as_str() {
+                Some(cursor) => {
+                    url = format!("to-do-items?page_size=2&search={marker}&cursor={cursor}")
+                }
+                None => break,
+            }
+        }
+        assert_eq!(titles, expected);
+
+        let response = client
+            .get(WEB_SERVER_PATH.to_owned() + "to-do-items?sort=status:asc:nulls_first")
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_get_all_applies_status_due_and_overdue_filters() {
+        let client = prepare_test_environment!();
+        let marker = format!("filter{}", Uuid::new_v4().simple());
+        let now = chrono::Utc::now();
+        let items = [
+            ("overdue", "pending", Some(now - chrono::Duration::days(2))),
+            ("done-late", "done", Some(now - chrono::Duration::days(2))),
+            (
+                "this-week",
+                "in_progress",
+                Some(now + chrono::Duration::days(3)),
+            ),
+            ("no-due", "pending", None),
+        ];
+        for (title, status, due_at) in items {
+            let response = client
+                .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
+                .json(&json!({
+                    "title": format!("{marker}-{title}"),
+                    "note": "note",
+                    "status": status,
+                    "due_at": due_at
+                }))
+                .send()
+                .await
+                .expect("Failed to execute request.");
+            assert!(response.status().is_success());
+        }
+
+        let list_titles = |query: String| {
+            let client = client.clone();
+            let marker = marker.clone();
+            async move {
+                let response = client
+                    .get(
+                        WEB_SERVER_PATH.to_owned()
+                            + format!("to-do-items?search={marker}&sort=title:asc&{query}")
+                                .as_str(),
+                    )
+                    .send()
+                    .await
+                    .expect("Failed to execute request.");
+                assert_eq!(response.status(), StatusCode::OK);
+                response
+                    .json::<Value>()
+                    .await
+                    .expect("Failed to deserialize response.")["items"]
+                    .as_array()
+                    .expect("items should be an array")
+                    .iter()
+                    .map(|item| {
+                        item["title"]
+                            .as_str()
+                            .unwrap_or_default()
+                            .trim_start_matches(&format!("{marker}-"))
+                            .to_string()
+                    })
+                    .collect::<Vec<_>>()
+            }
+        };
+        let timestamp = |value: chrono::DateTime<chrono::Utc>| {
+            value.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+        };
+
+        assert_eq!(list_titles("overdue=true".into()).await, ["overdue"]);
+        assert_eq!(
+            list_titles("status=pending,done".into()).await,
+            ["done-late", "no-due", "overdue"]
+        );
+        assert_eq!(
+            list_titles(format!(
+                "due_after={}&due_before={}",
+                timestamp(now),
+                timestamp(now + chrono::Duration::days(7))
+            ))
+            .await,
+            ["this-week"]
+        );
+        assert_eq!(
+            list_titles(format!(
+                "updated_since={}",
+                timestamp(now + chrono::Duration::days(1))
+            ))
+            .await,
+            Vec::<String>::new()
+        );
+
+        let response = client
+            .get(
+                WEB_SERVER_PATH.to_owned()
+                    + "to-do-items?due_after=2026-10-20T00:00:00Z&due_before=2026-10-19T00:00:00Z",
+            )
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
     #[serial]
     #[tokio::test]
     async fn test_query_contract_returns_read_model_with_metadata() {
@@ -971,9 +2328,82 @@ mod tests {
         let body = response
             .json::<Value>()
             .await
-            .expect("Failed to deserialize response.");
-        let items = body["items"].as_array().expect("items should be an array");
-        assert!(items.iter().any(|item| item["title"] == title));
+            .expect("Failed to deserialize response.");
+        let items = body["items"].as_array().expect("items should be an array");
+        assert!(items.iter().any(|item| item["title"] == title));
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_get_all_full_text_search_modes_relevance_and_highlights() {
+        let client = prepare_test_environment!();
+        let marker = format!("fts{}", Uuid::new_v4().simple());
+        for (title, note) in [
+            ("Walk the dog", "after dinner"),
+            ("Bake bread", "needs milk and flour"),
+            ("Buy milks", "at the corner shop"),
+        ] {
+            let response = client
+                .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
+                .json(&json!({
+                    "title": title,
+                    "note": format!("{marker} {note}"),
+                    "status": "pending"
+                }))
+                .send()
+                .await
+                .expect("Failed to execute request.");
+            assert_eq!(response.status(), StatusCode::CREATED);
+        }
+
+        let search = |query: String| {
+            let client = client.clone();
+            async move {
+                let response = client
+                    .get(WEB_SERVER_PATH.to_owned() + format!("to-do-items?{query}").as_str())
+                    .send()
+                    .await
+                    .expect("Failed to execute request.");
+                assert_eq!(response.status(), StatusCode::OK);
+                response
+                    .json::<Value>()
+                    .await
+                    .expect("Failed to deserialize response.")["items"]
+                    .as_array()
+                    .expect("items should be an array")
+                    .clone()
+            }
+        };
+        let titles = |items: &[Value]| {
+            items
+                .iter()
+                .map(|item| item["title"].as_str().unwrap_or_default().to_string())
+                .collect::<Vec<_>>()
+        };
+
+        let ranked = search(format!("search={marker}%20milk&sort=relevance")).await;
+        assert_eq!(titles(&ranked), ["Buy milks", "Bake bread"]);
+        assert!(ranked.iter().all(|item| item["highlight"]
+            .as_str()
+            .is_some_and(|highlight| highlight.contains("<mark>"))));
+
+        let phrase = search(format!("search={marker}%20needs%20milk&search_mode=phrase")).await;
+        assert_eq!(titles(&phrase), ["Bake bread"]);
+
+        let websearch = search(format!("search={marker}%20-milk&search_mode=websearch")).await;
+        assert_eq!(titles(&websearch), ["Walk the dog"]);
+
+        let unfiltered = search("page=1&page_size=10".into()).await;
+        assert!(unfiltered
+            .iter()
+            .all(|item| item.get("highlight").is_none()));
+
+        let response = client
+            .get(WEB_SERVER_PATH.to_owned() + "to-do-items?sort=relevance")
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
     }
 
     #[serial]
@@ -1015,7 +2445,7 @@ mod tests {
             .json(&json!({
                 "title": "command-flow-updated",
                 "note": "write path updated",
-                "status": "done"
+                "status": "in_progress"
             }))
             .send()
             .await
@@ -1066,7 +2496,10 @@ mod tests {
 
         let delete_response = client
             .delete(WEB_SERVER_PATH.to_owned() + format!("to-do-items/{id}").as_str())
-            .header("X-Actor-Id", actor_id.to_string())
+            .header(
+                "Authorization",
+                test_server::bearer_with_roles(actor_id, test_server::TEST_SCOPES, &["admin"]),
+            )
             .send()
             .await
             .expect("Failed to execute request.");
@@ -1133,6 +2566,42 @@ mod tests {
         assert!(body.contains("http_request_errors_total"));
     }
 
+    #[serial]
+    #[tokio::test]
+    async fn test_metrics_endpoint_exposes_database_metrics() {
+        let client = prepare_test_environment!();
+
+        let _ = client
+            .get(WEB_SERVER_PATH.to_owned() + "to-do-items?page=1&page_size=5")
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        let _ = client
+            .get(WEB_SERVER_PATH.to_owned() + format!("to-do-items/{}", Uuid::new_v4()).as_str())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+
+        let body = client
+            .get(METRICS_PATH)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .text()
+            .await
+            .expect("Failed to read metrics response body.");
+        assert!(body.contains("db_pool_size{pool=\"primary\"}"));
+        assert!(body.contains("db_pool_idle_connections{pool=\"primary\"}"));
+        assert!(body.contains("db_pool_in_use_connections{pool=\"primary\"}"));
+        assert!(body.contains("db_connection_wait_seconds_count{repository=\"to_do_items\"}"));
+        assert!(body.contains(
+            "db_query_duration_seconds_count{repository=\"to_do_items\",operation=\"get_all\"}"
+        ));
+        assert!(body.contains(
+            "db_query_errors_total{repository=\"to_do_items\",operation=\"get_by_id\",error=\"item_not_found\"}"
+        ));
+    }
+
     #[serial]
     #[tokio::test]
     async fn test_metrics_scrapes_do_not_increment_business_route_count() {
@@ -1159,6 +2628,57 @@ mod tests {
         );
     }
 
+    #[serial]
+    #[tokio::test]
+    async fn test_outbox_dispatcher_publishes_events_for_commands() {
+        let client = prepare_test_environment!();
+
+        let before = fetch_metric_value(&client, "outbox_messages_published_total").await;
+
+        let response = client
+            .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
+            .json(&json!({
+                "title": "outbox",
+                "note": "outbox note"
+            }))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert!(response.status().is_success());
+
+        let mut published = before;
+        for _ in 0..20 {
+            tokio::time::sleep(std::time::Duration::from_millis(250)).await;
+            published = fetch_metric_value(&client, "outbox_messages_published_total").await;
+            if published > before {
+                break;
+            }
+        }
+
+        assert!(
+            published > before,
+            "Outbox dispatcher should publish the created event"
+        );
+    }
+
+    async fn fetch_metric_value(client: &reqwest::Client, name: &str) -> f64 {
+        let metrics = client
+            .get(METRICS_PATH)
+            .send()
+            .await
+            .expect("Failed to execute metrics request.")
+            .text()
+            .await
+            .expect("Failed to read metrics response body.");
+
+        metrics
+            .lines()
+            .filter(|line| line.starts_with(&format!("{name} ")))
+            .filter_map(|line| line.split_whitespace().last())
+            .filter_map(|value| value.parse::<f64>().ok())
+            .sum()
+    }
+
     async fn fetch_route_counter(client: &reqwest::Client, route: &str) -> f64 {
         let response = client
             .get(METRICS_PATH)
@@ -1180,4 +2700,360 @@ mod tests {
             .filter_map(|value| value.parse::<f64>().ok())
             .sum()
     }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_to_do_items_require_a_valid_bearer_token() {
+        let client = prepare_test_environment!();
+        let anonymous = reqwest::Client::new();
+
+        let missing = anonymous
+            .get(WEB_SERVER_PATH.to_owned() + "to-do-items")
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(missing.status(), StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            missing
+                .headers()
+                .get("WWW-Authenticate")
+                .and_then(|value| value.to_str().ok()),
+            Some("Bearer")
+        );
+        assert_eq!(
+            missing
+                .headers()
+                .get("content-type")
+                .and_then(|value| value.to_str().ok()),
+            Some("application/problem+json")
+        );
+
+        let invalid = anonymous
+            .post(WEB_SERVER_PATH.to_owned() + "to-do-items:batch")
+            .header("Authorization", "Bearer not-a-token")
+            .json(&json!({"operations": []}))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(invalid.status(), StatusCode::UNAUTHORIZED);
+
+        let health = anonymous
+            .get(WEB_SERVER_PATH.to_owned() + "healthz/live")
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert!(health.status().is_success());
+
+        let authenticated = client
+            .get(WEB_SERVER_PATH.to_owned() + "to-do-items")
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(authenticated.status(), StatusCode::OK);
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_scopes_limit_what_callers_can_do() {
+        let client = prepare_test_environment!();
+        let reader = test_server::bearer_with_scope(Uuid::new_v4(), "todo:read");
+
+        let read = client
+            .get(WEB_SERVER_PATH.to_owned() + "to-do-items")
+            .header("Authorization", reader.as_str())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(read.status(), StatusCode::OK);
+
+        let write = client
+            .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
+            .header("Authorization", reader.as_str())
+            .json(&json!({"title": "forbidden", "note": "note1"}))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(write.status(), StatusCode::FORBIDDEN);
+        let problem = write
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        assert_eq!(problem["status"], 403);
+        assert!(problem["detail"]
+            .as_str()
+            .is_some_and(|detail| detail.contains("todo:write")));
+
+        let audit = client
+            .get(
+                WEB_SERVER_PATH.to_owned()
+                    + format!("audit/to-do-items/{}", Uuid::new_v4()).as_str(),
+            )
+            .header("Authorization", reader.as_str())
+            .header("X-Audit-Token", AUDIT_TOKEN)
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(audit.status(), StatusCode::FORBIDDEN);
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_items_are_only_visible_to_their_owner_and_admins() {
+        let client = prepare_test_environment!();
+        let owner = Uuid::new_v4();
+        let owner_token = test_server::bearer(owner);
+        let stranger = test_server::bearer(Uuid::new_v4());
+        let admin = test_server::bearer_with_roles(Uuid::new_v4(), "", &["admin"]);
+
+        let id = client
+            .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
+            .header("Authorization", owner_token.as_str())
+            .json(&json!({"title": "private", "note": "owned item"}))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json::<Uuid>()
+            .await
+            .expect("Failed to deserialize response.");
+        let item_path = WEB_SERVER_PATH.to_owned() + format!("to-do-items/{id}").as_str();
+
+        let owned = client
+            .get(item_path.as_str())
+            .header("Authorization", owner_token.as_str())
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        assert_eq!(owned["owner_id"], owner.to_string());
+
+        let hidden = client
+            .get(item_path.as_str())
+            .header("Authorization", stranger.as_str())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(hidden.status(), StatusCode::NOT_FOUND);
+
+        let updated = client
+            .put(item_path.as_str())
+            .header("Authorization", stranger.as_str())
+            .header("If-Match", "\"1\"")
+            .json(&json!({"title": "taken", "note": "over", "status": "pending"}))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(updated.status(), StatusCode::NOT_FOUND);
+
+        let listed = client
+            .get(WEB_SERVER_PATH.to_owned() + "to-do-items?page_size=100")
+            .header("Authorization", stranger.as_str())
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        assert!(listed["items"]
+            .as_array()
+            .expect("items")
+            .iter()
+            .all(|item| item["id"] != id.to_string()));
+
+        client
+            .delete(item_path.as_str())
+            .header("Authorization", stranger.as_str())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        let admin_view = client
+            .get(item_path.as_str())
+            .header("Authorization", admin.as_str())
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        assert_eq!(admin_view["title"], "private");
+        assert_eq!(admin_view["owner_id"], owner.to_string());
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_items_are_isolated_per_tenant() {
+        let client = prepare_test_environment!();
+        let other_admin = test_server::bearer_in_tenant(
+            test_server::TEST_SUBJECT,
+            test_server::TEST_SCOPES,
+            &["admin"],
+            "other-tenant",
+        );
+
+        let id = client
+            .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
+            .json(&json!({"title": "tenant", "note": "isolated item"}))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json::<Uuid>()
+            .await
+            .expect("Failed to deserialize response.");
+        let item_path = WEB_SERVER_PATH.to_owned() + format!("to-do-items/{id}").as_str();
+
+        let hidden = client
+            .get(item_path.as_str())
+            .header("Authorization", other_admin.as_str())
+            .header("X-Tenant-Id", "other-tenant")
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(hidden.status(), StatusCode::NOT_FOUND);
+
+        let updated = client
+            .put(item_path.as_str())
+            .header("Authorization", other_admin.as_str())
+            .header("X-Tenant-Id", "other-tenant")
+            .header("If-Match", "\"1\"")
+            .json(&json!({"title": "taken", "note": "over", "status": "pending"}))
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(updated.status(), StatusCode::NOT_FOUND);
+
+        let listed = client
+            .get(WEB_SERVER_PATH.to_owned() + "to-do-items?page_size=100")
+            .header("Authorization", other_admin.as_str())
+            .header("X-Tenant-Id", "other-tenant")
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        assert!(listed["items"]
+            .as_array()
+            .expect("items")
+            .iter()
+            .all(|item| item["id"] != id.to_string()));
+
+        client
+            .delete(item_path.as_str())
+            .header("Authorization", other_admin.as_str())
+            .header("X-Tenant-Id", "other-tenant")
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        let kept = client
+            .get(item_path.as_str())
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(kept.status(), StatusCode::OK);
+
+        let mismatched = client
+            .get(item_path.as_str())
+            .header("X-Tenant-Id", "other-tenant")
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(mismatched.status(), StatusCode::FORBIDDEN);
+
+        let invalid = client
+            .get(item_path.as_str())
+            .header("X-Tenant-Id", "not a tenant")
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(invalid.status(), StatusCode::BAD_REQUEST);
+
+        let missing = reqwest::Client::new()
+            .get(item_path.as_str())
+            .header(
+                "Authorization",
+                test_server::bearer(test_server::TEST_SUBJECT),
+            )
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(missing.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[serial]
+    #[tokio::test]
+    async fn test_api_keys_authenticate_service_callers_until_revoked() {
+        let client = prepare_test_environment!();
+        let created = starter::manage_api_keys_with_config(
+            test_server::CONFIG_FILE_PATH,
+            starter::ApiKeyCommand::Create {
+                name: "nightly-export".into(),
+                scopes: vec!["todo:read".into(), "todo:write".into()],
+                tenant: Some(test_server::TEST_TENANT.into()),
+                expires_in_days: Some(1),
+            },
+        )
+        .await
+        .expect("Failed to create API key.");
+        let key = created
+            .lines()
+            .find(|line| line.starts_with("tk_"))
+            .expect("created key");
+        let key_id = Uuid::parse_str(&created[..36]).expect("key id");
+
+        let id = client
+            .post(WEB_SERVER_PATH.to_owned() + "to-do-items")
+            .header("X-Api-Key", key)
+            .json(&json!({"title": "export", "note": "created by a service"}))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json::<Uuid>()
+            .await
+            .expect("Failed to deserialize response.");
+        let item_path = WEB_SERVER_PATH.to_owned() + format!("to-do-items/{id}").as_str();
+
+        let item = client
+            .get(item_path.as_str())
+            .header("Authorization", format!("ApiKey {key}"))
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .json::<Value>()
+            .await
+            .expect("Failed to deserialize response.");
+        assert_eq!(item["owner_id"], key_id.to_string());
+
+        let metrics = client
+            .get(METRICS_PATH)
+            .send()
+            .await
+            .expect("Failed to execute request.")
+            .text()
+            .await
+            .expect("Failed to read metrics.");
+        assert!(metrics.contains("api_key_authentications_total"));
+
+        starter::manage_api_keys_with_config(
+            test_server::CONFIG_FILE_PATH,
+            starter::ApiKeyCommand::Revoke { id: key_id },
+        )
+        .await
+        .expect("Failed to revoke API key.");
+        let revoked = client
+            .get(item_path.as_str())
+            .header("X-Api-Key", key)
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(revoked.status(), StatusCode::UNAUTHORIZED);
+
+        let unknown = client
+            .get(item_path.as_str())
+            .header("X-Api-Key", "tk_not-a-key")
+            .send()
+            .await
+            .expect("Failed to execute request.");
+        assert_eq!(unknown.status(), StatusCode::UNAUTHORIZED);
+    }
 }
@@ -1,10 +1,13 @@
 #[cfg(test)]
 mod tests {
     use application::{
-        ApplicationError, ApplicationResult, CreateToDoItemCommand, GetAllToDoItemsQuery,
-        PaginatedResult, ToDoItemCommandRepository, ToDoItemQueryRepository, ToDoItemService,
+        AccessScope, ApplicationError, ApplicationResult, CreateToDoItemCommand,
+        GetAllToDoItemsQuery, GetDeletedToDoItemsForAuditQuery, InMemoryUnitOfWorkFactory,
+        PaginatedResult, ToDoItemCommandRepository, ToDoItemQueryRepository, ToDoItemRevision,
+        ToDoItemService,
     };
-    use domain::ToDoItem;
+    use chrono::{DateTime, Utc};
+    use domain::{ToDoItem, ToDoItemStatus};
     use std::sync::{Arc, Mutex};
     use std::time::Duration;
     use tokio::time::sleep;
@@ -41,7 +44,8 @@ mod tests {
         ) -> ApplicationResult<PaginatedResult<ToDoItem>> {
             *self.operation_count.lock().unwrap() += 1;
             sleep(Duration::from_millis(10)).await; // Simulate some work
-            let items = self.items.lock().unwrap().clone();
+            let mut items = self.items.lock().unwrap().clone();
+            items.sort_by(|left, right| query.sort.compare(left, right));
             let total_items = items.len() as i64;
             let paged_items = items
                 .into_iter()
@@ -57,19 +61,23 @@ mod tests {
             ))
         }
 
-        async fn get_by_id(&self, id: Uuid) -> ApplicationResult<ToDoItem> {
+        async fn get_by_id(&self, id: Uuid, scope: AccessScope) -> ApplicationResult<ToDoItem> {
             *self.operation_count.lock().unwrap() += 1;
             sleep(Duration::from_millis(10)).await; // Simulate some work
             self.items
                 .lock()
                 .unwrap()
                 .iter()
-                .find(|item| item.id == id)
+                .find(|item| item.id == id && scope.permits(item))
                 .cloned()
                 .ok_or(ApplicationError::NotFound { id })
         }
 
-        async fn get_deleted_by_id_for_audit(&self, id: Uuid) -> ApplicationResult<ToDoItem> {
+        async fn get_deleted_by_id_for_audit(
+            &self,
+            id: Uuid,
+            _scope: AccessScope,
+        ) -> ApplicationResult<ToDoItem> {
             *self.operation_count.lock().unwrap() += 1;
             sleep(Duration::from_millis(10)).await; // Simulate some work
             self.items
@@ -80,10 +88,94 @@ mod tests {
                 .cloned()
                 .ok_or(ApplicationError::NotFound { id })
         }
+
+        async fn get_deleted_for_audit(
+            &self,
+            query: GetDeletedToDoItemsForAuditQuery,
+        ) -> ApplicationResult<PaginatedResult<ToDoItem>> {
+            *self.operation_count.lock().unwrap() += 1;
+            sleep(Duration::from_millis(10)).await; // Simulate some work
+            let mut items = self
+                .items
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|item| item.deleted_at.is_some())
+                .cloned()
+                .collect::<Vec<_>>();
+            items.sort_by(|left, right| query.sort.compare(left, right));
+            let total_items = items.len() as i64;
+            let paged_items = items
+                .into_iter()
+                .skip(query.offset() as usize)
+                .take(query.limit() as usize)
+                .collect();
+
+            Ok(PaginatedResult::new(
+                paged_items,
+                query.page,
+                query.page_size,
+                total_items,
+            ))
+        }
+
+        async fn get_history(
+            &self,
+            _id: Uuid,
+            page: u32,
+            page_size: u32,
+            _scope: AccessScope,
+        ) -> ApplicationResult<PaginatedResult<ToDoItemRevision>> {
+            Ok(PaginatedResult::new(Vec::new(), page, page_size, 0))
+        }
+
+        async fn get_revision(
+            &self,
+            id: Uuid,
+            _version: i32,
+            _scope: AccessScope,
+        ) -> ApplicationResult<ToDoItemRevision> {
+            Err(ApplicationError::NotFound { id })
+        }
+
+        async fn get_revisions(
+            &self,
+            _id: Uuid,
+            _from: i32,
+            _to: i32,
+            _scope: AccessScope,
+        ) -> ApplicationResult<Vec<ToDoItemRevision>> {
+            Ok(Vec::new())
+        }
     }
 
     #[async_trait::async_trait]
     impl ToDoItemCommandRepository for TestToDoItemRepository {
+        async fn get_for_update(
+            &self,
+            id: Uuid,
+            scope: AccessScope,
+        ) -> ApplicationResult<ToDoItem> {
+            *self.operation_count.lock().unwrap() += 1;
+            sleep(Duration::from_millis(10)).await; // Simulate some work
+            self.items
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|item| item.id == id && scope.permits(item))
+                .cloned()
+                .ok_or(ApplicationError::NotFound { id })
+        }
+
+        async fn get_deleted_for_update(
+            &self,
+            id: Uuid,
+            _scope: AccessScope,
+        ) -> ApplicationResult<ToDoItem> {
+            *self.operation_count.lock().unwrap() += 1;
+            Err(ApplicationError::NotFound { id })
+        }
+
         async fn create(&self, entity: ToDoItem) -> ApplicationResult<Uuid> {
             *self.operation_count.lock().unwrap() += 1;
             sleep(Duration::from_millis(10)).await; // Simulate some work
@@ -94,7 +186,17 @@ mod tests {
             Ok(id)
         }
 
-        async fn update(&self, entity: ToDoItem) -> ApplicationResult<Uuid> {
+        async fn create_many(&self, entities: Vec<ToDoItem>) -> ApplicationResult<Vec<Uuid>> {
+            *self.operation_count.lock().unwrap() += 1;
+            sleep(Duration::from_millis(10)).await; // Simulate some work
+            let ids = entities.iter().map(|entity| entity.id).collect();
+
+            let mut items = self.items.lock().unwrap();
+            items.extend(entities);
+            Ok(ids)
+        }
+
+        async fn update(&self, entity: ToDoItem) -> ApplicationResult<ToDoItem> {
             *self.operation_count.lock().unwrap() += 1;
             sleep(Duration::from_millis(10)).await; // Simulate some work
             let id = entity.id;
@@ -117,22 +219,57 @@ mod tests {
             existing.note = entity.note;
             existing.version += 1;
 
-            Ok(id)
+            Ok(existing.clone())
         }
 
-        async fn delete(&self, id: Uuid, _deleted_by: Option<Uuid>) -> ApplicationResult<()> {
+        async fn delete(
+            &self,
+            id: Uuid,
+            deleted_by: Option<Uuid>,
+            _scope: AccessScope,
+        ) -> ApplicationResult<ToDoItem> {
             *self.operation_count.lock().unwrap() += 1;
             sleep(Duration::from_millis(10)).await; // Simulate some work
             let mut items = self.items.lock().unwrap();
-            items.retain(|item| item.id != id);
+            let position = items
+                .iter()
+                .position(|item| item.id == id)
+                .ok_or(ApplicationError::NotFound { id })?;
+            let mut deleted = items.remove(position);
+            deleted.deleted_at = Some(Utc::now());
+            deleted.deleted_by = deleted_by;
+            deleted.version += 1;
+            Ok(deleted)
+        }
+
+        async fn restore(&self, entity: ToDoItem) -> ApplicationResult<Uuid> {
+            *self.operation_count.lock().unwrap() += 1;
+            Ok(entity.id)
+        }
+
+        async fn get_purgeable_ids(
+            &self,
+            _deleted_before: DateTime<Utc>,
+            _limit: i64,
+        ) -> ApplicationResult<Vec<Uuid>> {
+            *self.operation_count.lock().unwrap() += 1;
+            Ok(Vec::new())
+        }
+
+        async fn purge(&self, _ids: Vec<Uuid>) -> ApplicationResult<()> {
+            *self.operation_count.lock().unwrap() += 1;
             Ok(())
         }
     }
 
+    fn unit_of_work(repository: &Arc<TestToDoItemRepository>) -> Arc<InMemoryUnitOfWorkFactory> {
+        Arc::new(InMemoryUnitOfWorkFactory::new(repository.clone()))
+    }
+
     #[tokio::test]
     async fn test_arc_memory_sharing() {
         let repository = Arc::new(TestToDoItemRepository::new());
-        let service = ToDoItemService::new(repository.clone(), repository.clone());
+        let service = ToDoItemService::new(repository.clone(), unit_of_work(&repository));
 
         // Create multiple handlers from the same service
         let handler1 = service.get_all_query_handler();
@@ -161,7 +298,7 @@ mod tests {
     #[tokio::test]
     async fn test_service_cloning_efficiency() {
         let repository = Arc::new(TestToDoItemRepository::new());
-        let original_service = ToDoItemService::new(repository.clone(), repository.clone());
+        let original_service = ToDoItemService::new(repository.clone(), unit_of_work(&repository));
 
         // Clone service multiple times
         let cloned_services: Vec<_> = (0..100).map(|_| original_service.clone()).collect();
@@ -198,7 +335,10 @@ mod tests {
     #[tokio::test]
     async fn test_concurrent_handler_access() {
         let repository = Arc::new(TestToDoItemRepository::new());
-        let service = Arc::new(ToDoItemService::new(repository.clone(), repository.clone()));
+        let service = Arc::new(ToDoItemService::new(
+            repository.clone(),
+            unit_of_work(&repository),
+        ));
 
         // Add test data
         let test_item = ToDoItem::new(
@@ -226,7 +366,7 @@ mod tests {
                         let query = CreateToDoItemCommand::new(
                             format!("Task {}", i),
                             format!("Note {}", i),
-                            "pending",
+                            ToDoItemStatus::Pending,
                             None,
                         );
                         handler.execute(query).await.map(|_| 1)
@@ -265,7 +405,7 @@ mod tests {
     #[tokio::test]
     async fn test_memory_leak_prevention() {
         let repository = Arc::new(TestToDoItemRepository::new());
-        let service = ToDoItemService::new(repository.clone(), repository.clone());
+        let service = ToDoItemService::new(repository.clone(), unit_of_work(&repository));
         let baseline_strong_count = Arc::strong_count(&repository);
 
         // Create many services and let them go out of scope
@@ -290,7 +430,7 @@ mod tests {
 
         // Test Arc-based service
         repository.reset_count();
-        let arc_service = ToDoItemService::new(repository.clone(), repository.clone());
+        let arc_service = ToDoItemService::new(repository.clone(), unit_of_work(&repository));
 
         let arc_start = std::time::Instant::now();
         for _ in 0..100 {
@@ -303,7 +443,7 @@ mod tests {
         // Test Box-based service
         repository.reset_count();
         let box_service =
-            application::ToDoItemServiceBoxed::new(repository.clone(), repository.clone());
+            application::ToDoItemServiceBoxed::new(repository.clone(), unit_of_work(&repository));
 
         let box_start = std::time::Instant::now();
         for _ in 0..100 {
@@ -328,7 +468,7 @@ mod tests {
     #[tokio::test]
     async fn test_service_send_sync_compliance() {
         let repository = Arc::new(TestToDoItemRepository::new());
-        let service = ToDoItemService::new(repository.clone(), repository);
+        let service = ToDoItemService::new(repository.clone(), unit_of_work(&repository));
 
         // Test that service can be sent across thread boundaries
         let service_arc = Arc::new(service);
@@ -348,7 +488,7 @@ mod tests {
     #[test]
     fn test_compile_time_send_sync() {
         let repository = Arc::new(TestToDoItemRepository::new());
-        let service = ToDoItemService::new(repository.clone(), repository.clone());
+        let service = ToDoItemService::new(repository.clone(), unit_of_work(&repository));
 
         // These should compile without errors, proving Send + Sync compliance
         _verify_send_sync(service.clone());
@@ -1,67 +1,265 @@
+use chrono::Utc;
 use ctor::dtor;
-use std::time::Duration;
+use jsonwebtoken::{encode, EncodingKey, Header};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::thread::JoinHandle;
 use testcontainers::core::IntoContainerPort;
 use testcontainers::{ContainerAsync, ImageExt};
 use testcontainers_modules::postgres::Postgres;
 use testcontainers_modules::testcontainers::runners::AsyncRunner;
-use tokio::sync::OnceCell;
-use tokio::task::JoinHandle;
+use tokio::sync::{oneshot, OnceCell};
+use uuid::Uuid;
 
-const CONFIG_FILE_PATH: &str = "./../../";
+pub const CONFIG_FILE_PATH: &str = "./../../";
+const CONFIG_FILE_NAME: &str = "config.app.toml";
+
+/// Token of an audit credential rotated in next to the `local` one of the config file.
+pub const ROTATED_AUDIT_TOKEN: &str = "rotated-audit-token";
+/// Token of an audit credential that has expired.
+pub const EXPIRED_AUDIT_TOKEN: &str = "expired-audit-token";
+
+/// Credentials of [`ROTATED_AUDIT_TOKEN`] and [`EXPIRED_AUDIT_TOKEN`], which the servers
+/// accept in addition to the ones of the config file.
+const TEST_AUDIT_CREDENTIALS: &str = "
+[[audit.credentials]]
+name = 'rotated'
+token_hash = '55e29c6fe954e889179649d43f5af68d6df82edb7497c561311e7eea24983010'
+not_before = '2020-01-01T00:00:00Z'
+
+[[audit.credentials]]
+name = 'expired'
+token_hash = '741726c44c0227d8e58b95295aa3b2154441f1c6d3d4762acc10240a51e3d81d'
+expires_at = '2020-01-01T00:00:00Z'
+";
+const JWT_SECRET: &str = "local-dev-jwt-secret-change-me";
+const JWT_ISSUER: &str = "rust-template";
+const JWT_AUDIENCE: &str = "rust-template-api";
+
+/// Subject of the token that clients from `prepare_test_environment!` send.
+pub const TEST_SUBJECT: Uuid = Uuid::from_u128(0x9f1c_2b7e_4d3a_4e8f_a1b2_c3d4_e5f6_0718);
+
+/// Tenant that clients from `prepare_test_environment!` act for.
+pub const TEST_TENANT: &str = "test-tenant";
 
 #[macro_export]
 macro_rules! prepare_test_environment {
-    () => {{
-        test_server::init().await;
-        reqwest::Client::new()
+    () => {
+        $crate::prepare_test_environment!(test_server::Driver::Diesel)
+    };
+    ($driver:expr) => {{
+        test_server::init($driver).await;
+        let mut headers = reqwest::header::HeaderMap::new();
+        headers.insert(
+            reqwest::header::AUTHORIZATION,
+            test_server::bearer(test_server::TEST_SUBJECT)
+                .parse()
+                .unwrap(),
+        );
+        headers.insert(
+            "X-Tenant-Id",
+            reqwest::header::HeaderValue::from_static(test_server::TEST_TENANT),
+        );
+        reqwest::Client::builder()
+            .default_headers(headers)
+            .build()
+            .unwrap()
     }};
 }
 
+/// Names a database to run against instead of starting a Postgres container, for hosts
+/// without Docker. The database is migrated on startup and must be empty.
+pub const TEST_DATABASE_URL: &str = "TEST_DATABASE_URL";
+
+/// Database driver a test server runs with. Every driver gets a server of its own, on its
+/// own port, against the same database.
+#[derive(Debug, Clone, Copy)]
+pub enum Driver {
+    Diesel,
+    TokioPostgres,
+}
+
+impl Driver {
+    /// Value of `database.driver` that selects this driver.
+    pub fn name(self) -> &'static str {
+        match self {
+            Driver::Diesel => "diesel",
+            Driver::TokioPostgres => "tokio_postgres",
+        }
+    }
+
+    /// Base URL of the API served by the server of this driver.
+    pub fn api_path(self) -> &'static str {
+        match self {
+            Driver::Diesel => "http://localhost:8181/api/v1/",
+            Driver::TokioPostgres => "http://localhost:8182/api/v1/",
+        }
+    }
+
+    fn http_url(self) -> &'static str {
+        match self {
+            Driver::Diesel => "127.0.0.1:8181",
+            Driver::TokioPostgres => "127.0.0.1:8182",
+        }
+    }
+}
+
+/// Writes the config file of the servers: the one at [`CONFIG_FILE_PATH`] with the test
+/// audit credentials appended. Returns the directory it is in.
+fn write_server_config() -> PathBuf {
+    let config = std::fs::read_to_string(Path::new(CONFIG_FILE_PATH).join(CONFIG_FILE_NAME))
+        .expect("Failed to read the config file");
+    let directory = server_config_directory();
+    std::fs::create_dir_all(&directory).expect("Failed to create the test config directory");
+    std::fs::write(
+        directory.join(CONFIG_FILE_NAME),
+        config + TEST_AUDIT_CREDENTIALS,
+    )
+    .expect("Failed to write the test config file");
+    directory
+}
+
+fn server_config_directory() -> PathBuf {
+    std::env::temp_dir().join(format!("starter-tests-{}", std::process::id()))
+}
+
+/// Starts the Postgres container the servers share, unless [`TEST_DATABASE_URL`] names a
+/// database to use instead.
+async fn start_database() -> Option<ContainerAsync<Postgres>> {
+    match std::env::var(TEST_DATABASE_URL) {
+        Ok(database_url) => {
+            std::env::set_var("MICROSERVICE__DATABASE__DATABASE_URL", database_url);
+            None
+        }
+        Err(_) => Some(
+            Postgres::default()
+                .with_db_name("rust_template_db")
+                .with_mapped_port(5432, 5432.tcp())
+                .with_tag("16-alpine")
+                .start()
+                .await
+                .unwrap(),
+        ),
+    }
+}
+
 pub struct Server {
     #[allow(dead_code)]
     server_handle: JoinHandle<()>,
-    container: ContainerAsync<Postgres>,
 }
 
 impl Server {
-    pub async fn start() -> Self {
-        let container = Postgres::default()
-            .with_db_name("rust_template_db")
-            .with_mapped_port(5432, 5432.tcp())
-            .with_tag("16-alpine")
-            .start()
-            .await
-            .unwrap();
+    pub async fn start(driver: Driver) -> Self {
+        DATABASE.get_or_init(start_database).await;
 
-        let server_handle = tokio::spawn(async move {
-            let server = starter::run_with_config(CONFIG_FILE_PATH)
-                .await
-                .expect("Failed to bind address");
-            let _server_task = tokio::spawn(server);
+        std::env::set_var("MICROSERVICE__DATABASE__DRIVER", driver.name());
+        std::env::set_var("MICROSERVICE__SERVICE__HTTP_URL", driver.http_url());
+        std::env::set_var("MICROSERVICE__AUTHENTICATION__ENABLED", "true");
+        std::env::set_var("MICROSERVICE__TENANCY__ENABLED", "true");
+        std::env::set_var("MICROSERVICE__API_KEYS__ENABLED", "true");
+        std::env::set_var(
+            "MICROSERVICE__HTTP_CACHE__HISTORY_CACHE_CONTROL",
+            HISTORY_CACHE_CONTROL,
+        );
+        std::env::set_var(
+            "MICROSERVICE__HTTP_CACHE__REVISION_CACHE_CONTROL",
+            REVISION_CACHE_CONTROL,
+        );
+        std::env::set_var(
+            "MICROSERVICE__HTTP_CACHE__DIFF_CACHE_CONTROL",
+            DIFF_CACHE_CONTROL,
+        );
+        // Every test has its own runtime, so the server and its background tasks get a
+        // runtime of their own that outlives the test that started them. The server has
+        // read its settings once it is bound, so the next one can change the environment.
+        let config_path = write_server_config();
+        let (bound, server_bound) = oneshot::channel();
+        let server_handle = std::thread::spawn(move || {
+            tokio::runtime::Runtime::new()
+                .expect("Failed to build the server runtime")
+                .block_on(async move {
+                    let server = starter::run_with_config(&config_path.to_string_lossy())
+                        .await
+                        .expect("Failed to bind address");
+                    let _ = bound.send(());
+                    let _ = server.await;
+                });
         });
-        tokio::time::sleep(Duration::from_secs(1)).await;
-        Server {
-            server_handle,
-            container,
-        }
+        server_bound
+            .await
+            .expect("Test server stopped before it was bound");
+        Server { server_handle }
     }
+}
 
-    pub fn container(&self) -> &ContainerAsync<Postgres> {
-        &self.container
-    }
+/// The Postgres container the servers run against, if one was started.
+pub fn container() -> Option<&'static ContainerAsync<Postgres>> {
+    DATABASE.get().and_then(Option::as_ref)
+}
+
+/// `Cache-Control` values the server is configured with for the history routes, each
+/// different so tests can tell the routes apart.
+pub const HISTORY_CACHE_CONTROL: &str = "private, max-age=10";
+pub const REVISION_CACHE_CONTROL: &str = "private, max-age=3600, immutable";
+pub const DIFF_CACHE_CONTROL: &str = "private, max-age=20";
+
+/// Scopes granted to the tokens from [`bearer`].
+pub const TEST_SCOPES: &str = "todo:read todo:write audit:read";
+
+/// `Authorization` header value with an HS256 token for `subject` that the server accepts.
+pub fn bearer(subject: Uuid) -> String {
+    bearer_with_scope(subject, TEST_SCOPES)
+}
+
+/// Like [`bearer`], but granting only `scope`.
+pub fn bearer_with_scope(subject: Uuid, scope: &str) -> String {
+    bearer_with_roles(subject, scope, &[])
+}
+
+/// Like [`bearer_with_scope`], additionally carrying the `roles` claim.
+pub fn bearer_with_roles(subject: Uuid, scope: &str, roles: &[&str]) -> String {
+    bearer_in_tenant(subject, scope, roles, TEST_TENANT)
+}
+
+/// Like [`bearer_with_roles`], for a caller of `tenant` instead of [`TEST_TENANT`].
+pub fn bearer_in_tenant(subject: Uuid, scope: &str, roles: &[&str], tenant: &str) -> String {
+    let claims = json!({
+        "sub": subject.to_string(),
+        "scope": scope,
+        "roles": roles,
+        "tenant_id": tenant,
+        "iss": JWT_ISSUER,
+        "aud": JWT_AUDIENCE,
+        "exp": Utc::now().timestamp() + 3600,
+    });
+    let token = encode(
+        &Header::default(),
+        &claims,
+        &EncodingKey::from_secret(JWT_SECRET.as_bytes()),
+    )
+    .unwrap();
+
+    format!("Bearer {token}")
 }
 
-pub(crate) static TEST_SERVER_ONCE: OnceCell<Server> = OnceCell::const_new();
+static DATABASE: OnceCell<Option<ContainerAsync<Postgres>>> = OnceCell::const_new();
+static DIESEL_SERVER: OnceCell<Server> = OnceCell::const_new();
+static TOKIO_POSTGRES_SERVER: OnceCell<Server> = OnceCell::const_new();
 
-pub(crate) async fn init() {
-    TEST_SERVER_ONCE.get_or_init(Server::start).await;
+pub(crate) async fn init(driver: Driver) {
+    let server = match driver {
+        Driver::Diesel => &DIESEL_SERVER,
+        Driver::TokioPostgres => &TOKIO_POSTGRES_SERVER,
+    };
+    server.get_or_init(|| Server::start(driver)).await;
 }
 
 //see https://stackoverflow.com/questions/78969766/how-can-i-call-drop-in-a-tokio-static-oncelock-in-rust
 #[dtor]
 fn cleanup() {
-    if let Some(server) = TEST_SERVER_ONCE.get() {
-        let id = server.container().id();
+    let _ = std::fs::remove_dir_all(server_config_directory());
+    if let Some(container) = container() {
+        let id = container.id();
         let _ = std::process::Command::new("docker")
             .arg("kill")
             .arg(id)